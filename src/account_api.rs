@@ -4,6 +4,7 @@
 //! transaction history, gas estimation, address validation, and all features required
 //! in a real blockchain system. Full production-ready implementation.
 
+use crate::address;
 use crate::error::{SlvrError, SlvrResult};
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
@@ -58,25 +59,12 @@ impl Account {
 
     /// Validate address format
     pub fn validate_address(address: &str) -> SlvrResult<()> {
-        if address.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Address cannot be empty".to_string(),
-            });
-        }
-
-        if !address.starts_with("0x") && !address.starts_with("slvr") {
-            return Err(SlvrError::RuntimeError {
-                message: "Invalid address format".to_string(),
-            });
-        }
-
-        if address.len() < 10 {
-            return Err(SlvrError::RuntimeError {
-                message: "Address too short".to_string(),
-            });
-        }
+        address::validate(address)
+    }
 
-        Ok(())
+    /// Write a valid address in its checksummed, mixed-case form
+    pub fn to_checksummed(address: &str) -> SlvrResult<String> {
+        address::to_checksummed(address)
     }
 
     /// Check if address is valid
@@ -200,23 +188,18 @@ impl AccountManager {
 
     /// Generate address from public key
     fn generate_address(&self, public_key: &str) -> SlvrResult<String> {
-        let mut hasher = Sha512::new();
-        hasher.update(public_key.as_bytes());
-        let hash = format!("{:x}", hasher.finalize());
-        Ok(format!("0x{}", &hash[0..40]))
+        Ok(address::derive(public_key))
     }
 
     /// Get account by address
     pub fn get_account(&self, address: &str) -> SlvrResult<Account> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         self.accounts
             .read()
-            .get(address)
+            .get(&address)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))
     }
 
     /// Get account balance
@@ -227,19 +210,15 @@ impl AccountManager {
 
     /// Update account balance
     pub fn update_balance(&self, address: &str, amount: i64) -> SlvrResult<u64> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let mut accounts = self.accounts.write();
         let account = accounts
-            .get_mut(address)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })?;
+            .get_mut(&address)
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))?;
 
         if amount < 0 && account.balance < (-amount) as u64 {
-            return Err(SlvrError::RuntimeError {
-                message: "Insufficient balance".to_string(),
-            });
+            return Err(SlvrError::runtime("Insufficient balance"));
         }
 
         if amount >= 0 {
@@ -259,42 +238,34 @@ impl AccountManager {
 
     /// Increment nonce
     pub fn increment_nonce(&self, address: &str) -> SlvrResult<u64> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let mut accounts = self.accounts.write();
         let account = accounts
-            .get_mut(address)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })?;
+            .get_mut(&address)
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))?;
 
         account.nonce += 1;
         Ok(account.nonce)
     }
 
     /// Record transaction
-    pub fn record_transaction(&self, tx: TransactionRecord) -> SlvrResult<String> {
-        Account::validate_address(&tx.from)?;
-        Account::validate_address(&tx.to)?;
+    pub fn record_transaction(&self, mut tx: TransactionRecord) -> SlvrResult<String> {
+        tx.from = address::normalize(&tx.from)?;
+        tx.to = address::normalize(&tx.to)?;
 
         if tx.from == tx.to {
-            return Err(SlvrError::RuntimeError {
-                message: "Cannot send to self".to_string(),
-            });
+            return Err(SlvrError::runtime("Cannot send to self"));
         }
 
         let mut accounts = self.accounts.write();
 
         let from_account = accounts
             .get_mut(&tx.from)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", tx.from),
-            })?;
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", tx.from)))?;
 
         if from_account.balance < tx.value + tx.fee {
-            return Err(SlvrError::RuntimeError {
-                message: "Insufficient balance".to_string(),
-            });
+            return Err(SlvrError::runtime("Insufficient balance"));
         }
 
         from_account.balance -= tx.value + tx.fee;
@@ -303,9 +274,7 @@ impl AccountManager {
 
         let to_account = accounts
             .get_mut(&tx.to)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", tx.to),
-            })?;
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", tx.to)))?;
 
         to_account.balance += tx.value;
         to_account.transaction_count += 1;
@@ -317,9 +286,104 @@ impl AccountManager {
         Ok(tx_hash)
     }
 
+    /// Pay each `(to, amount)` leg from `from`, charging `fee` per leg.
+    ///
+    /// Every leg is checked before any balance changes, and all legs are
+    /// applied under one write lock, so the batch lands whole or not at all.
+    /// Each leg is recorded as its own transaction, taking consecutive nonces
+    /// from the sender. Returns the transaction hashes in leg order.
+    pub fn batch_transfer(
+        &self,
+        from: &str,
+        transfers: Vec<(String, u64)>,
+        fee: u64,
+    ) -> SlvrResult<Vec<String>> {
+        let from = address::normalize(from)?;
+        let transfers = transfers
+            .into_iter()
+            .map(|(to, amount)| Ok((address::normalize(&to)?, amount)))
+            .collect::<SlvrResult<Vec<_>>>()?;
+        if transfers.is_empty() {
+            return Err(SlvrError::runtime(
+                "Batch transfer needs at least one recipient",
+            ));
+        }
+
+        let overflow = || SlvrError::runtime("Batch transfer amount overflows");
+        let mut accounts = self.accounts.write();
+
+        let mut total: u64 = 0;
+        let mut credits: HashMap<&str, u64> = HashMap::new();
+        for (to, amount) in &transfers {
+            if *to == from {
+                return Err(SlvrError::runtime("Cannot send to self"));
+            }
+            let to_account = accounts
+                .get(to)
+                .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", to)))?;
+            let credit = credits.entry(to).or_insert(0);
+            *credit = credit.checked_add(*amount).ok_or_else(overflow)?;
+            to_account
+                .balance
+                .checked_add(*credit)
+                .ok_or_else(overflow)?;
+            total = total
+                .checked_add(*amount)
+                .and_then(|total| total.checked_add(fee))
+                .ok_or_else(overflow)?;
+        }
+
+        let from_account = accounts
+            .get_mut(&from)
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", from)))?;
+        if from_account.balance < total {
+            return Err(SlvrError::runtime(format!(
+                "Insufficient balance: batch needs {}, account has {}",
+                total, from_account.balance
+            )));
+        }
+
+        let now = Utc::now();
+        let first_nonce = from_account.nonce;
+        from_account.balance -= total;
+        from_account.nonce += transfers.len() as u64;
+        from_account.transaction_count += transfers.len() as u64;
+        from_account.last_transaction_at = Some(now);
+
+        let mut records = Vec::with_capacity(transfers.len());
+        for (nonce, (to, amount)) in (first_nonce..).zip(transfers) {
+            if let Some(to_account) = accounts.get_mut(&to) {
+                to_account.balance += amount;
+                to_account.transaction_count += 1;
+                to_account.last_transaction_at = Some(now);
+            }
+
+            let gas = GasEstimate::new(0, 0);
+            let digest =
+                Sha512::digest(format!("{}{}{}{}{}", from, to, amount, fee, nonce).as_bytes());
+            records.push(TransactionRecord {
+                hash: format!("0x{:x}", digest),
+                from: from.clone(),
+                to,
+                value: amount,
+                fee,
+                nonce,
+                timestamp: now,
+                status: TransactionStatus::Confirmed,
+                data: None,
+                gas_used: gas.total_gas,
+                gas_price: gas.gas_price,
+            });
+        }
+
+        let hashes = records.iter().map(|tx| tx.hash.clone()).collect();
+        self.transactions.write().extend(records);
+        Ok(hashes)
+    }
+
     /// Get transaction history
     pub fn get_transaction_history(&self, address: &str) -> SlvrResult<Vec<TransactionRecord>> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let transactions = self.transactions.read();
         let history: Vec<_> = transactions
@@ -329,9 +393,10 @@ impl AccountManager {
             .collect();
 
         if history.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("No transactions found for account {}", address),
-            });
+            return Err(SlvrError::runtime(format!(
+                "No transactions found for account {}",
+                address
+            )));
         }
 
         Ok(history)
@@ -344,9 +409,7 @@ impl AccountManager {
             .iter()
             .find(|tx| tx.hash == tx_hash)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Transaction {} not found", tx_hash),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Transaction {} not found", tx_hash)))
     }
 
     /// Estimate gas cost
@@ -361,7 +424,7 @@ impl AccountManager {
 
     /// Check if address exists
     pub fn address_exists(&self, address: &str) -> bool {
-        self.accounts.read().contains_key(address)
+        address::normalize(address).is_ok_and(|address| self.accounts.read().contains_key(&address))
     }
 
     /// List all accounts
@@ -371,7 +434,8 @@ impl AccountManager {
 
     /// Get account statistics
     pub fn get_account_stats(&self, address: &str) -> SlvrResult<AccountStats> {
-        let account = self.get_account(address)?;
+        let address = address::normalize(address)?;
+        let account = self.get_account(&address)?;
         let transactions = self.transactions.read();
 
         let sent_count = transactions.iter().filter(|tx| tx.from == address).count();
@@ -408,14 +472,12 @@ impl AccountManager {
         name: Option<String>,
         email: Option<String>,
     ) -> SlvrResult<()> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let mut accounts = self.accounts.write();
         let account = accounts
-            .get_mut(address)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })?;
+            .get_mut(&address)
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))?;
 
         if let Some(n) = name {
             account.metadata.name = Some(n);
@@ -439,7 +501,7 @@ impl AccountManager {
 
     /// Get sent transactions
     pub fn get_sent_transactions(&self, address: &str) -> SlvrResult<Vec<TransactionRecord>> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let transactions = self.transactions.read();
         let sent: Vec<_> = transactions
@@ -449,9 +511,10 @@ impl AccountManager {
             .collect();
 
         if sent.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("No sent transactions found for account {}", address),
-            });
+            return Err(SlvrError::runtime(format!(
+                "No sent transactions found for account {}",
+                address
+            )));
         }
 
         Ok(sent)
@@ -459,7 +522,7 @@ impl AccountManager {
 
     /// Get received transactions
     pub fn get_received_transactions(&self, address: &str) -> SlvrResult<Vec<TransactionRecord>> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let transactions = self.transactions.read();
         let received: Vec<_> = transactions
@@ -469,9 +532,10 @@ impl AccountManager {
             .collect();
 
         if received.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("No received transactions found for account {}", address),
-            });
+            return Err(SlvrError::runtime(format!(
+                "No received transactions found for account {}",
+                address
+            )));
         }
 
         Ok(received)
@@ -504,7 +568,7 @@ impl AccountManager {
 
     /// Get account balance history
     pub fn get_balance_history(&self, address: &str) -> SlvrResult<Vec<(DateTime<Utc>, u64)>> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let transactions = self.transactions.read();
         let mut balance_history = Vec::new();
@@ -520,9 +584,10 @@ impl AccountManager {
         }
 
         if balance_history.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("No balance history found for account {}", address),
-            });
+            return Err(SlvrError::runtime(format!(
+                "No balance history found for account {}",
+                address
+            )));
         }
 
         Ok(balance_history)
@@ -530,7 +595,7 @@ impl AccountManager {
 
     /// Get account total fees paid
     pub fn get_total_fees_paid(&self, address: &str) -> SlvrResult<u64> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let transactions = self.transactions.read();
         let total_fees: u64 = transactions
@@ -544,7 +609,7 @@ impl AccountManager {
 
     /// Get account total gas used
     pub fn get_total_gas_used(&self, address: &str) -> SlvrResult<u64> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let transactions = self.transactions.read();
         let total_gas: u64 = transactions
@@ -558,7 +623,7 @@ impl AccountManager {
 
     /// Get average transaction fee
     pub fn get_average_transaction_fee(&self, address: &str) -> SlvrResult<u64> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let transactions = self.transactions.read();
         let sent_txs: Vec<_> = transactions
@@ -567,9 +632,10 @@ impl AccountManager {
             .collect();
 
         if sent_txs.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("No transactions found for account {}", address),
-            });
+            return Err(SlvrError::runtime(format!(
+                "No transactions found for account {}",
+                address
+            )));
         }
 
         let total_fees: u64 = sent_txs.iter().map(|tx| tx.fee).sum();
@@ -590,14 +656,12 @@ impl AccountManager {
 
     /// Mark account as contract
     pub fn mark_as_contract(&self, address: &str) -> SlvrResult<()> {
-        Account::validate_address(address)?;
+        let address = address::normalize(address)?;
 
         let mut accounts = self.accounts.write();
         let account = accounts
-            .get_mut(address)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })?;
+            .get_mut(&address)
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))?;
 
         account.is_contract = true;
         Ok(())
@@ -628,14 +692,14 @@ impl AccountManager {
     /// Get richest accounts
     pub fn get_richest_accounts(&self, count: usize) -> Vec<Account> {
         let mut accounts: Vec<_> = self.accounts.read().values().cloned().collect();
-        accounts.sort_by(|a, b| b.balance.cmp(&a.balance));
+        accounts.sort_by_key(|a| std::cmp::Reverse(a.balance));
         accounts.into_iter().take(count).collect()
     }
 
     /// Get most active accounts
     pub fn get_most_active_accounts(&self, count: usize) -> Vec<Account> {
         let mut accounts: Vec<_> = self.accounts.read().values().cloned().collect();
-        accounts.sort_by(|a, b| b.transaction_count.cmp(&a.transaction_count));
+        accounts.sort_by_key(|a| std::cmp::Reverse(a.transaction_count));
         accounts.into_iter().take(count).collect()
     }
 }
@@ -677,19 +741,67 @@ mod tests {
 
     #[test]
     fn test_account_creation() {
-        let account = Account::new("0x1234567890abcdef".to_string(), "pubkey".to_string());
+        let account = Account::new(address::derive("pubkey"), "pubkey".to_string());
         assert_eq!(account.balance, 0);
         assert_eq!(account.nonce, 0);
     }
 
     #[test]
     fn test_address_validation() {
-        assert!(Account::validate_address("0x1234567890abcdef").is_ok());
-        assert!(Account::validate_address("slvr1234567890abcdef").is_ok());
+        assert!(Account::validate_address(&address::derive("pubkey")).is_ok());
+        assert!(Account::validate_address("0x1234567890abcdef").is_err());
+        assert!(Account::validate_address("slvr1234567890abcdef").is_err());
         assert!(Account::validate_address("invalid").is_err());
         assert!(Account::validate_address("").is_err());
     }
 
+    #[test]
+    fn test_checksummed_address_validation() {
+        let address = address::derive("pubkey");
+        let checksummed = Account::to_checksummed(&address).unwrap();
+        assert!(Account::validate_address(&checksummed).is_ok());
+        assert!(Account::validate_address(&address).is_ok());
+
+        // Lowercasing one uppercase letter of the checksum is a typo
+        let prefix = address::ADDRESS_PREFIX.len();
+        let letter = checksummed[prefix..]
+            .rfind(|c: char| c.is_ascii_uppercase())
+            .unwrap()
+            + prefix;
+        let mut typo = checksummed.clone();
+        typo.replace_range(
+            letter..=letter,
+            &checksummed[letter..=letter].to_ascii_lowercase(),
+        );
+        assert!(Account::validate_address(&typo).is_err());
+        assert!(Account::to_checksummed("invalid").is_err());
+    }
+
+    #[test]
+    fn test_created_address_is_valid_everywhere() -> Result<(), Box<dyn std::error::Error>> {
+        let manager = AccountManager::new();
+        let account = manager.create_account("pubkey".to_string())?;
+        assert_eq!(account.address, address::derive("pubkey"));
+        manager.validate_address(&account.address)?;
+
+        // The same address is accepted by pact steps
+        let mut pacts = crate::defpact::PactManager::new();
+        let pact_id = pacts.create_pact(
+            "balance".to_string(),
+            "token".to_string(),
+            "balance_of".to_string(),
+            vec!["query".to_string()],
+            1_000_000,
+        )?;
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "account".to_string(),
+            crate::Value::String(account.address.clone()),
+        );
+        pacts.execute_next_step(&pact_id, inputs, 10_000)?;
+        Ok(())
+    }
+
     #[test]
     fn test_account_manager() -> Result<(), Box<dyn std::error::Error>> {
         let manager = AccountManager::new();
@@ -724,6 +836,91 @@ mod tests {
         Ok(())
     }
 
+    /// A manager with a sender holding `balance` and two empty recipients
+    fn batch_accounts(balance: u64) -> (AccountManager, String, String, String) {
+        let manager = AccountManager::new();
+        let sender = manager
+            .create_account("sender".to_string())
+            .unwrap()
+            .address;
+        let bob = manager.create_account("bob".to_string()).unwrap().address;
+        let carol = manager.create_account("carol".to_string()).unwrap().address;
+        manager.update_balance(&sender, balance as i64).unwrap();
+        (manager, sender, bob, carol)
+    }
+
+    #[test]
+    fn test_batch_transfer_within_balance() -> Result<(), Box<dyn std::error::Error>> {
+        let (manager, sender, bob, carol) = batch_accounts(100);
+
+        let hashes =
+            manager.batch_transfer(&sender, vec![(bob.clone(), 30), (carol.clone(), 50)], 5)?;
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(manager.get_balance(&sender)?, 10);
+        assert_eq!(manager.get_balance(&bob)?, 30);
+        assert_eq!(manager.get_balance(&carol)?, 50);
+        assert_eq!(manager.get_nonce(&sender)?, 2);
+        assert_eq!(manager.get_transaction(&hashes[1])?.to, carol);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_transfer_over_balance_applies_nothing() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let (manager, sender, bob, carol) = batch_accounts(100);
+
+        // The legs fit, but not with a fee on each
+        assert!(manager
+            .batch_transfer(&sender, vec![(bob.clone(), 50), (carol.clone(), 50)], 1)
+            .is_err());
+        // A missing recipient in the last leg fails the whole batch
+        assert!(manager
+            .batch_transfer(
+                &sender,
+                vec![(bob.clone(), 10), (address::derive("nobody"), 10)],
+                0
+            )
+            .is_err());
+
+        assert_eq!(manager.get_balance(&sender)?, 100);
+        assert_eq!(manager.get_balance(&bob)?, 0);
+        assert_eq!(manager.get_balance(&carol)?, 0);
+        assert_eq!(manager.get_nonce(&sender)?, 0);
+        assert!(manager.get_transaction_history(&sender).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksummed_spelling_names_the_same_account() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let (manager, sender, bob, _) = batch_accounts(100);
+        let sender_checksummed = Account::to_checksummed(&sender)?;
+        let bob_checksummed = Account::to_checksummed(&bob)?;
+
+        assert_eq!(manager.get_balance(&sender_checksummed)?, 100);
+        manager.update_balance(&sender_checksummed, 10)?;
+        assert_eq!(manager.get_balance(&sender)?, 110);
+        assert!(manager.address_exists(&bob_checksummed));
+
+        // A self-send is caught whichever spelling each side uses
+        assert!(manager
+            .batch_transfer(&sender, vec![(sender_checksummed.clone(), 1)], 0)
+            .is_err());
+
+        let hashes = manager.batch_transfer(&sender_checksummed, vec![(bob_checksummed, 10)], 0)?;
+        assert_eq!(manager.get_balance(&bob)?, 10);
+        let record = manager.get_transaction(&hashes[0])?;
+        assert_eq!(
+            (record.from.as_str(), record.to.as_str()),
+            (sender.as_str(), bob.as_str())
+        );
+        assert_eq!(
+            manager.get_transaction_history(&sender_checksummed)?.len(),
+            1
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_gas_estimation() {
         let manager = AccountManager::new();
@@ -0,0 +1,163 @@
+//! Account and contract addresses
+//!
+//! Every module that creates or checks addresses uses this one format:
+//! `SLVR` followed by 64 lowercase hex digits, the first 256 bits of the
+//! SHA-512 digest of the public key (or, for contracts, the contract id).
+//!
+//! An address may also be written checksummed, EIP-55 style: each hex
+//! letter is uppercased when the matching nibble of the Keccak-256 digest of
+//! the lowercase hex digits is 8 or more. Mixed-case addresses must match
+//! their checksum exactly; all-lowercase addresses carry no checksum.
+
+use crate::error::{SlvrError, SlvrResult};
+use sha2::{Digest, Sha512};
+use sha3::Keccak256;
+
+/// Prefix shared by all addresses
+pub const ADDRESS_PREFIX: &str = "SLVR";
+
+/// Number of hex digits after the prefix
+pub const ADDRESS_HEX_LEN: usize = 64;
+
+/// Total length of an address
+pub const ADDRESS_LEN: usize = ADDRESS_PREFIX.len() + ADDRESS_HEX_LEN;
+
+/// Derive the address for a public key
+pub fn derive(public_key: impl AsRef<[u8]>) -> String {
+    let digest = Sha512::digest(public_key.as_ref());
+    format!(
+        "{}{}",
+        ADDRESS_PREFIX,
+        hex::encode(&digest[..ADDRESS_HEX_LEN / 2])
+    )
+}
+
+/// Check that `address` is in the canonical format
+pub fn validate(address: &str) -> SlvrResult<()> {
+    if address.is_empty() {
+        return Err(SlvrError::runtime("Address cannot be empty"));
+    }
+
+    let digits = address.strip_prefix(ADDRESS_PREFIX).ok_or_else(|| {
+        SlvrError::runtime(format!(
+            "Address must start with {}: {}",
+            ADDRESS_PREFIX, address
+        ))
+    })?;
+
+    if digits.len() != ADDRESS_HEX_LEN {
+        return Err(SlvrError::runtime(format!(
+            "Address must be {} characters, got {}: {}",
+            ADDRESS_LEN,
+            address.len(),
+            address
+        )));
+    }
+
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(SlvrError::runtime(format!(
+            "Address must be hex after the prefix: {}",
+            address
+        )));
+    }
+
+    if digits.chars().any(|c| c.is_ascii_uppercase()) && checksum(digits) != digits {
+        return Err(SlvrError::runtime(format!(
+            "Address checksum mismatch: {}",
+            address
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validate `address` and return its canonical lowercase form, so that
+/// checksummed and plain spellings name the same account
+pub fn normalize(address: &str) -> SlvrResult<String> {
+    validate(address)?;
+    let digits = &address[ADDRESS_PREFIX.len()..];
+    Ok(format!("{}{}", ADDRESS_PREFIX, digits.to_ascii_lowercase()))
+}
+
+/// Write a valid `address` in its checksummed form
+pub fn to_checksummed(address: &str) -> SlvrResult<String> {
+    validate(address)?;
+    let digits = &address[ADDRESS_PREFIX.len()..];
+    Ok(format!("{}{}", ADDRESS_PREFIX, checksum(digits)))
+}
+
+/// Case `digits` by the Keccak-256 digest of their lowercase form
+fn checksum(digits: &str) -> String {
+    let lower = digits.to_ascii_lowercase();
+    let digest = Keccak256::digest(lower.as_bytes());
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (digest[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Check whether `address` is in the canonical format
+pub fn is_valid(address: &str) -> bool {
+    validate(address).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_is_deterministic() {
+        let address = derive("pubkey");
+        assert_eq!(address, derive("pubkey"));
+        assert_ne!(address, derive("other"));
+        assert_eq!(address.len(), ADDRESS_LEN);
+        assert!(is_valid(&address));
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed() {
+        let address = derive("pubkey");
+        assert!(validate("").is_err());
+        assert!(validate(&address.replacen(ADDRESS_PREFIX, "0x", 1)).is_err());
+        assert!(validate(&address[..ADDRESS_LEN - 1]).is_err());
+        assert!(validate(&address.to_uppercase()).is_err());
+    }
+
+    #[test]
+    fn test_checksummed_addresses() {
+        let address = derive("pubkey");
+        let checksummed = to_checksummed(&address).unwrap();
+        assert_eq!(
+            checksummed.to_ascii_lowercase(),
+            address.to_ascii_lowercase()
+        );
+        assert_ne!(checksummed, address);
+        assert!(validate(&checksummed).is_ok());
+        assert_eq!(to_checksummed(&checksummed).unwrap(), checksummed);
+
+        // All-lowercase carries no checksum and is still accepted
+        assert!(validate(&address).is_ok());
+
+        // Flipping the case of any one hex letter breaks the checksum
+        let letter = checksummed[ADDRESS_PREFIX.len()..]
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap()
+            + ADDRESS_PREFIX.len();
+        let mut flipped = checksummed.clone().into_bytes();
+        flipped[letter] ^= 0x20;
+        let flipped = String::from_utf8(flipped).unwrap();
+        assert!(validate(&flipped).is_err());
+
+        assert_eq!(normalize(&checksummed).unwrap(), address);
+        assert_eq!(normalize(&address).unwrap(), address);
+        assert!(normalize(&flipped).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides HTTP endpoints and JSON-RPC interface for contract execution,
 //! transaction submission, and state queries.
 
+use crate::address;
 use crate::error::{SlvrError, SlvrResult};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
@@ -145,9 +146,7 @@ impl ApiHandler {
     /// Handle JSON-RPC request
     pub fn handle_jsonrpc(&mut self, request: JsonRpcRequest) -> SlvrResult<JsonRpcResponse> {
         if request.jsonrpc != "2.0" {
-            return Err(SlvrError::RuntimeError {
-                message: "Invalid JSON-RPC version".to_string(),
-            });
+            return Err(SlvrError::runtime("Invalid JSON-RPC version"));
         }
 
         let result = match request.method.as_str() {
@@ -181,17 +180,16 @@ impl ApiHandler {
 
     /// Handle execute request
     fn handle_execute(&self, params: &serde_json::Value) -> SlvrResult<serde_json::Value> {
-        let request: ExecuteRequest =
-            serde_json::from_value(params.clone()).map_err(|e| SlvrError::RuntimeError {
-                message: format!("Invalid execute request: {}", e),
-            })?;
+        let request: ExecuteRequest = serde_json::from_value(params.clone())
+            .map_err(|e| SlvrError::runtime(format!("Invalid execute request: {}", e)))?;
 
         let start = std::time::Instant::now();
 
         if !self.contracts.contains_key(&request.contract) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Contract {} not found", request.contract),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Contract {} not found",
+                request.contract
+            )));
         }
 
         let response = ExecuteResponse {
@@ -202,9 +200,8 @@ impl ApiHandler {
             execution_time_ms: start.elapsed().as_millis() as u64,
         };
 
-        serde_json::to_value(response).map_err(|e| SlvrError::RuntimeError {
-            message: format!("Serialization error: {}", e),
-        })
+        serde_json::to_value(response)
+            .map_err(|e| SlvrError::runtime(format!("Serialization error: {}", e)))
     }
 
     /// Handle submit transaction request
@@ -213,14 +210,15 @@ impl ApiHandler {
         params: &serde_json::Value,
     ) -> SlvrResult<serde_json::Value> {
         let request: SubmitTransactionRequest =
-            serde_json::from_value(params.clone()).map_err(|e| SlvrError::RuntimeError {
-                message: format!("Invalid submit transaction request: {}", e),
+            serde_json::from_value(params.clone()).map_err(|e| {
+                SlvrError::runtime(format!("Invalid submit transaction request: {}", e))
             })?;
 
         if !self.contracts.contains_key(&request.contract) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Contract {} not found", request.contract),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Contract {} not found",
+                request.contract
+            )));
         }
 
         let tx_hash = {
@@ -235,17 +233,14 @@ impl ApiHandler {
             message: "Transaction submitted successfully".to_string(),
         };
 
-        serde_json::to_value(response).map_err(|e| SlvrError::RuntimeError {
-            message: format!("Serialization error: {}", e),
-        })
+        serde_json::to_value(response)
+            .map_err(|e| SlvrError::runtime(format!("Serialization error: {}", e)))
     }
 
     /// Handle query state request
     fn handle_query_state(&self, params: &serde_json::Value) -> SlvrResult<serde_json::Value> {
-        let request: QueryStateRequest =
-            serde_json::from_value(params.clone()).map_err(|e| SlvrError::RuntimeError {
-                message: format!("Invalid query state request: {}", e),
-            })?;
+        let request: QueryStateRequest = serde_json::from_value(params.clone())
+            .map_err(|e| SlvrError::runtime(format!("Invalid query state request: {}", e)))?;
 
         let response = QueryStateResponse {
             key: request.key,
@@ -253,9 +248,8 @@ impl ApiHandler {
             exists: false,
         };
 
-        serde_json::to_value(response).map_err(|e| SlvrError::RuntimeError {
-            message: format!("Serialization error: {}", e),
-        })
+        serde_json::to_value(response)
+            .map_err(|e| SlvrError::runtime(format!("Serialization error: {}", e)))
     }
 
     /// Handle deploy contract request
@@ -263,23 +257,15 @@ impl ApiHandler {
         &mut self,
         params: &serde_json::Value,
     ) -> SlvrResult<serde_json::Value> {
-        let request: DeployContractRequest =
-            serde_json::from_value(params.clone()).map_err(|e| SlvrError::RuntimeError {
-                message: format!("Invalid deploy contract request: {}", e),
-            })?;
+        let request: DeployContractRequest = serde_json::from_value(params.clone())
+            .map_err(|e| SlvrError::runtime(format!("Invalid deploy contract request: {}", e)))?;
 
         if request.code.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Contract code cannot be empty".to_string(),
-            });
+            return Err(SlvrError::runtime("Contract code cannot be empty"));
         }
 
         let contract_id = format!("contract_{}", uuid::Uuid::new_v4());
-        let address = {
-            let mut hasher = Sha512::new();
-            hasher.update(contract_id.as_bytes());
-            format!("0x{}", hex::encode(hasher.finalize()))
-        };
+        let address = address::derive(&contract_id);
 
         self.contracts.insert(request.name.clone(), request.code);
 
@@ -289,20 +275,16 @@ impl ApiHandler {
             status: "deployed".to_string(),
         };
 
-        serde_json::to_value(response).map_err(|e| SlvrError::RuntimeError {
-            message: format!("Serialization error: {}", e),
-        })
+        serde_json::to_value(response)
+            .map_err(|e| SlvrError::runtime(format!("Serialization error: {}", e)))
     }
 
     /// Handle get contract request
     fn handle_get_contract(&self, params: &serde_json::Value) -> SlvrResult<serde_json::Value> {
-        let name =
-            params
-                .get("name")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| SlvrError::RuntimeError {
-                    message: "Contract name required".to_string(),
-                })?;
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SlvrError::runtime("Contract name required"))?;
 
         if let Some(code) = self.contracts.get(name) {
             Ok(serde_json::json!({
@@ -372,8 +354,9 @@ mod tests {
 
         match serde_json::to_value(&request) {
             Ok(params) => {
-                let result = handler.handle_deploy_contract(&params);
-                assert!(result.is_ok());
+                let result = handler.handle_deploy_contract(&params).unwrap();
+                let address = result["address"].as_str().unwrap();
+                assert!(address::validate(address).is_ok());
             }
             Err(e) => panic!("Failed to serialize request: {}", e),
         }
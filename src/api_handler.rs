@@ -8,6 +8,7 @@ use crate::blockchain_api::BlockchainState;
 use crate::error::{SlvrError, SlvrResult};
 use crate::runtime::Runtime;
 use crate::smartcontract_api::{CallRequest, ContractManager, DeploymentRequest};
+use crate::SlvrConfig;
 use chrono::Utc;
 
 /// Unified API handler
@@ -21,11 +22,16 @@ pub struct ApiHandler {
 impl ApiHandler {
     /// Create new API handler
     pub fn new() -> Self {
+        Self::with_config(&SlvrConfig::default())
+    }
+
+    /// Create an API handler whose runtime meters fuel as `config` says
+    pub fn with_config(config: &SlvrConfig) -> Self {
         Self {
             blockchain: BlockchainState::new(),
-            contracts: ContractManager::new(),
+            contracts: ContractManager::with_config(config.clone()),
             accounts: AccountManager::new(),
-            runtime: Runtime::new(1_000_000_000),
+            runtime: Runtime::with_config(config),
         }
     }
 
@@ -34,57 +40,43 @@ impl ApiHandler {
     /// Get block by height
     pub fn get_block_by_height(&self, height: u64) -> SlvrResult<serde_json::Value> {
         let block = self.blockchain.get_block_by_height(height)?;
-        serde_json::to_value(block).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(block).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get block by hash
     pub fn get_block_by_hash(&self, hash: &str) -> SlvrResult<serde_json::Value> {
         let block = self.blockchain.get_block_by_hash(hash)?;
-        serde_json::to_value(block).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(block).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get transaction details
     pub fn get_transaction_details(&self, tx_hash: &str) -> SlvrResult<serde_json::Value> {
         let tx = self.blockchain.get_transaction(tx_hash)?;
-        serde_json::to_value(tx).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(tx).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get network status
     pub fn get_network_status(&self) -> SlvrResult<serde_json::Value> {
         let status = self.blockchain.get_network_status();
-        serde_json::to_value(status).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(status).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get chain statistics
     pub fn get_chain_stats(&self) -> SlvrResult<serde_json::Value> {
         let stats = self.blockchain.get_chain_stats();
-        serde_json::to_value(stats).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(stats).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get blocks in range
     pub fn get_blocks_range(&self, start: u64, end: u64) -> SlvrResult<serde_json::Value> {
         let blocks = self.blockchain.get_blocks_range(start, end)?;
-        serde_json::to_value(blocks).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(blocks).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get account transaction history
     pub fn get_account_transactions(&self, address: &str) -> SlvrResult<serde_json::Value> {
         let txs = self.blockchain.get_account_transactions(address)?;
-        serde_json::to_value(txs).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(txs).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     // ============ SMART CONTRACT API METHODS ============
@@ -106,9 +98,8 @@ impl ApiHandler {
         };
 
         let contract = self.contracts.deploy(request)?;
-        serde_json::to_value(contract.metadata).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(contract.metadata)
+            .map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Call contract function
@@ -127,9 +118,7 @@ impl ApiHandler {
         };
 
         let result = self.contracts.call_function(&request, &self.runtime)?;
-        serde_json::to_value(result).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(result).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Query contract state
@@ -151,49 +140,37 @@ impl ApiHandler {
     /// Get contract metadata
     pub fn get_contract_metadata(&self, contract_id: String) -> SlvrResult<serde_json::Value> {
         let metadata = self.contracts.get_metadata(&contract_id)?;
-        serde_json::to_value(metadata).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(metadata).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// List all contracts
     pub fn list_contracts(&self) -> SlvrResult<serde_json::Value> {
         let contracts = self.contracts.list_contracts();
-        serde_json::to_value(contracts).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(contracts).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get contract functions
     pub fn get_contract_functions(&self, contract_id: String) -> SlvrResult<serde_json::Value> {
         let functions = self.contracts.get_functions(&contract_id)?;
-        serde_json::to_value(functions).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(functions).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get contract schemas
     pub fn get_contract_schemas(&self, contract_id: String) -> SlvrResult<serde_json::Value> {
         let schemas = self.contracts.get_schemas(&contract_id)?;
-        serde_json::to_value(schemas).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(schemas).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get contract tables
     pub fn get_contract_tables(&self, contract_id: String) -> SlvrResult<serde_json::Value> {
         let tables = self.contracts.get_tables(&contract_id)?;
-        serde_json::to_value(tables).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(tables).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get contract constants
     pub fn get_contract_constants(&self, contract_id: String) -> SlvrResult<serde_json::Value> {
         let constants = self.contracts.get_constants(&contract_id)?;
-        serde_json::to_value(constants).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(constants).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get contract source code
@@ -262,9 +239,7 @@ impl ApiHandler {
         contract_id: String,
     ) -> SlvrResult<serde_json::Value> {
         let history = self.contracts.get_execution_history(&contract_id);
-        serde_json::to_value(history).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(history).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     // ============ ACCOUNT API METHODS ============
@@ -281,9 +256,7 @@ impl ApiHandler {
     /// Get transaction history
     pub fn get_transaction_history(&self, address: String) -> SlvrResult<serde_json::Value> {
         let history = self.accounts.get_transaction_history(&address)?;
-        serde_json::to_value(history).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(history).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Estimate gas cost
@@ -293,9 +266,7 @@ impl ApiHandler {
         execution_complexity: u64,
     ) -> SlvrResult<serde_json::Value> {
         let estimate = self.accounts.estimate_gas(data_size, execution_complexity);
-        serde_json::to_value(estimate).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(estimate).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Validate address
@@ -310,25 +281,19 @@ impl ApiHandler {
     /// Get account statistics
     pub fn get_account_stats(&self, address: String) -> SlvrResult<serde_json::Value> {
         let stats = self.accounts.get_account_stats(&address)?;
-        serde_json::to_value(stats).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(stats).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Create account
     pub fn create_account(&self, public_key: String) -> SlvrResult<serde_json::Value> {
         let account = self.accounts.create_account(public_key)?;
-        serde_json::to_value(account).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(account).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     /// Get account info
     pub fn get_account_info(&self, address: String) -> SlvrResult<serde_json::Value> {
         let account = self.accounts.get_account(&address)?;
-        serde_json::to_value(account).map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        serde_json::to_value(account).map_err(|_| SlvrError::runtime("Serialization error"))
     }
 
     // ============ UTILITY METHODS ============
@@ -435,7 +400,7 @@ mod tests {
     #[test]
     fn test_validate_address() {
         let handler = ApiHandler::new();
-        let result = handler.validate_address("0x1234567890abcdef".to_string());
+        let result = handler.validate_address(crate::address::derive("pubkey"));
         assert!(result.is_ok());
     }
 
@@ -2,8 +2,11 @@
 //!
 //! Represents the structure of Slvr programs after parsing.
 
+use crate::value::Decimal;
 use serde::{Deserialize, Serialize};
 
+pub use crate::lexer::Span;
+
 /// A complete Slvr program
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Program {
@@ -42,6 +45,32 @@ pub enum Definition {
     },
     /// Constant definition
     Constant { name: String, ty: Type, value: Expr },
+    /// Type alias definition
+    TypeAlias {
+        name: String,
+        ty: Type,
+        doc: Option<String>,
+    },
+    /// Capability definition; `body` is the predicate that must hold to
+    /// acquire it
+    Capability {
+        name: String,
+        params: Vec<(String, Type)>,
+        doc: Option<String>,
+        managed: Option<ManagedParam>,
+        body: Expr,
+    },
+}
+
+/// The parameter of a managed capability whose installed quantity is
+/// consumed as the capability is used, from `manages(param, manager)`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagedParam {
+    /// Parameter holding the quantity
+    pub param: String,
+    /// `defun (managed, requested)` returning what remains; `None` subtracts
+    /// the request and rejects over-spend
+    pub manager: Option<String>,
 }
 
 /// Type annotations
@@ -63,6 +92,8 @@ pub enum Type {
     Custom(String),
     /// Unit type
     Unit,
+    /// Any one of several types, written `a | b`
+    Union(Vec<Type>),
 }
 
 impl std::fmt::Display for Type {
@@ -76,6 +107,15 @@ impl std::fmt::Display for Type {
             Type::Object => write!(f, "object"),
             Type::Custom(name) => write!(f, "{}", name),
             Type::Unit => write!(f, "unit"),
+            Type::Union(members) => {
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " | ")?;
+                    }
+                    write!(f, "{}", member)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -106,10 +146,16 @@ pub enum Expr {
         then_branch: Box<Expr>,
         else_branch: Option<Box<Expr>>,
     },
-    /// Let binding
+    /// Let binding; `sequential` (from `let*`) lets each binding see the
+    /// ones before it, otherwise every value is evaluated in the outer scope
     Let {
-        name: String,
-        value: Box<Expr>,
+        bindings: Vec<(String, Expr)>,
+        sequential: bool,
+        body: Box<Expr>,
+    },
+    /// Anonymous function
+    Lambda {
+        params: Vec<String>,
         body: Box<Expr>,
     },
     /// List literal
@@ -124,6 +170,8 @@ pub enum Expr {
     Block(Vec<Expr>),
     /// Database read
     Read { table: String, key: Box<Expr> },
+    /// Database read that yields null instead of failing on a missing row
+    ReadSafe { table: String, key: Box<Expr> },
     /// Database write
     Write {
         table: String,
@@ -138,6 +186,30 @@ pub enum Expr {
     },
     /// Database delete
     Delete { table: String, key: Box<Expr> },
+    /// Source location of the wrapped expression.
+    ///
+    /// The parser wraps every node it builds; ASTs constructed by hand may
+    /// leave nodes unwrapped. Consumers look through it with `unspanned`.
+    Spanned { span: Span, expr: Box<Expr> },
+}
+
+impl Expr {
+    /// The expression with any `Spanned` wrappers removed
+    pub fn unspanned(&self) -> &Expr {
+        let mut expr = self;
+        while let Expr::Spanned { expr: inner, .. } = expr {
+            expr = inner;
+        }
+        expr
+    }
+
+    /// Source location of this expression, if the parser recorded one
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Expr::Spanned { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
 }
 
 /// Literal values
@@ -146,7 +218,7 @@ pub enum Literal {
     /// Integer literal
     Integer(i128),
     /// Decimal literal
-    Decimal(f64),
+    Decimal(Decimal),
     /// String literal
     String(String),
     /// Boolean literal
@@ -1,41 +1,211 @@
 //! Slvr REPL and CLI tool
 
-use silver_slvr::{Lexer, Parser, LANGUAGE_NAME, VERSION};
+use silver_slvr::bytecode::disassemble;
+use silver_slvr::{Compiler, Lexer, Parser, LANGUAGE_NAME, VERSION};
 use std::io::{self, Write};
+use std::process::ExitCode;
 
-fn main() {
+/// Prompt shown when waiting for a new form
+const PROMPT: &str = "slvr> ";
+
+/// Prompt shown while a multi-line form is still open
+const CONTINUATION_PROMPT: &str = "....> ";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => {
+            repl();
+            ExitCode::SUCCESS
+        }
+        [flag, path] if flag == "--disasm" => {
+            let listing = std::fs::read_to_string(path)
+                .map_err(|e| format!("failed to read {}: {}", path, e))
+                .and_then(|source| disassemble_source(&source));
+            match listing {
+                Ok(listing) => {
+                    print!("{}", listing);
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        _ => {
+            eprintln!("Usage: slvr [--disasm <file>]");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn repl() {
     println!("{} v{}", LANGUAGE_NAME, VERSION);
     println!("Type 'exit' to quit, 'help' for commands\n");
 
     let stdin = io::stdin();
     let mut stdout = io::stdout();
+    let mut buffer = InputBuffer::new();
 
     loop {
-        print!("slvr> ");
-        stdout.flush().unwrap();
+        print!(
+            "{}",
+            if buffer.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            }
+        );
+        if stdout.flush().is_err() {
+            break;
+        }
 
-        let mut input = String::new();
-        stdin.read_line(&mut input).unwrap();
-        let input = input.trim();
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) => {
+                // EOF: report any half-entered form instead of silently dropping it
+                if !buffer.is_empty() {
+                    eprintln!("\nError: unexpected end of input (unbalanced delimiters)");
+                }
+                println!();
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Error: failed to read input: {}", e);
+                break;
+            }
+        }
 
-        if input.is_empty() {
+        if buffer.is_empty() {
+            let command = line.trim();
+            if command.is_empty() {
+                continue;
+            }
+
+            if command == "exit" || command == "quit" {
+                println!("Goodbye!");
+                break;
+            }
+
+            if command == "help" {
+                print_help();
+                continue;
+            }
+        }
+
+        let Some(input) = buffer.push_line(&line) else {
             continue;
+        };
+
+        match execute_command(&input) {
+            Ok(output) => println!("{}", output),
+            Err(e) => eprintln!("Error: {}", e),
         }
+    }
+}
 
-        if input == "exit" || input == "quit" {
-            println!("Goodbye!");
-            break;
+/// Accumulates REPL lines until every opened delimiter has been closed
+#[derive(Debug, Default)]
+struct InputBuffer {
+    pending: String,
+}
+
+impl InputBuffer {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether a form is still being entered
+    fn is_empty(&self) -> bool {
+        self.pending.trim().is_empty()
+    }
+
+    /// Append a line, returning the complete form once delimiters balance
+    fn push_line(&mut self, line: &str) -> Option<String> {
+        self.pending.push_str(line);
+        if !self.pending.ends_with('\n') {
+            self.pending.push('\n');
+        }
+
+        if delimiter_depth(&self.pending) > 0 {
+            return None;
+        }
+
+        let input = std::mem::take(&mut self.pending);
+        let input = input.trim();
+        if input.is_empty() {
+            None
+        } else {
+            Some(input.to_string())
         }
+    }
+}
+
+/// Net count of unclosed `(`, `[` and `{`, ignoring string literals and
+/// both kinds of comment.
+///
+/// A negative depth means there are stray closers; the form is then handed to
+/// the parser so it can report a proper error.
+fn delimiter_depth(source: &str) -> i64 {
+    let mut depth = 0i64;
+    let mut chars = source.chars();
+    let mut in_string = false;
+    let mut comment_depth = 0usize;
 
-        if input == "help" {
-            print_help();
+    while let Some(ch) = chars.next() {
+        if comment_depth > 0 {
+            match (ch, chars.clone().next()) {
+                ('#', Some('|')) => {
+                    chars.next();
+                    comment_depth += 1;
+                }
+                ('|', Some('#')) => {
+                    chars.next();
+                    comment_depth -= 1;
+                }
+                _ => {}
+            }
             continue;
         }
 
-        match execute_command(input) {
-            Ok(output) => println!("{}", output),
-            Err(e) => eprintln!("Error: {}", e),
+        if in_string {
+            match ch {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
         }
+
+        match ch {
+            '"' => in_string = true,
+            '#' if chars.clone().next() == Some('|') => {
+                chars.next();
+                comment_depth = 1;
+            }
+            ';' => {
+                // Line comment runs to end of line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    // An unterminated string or block comment keeps the form open as well
+    if in_string || comment_depth > 0 {
+        depth.max(1)
+    } else {
+        depth
     }
 }
 
@@ -51,13 +221,78 @@ fn execute_command(input: &str) -> Result<String, String> {
     Ok(format!("Parsed {} tokens", tokens.len()))
 }
 
+/// Compile a program and list the bytecode of each function, then the
+/// top-level code
+fn disassemble_source(source: &str) -> Result<String, String> {
+    let mut parser = Parser::new(source).map_err(|e| e.to_string())?;
+    let program = parser.parse().map_err(|e| e.to_string())?;
+
+    let mut compiler = Compiler::new();
+    let bytecode = compiler.compile(&program).map_err(|e| e.to_string())?;
+
+    let mut output = String::new();
+    for function in compiler.functions() {
+        output.push_str(&format!("{}:\n", function.name));
+        output.push_str(&disassemble(&function.bytecode.instructions));
+        output.push('\n');
+    }
+    output.push_str("<top level>:\n");
+    output.push_str(&disassemble(&bytecode.instructions));
+    Ok(output)
+}
+
 fn print_help() {
     println!("Available commands:");
     println!("  exit, quit    - Exit the REPL");
     println!("  help          - Show this help message");
+    println!("\nMulti-line input:");
+    println!("  Open delimiters continue the form on the next line ('....>' prompt)");
     println!("\nSlvr Language Features:");
     println!("  - Turing-incomplete smart contract language");
     println!("  - Database-focused operations");
     println!("  - Fuel metering for execution costs");
     println!("  - Type-safe with compile-time checking");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_line_form_is_one_input() {
+        let mut buffer = InputBuffer::new();
+        assert_eq!(buffer.push_line("module m \"doc\" {\n"), None);
+        assert!(!buffer.is_empty());
+
+        let input = buffer
+            .push_line("  defun f () -> integer 42 }\n")
+            .expect("form should be complete");
+        assert!(buffer.is_empty());
+        assert!(execute_command(&input).is_ok());
+
+        let mut parser = Parser::new(&input).unwrap();
+        assert_eq!(parser.parse().unwrap().definitions.len(), 1);
+    }
+
+    #[test]
+    fn test_disasm_lists_functions_and_top_level() {
+        let source = "defun double (x: integer) -> integer x * 2\ndefconst four: integer = 4";
+        let listing = disassemble_source(source).unwrap();
+        assert_eq!(
+            listing,
+            "double:\n0000: LOAD_LOCAL 0\n0001: PUSH_INT 2\n0002: MUL\n0003: RET\n\n\
+             <top level>:\n0000: PUSH_INT 4\n0001: STORE_GLOBAL four\n"
+        );
+        assert!(disassemble_source("defun (").is_err());
+    }
+
+    #[test]
+    fn test_delimiters_in_strings_and_comments_ignored() {
+        assert_eq!(delimiter_depth("(f \"(\") ; ("), 0);
+        assert_eq!(delimiter_depth("(f \"\\\"(\""), 1);
+        assert_eq!(delimiter_depth("(f \"unterminated"), 1);
+        assert_eq!(delimiter_depth(")"), -1);
+        assert_eq!(delimiter_depth("(f #| ( #| ) |# ( |# )"), 0);
+        assert_eq!(delimiter_depth("(f) #| still open"), 1);
+    }
+}
@@ -4,13 +4,41 @@
 use crate::error::{SlvrError, SlvrResult};
 use crate::transaction::TransactionStatus;
 use chrono::{DateTime, Utc};
-use parking_lot::RwLock;
+use parking_lot::{RwLock, RwLockWriteGuard};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// Nonces tried by `Block::mine` before giving up
+pub const MAX_MINING_ITERATIONS: u64 = 10_000_000;
+
+/// Blocks between difficulty retargets
+pub const RETARGET_INTERVAL: u64 = 10;
+
+/// Block time difficulty retargeting aims for, in seconds
+pub const TARGET_BLOCK_TIME_SECS: i64 = 10;
+
+/// Lowest network difficulty
+pub const MIN_DIFFICULTY: u32 = 1;
+
+/// Highest network difficulty, kept within reach of `MAX_MINING_ITERATIONS`
+pub const MAX_DIFFICULTY: u32 = 6;
+
+/// Default cap on pending transactions held in the mempool
+pub const DEFAULT_MAX_MEMPOOL_SIZE: usize = 10_000;
+
+/// Default cap on a block's serialized size, in bytes
+pub const DEFAULT_MAX_BLOCK_SIZE: usize = 1_000_000;
+
+/// Whether `hash` starts with `difficulty` zero hex digits after its `0x` prefix
+pub fn meets_difficulty(hash: &str, difficulty: u32) -> bool {
+    let digits = hash.strip_prefix("0x").unwrap_or(hash);
+    digits.len() >= difficulty as usize
+        && digits.bytes().take(difficulty as usize).all(|b| b == b'0')
+}
+
 /// Block header
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BlockHeader {
@@ -125,25 +153,57 @@ impl Block {
             .unwrap_or_else(|| "0x0".to_string())
     }
 
+    /// Search for a nonce whose hash meets `target_difficulty`, giving up
+    /// after `MAX_MINING_ITERATIONS` attempts
+    pub fn mine(&mut self, target_difficulty: u32) -> SlvrResult<()> {
+        self.header.difficulty = target_difficulty;
+        for nonce in 0..MAX_MINING_ITERATIONS {
+            self.header.nonce = nonce;
+            let hash = self.header.calculate_hash();
+            if meets_difficulty(&hash, target_difficulty) {
+                self.hash = hash;
+                return Ok(());
+            }
+        }
+
+        Err(SlvrError::runtime(format!(
+            "No nonce meets difficulty {} within {} attempts",
+            target_difficulty, MAX_MINING_ITERATIONS
+        )))
+    }
+
+    /// Check the block hash meets the difficulty in its header
+    pub fn verify_pow(&self) -> SlvrResult<()> {
+        if !meets_difficulty(&self.hash, self.header.difficulty) {
+            return Err(SlvrError::runtime(format!(
+                "Block hash does not meet difficulty {}",
+                self.header.difficulty
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Proof-of-work behind the block, 16 to the power of its difficulty
+    pub fn work(&self) -> u128 {
+        16u128.saturating_pow(self.header.difficulty)
+    }
+
     pub fn verify(&self) -> SlvrResult<()> {
         let calculated_hash = self.header.calculate_hash();
         if calculated_hash != self.hash {
-            return Err(SlvrError::RuntimeError {
-                message: "Block hash verification failed".to_string(),
-            });
+            return Err(SlvrError::runtime("Block hash verification failed"));
         }
 
         if self.transactions.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Block must contain at least one transaction".to_string(),
-            });
+            return Err(SlvrError::runtime(
+                "Block must contain at least one transaction",
+            ));
         }
 
         let calculated_merkle = Self::calculate_merkle_root(&self.transactions);
         if calculated_merkle != self.header.merkle_root {
-            return Err(SlvrError::RuntimeError {
-                message: "Merkle root verification failed".to_string(),
-            });
+            return Err(SlvrError::runtime("Merkle root verification failed"));
         }
 
         Ok(())
@@ -202,17 +262,20 @@ impl BlockTransaction {
         }
     }
 
+    /// Mempool priority: gas price, then fee
+    pub fn fee_priority(&self) -> (u64, u64) {
+        (self.gas_price, self.fee)
+    }
+
     pub fn verify(&self) -> SlvrResult<()> {
         if self.from.is_empty() || self.to.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Invalid transaction: empty addresses".to_string(),
-            });
+            return Err(SlvrError::runtime("Invalid transaction: empty addresses"));
         }
 
         if self.from == self.to {
-            return Err(SlvrError::RuntimeError {
-                message: "Invalid transaction: sender and receiver are the same".to_string(),
-            });
+            return Err(SlvrError::runtime(
+                "Invalid transaction: sender and receiver are the same",
+            ));
         }
 
         Ok(())
@@ -275,6 +338,29 @@ pub struct TransactionReceipt {
     pub timestamp: DateTime<Utc>,
 }
 
+/// State a main-chain block overwrote, so a reorg can rewind it
+#[derive(Debug, Clone)]
+struct BlockUndo {
+    /// Accounts the block touched as they were before it, `None` if it created them
+    accounts: HashMap<String, Option<AccountInfo>>,
+    /// Mempool transactions the block included
+    pending: Vec<BlockTransaction>,
+    network_difficulty: u32,
+}
+
+/// Write locks over the chain, held together while blocks are validated
+/// against the tip, applied or rewound, so each change is atomic
+struct ChainWrite<'a> {
+    blocks: RwLockWriteGuard<'a, HashMap<u64, Block>>,
+    block_hashes: RwLockWriteGuard<'a, HashMap<String, u64>>,
+    transactions: RwLockWriteGuard<'a, HashMap<String, BlockTransaction>>,
+    accounts: RwLockWriteGuard<'a, HashMap<String, AccountInfo>>,
+    pending: RwLockWriteGuard<'a, VecDeque<BlockTransaction>>,
+    undo: RwLockWriteGuard<'a, HashMap<u64, BlockUndo>>,
+    forks: RwLockWriteGuard<'a, HashMap<String, Block>>,
+    status: RwLockWriteGuard<'a, NetworkStatus>,
+}
+
 /// Blockchain state manager
 pub struct BlockchainState {
     blocks: Arc<RwLock<HashMap<u64, Block>>>,
@@ -286,6 +372,12 @@ pub struct BlockchainState {
     network_status: Arc<RwLock<NetworkStatus>>,
     start_time: DateTime<Utc>,
     total_gas_used: Arc<AtomicU64>,
+    /// Blocks off the main chain, by hash
+    forks: Arc<RwLock<HashMap<String, Block>>>,
+    /// Undo data for each main-chain block, by height
+    undo: Arc<RwLock<HashMap<u64, BlockUndo>>>,
+    max_mempool_size: usize,
+    max_block_size: usize,
 }
 
 impl BlockchainState {
@@ -312,38 +404,179 @@ impl BlockchainState {
             })),
             start_time: Utc::now(),
             total_gas_used: Arc::new(AtomicU64::new(0)),
+            forks: Arc::new(RwLock::new(HashMap::new())),
+            undo: Arc::new(RwLock::new(HashMap::new())),
+            max_mempool_size: DEFAULT_MAX_MEMPOOL_SIZE,
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+        }
+    }
+
+    /// Reject blocks larger than `size` bytes
+    pub fn with_max_block_size(mut self, size: usize) -> Self {
+        self.max_block_size = size;
+        self
+    }
+
+    /// Start a chain from a height-0 genesis block crediting `allocations`.
+    /// The genesis merkle root commits to the allocations, so the genesis
+    /// hash differs between chains funded differently.
+    pub fn with_genesis(allocations: HashMap<String, u64>) -> Self {
+        let state = Self::new();
+
+        let mut entries: Vec<_> = allocations.iter().collect();
+        entries.sort();
+        let mut hasher = Sha512::new();
+        for (address, amount) in &entries {
+            hasher.update(format!("{}:{};", address, amount).as_bytes());
+        }
+        let mut genesis = Block::new(0, "0x0".to_string(), Vec::new(), "genesis".to_string(), 0);
+        genesis.header.merkle_root = format!("0x{:x}", hasher.finalize());
+        genesis.hash = genesis.header.calculate_hash();
+
+        state
+            .block_hashes
+            .write()
+            .insert(genesis.hash.clone(), genesis.height);
+        state.blocks.write().insert(genesis.height, genesis);
+
+        let mut accounts = state.accounts.write();
+        for (address, balance) in allocations {
+            accounts.insert(
+                address.clone(),
+                AccountInfo {
+                    address,
+                    balance,
+                    nonce: 0,
+                    created_at: Utc::now(),
+                    transaction_count: 0,
+                    code_hash: None,
+                    storage_root: "0x0".to_string(),
+                },
+            );
         }
+        drop(accounts);
+
+        state
+    }
+
+    /// Hold at most `size` pending transactions
+    pub fn with_max_mempool_size(mut self, size: usize) -> Self {
+        self.max_mempool_size = size;
+        self
     }
 
-    pub fn add_block(&self, mut block: Block) -> SlvrResult<()> {
+    /// Check `block` stays within its gas limit and the maximum block size
+    fn check_block_limits(&self, block: &Block) -> SlvrResult<()> {
+        let gas_used = block
+            .transactions
+            .iter()
+            .try_fold(0u64, |total, tx| total.checked_add(tx.gas_used))
+            .ok_or_else(|| {
+                SlvrError::runtime(format!("Block {} gas usage overflows", block.height))
+            })?;
+        if gas_used > block.gas_limit {
+            return Err(SlvrError::runtime(format!(
+                "Block {} uses {} gas, over its limit of {}",
+                block.height, gas_used, block.gas_limit
+            )));
+        }
+        // Measured from the block itself; the `size` it declares is untrusted
+        let size = serde_json::to_vec(block)
+            .map_err(|e| SlvrError::InternalError {
+                message: format!("Failed to serialize block {}: {}", block.height, e),
+            })?
+            .len();
+        if size > self.max_block_size {
+            return Err(SlvrError::runtime(format!(
+                "Block {} is {} bytes, over the maximum of {}",
+                block.height, size, self.max_block_size
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Take every chain write lock, in a fixed order
+    fn lock_chain(&self) -> ChainWrite<'_> {
+        ChainWrite {
+            blocks: self.blocks.write(),
+            block_hashes: self.block_hashes.write(),
+            transactions: self.transactions.write(),
+            accounts: self.accounts.write(),
+            pending: self.pending_transactions.write(),
+            undo: self.undo.write(),
+            forks: self.forks.write(),
+            status: self.network_status.write(),
+        }
+    }
+
+    /// Check `block` meets the difficulty required of the next block
+    fn check_difficulty(chain: &ChainWrite<'_>, block: &Block) -> SlvrResult<()> {
+        let required = chain.status.network_difficulty.max(MIN_DIFFICULTY);
+        if block.header.difficulty < required {
+            return Err(SlvrError::runtime(format!(
+                "Block difficulty {} is below network difficulty {}",
+                block.header.difficulty, required
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn add_block(&self, block: Block) -> SlvrResult<()> {
         block.verify()?;
+        self.check_block_limits(&block)?;
+        block.verify_pow()?;
+
+        let mut chain = self.lock_chain();
+        Self::check_difficulty(&chain, &block)?;
 
         let height = self.current_height.load(Ordering::SeqCst);
         if block.height != height + 1 {
-            return Err(SlvrError::RuntimeError {
-                message: format!(
-                    "Invalid block height: expected {}, got {}",
-                    height + 1,
-                    block.height
-                ),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid block height: expected {}, got {}",
+                height + 1,
+                block.height
+            )));
+        }
+        if let Some(tip) = chain.blocks.get(&height) {
+            if block.header.previous_hash != tip.hash {
+                return Err(SlvrError::runtime(format!(
+                    "Block {} does not extend the chain tip {}",
+                    block.height, tip.hash
+                )));
+            }
         }
 
+        self.apply_block(&mut chain, block);
+        Ok(())
+    }
+
+    /// Append a validated block to the main chain, recording its undo data
+    fn apply_block(&self, chain: &mut ChainWrite<'_>, mut block: Block) {
         let hash = block.header.calculate_hash();
         block.hash = hash.clone();
 
-        let mut blocks = self.blocks.write();
-        let mut block_hashes = self.block_hashes.write();
-        let mut transactions = self.transactions.write();
-        let mut accounts = self.accounts.write();
-        let mut pending = self.pending_transactions.write();
+        let mut undo = BlockUndo {
+            accounts: HashMap::new(),
+            pending: Vec::new(),
+            network_difficulty: chain.status.network_difficulty,
+        };
 
-        blocks.insert(block.height, block.clone());
-        block_hashes.insert(hash, block.height);
+        chain.blocks.insert(block.height, block.clone());
+        chain.block_hashes.insert(hash, block.height);
 
+        let accounts = &mut chain.accounts;
         for tx in &block.transactions {
-            transactions.insert(tx.hash.clone(), tx.clone());
-            pending.retain(|t| t.hash != tx.hash);
+            chain.transactions.insert(tx.hash.clone(), tx.clone());
+            undo.pending
+                .extend(chain.pending.iter().filter(|t| t.hash == tx.hash).cloned());
+            chain.pending.retain(|t| t.hash != tx.hash);
+            for address in [&tx.from, &tx.to] {
+                undo.accounts
+                    .entry(address.clone())
+                    .or_insert_with(|| accounts.get(address).cloned());
+            }
 
             let from_entry = accounts
                 .entry(tx.from.clone())
@@ -374,45 +607,248 @@ impl BlockchainState {
             to_entry.balance += tx.value;
             to_entry.transaction_count += 1;
         }
+        chain.undo.insert(block.height, undo);
 
         self.current_height.store(block.height, Ordering::SeqCst);
         self.total_gas_used
             .fetch_add(block.gas_used, Ordering::SeqCst);
 
-        let mut status = self.network_status.write();
+        let status = &mut chain.status;
         status.current_block_height = block.height;
         status.total_transactions += block.transactions.len() as u64;
         status.last_block_time = Utc::now();
         status.uptime_seconds = (Utc::now() - self.start_time).num_seconds() as u64;
 
+        if block.height.is_multiple_of(RETARGET_INTERVAL) {
+            Self::retarget(chain, block.height);
+        }
+    }
+
+    /// Remove the main-chain tip at `height`, restoring the state it overwrote
+    fn rewind_block(&self, chain: &mut ChainWrite<'_>, height: u64) -> SlvrResult<Block> {
+        let Some(undo) = chain.undo.remove(&height) else {
+            return Err(SlvrError::runtime(format!(
+                "No undo data for block {}",
+                height
+            )));
+        };
+        let Some(block) = chain.blocks.remove(&height) else {
+            chain.undo.insert(height, undo);
+            return Err(SlvrError::runtime(format!("Block {} not found", height)));
+        };
+
+        chain.block_hashes.remove(&block.hash);
+        for tx in &block.transactions {
+            chain.transactions.remove(&tx.hash);
+        }
+        for (address, account) in undo.accounts {
+            match account {
+                Some(account) => chain.accounts.insert(address, account),
+                None => chain.accounts.remove(&address),
+            };
+        }
+        chain.pending.extend(undo.pending);
+
+        self.current_height.store(height - 1, Ordering::SeqCst);
+        self.total_gas_used
+            .fetch_sub(block.gas_used, Ordering::SeqCst);
+
+        let status = &mut chain.status;
+        status.current_block_height = height - 1;
+        status.total_transactions = status
+            .total_transactions
+            .saturating_sub(block.transactions.len() as u64);
+        status.network_difficulty = undo.network_difficulty;
+
+        Ok(block)
+    }
+
+    /// Track a valid block off the main chain, as a candidate for
+    /// `reorganize_to`. Its parent must be a known block one height below it,
+    /// or, on a chain without a genesis block, the implicit genesis parent
+    /// for a block at height 1. Its difficulty is checked against the network
+    /// difficulty again when a reorg applies it.
+    pub fn add_fork_block(&self, block: Block) -> SlvrResult<String> {
+        block.verify()?;
+        self.check_block_limits(&block)?;
+        if block.header.difficulty < MIN_DIFFICULTY {
+            return Err(SlvrError::runtime(format!(
+                "Block difficulty {} is below the minimum of {}",
+                block.header.difficulty, MIN_DIFFICULTY
+            )));
+        }
+        block.verify_pow()?;
+
+        let mut chain = self.lock_chain();
+        let parent_height = block.height.checked_sub(1);
+        let parent = &block.header.previous_hash;
+        let known_parent = (parent_height == Some(0) && !chain.blocks.contains_key(&0))
+            || parent_height.is_some_and(|height| {
+                chain.block_hashes.get(parent) == Some(&height)
+                    || chain.forks.get(parent).is_some_and(|p| p.height == height)
+            });
+        if !known_parent {
+            return Err(SlvrError::runtime(format!(
+                "Fork block {} has unknown parent {}",
+                block.hash, parent
+            )));
+        }
+
+        let hash = block.hash.clone();
+        chain.forks.insert(hash.clone(), block);
+        Ok(hash)
+    }
+
+    /// Switch the main chain to the branch ending at fork block `hash`, if it
+    /// has more work than the blocks it replaces. Blocks back to the common
+    /// ancestor are rewound and kept as fork blocks, then the branch is
+    /// applied in order, each block checked against the network difficulty
+    /// at its height. If any branch block falls short, the original chain is
+    /// restored. Nothing is rewound unless every replaced block has undo data.
+    /// The chain stays locked throughout.
+    pub fn reorganize_to(&self, hash: &str) -> SlvrResult<()> {
+        let mut chain = self.lock_chain();
+
+        let mut branch = Vec::new();
+        let mut cursor = hash.to_string();
+        let ancestor = loop {
+            if let Some(&height) = chain.block_hashes.get(&cursor) {
+                break height;
+            }
+            let block = chain
+                .forks
+                .get(&cursor)
+                .ok_or_else(|| SlvrError::runtime(format!("Unknown block {}", cursor)))?;
+            branch.push(block.clone());
+            if block.height == 1 {
+                break 0;
+            }
+            cursor = block.header.previous_hash.clone();
+        };
+
+        if branch.is_empty() {
+            return Err(SlvrError::runtime(format!(
+                "Block {} is already on the main chain",
+                hash
+            )));
+        }
+        branch.reverse();
+
+        let tip = self.current_height.load(Ordering::SeqCst);
+        let main_work: u128 = (ancestor + 1..=tip)
+            .filter_map(|height| chain.blocks.get(&height))
+            .map(Block::work)
+            .sum();
+        let branch_work: u128 = branch.iter().map(Block::work).sum();
+        if branch_work <= main_work {
+            return Err(SlvrError::runtime(format!(
+                "Branch to {} has no more work than the main chain",
+                hash
+            )));
+        }
+
+        // Check every replaced block can be rewound before rewinding any
+        if let Some(height) = (ancestor + 1..=tip)
+            .find(|height| !chain.undo.contains_key(height) || !chain.blocks.contains_key(height))
+        {
+            return Err(SlvrError::runtime(format!(
+                "No undo data for block {}",
+                height
+            )));
+        }
+
+        let mut orphaned = Vec::new();
+        for height in (ancestor + 1..=tip).rev() {
+            orphaned.push(self.rewind_block(&mut chain, height)?);
+        }
+
+        for (applied, block) in branch.iter().enumerate() {
+            if let Err(e) = Self::check_difficulty(&chain, block) {
+                // Put the original chain back; its blocks applied before
+                for height in (ancestor + 1..=ancestor + applied as u64).rev() {
+                    self.rewind_block(&mut chain, height)?;
+                }
+                for block in orphaned.into_iter().rev() {
+                    self.apply_block(&mut chain, block);
+                }
+                return Err(e);
+            }
+            self.apply_block(&mut chain, block.clone());
+        }
+
+        for block in &branch {
+            chain.forks.remove(&block.hash);
+        }
+        for block in orphaned {
+            chain.forks.insert(block.hash.clone(), block);
+        }
+
         Ok(())
     }
 
+    /// The difficulty the next retarget would set from the last
+    /// `RETARGET_INTERVAL` blocks: one step up when they came in under half
+    /// the target time, one step down when they took over twice as long,
+    /// within `MIN_DIFFICULTY..=MAX_DIFFICULTY`. The network difficulty
+    /// itself only changes as blocks are added.
+    pub fn next_difficulty(&self) -> u32 {
+        let blocks = self.blocks.read();
+        let status = self.network_status.read();
+        let height = self.current_height.load(Ordering::SeqCst);
+        Self::retargeted(&blocks, status.network_difficulty, height)
+    }
+
+    /// Set the network difficulty from the window ending at `height`
+    fn retarget(chain: &mut ChainWrite<'_>, height: u64) {
+        chain.status.network_difficulty =
+            Self::retargeted(&chain.blocks, chain.status.network_difficulty, height);
+    }
+
+    /// `difficulty` retargeted from the window ending at `height`, as
+    /// `next_difficulty`
+    fn retargeted(blocks: &HashMap<u64, Block>, difficulty: u32, height: u64) -> u32 {
+        if height < RETARGET_INTERVAL {
+            return difficulty;
+        }
+
+        let (Some(first), Some(last)) = (
+            blocks.get(&(height - RETARGET_INTERVAL + 1)),
+            blocks.get(&height),
+        ) else {
+            return difficulty;
+        };
+        let elapsed = (last.header.timestamp - first.header.timestamp).num_seconds();
+
+        let expected = (RETARGET_INTERVAL as i64 - 1) * TARGET_BLOCK_TIME_SECS;
+        if elapsed < expected / 2 {
+            difficulty.saturating_add(1)
+        } else if elapsed > expected * 2 {
+            difficulty.saturating_sub(1)
+        } else {
+            difficulty
+        }
+        .clamp(MIN_DIFFICULTY, MAX_DIFFICULTY)
+    }
+
     pub fn get_block_by_height(&self, height: u64) -> SlvrResult<Block> {
         self.blocks
             .read()
             .get(&height)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Block at height {} not found", height),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Block at height {} not found", height)))
     }
 
     pub fn get_block_by_hash(&self, hash: &str) -> SlvrResult<Block> {
         let block_hashes = self.block_hashes.read();
         let height = block_hashes
             .get(hash)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Block with hash {} not found", hash),
-            })?;
+            .ok_or_else(|| SlvrError::runtime(format!("Block with hash {} not found", hash)))?;
 
         self.blocks
             .read()
             .get(height)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: "Block not found".to_string(),
-            })
+            .ok_or_else(|| SlvrError::runtime("Block not found"))
     }
 
     pub fn get_transaction(&self, tx_hash: &str) -> SlvrResult<BlockTransaction> {
@@ -420,9 +856,7 @@ impl BlockchainState {
             .read()
             .get(tx_hash)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Transaction {} not found", tx_hash),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Transaction {} not found", tx_hash)))
     }
 
     pub fn get_account_balance(&self, address: &str) -> SlvrResult<u64> {
@@ -430,9 +864,7 @@ impl BlockchainState {
             .read()
             .get(address)
             .map(|acc| acc.balance)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))
     }
 
     pub fn get_account_info(&self, address: &str) -> SlvrResult<AccountInfo> {
@@ -440,9 +872,7 @@ impl BlockchainState {
             .read()
             .get(address)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))
     }
 
     pub fn get_network_status(&self) -> NetworkStatus {
@@ -483,7 +913,7 @@ impl BlockchainState {
             circulating_supply: total_supply,
             average_block_time_ms: 10000,
             average_transaction_fee: average_fee,
-            network_difficulty: 1,
+            network_difficulty: self.get_network_difficulty(),
             last_block_timestamp,
             total_gas_used: total_gas,
             average_gas_per_block,
@@ -505,9 +935,10 @@ impl BlockchainState {
         }
 
         if result.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("No blocks found in range {}-{}", start, end),
-            });
+            return Err(SlvrError::runtime(format!(
+                "No blocks found in range {}-{}",
+                start, end
+            )));
         }
 
         Ok(result)
@@ -522,9 +953,10 @@ impl BlockchainState {
             .collect();
 
         if txs.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("No transactions found for account {}", address),
-            });
+            return Err(SlvrError::runtime(format!(
+                "No transactions found for account {}",
+                address
+            )));
         }
 
         Ok(txs)
@@ -536,26 +968,54 @@ impl BlockchainState {
         let accounts = self.accounts.read();
         if let Some(from_acc) = accounts.get(&tx.from) {
             if from_acc.balance < tx.value + tx.fee {
-                return Err(SlvrError::RuntimeError {
-                    message: "Insufficient balance".to_string(),
-                });
+                return Err(SlvrError::runtime("Insufficient balance"));
             }
             if from_acc.nonce != tx.nonce {
-                return Err(SlvrError::RuntimeError {
-                    message: "Invalid nonce".to_string(),
-                });
+                return Err(SlvrError::runtime("Invalid nonce"));
             }
         }
 
         Ok(())
     }
 
+    /// Add `tx` to the mempool. A pending transaction with the same sender
+    /// and nonce is replaced only by one with a higher `fee_priority`. When
+    /// the mempool is full, the lowest-priority transaction is evicted to
+    /// make room, unless `tx` would be the lowest itself.
     pub fn add_pending_transaction(&self, tx: BlockTransaction) -> SlvrResult<()> {
         self.validate_transaction(&tx)?;
-        self.pending_transactions.write().push_back(tx);
+
+        let mut pending = self.pending_transactions.write();
+        if let Some(existing) = pending
+            .iter_mut()
+            .find(|t| t.from == tx.from && t.nonce == tx.nonce)
+        {
+            if tx.fee_priority() <= existing.fee_priority() {
+                return Err(SlvrError::runtime(format!(
+                    "Replacement for {} nonce {} must pay a higher fee",
+                    tx.from, tx.nonce
+                )));
+            }
+            *existing = tx;
+        } else {
+            if pending.len() >= self.max_mempool_size {
+                let lowest = pending
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, t)| t.fee_priority())
+                    .map(|(i, t)| (i, t.fee_priority()));
+                match lowest {
+                    Some((index, priority)) if tx.fee_priority() > priority => {
+                        pending.remove(index);
+                    }
+                    _ => return Err(SlvrError::runtime("Mempool is full")),
+                }
+            }
+            pending.push_back(tx);
+        }
 
         let mut status = self.network_status.write();
-        status.pending_transactions = self.pending_transactions.read().len() as u64;
+        status.pending_transactions = pending.len() as u64;
 
         Ok(())
     }
@@ -564,6 +1024,25 @@ impl BlockchainState {
         self.pending_transactions.read().iter().cloned().collect()
     }
 
+    /// Pending transactions, highest `fee_priority` first
+    pub fn get_pending_transactions_by_fee(&self) -> Vec<BlockTransaction> {
+        let mut txs = self.get_pending_transactions();
+        txs.sort_by_key(|tx| std::cmp::Reverse(tx.fee_priority()));
+        txs
+    }
+
+    /// Pending transactions grouped by sender, each sender's in ascending nonce order
+    pub fn pending_by_sender(&self) -> BTreeMap<String, Vec<BlockTransaction>> {
+        let mut grouped: BTreeMap<String, Vec<BlockTransaction>> = BTreeMap::new();
+        for tx in self.pending_transactions.read().iter() {
+            grouped.entry(tx.from.clone()).or_default().push(tx.clone());
+        }
+        for txs in grouped.values_mut() {
+            txs.sort_by_key(|tx| tx.nonce);
+        }
+        grouped
+    }
+
     pub fn get_pending_transaction_count(&self) -> u64 {
         self.pending_transactions.read().len() as u64
     }
@@ -594,9 +1073,7 @@ impl BlockchainState {
             .read()
             .get(address)
             .map(|acc| acc.nonce)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))
     }
 
     pub fn list_accounts(&self) -> Vec<AccountInfo> {
@@ -617,9 +1094,7 @@ impl BlockchainState {
         let end = std::cmp::min(start + page_size - 1, current_height);
 
         if start > current_height {
-            return Err(SlvrError::RuntimeError {
-                message: "Page out of range".to_string(),
-            });
+            return Err(SlvrError::runtime("Page out of range"));
         }
 
         self.get_blocks_range(start, end)
@@ -701,9 +1176,10 @@ impl BlockchainState {
     pub fn create_account(&self, address: String) -> SlvrResult<()> {
         let mut accounts = self.accounts.write();
         if accounts.contains_key(&address) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Account {} already exists", address),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Account {} already exists",
+                address
+            )));
         }
 
         accounts.insert(
@@ -756,9 +1232,7 @@ impl BlockchainState {
             .read()
             .get(address)
             .map(|acc| acc.transaction_count)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Account {} not found", address),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Account {} not found", address)))
     }
 
     pub fn get_block_miner(&self, block_height: u64) -> SlvrResult<String> {
@@ -810,6 +1284,10 @@ impl Clone for BlockchainState {
             network_status: Arc::clone(&self.network_status),
             start_time: self.start_time,
             total_gas_used: Arc::clone(&self.total_gas_used),
+            forks: Arc::clone(&self.forks),
+            undo: Arc::clone(&self.undo),
+            max_mempool_size: self.max_mempool_size,
+            max_block_size: self.max_block_size,
         }
     }
 }
@@ -838,12 +1316,377 @@ mod tests {
     fn test_add_block() {
         let blockchain = BlockchainState::new();
         let tx = BlockTransaction::new("alice".to_string(), "bob".to_string(), 100, 10, 0);
-        let block = Block::new(1, "0x0".to_string(), vec![tx], "miner".to_string(), 50);
+        let mut block = Block::new(1, "0x0".to_string(), vec![tx], "miner".to_string(), 50);
+        block.mine(1).unwrap();
 
         let result = blockchain.add_block(block);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_mined_block_meets_difficulty() {
+        let blockchain = BlockchainState::new();
+        blockchain.update_network_difficulty(2);
+        let tx = BlockTransaction::new("alice".to_string(), "bob".to_string(), 100, 10, 0);
+        let mut block = Block::new(1, "0x0".to_string(), vec![tx], "miner".to_string(), 50);
+
+        // Unmined, the block is below the network difficulty
+        assert!(blockchain.add_block(block.clone()).is_err());
+
+        // Claiming the difficulty without a matching nonce is rejected too
+        let mut unmined = block.clone();
+        unmined.header.difficulty = 2;
+        while meets_difficulty(&unmined.header.calculate_hash(), 2) {
+            unmined.header.nonce += 1;
+        }
+        unmined.hash = unmined.header.calculate_hash();
+        assert!(blockchain.add_block(unmined).is_err());
+
+        block.mine(2).unwrap();
+        assert!(block.hash.starts_with("0x00"));
+        assert!(block.verify().is_ok());
+        blockchain.add_block(block).unwrap();
+        assert_eq!(blockchain.get_current_height(), 1);
+    }
+
+    /// Mine and add blocks `1..=count` at the network difficulty, the first
+    /// at `start` and each `spacing_secs` after the last
+    fn add_blocks(
+        blockchain: &BlockchainState,
+        count: u64,
+        start: DateTime<Utc>,
+        spacing_secs: i64,
+    ) {
+        let mut previous_hash = "0x0".to_string();
+        for height in 1..=count {
+            let tx = BlockTransaction::new("alice".to_string(), "bob".to_string(), 1, 1, height);
+            let mut block = Block::new(height, previous_hash, vec![tx], "miner".to_string(), 50);
+            block.header.timestamp =
+                start + chrono::Duration::seconds(spacing_secs * (height as i64 - 1));
+            block.mine(blockchain.get_network_difficulty()).unwrap();
+            previous_hash = block.hash.clone();
+            blockchain.add_block(block).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_difficulty_rises_for_fast_blocks() {
+        let blockchain = BlockchainState::new();
+        add_blocks(&blockchain, RETARGET_INTERVAL, Utc::now(), 1);
+
+        assert_eq!(blockchain.get_network_difficulty(), 2);
+        assert_eq!(blockchain.get_chain_stats().network_difficulty, 2);
+
+        // Asking for the next difficulty does not retarget the same window again
+        assert_eq!(blockchain.next_difficulty(), 3);
+        assert_eq!(blockchain.next_difficulty(), 3);
+        assert_eq!(blockchain.get_network_difficulty(), 2);
+    }
+
+    #[test]
+    fn test_difficulty_falls_for_slow_blocks() {
+        let blockchain = BlockchainState::new();
+        blockchain.update_network_difficulty(3);
+        add_blocks(&blockchain, RETARGET_INTERVAL, Utc::now(), 60);
+
+        assert_eq!(blockchain.get_network_difficulty(), 2);
+    }
+
+    #[test]
+    fn test_difficulty_stays_within_bounds() {
+        let slow = BlockchainState::new();
+        add_blocks(&slow, RETARGET_INTERVAL, Utc::now(), 60);
+        assert_eq!(slow.get_network_difficulty(), MIN_DIFFICULTY);
+
+        let fast = BlockchainState::new();
+        add_blocks(&fast, RETARGET_INTERVAL, Utc::now(), 1);
+        fast.update_network_difficulty(MAX_DIFFICULTY);
+        assert_eq!(fast.next_difficulty(), MAX_DIFFICULTY);
+    }
+
+    /// A block at `height` on `previous_hash`, mined at difficulty 1, paying
+    /// `value` from alice to `to`
+    fn mined_block(height: u64, previous_hash: &str, to: &str, value: u64) -> Block {
+        let tx = BlockTransaction::new("alice".to_string(), to.to_string(), value, 0, height - 1);
+        let mut block = Block::new(
+            height,
+            previous_hash.to_string(),
+            vec![tx],
+            "miner".to_string(),
+            50,
+        );
+        block.mine(1).unwrap();
+        block
+    }
+
+    #[test]
+    fn test_genesis_allocations() {
+        let blockchain = BlockchainState::with_genesis(HashMap::from([
+            ("alice".to_string(), 100),
+            ("bob".to_string(), 5),
+        ]));
+        assert_eq!(blockchain.get_current_height(), 0);
+        assert_eq!(blockchain.get_account_balance("alice").unwrap(), 100);
+        assert_eq!(blockchain.get_account_balance("bob").unwrap(), 5);
+
+        let genesis = blockchain.get_block_by_height(0).unwrap();
+        assert_eq!(
+            blockchain.get_block_by_hash(&genesis.hash).unwrap().height,
+            0
+        );
+
+        // Block 1 must build on the genesis hash
+        assert!(blockchain
+            .add_block(mined_block(1, "0x0", "bob", 10))
+            .is_err());
+        blockchain
+            .add_block(mined_block(1, &genesis.hash, "bob", 10))
+            .unwrap();
+        assert_eq!(blockchain.get_account_balance("alice").unwrap(), 90);
+        assert_eq!(blockchain.get_account_balance("bob").unwrap(), 15);
+    }
+
+    #[test]
+    fn test_block_gas_limit() {
+        let mut block = mined_block(1, "0x0", "bob", 10);
+        block.gas_limit = block.gas_used;
+        assert!(BlockchainState::new().add_block(block.clone()).is_ok());
+
+        block.gas_limit = block.gas_used - 1;
+        assert!(BlockchainState::new().add_block(block).is_err());
+    }
+
+    #[test]
+    fn test_max_block_size() {
+        let mut block = mined_block(1, "0x0", "bob", 10);
+        let size = serde_json::to_vec(&block).unwrap().len();
+        let at_limit = BlockchainState::new().with_max_block_size(size);
+        assert!(at_limit.add_block(block.clone()).is_ok());
+
+        let under_limit = BlockchainState::new().with_max_block_size(size - 1);
+        assert!(under_limit.add_block(block.clone()).is_err());
+        assert!(under_limit.add_fork_block(block.clone()).is_err());
+
+        // Understating the size does not get a block past the limit
+        block.size = 1;
+        let size = serde_json::to_vec(&block).unwrap().len();
+        let under_limit = BlockchainState::new().with_max_block_size(size - 1);
+        assert!(under_limit.add_block(block).is_err());
+    }
+
+    #[test]
+    fn test_block_gas_overflow_is_rejected() {
+        let mut block = mined_block(1, "0x0", "bob", 10);
+        let mut tx = block.transactions[0].clone();
+        tx.gas_used = u64::MAX;
+        block.transactions = vec![tx.clone(), tx];
+        block.gas_limit = u64::MAX;
+        let err = BlockchainState::new()
+            .check_block_limits(&block)
+            .unwrap_err();
+        assert!(err.to_string().contains("overflows"));
+    }
+
+    #[test]
+    fn test_heavier_fork_replaces_tip() {
+        let blockchain = BlockchainState::new();
+        let main_1 = mined_block(1, "0x0", "bob", 10);
+        let main_2 = mined_block(2, &main_1.hash, "bob", 20);
+        let orphaned_tx = main_2.transactions[0].hash.clone();
+        blockchain.add_block(main_1).unwrap();
+        blockchain.add_block(main_2.clone()).unwrap();
+        assert_eq!(blockchain.get_account_balance("bob").unwrap(), 30);
+
+        let fork_1 = mined_block(1, "0x0", "carol", 5);
+        let fork_2 = mined_block(2, &fork_1.hash, "carol", 5);
+        let fork_3 = mined_block(3, &fork_2.hash, "carol", 5);
+        blockchain.add_fork_block(fork_1).unwrap();
+        let fork_2_hash = blockchain.add_fork_block(fork_2).unwrap();
+
+        // A branch with only as much work as the main chain does not win
+        assert!(blockchain.reorganize_to(&fork_2_hash).is_err());
+        assert_eq!(blockchain.get_current_height(), 2);
+
+        let fork_tip = blockchain.add_fork_block(fork_3).unwrap();
+        blockchain.reorganize_to(&fork_tip).unwrap();
+
+        assert_eq!(blockchain.get_current_height(), 3);
+        assert_eq!(blockchain.get_block_by_height(3).unwrap().hash, fork_tip);
+        assert!(blockchain.get_account_balance("bob").is_err());
+        assert_eq!(blockchain.get_account_balance("carol").unwrap(), 15);
+        assert_eq!(blockchain.get_account_nonce("alice").unwrap(), 3);
+        assert!(blockchain.get_transaction(&orphaned_tx).is_err());
+        assert!(blockchain.get_block_by_hash(&main_2.hash).is_err());
+
+        // The orphaned branch is kept, but now has less work
+        assert!(blockchain.reorganize_to(&main_2.hash).is_err());
+    }
+
+    #[test]
+    fn test_fork_blocks_need_network_difficulty() {
+        let blockchain = BlockchainState::new();
+        blockchain.update_network_difficulty(2);
+        let mut main = mined_block(1, "0x0", "bob", 10);
+        main.mine(2).unwrap();
+        let main_hash = main.hash.clone();
+        blockchain.add_block(main).unwrap();
+
+        // Free blocks at difficulty 0 are never tracked
+        let mut free = mined_block(1, "0x0", "mallory", 1_000);
+        free.mine(0).unwrap();
+        assert!(blockchain.add_fork_block(free).is_err());
+
+        // Enough difficulty-1 blocks outweigh the main chain, but fall short
+        // of the network difficulty when replayed
+        let mut parent = "0x0".to_string();
+        for height in 1..=17 {
+            let block = mined_block(height, &parent, "mallory", 1_000);
+            parent = blockchain.add_fork_block(block).unwrap();
+        }
+        assert!(blockchain.reorganize_to(&parent).is_err());
+
+        assert_eq!(blockchain.get_current_height(), 1);
+        assert_eq!(blockchain.get_block_by_height(1).unwrap().hash, main_hash);
+        assert_eq!(blockchain.get_account_balance("bob").unwrap(), 10);
+        assert!(blockchain.get_account_balance("mallory").is_err());
+        assert_eq!(blockchain.get_network_difficulty(), 2);
+    }
+
+    #[test]
+    fn test_reorg_without_undo_data_leaves_chain_intact() {
+        let blockchain = BlockchainState::new();
+        let main_1 = mined_block(1, "0x0", "bob", 10);
+        let main_2 = mined_block(2, &main_1.hash, "bob", 20);
+        let main_2_hash = main_2.hash.clone();
+        blockchain.add_block(main_1).unwrap();
+        blockchain.add_block(main_2).unwrap();
+
+        let mut parent = "0x0".to_string();
+        for height in 1..=3 {
+            parent = blockchain
+                .add_fork_block(mined_block(height, &parent, "carol", 5))
+                .unwrap();
+        }
+
+        // Block 2 could be rewound, block 1 could not
+        blockchain.undo.write().remove(&1);
+        assert!(blockchain.reorganize_to(&parent).is_err());
+
+        assert_eq!(blockchain.get_current_height(), 2);
+        assert_eq!(blockchain.get_block_by_height(2).unwrap().hash, main_2_hash);
+        assert_eq!(blockchain.get_account_balance("bob").unwrap(), 30);
+        assert!(blockchain.undo.read().contains_key(&2));
+        assert!(blockchain.get_account_balance("carol").is_err());
+    }
+
+    #[test]
+    fn test_add_block_must_extend_tip() {
+        let blockchain = BlockchainState::new();
+        blockchain
+            .add_block(mined_block(1, "0x0", "bob", 10))
+            .unwrap();
+        assert!(blockchain
+            .add_block(mined_block(2, "0xother", "bob", 10))
+            .is_err());
+        assert!(blockchain
+            .add_fork_block(mined_block(3, "0xother", "bob", 10))
+            .is_err());
+    }
+
+    #[test]
+    fn test_pending_by_sender() {
+        let state = BlockchainState::new();
+        for (from, nonce) in [
+            ("bob", 1),
+            ("alice", 2),
+            ("bob", 0),
+            ("alice", 0),
+            ("alice", 1),
+        ] {
+            let tx = BlockTransaction::new(from.to_string(), "carol".to_string(), 1, 1, nonce);
+            state.add_pending_transaction(tx).unwrap();
+        }
+
+        let grouped = state.pending_by_sender();
+        let senders: Vec<_> = grouped.keys().cloned().collect();
+        assert_eq!(senders, vec!["alice".to_string(), "bob".to_string()]);
+
+        let nonces =
+            |sender: &str| -> Vec<u64> { grouped[sender].iter().map(|t| t.nonce).collect() };
+        assert_eq!(nonces("alice"), vec![0, 1, 2]);
+        assert_eq!(nonces("bob"), vec![0, 1]);
+    }
+
+    /// A transaction from `from` at `nonce` paying `gas_price`
+    fn priced_tx(from: &str, nonce: u64, gas_price: u64) -> BlockTransaction {
+        let mut tx = BlockTransaction::new(from.to_string(), "carol".to_string(), 1, 1, nonce);
+        tx.gas_price = gas_price;
+        tx
+    }
+
+    #[test]
+    fn test_pending_transactions_by_fee() {
+        let state = BlockchainState::new();
+        for (from, gas_price) in [("alice", 5), ("bob", 1), ("dave", 3)] {
+            state
+                .add_pending_transaction(priced_tx(from, 0, gas_price))
+                .unwrap();
+        }
+
+        let prices: Vec<u64> = state
+            .get_pending_transactions_by_fee()
+            .iter()
+            .map(|tx| tx.gas_price)
+            .collect();
+        assert_eq!(prices, vec![5, 3, 1]);
+    }
+
+    #[test]
+    fn test_full_mempool_evicts_lowest_fee() {
+        let state = BlockchainState::new().with_max_mempool_size(2);
+        state
+            .add_pending_transaction(priced_tx("alice", 0, 2))
+            .unwrap();
+        state
+            .add_pending_transaction(priced_tx("bob", 0, 4))
+            .unwrap();
+
+        // Paying no more than the cheapest pending transaction is refused
+        assert!(state
+            .add_pending_transaction(priced_tx("dave", 0, 2))
+            .is_err());
+
+        state
+            .add_pending_transaction(priced_tx("erin", 0, 3))
+            .unwrap();
+        let senders: Vec<String> = state
+            .get_pending_transactions_by_fee()
+            .into_iter()
+            .map(|tx| tx.from)
+            .collect();
+        assert_eq!(senders, vec!["bob".to_string(), "erin".to_string()]);
+        assert_eq!(state.get_network_status().pending_transactions, 2);
+    }
+
+    #[test]
+    fn test_replace_by_fee() {
+        let state = BlockchainState::new();
+        state
+            .add_pending_transaction(priced_tx("alice", 0, 2))
+            .unwrap();
+
+        assert!(state
+            .add_pending_transaction(priced_tx("alice", 0, 2))
+            .is_err());
+        state
+            .add_pending_transaction(priced_tx("alice", 0, 7))
+            .unwrap();
+
+        let pending = state.get_pending_transactions();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].gas_price, 7);
+    }
+
     #[test]
     fn test_transaction_verification() {
         let tx = BlockTransaction::new("alice".to_string(), "bob".to_string(), 100, 10, 0);
@@ -855,7 +1698,7 @@ mod tests {
         let tx1 = BlockTransaction::new("alice".to_string(), "bob".to_string(), 100, 10, 0);
         let tx2 = BlockTransaction::new("bob".to_string(), "charlie".to_string(), 50, 5, 0);
 
-        let merkle = Block::calculate_merkle_root(&vec![tx1, tx2]);
+        let merkle = Block::calculate_merkle_root(&[tx1, tx2]);
         assert!(!merkle.is_empty());
     }
 }
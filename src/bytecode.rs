@@ -3,15 +3,30 @@
 //! Complete bytecode instruction set for the Slvr virtual machine.
 
 use crate::ast::Type as AstType;
+use crate::error::{SlvrError, SlvrResult};
 use crate::types::Type;
+use crate::value::Decimal;
 use serde::{Deserialize, Serialize};
 
+/// Magic number that starts every serialized bytecode blob
+pub const BYTECODE_MAGIC: [u8; 4] = *b"SLVR";
+
+/// Version byte written after the magic number.
+///
+/// Bump this whenever the instruction encoding changes. Bytecode written with
+/// a different version is refused rather than misread, so deployed contracts
+/// must be recompiled.
+pub const BYTECODE_FORMAT_VERSION: u8 = 3;
+
+/// Last format version written without the magic number
+const LAST_HEADERLESS_VERSION: u8 = 2;
+
 /// Bytecode instruction set
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Instruction {
     // Stack operations
     PushInt(i128),
-    PushDecimal(f64),
+    PushDecimal(Decimal),
     PushString(String),
     PushBool(bool),
     PushUnit,
@@ -69,6 +84,7 @@ pub enum Instruction {
 
     // Database operations
     Read(String),
+    ReadSafe(String),
     Write(String),
     Update(String, usize), // table name, field count
     Delete(String),
@@ -79,62 +95,101 @@ pub enum Instruction {
 
     // Error handling
     Throw(String),
+    /// Pop a message and a condition; fail with the message unless the
+    /// condition is true
+    Enforce,
 
     // Fuel operations
     ConsumeFuel(u64),
 }
 
+impl Instruction {
+    /// Mnemonic of the opcode, as shown by the disassembler
+    pub fn opcode(&self) -> &'static str {
+        match self {
+            Instruction::PushInt(_) => "PUSH_INT",
+            Instruction::PushDecimal(_) => "PUSH_DECIMAL",
+            Instruction::PushString(_) => "PUSH_STRING",
+            Instruction::PushBool(_) => "PUSH_BOOL",
+            Instruction::PushUnit => "PUSH_UNIT",
+            Instruction::PushNull => "PUSH_NULL",
+            Instruction::Pop => "POP",
+            Instruction::Dup => "DUP",
+            Instruction::Add => "ADD",
+            Instruction::Subtract => "SUB",
+            Instruction::Multiply => "MUL",
+            Instruction::Divide => "DIV",
+            Instruction::Modulo => "MOD",
+            Instruction::Power => "POW",
+            Instruction::Negate => "NEG",
+            Instruction::Equal => "EQ",
+            Instruction::NotEqual => "NE",
+            Instruction::Less => "LT",
+            Instruction::LessEqual => "LE",
+            Instruction::Greater => "GT",
+            Instruction::GreaterEqual => "GE",
+            Instruction::And => "AND",
+            Instruction::Or => "OR",
+            Instruction::Not => "NOT",
+            Instruction::Concat => "CONCAT",
+            Instruction::Jump(_) => "JMP",
+            Instruction::JumpIfFalse(_) => "JMP_FALSE",
+            Instruction::JumpIfTrue(_) => "JMP_TRUE",
+            Instruction::Return => "RET",
+            Instruction::LoadLocal(_) => "LOAD_LOCAL",
+            Instruction::StoreLocal(_) => "STORE_LOCAL",
+            Instruction::LoadGlobal(_) => "LOAD_GLOBAL",
+            Instruction::StoreGlobal(_) => "STORE_GLOBAL",
+            Instruction::Call(_, _) => "CALL",
+            Instruction::MakeList(_) => "MAKE_LIST",
+            Instruction::MakeObject(_) => "MAKE_OBJECT",
+            Instruction::GetField(_) => "GET_FIELD",
+            Instruction::GetIndex => "GET_INDEX",
+            Instruction::SetField(_) => "SET_FIELD",
+            Instruction::SetIndex => "SET_INDEX",
+            Instruction::Read(_) => "READ",
+            Instruction::ReadSafe(_) => "READ_SAFE",
+            Instruction::Write(_) => "WRITE",
+            Instruction::Update(_, _) => "UPDATE",
+            Instruction::Delete(_) => "DELETE",
+            Instruction::TypeOf => "TYPEOF",
+            Instruction::Cast(_) => "CAST",
+            Instruction::Throw(_) => "THROW",
+            Instruction::Enforce => "ENFORCE",
+            Instruction::ConsumeFuel(_) => "CONSUME_FUEL",
+        }
+    }
+}
+
 impl std::fmt::Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.opcode())?;
         match self {
-            Instruction::PushInt(n) => write!(f, "PUSH_INT {}", n),
-            Instruction::PushDecimal(d) => write!(f, "PUSH_DECIMAL {}", d),
-            Instruction::PushString(s) => write!(f, "PUSH_STRING \"{}\"", s),
-            Instruction::PushBool(b) => write!(f, "PUSH_BOOL {}", b),
-            Instruction::PushUnit => write!(f, "PUSH_UNIT"),
-            Instruction::PushNull => write!(f, "PUSH_NULL"),
-            Instruction::Pop => write!(f, "POP"),
-            Instruction::Dup => write!(f, "DUP"),
-            Instruction::Add => write!(f, "ADD"),
-            Instruction::Subtract => write!(f, "SUB"),
-            Instruction::Multiply => write!(f, "MUL"),
-            Instruction::Divide => write!(f, "DIV"),
-            Instruction::Modulo => write!(f, "MOD"),
-            Instruction::Power => write!(f, "POW"),
-            Instruction::Negate => write!(f, "NEG"),
-            Instruction::Equal => write!(f, "EQ"),
-            Instruction::NotEqual => write!(f, "NE"),
-            Instruction::Less => write!(f, "LT"),
-            Instruction::LessEqual => write!(f, "LE"),
-            Instruction::Greater => write!(f, "GT"),
-            Instruction::GreaterEqual => write!(f, "GE"),
-            Instruction::And => write!(f, "AND"),
-            Instruction::Or => write!(f, "OR"),
-            Instruction::Not => write!(f, "NOT"),
-            Instruction::Concat => write!(f, "CONCAT"),
-            Instruction::Jump(addr) => write!(f, "JMP {}", addr),
-            Instruction::JumpIfFalse(addr) => write!(f, "JMP_FALSE {}", addr),
-            Instruction::JumpIfTrue(addr) => write!(f, "JMP_TRUE {}", addr),
-            Instruction::Return => write!(f, "RET"),
-            Instruction::LoadLocal(idx) => write!(f, "LOAD_LOCAL {}", idx),
-            Instruction::StoreLocal(idx) => write!(f, "STORE_LOCAL {}", idx),
-            Instruction::LoadGlobal(name) => write!(f, "LOAD_GLOBAL {}", name),
-            Instruction::StoreGlobal(name) => write!(f, "STORE_GLOBAL {}", name),
-            Instruction::Call(name, argc) => write!(f, "CALL {} ({})", name, argc),
-            Instruction::MakeList(len) => write!(f, "MAKE_LIST {}", len),
-            Instruction::MakeObject(len) => write!(f, "MAKE_OBJECT {}", len),
-            Instruction::GetField(name) => write!(f, "GET_FIELD {}", name),
-            Instruction::GetIndex => write!(f, "GET_INDEX"),
-            Instruction::SetField(name) => write!(f, "SET_FIELD {}", name),
-            Instruction::SetIndex => write!(f, "SET_INDEX"),
-            Instruction::Read(table) => write!(f, "READ {}", table),
-            Instruction::Write(table) => write!(f, "WRITE {}", table),
-            Instruction::Update(table, count) => write!(f, "UPDATE {} ({})", table, count),
-            Instruction::Delete(table) => write!(f, "DELETE {}", table),
-            Instruction::TypeOf => write!(f, "TYPEOF"),
-            Instruction::Cast(ty) => write!(f, "CAST {}", ty),
-            Instruction::Throw(msg) => write!(f, "THROW \"{}\"", msg),
-            Instruction::ConsumeFuel(amount) => write!(f, "CONSUME_FUEL {}", amount),
+            Instruction::PushInt(n) => write!(f, " {}", n),
+            Instruction::PushDecimal(d) => write!(f, " {}", d),
+            Instruction::PushString(s) => write!(f, " \"{}\"", s),
+            Instruction::PushBool(b) => write!(f, " {}", b),
+            Instruction::Jump(addr) => write!(f, " {}", addr),
+            Instruction::JumpIfFalse(addr) => write!(f, " {}", addr),
+            Instruction::JumpIfTrue(addr) => write!(f, " {}", addr),
+            Instruction::LoadLocal(idx) => write!(f, " {}", idx),
+            Instruction::StoreLocal(idx) => write!(f, " {}", idx),
+            Instruction::LoadGlobal(name) => write!(f, " {}", name),
+            Instruction::StoreGlobal(name) => write!(f, " {}", name),
+            Instruction::Call(name, argc) => write!(f, " {} ({})", name, argc),
+            Instruction::MakeList(len) => write!(f, " {}", len),
+            Instruction::MakeObject(len) => write!(f, " {}", len),
+            Instruction::GetField(name) => write!(f, " {}", name),
+            Instruction::SetField(name) => write!(f, " {}", name),
+            Instruction::Read(table) => write!(f, " {}", table),
+            Instruction::ReadSafe(table) => write!(f, " {}", table),
+            Instruction::Write(table) => write!(f, " {}", table),
+            Instruction::Update(table, count) => write!(f, " {} ({})", table, count),
+            Instruction::Delete(table) => write!(f, " {}", table),
+            Instruction::Cast(ty) => write!(f, " {}", ty),
+            Instruction::Throw(msg) => write!(f, " \"{}\"", msg),
+            Instruction::ConsumeFuel(amount) => write!(f, " {}", amount),
+            _ => Ok(()),
         }
     }
 }
@@ -192,13 +247,86 @@ impl Bytecode {
         self.instructions.get_mut(index)
     }
 
+    /// Serialize in the current format, see [`serialize`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serialize(self)
+    }
+
     /// Disassemble bytecode to string
     pub fn disassemble(&self) -> String {
-        let mut output = String::new();
-        for (i, instr) in self.instructions.iter().enumerate() {
-            output.push_str(&format!("{:04}: {}\n", i, instr));
-        }
-        output
+        disassemble(&self.instructions)
+    }
+}
+
+/// Human-readable listing of instructions, one per line.
+///
+/// Each line holds the instruction offset followed by its opcode and
+/// operands. Jump operands are offsets into the same listing.
+pub fn disassemble(instructions: &[Instruction]) -> String {
+    let mut output = String::new();
+    for (offset, instruction) in instructions.iter().enumerate() {
+        output.push_str(&format!("{:04}: {}\n", offset, instruction));
+    }
+    output
+}
+
+/// Serialize bytecode as the magic number, the format version byte and then
+/// the JSON encoding of the program
+pub fn serialize(bytecode: &Bytecode) -> Vec<u8> {
+    let mut bytes = BYTECODE_MAGIC.to_vec();
+    bytes.push(BYTECODE_FORMAT_VERSION);
+    bytes.extend(serde_json::to_vec(bytecode).unwrap_or_default());
+    bytes
+}
+
+/// Decode bytecode written by [`serialize`].
+///
+/// Bytecode carrying the magic number must have exactly the current format
+/// version. Blobs without it predate the header and are read by
+/// [`migrate_format`].
+pub fn deserialize(bytes: &[u8]) -> SlvrResult<Bytecode> {
+    let Some(rest) = bytes.strip_prefix(&BYTECODE_MAGIC) else {
+        return migrate_format(bytes);
+    };
+
+    match rest {
+        [BYTECODE_FORMAT_VERSION, payload @ ..] => serde_json::from_slice(payload).map_err(|e| {
+            SlvrError::compilation(format!(
+                "corrupt bytecode (format {}): {}",
+                BYTECODE_FORMAT_VERSION, e
+            ))
+        }),
+        [version, ..] => Err(SlvrError::compilation(format!(
+            "bytecode format {} does not match supported format {}; recompile the contract",
+            version, BYTECODE_FORMAT_VERSION
+        ))),
+        [] => Err(SlvrError::compilation(
+            "bytecode header has no format version",
+        )),
+    }
+}
+
+/// Decode bytecode written before the magic number was introduced.
+///
+/// Version 0 is the unversioned JSON written before any header existed; it is
+/// recognised by its leading `{`. Versions 1 and 2 start with a bare version
+/// byte. Versions 0 and 1 wrote decimal literals as JSON floats, which still
+/// decode. Any other version is rejected.
+pub fn migrate_format(bytes: &[u8]) -> SlvrResult<Bytecode> {
+    let (version, payload) = match bytes {
+        [b'{', ..] => (0, bytes),
+        [version, payload @ ..] => (*version, payload),
+        [] => return Err(SlvrError::compilation("bytecode is empty")),
+    };
+
+    match version {
+        0..=LAST_HEADERLESS_VERSION => serde_json::from_slice(payload).map_err(|e| {
+            SlvrError::compilation(format!("corrupt bytecode (format {}): {}", version, e))
+        }),
+        _ => Err(SlvrError::compilation(format!(
+            "unsupported bytecode format {} (expected {})",
+            version, BYTECODE_FORMAT_VERSION
+        ))),
     }
 }
 
@@ -235,6 +363,33 @@ mod tests {
         assert!(matches!(bytecode.get(0), Some(Instruction::PushInt(42))));
     }
 
+    #[test]
+    fn test_disassemble_program() {
+        let source = "defconst limit: integer = 10\ndefconst big: boolean = limit > 5";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+        let bytecode = crate::compiler::Compiler::new().compile(&program).unwrap();
+
+        let listing = disassemble(&bytecode.instructions);
+        let mnemonics: Vec<_> = listing
+            .lines()
+            .map(|line| line.split_whitespace().nth(1).unwrap())
+            .collect();
+        assert_eq!(
+            mnemonics,
+            [
+                "PUSH_INT",
+                "STORE_GLOBAL",
+                "LOAD_GLOBAL",
+                "PUSH_INT",
+                "GT",
+                "STORE_GLOBAL"
+            ]
+        );
+        assert_eq!(listing.lines().next(), Some("0000: PUSH_INT 10"));
+        assert_eq!(listing.lines().nth(4), Some("0004: GT"));
+        assert_eq!(bytecode.disassemble(), listing);
+    }
+
     #[test]
     fn test_bytecode_extend() {
         let mut bytecode1 = Bytecode::new();
@@ -247,6 +402,57 @@ mod tests {
         assert_eq!(bytecode1.len(), 2);
     }
 
+    #[test]
+    fn test_bytecode_format_round_trip() {
+        let mut bytecode = Bytecode::new();
+        bytecode.push(Instruction::PushInt(42));
+        bytecode.push(Instruction::Return);
+
+        let bytes = serialize(&bytecode);
+        assert_eq!(bytes[..4], BYTECODE_MAGIC);
+        assert_eq!(bytes[4], BYTECODE_FORMAT_VERSION);
+        let decoded = deserialize(&bytes).unwrap();
+        assert_eq!(decoded.instructions, bytecode.instructions);
+        assert_eq!(bytecode.to_bytes(), bytes);
+
+        // Unversioned JSON from before the header is upgraded
+        let legacy = serde_json::to_vec(&bytecode).unwrap();
+        assert_eq!(deserialize(&legacy).unwrap().len(), 2);
+
+        // So is format 2, a bare version byte without the magic number
+        let mut v2 = vec![2];
+        v2.extend(serde_json::to_vec(&bytecode).unwrap());
+        assert_eq!(deserialize(&v2).unwrap().len(), 2);
+
+        // Format 1 stored decimal literals as floats
+        let mut v1 = vec![1];
+        v1.extend_from_slice(br#"{"instructions":[{"PushDecimal":0.1}]}"#);
+        match deserialize(&v1) {
+            Ok(decoded) => assert!(matches!(
+                decoded.instructions.first(),
+                Some(Instruction::PushDecimal(d)) if d.to_string() == "0.1"
+            )),
+            Err(e) => panic!("format 1 should decode: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_bytecode_format_rejects_bad_header() {
+        let mut bytes = serialize(&Bytecode::new());
+        bytes[4] = BYTECODE_FORMAT_VERSION + 1;
+        match deserialize(&bytes) {
+            Err(e) => assert!(e.to_string().contains("recompile the contract")),
+            Ok(_) => panic!("a bumped format version should be rejected"),
+        }
+
+        let mut truncated = serialize(&Bytecode::new());
+        truncated.truncate(7);
+        assert!(deserialize(&truncated).is_err());
+        assert!(deserialize(&BYTECODE_MAGIC).is_err());
+        assert!(deserialize(&[]).is_err());
+        assert!(deserialize(&[LAST_HEADERLESS_VERSION + 1]).is_err());
+    }
+
     #[test]
     fn test_instruction_display() {
         let instr = Instruction::PushInt(42);
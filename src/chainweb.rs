@@ -4,11 +4,12 @@
 //! cross-chain messaging, atomic swaps, and chain synchronization.
 
 use crate::error::{SlvrError, SlvrResult};
+use crate::value::Value;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha512};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
@@ -39,6 +40,9 @@ pub struct ChainConfig {
     pub block_time_ms: u64,
     pub max_block_size: u64,
     pub consensus_type: ConsensusType,
+    /// Hex Ed25519 keys whose cross-chain proofs from this chain are accepted
+    #[serde(default)]
+    pub trusted_signers: Vec<String>,
 }
 
 /// Consensus type
@@ -220,14 +224,115 @@ pub enum TransactionStatus {
     Failed,
 }
 
+/// A pact that yielded on one chain, waiting to resume on another
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PactContinuation {
+    pub pact_id: String,
+    pub source_chain: ChainId,
+    pub target_chain: ChainId,
+    /// Step the pact resumes at on the target chain
+    pub step: usize,
+    /// Value handed to the resuming step
+    pub value: Value,
+}
+
+impl PactContinuation {
+    /// SHA-512 of the continuation's canonical JSON, the leaf its SPV proof commits to
+    pub fn hash(&self) -> SlvrResult<String> {
+        let bytes = crate::stdlib::json::canonical_bytes(self)?;
+        Ok(hex::encode(Sha512::digest(&bytes)))
+    }
+}
+
+/// Merkle root of a block recording `leaves`, hashed as in chain sync
+fn merkle_root(leaves: &[String]) -> String {
+    let mut hasher = Sha512::new();
+    for leaf in leaves {
+        hasher.update(leaf.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Whether a hex block hash, read as a little-endian integer from its first
+/// 16 bytes, is within the target for `difficulty`
+fn meets_difficulty(hash: &str, difficulty: u64) -> bool {
+    let Some(prefix) = hex::decode(hash)
+        .ok()
+        .and_then(|bytes| <[u8; 16]>::try_from(bytes.get(..16)?).ok())
+    else {
+        return false;
+    };
+    u128::from_le_bytes(prefix) <= u128::MAX / (difficulty as u128 + 1)
+}
+
+/// Error for a continuation that was resumed before
+fn already_resumed(continuation: &PactContinuation) -> SlvrError {
+    SlvrError::runtime(format!(
+        "Continuation of pact {} at step {} was already resumed",
+        continuation.pact_id, continuation.step
+    ))
+}
+
+/// Public key and signature preceding the data of a cross-chain proof
+const PROOF_HEADER_LEN: usize = 32 + 64;
+
+/// Largest cross-chain proof accepted
+const MAX_PROOF_LEN: usize = 2048;
+
+/// How far ahead of the local clock a cross-chain proof may be dated, in seconds
+const MAX_PROOF_CLOCK_SKEW_SECS: u64 = 300;
+
+/// Message a cross-chain proof signs: `source || target || data`
+fn proof_message(source_chain: ChainId, target_chain: ChainId, data: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(8 + data.len());
+    message.extend_from_slice(&source_chain.0.to_le_bytes());
+    message.extend_from_slice(&target_chain.0.to_le_bytes());
+    message.extend_from_slice(data);
+    message
+}
+
+/// Proof that a continuation was recorded in a commitment on its source chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpvProof {
+    /// Position of the commitment in the source chain's continuation log
+    pub block_height: u64,
+    pub block_hash: String,
+    pub continuation: PactContinuation,
+}
+
+/// Requirements a cross-chain proof must meet to verify
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossChainPolicy {
+    /// Blocks from the including block to the source chain's tip
+    pub min_confirmations: u64,
+    /// Oldest proof accepted, in seconds
+    pub max_proof_age_secs: u64,
+}
+
+impl Default for CrossChainPolicy {
+    fn default() -> Self {
+        Self {
+            min_confirmations: 6,
+            max_proof_age_secs: 86400,
+        }
+    }
+}
+
 /// Chainweb network
 pub struct ChainwebNetwork {
     chains: Arc<Mutex<HashMap<ChainId, ChainConfig>>>,
     blocks: Arc<Mutex<HashMap<ChainId, Vec<Block>>>>,
+    /// Commitments to yielded continuations, by source chain. They are kept
+    /// apart from `blocks` so recording one never shifts the heights or
+    /// parent links that peer sync and inclusion proofs rely on
+    continuations: Arc<Mutex<HashMap<ChainId, Vec<Block>>>>,
     transactions: Arc<Mutex<HashMap<String, ChainTransaction>>>,
     cross_chain_txs: Arc<Mutex<HashMap<String, CrossChainTransaction>>>,
     atomic_swaps: Arc<Mutex<HashMap<String, AtomicSwap>>>,
     peer_connections: Arc<Mutex<HashMap<ChainId, Vec<String>>>>,
+    /// Continuations already resumed, by pact id and step
+    resumed_continuations: Arc<Mutex<HashSet<(String, usize)>>>,
+    cross_chain_policy: CrossChainPolicy,
 }
 
 impl Default for ChainwebNetwork {
@@ -242,13 +347,27 @@ impl ChainwebNetwork {
         Self {
             chains: Arc::new(Mutex::new(HashMap::new())),
             blocks: Arc::new(Mutex::new(HashMap::new())),
+            continuations: Arc::new(Mutex::new(HashMap::new())),
             transactions: Arc::new(Mutex::new(HashMap::new())),
             cross_chain_txs: Arc::new(Mutex::new(HashMap::new())),
             atomic_swaps: Arc::new(Mutex::new(HashMap::new())),
             peer_connections: Arc::new(Mutex::new(HashMap::new())),
+            resumed_continuations: Arc::new(Mutex::new(HashSet::new())),
+            cross_chain_policy: CrossChainPolicy::default(),
         }
     }
 
+    /// Use `policy` when verifying cross-chain proofs
+    pub fn with_cross_chain_policy(mut self, policy: CrossChainPolicy) -> Self {
+        self.cross_chain_policy = policy;
+        self
+    }
+
+    /// Requirements cross-chain proofs are verified against
+    pub fn cross_chain_policy(&self) -> CrossChainPolicy {
+        self.cross_chain_policy
+    }
+
     /// Register chain
     pub fn register_chain(&self, config: ChainConfig) -> SlvrResult<()> {
         let chain_id = config.chain_id;
@@ -359,6 +478,7 @@ impl ChainwebNetwork {
             target_asset: String::new(),
             source_amount: 0,
             target_amount: 0,
+            hash_lock: None,
         }
     }
 
@@ -380,6 +500,81 @@ impl ChainwebNetwork {
         Ok(())
     }
 
+    /// Refund an atomic swap whose time lock has expired without it completing
+    pub fn refund_atomic_swap(&self, id: &str) -> SlvrResult<()> {
+        let mut swaps = self
+            .atomic_swaps
+            .lock()
+            .map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        let swap = swaps
+            .get_mut(id)
+            .ok_or_else(|| SlvrError::runtime(format!("Atomic swap {} not found", id)))?;
+
+        if matches!(
+            swap.status,
+            AtomicSwapStatus::Completed | AtomicSwapStatus::Refunded
+        ) {
+            return Err(SlvrError::runtime(format!(
+                "Atomic swap {} is already {}",
+                id, swap.status
+            )));
+        }
+        let now = Utc::now();
+        if now <= swap.time_lock {
+            return Err(SlvrError::runtime(format!(
+                "Atomic swap {} is time locked until {}",
+                id, swap.time_lock
+            )));
+        }
+
+        swap.status = AtomicSwapStatus::Refunded;
+        swap.completed_at = Some(now);
+        Ok(())
+    }
+
+    /// Redeem an atomic swap by revealing the secret behind its hash lock,
+    /// completing it
+    pub fn redeem_atomic_swap(&self, id: &str, secret: &[u8]) -> SlvrResult<()> {
+        use subtle::ConstantTimeEq;
+
+        let mut swaps = self
+            .atomic_swaps
+            .lock()
+            .map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        let swap = swaps
+            .get_mut(id)
+            .ok_or_else(|| SlvrError::runtime(format!("Atomic swap {} not found", id)))?;
+
+        if matches!(
+            swap.status,
+            AtomicSwapStatus::Completed | AtomicSwapStatus::Refunded | AtomicSwapStatus::Failed
+        ) {
+            return Err(SlvrError::runtime(format!(
+                "Atomic swap {} is already {}",
+                id, swap.status
+            )));
+        }
+        let now = Utc::now();
+        if now > swap.time_lock {
+            return Err(SlvrError::runtime(format!(
+                "Atomic swap {} time lock expired at {}",
+                id, swap.time_lock
+            )));
+        }
+        let matches = hex::decode(&swap.hash_lock)
+            .is_ok_and(|hash_lock| bool::from(Sha256::digest(secret).as_slice().ct_eq(&hash_lock)));
+        if !matches {
+            return Err(SlvrError::runtime(format!(
+                "Secret does not match the hash lock of atomic swap {}",
+                id
+            )));
+        }
+
+        swap.status = AtomicSwapStatus::Completed;
+        swap.completed_at = Some(now);
+        Ok(())
+    }
+
     /// Connect peer
     pub fn connect_peer(&self, chain_id: ChainId, peer_address: String) -> SlvrResult<()> {
         let mut peers = self.peer_connections.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
@@ -401,26 +596,24 @@ impl ChainwebNetwork {
         let chains = self.chains.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
         let chain = chains
             .get(&chain_id)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Chain {} not found", chain_id),
-            })?
+            .ok_or_else(|| SlvrError::runtime(format!("Chain {} not found", chain_id)))?
             .clone();
         drop(chains);
 
-        // 2. Get current block height
-        let blocks = self.blocks.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
-        let current_height = blocks.get(&chain_id).map(|b: &Vec<Block>| b.len() as u64).unwrap_or(0);
-        drop(blocks);
-
-        // 3. Connect to peers and request missing blocks
+        // 2. Connect to peers and request missing blocks
         let peers = self.get_peers(chain_id)?;
         if peers.is_empty() {
             tracing::warn!("No peers available for chain {} synchronization", chain_id);
             return Ok(());
         }
 
-        // 4. For each peer, request blocks starting from current_height
+        // 3. For each peer, request blocks starting from the current height,
+        // which advances as earlier peers supply blocks
         for peer in peers {
+            let blocks = self.blocks.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+            let current_height = blocks.get(&chain_id).map(|b: &Vec<Block>| b.len() as u64).unwrap_or(0);
+            drop(blocks);
+
             // REAL IMPLEMENTATION: Full peer synchronization with network communication
             match self.sync_with_peer(chain_id, &chain, current_height, &peer) {
                 Ok(blocks_synced) => {
@@ -489,15 +682,17 @@ impl ChainwebNetwork {
                     Ok(mut addrs) => match addrs.next() {
                         Some(addr) => addr,
                         None => {
-                            return Err(SlvrError::RuntimeError {
-                                message: format!("Failed to resolve peer address: {}", peer),
-                            });
+                            return Err(SlvrError::runtime(format!(
+                                "Failed to resolve peer address: {}",
+                                peer
+                            )));
                         }
                     },
                     Err(e) => {
-                        return Err(SlvrError::RuntimeError {
-                            message: format!("Invalid peer address '{}': {}", peer, e),
-                        });
+                        return Err(SlvrError::runtime(format!(
+                            "Invalid peer address '{}': {}",
+                            peer, e
+                        )));
                     }
                 }
             }
@@ -512,9 +707,7 @@ impl ChainwebNetwork {
                 }
                 Err(e) => {
                     tracing::warn!("Failed to connect to peer {}: {}", peer_addr, e);
-                    return Err(SlvrError::RuntimeError {
-                        message: format!("Connection failed: {}", e),
-                    });
+                    return Err(SlvrError::runtime(format!("Connection failed: {}", e)));
                 }
             };
 
@@ -540,9 +733,10 @@ impl ChainwebNetwork {
             let request_str = match serde_json::to_string(&json_request) {
                 Ok(s) => s,
                 Err(e) => {
-                    return Err(SlvrError::RuntimeError {
-                        message: format!("Failed to serialize JSON-RPC request: {}", e),
-                    });
+                    return Err(SlvrError::runtime(format!(
+                        "Failed to serialize JSON-RPC request: {}",
+                        e
+                    )));
                 }
             };
 
@@ -550,12 +744,10 @@ impl ChainwebNetwork {
             if let Err(e) = stream.write_all(request_str.as_bytes()) {
                 retry_count += 1;
                 if retry_count >= MAX_RETRIES {
-                    return Err(SlvrError::RuntimeError {
-                        message: format!(
-                            "Failed to send request after {} retries: {}",
-                            MAX_RETRIES, e
-                        ),
-                    });
+                    return Err(SlvrError::runtime(format!(
+                        "Failed to send request after {} retries: {}",
+                        MAX_RETRIES, e
+                    )));
                 }
                 tracing::warn!("Failed to send request (retry {}): {}", retry_count, e);
                 // Exponential backoff
@@ -574,12 +766,10 @@ impl ChainwebNetwork {
                 Err(e) => {
                     retry_count += 1;
                     if retry_count >= MAX_RETRIES {
-                        return Err(SlvrError::RuntimeError {
-                            message: format!(
-                                "Failed to read response after {} retries: {}",
-                                MAX_RETRIES, e
-                            ),
-                        });
+                        return Err(SlvrError::runtime(format!(
+                            "Failed to read response after {} retries: {}",
+                            MAX_RETRIES, e
+                        )));
                     }
                     tracing::warn!("Failed to read response (retry {}): {}", retry_count, e);
                     std::thread::sleep(Duration::from_millis(100 * (2_u64.pow(retry_count - 1))));
@@ -591,18 +781,20 @@ impl ChainwebNetwork {
             let response_str = match String::from_utf8(buffer[..bytes_read].to_vec()) {
                 Ok(s) => s,
                 Err(e) => {
-                    return Err(SlvrError::RuntimeError {
-                        message: format!("Invalid UTF-8 in response: {}", e),
-                    });
+                    return Err(SlvrError::runtime(format!(
+                        "Invalid UTF-8 in response: {}",
+                        e
+                    )));
                 }
             };
 
             let response: serde_json::Value = match serde_json::from_str(&response_str) {
                 Ok(v) => v,
                 Err(e) => {
-                    return Err(SlvrError::RuntimeError {
-                        message: format!("Failed to parse JSON-RPC response: {}", e),
-                    });
+                    return Err(SlvrError::runtime(format!(
+                        "Failed to parse JSON-RPC response: {}",
+                        e
+                    )));
                 }
             };
 
@@ -687,43 +879,8 @@ impl ChainwebNetwork {
                     continue;
                 }
 
-                // 10. Verify SHA-512 proof-of-work meets difficulty target
-                let block_header = format!(
-                    "{}:{}:{}:{}:{}",
-                    parent_hash, merkle_root, timestamp, difficulty, nonce
-                );
-
-                let mut hasher = sha2::Sha512::new();
-                hasher.update(block_header.as_bytes());
-                let pow_hash = hasher.finalize();
-
-                // Convert hash to integer for difficulty comparison
-                let hash_int = u128::from_le_bytes([
-                    pow_hash[0],
-                    pow_hash[1],
-                    pow_hash[2],
-                    pow_hash[3],
-                    pow_hash[4],
-                    pow_hash[5],
-                    pow_hash[6],
-                    pow_hash[7],
-                    pow_hash[8],
-                    pow_hash[9],
-                    pow_hash[10],
-                    pow_hash[11],
-                    pow_hash[12],
-                    pow_hash[13],
-                    pow_hash[14],
-                    pow_hash[15],
-                ]);
-
-                let target = u128::MAX / (difficulty as u128 + 1);
-                if hash_int > target {
-                    tracing::warn!("Block {} failed PoW verification", block_height);
-                    continue;
-                }
-
-                // 11. Validate merkle root against transactions
+                // 10. Validate merkle root against transactions, which must
+                // all be encoded transactions so nothing is dropped unhashed
                 let transactions = match block_json.get("transactions").and_then(|t| t.as_array()) {
                     Some(txs) => txs,
                     None => {
@@ -731,15 +888,24 @@ impl ChainwebNetwork {
                         continue;
                     }
                 };
+                let transactions = transactions
+                    .iter()
+                    .map(|tx| {
+                        tx.as_str().ok_or_else(|| {
+                            SlvrError::runtime(format!(
+                                "Block {} has a malformed transaction: {}",
+                                block_height, tx
+                            ))
+                        })
+                    })
+                    .collect::<SlvrResult<Vec<_>>>()?;
 
                 // Calculate merkle root from transactions
                 let mut tx_hashes = Vec::new();
-                for tx in transactions {
-                    if let Some(tx_str) = tx.as_str() {
-                        let mut hasher = sha2::Sha512::new();
-                        hasher.update(tx_str.as_bytes());
-                        tx_hashes.push(hex::encode(hasher.finalize()));
-                    }
+                for tx_str in &transactions {
+                    let mut hasher = sha2::Sha512::new();
+                    hasher.update(tx_str.as_bytes());
+                    tx_hashes.push(hex::encode(hasher.finalize()));
                 }
 
                 let calculated_merkle = if !tx_hashes.is_empty() {
@@ -757,7 +923,7 @@ impl ChainwebNetwork {
                     continue;
                 }
 
-                // 12. Check block timestamp is reasonable (within 2 hours of now)
+                // 11. Check block timestamp is reasonable (within 2 hours of now)
                 let now = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap_or_default()
@@ -768,45 +934,77 @@ impl ChainwebNetwork {
                     continue;
                 }
 
-                // 13. Verify parent hash matches previous block (if not genesis)
-                if block_height > 0 && blocks_synced > 0 {
-                    // PRODUCTION IMPLEMENTATION: Verify parent hash against stored previous block
-                    // This ensures the blockchain is properly linked
-
-                    // Get the previous block from our local blockchain
-                    let local_blocks = self.blocks.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
-                    if let Some(prev_blocks) = local_blocks.get(&chain_id) {
-                        if !prev_blocks.is_empty() {
-                            let prev_block = &prev_blocks[prev_blocks.len() - 1];
-
-                            // Verify parent hash matches
-                            if prev_block.hash != parent_hash {
-                                tracing::warn!(
-                                    "Block {} parent hash mismatch: expected {}, got {}",
-                                    block_height,
-                                    &prev_block.hash[..16],
-                                    &parent_hash[..16]
-                                );
-                                continue;
-                            }
-
-                            // Verify block height is sequential
-                            if block_height != prev_blocks.len() as u64 {
-                                tracing::warn!(
-                                    "Block {} height mismatch: expected {}, got {}",
-                                    block_height,
-                                    prev_blocks.len(),
-                                    block_height
-                                );
-                                continue;
-                            }
-                        }
+                // 12. Decode the transactions the merkle root was checked against
+                let decoded: Result<Vec<ChainTransaction>, _> =
+                    transactions.iter().map(|tx| serde_json::from_str(tx)).collect();
+                let transactions = match decoded {
+                    Ok(txs) => txs,
+                    Err(e) => {
+                        tracing::warn!("Block {} has an undecodable transaction: {}", block_height, e);
+                        continue;
+                    }
+                };
+
+                let mut block = Block {
+                    header: BlockHeader {
+                        chain_id,
+                        height: block_height,
+                        timestamp: DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default(),
+                        parent_hash: parent_hash.to_string(),
+                        merkle_root: merkle_root.to_string(),
+                        nonce,
+                        difficulty,
+                        miner: block_json
+                            .get("miner")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                    },
+                    transactions,
+                    hash: String::new(),
+                };
+
+                // 13. The hash the peer sent must be the header's own hash, and
+                // that hash must meet the difficulty target
+                block.hash = block.calculate_hash();
+                if block.hash != block_hash {
+                    tracing::warn!("Block {} hash does not match its header", block_height);
+                    continue;
+                }
+                if !meets_difficulty(&block.hash, difficulty) {
+                    tracing::warn!("Block {} failed PoW verification", block_height);
+                    continue;
+                }
+
+                // 14. Block is valid - append it if it extends the local chain,
+                // checking and storing under one lock so the link cannot go stale
+                let mut local_blocks = self.blocks.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+                let chain_blocks = local_blocks.entry(chain_id).or_default();
+                if block_height != chain_blocks.len() as u64 {
+                    tracing::warn!(
+                        "Block {} height mismatch: expected {}, got {}",
+                        block_height,
+                        chain_blocks.len(),
+                        block_height
+                    );
+                    continue;
+                }
+                if let Some(prev_block) = chain_blocks.last() {
+                    if prev_block.hash != parent_hash {
+                        tracing::warn!(
+                            "Block {} parent hash mismatch: expected {}, got {}",
+                            block_height,
+                            &prev_block.hash[..prev_block.hash.len().min(16)],
+                            &parent_hash[..16]
+                        );
+                        continue;
                     }
                 }
+                chain_blocks.push(block);
+                drop(local_blocks);
 
-                // 14. Block is valid - add to blockchain atomically
                 tracing::debug!(
-                    "Validated block {} from peer (PoW verified, merkle root valid)",
+                    "Stored block {} from peer (PoW verified, merkle root valid)",
                     block_height
                 );
 
@@ -831,23 +1029,91 @@ impl ChainwebNetwork {
         Ok(blocks_synced)
     }
 
-    /// Verify cross-chain proof with real SHA-512 validation
+    /// Build a proof that transaction `tx_hash` (its id) is included on
+    /// `source_chain`, for `verify_cross_chain_proof` on `target_chain`.
+    ///
+    /// The proof is `[pubkey(32) | sig(64) | data]` where `data` is the
+    /// including block's height and the current time, both little-endian
+    /// `u64`s, followed by the transaction id. The signature covers
+    /// `source || target || data`. The proof only verifies if `signing_key`
+    /// is in the source chain's `trusted_signers`.
+    pub fn generate_cross_chain_proof(
+        &self,
+        source_chain: ChainId,
+        target_chain: ChainId,
+        tx_hash: &str,
+        signing_key: &ed25519_dalek::SigningKey,
+    ) -> SlvrResult<Vec<u8>> {
+        use ed25519_dalek::Signer;
+
+        let blocks = self.blocks.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        let height = blocks
+            .get(&source_chain)
+            .and_then(|chain_blocks| {
+                chain_blocks
+                    .iter()
+                    .position(|block| block.transactions.iter().any(|tx| tx.id == tx_hash))
+            })
+            .ok_or_else(|| {
+                SlvrError::runtime(format!(
+                    "Transaction {} not found on chain {}",
+                    tx_hash, source_chain
+                ))
+            })?;
+        drop(blocks);
+
+        let timestamp = Utc::now().timestamp().max(0) as u64;
+        let mut data = Vec::new();
+        data.extend_from_slice(&(height as u64).to_le_bytes());
+        data.extend_from_slice(&timestamp.to_le_bytes());
+        data.extend_from_slice(tx_hash.as_bytes());
+
+        let signature = signing_key.sign(&proof_message(source_chain, target_chain, &data));
+
+        let mut proof = Vec::with_capacity(PROOF_HEADER_LEN + data.len());
+        proof.extend_from_slice(&signing_key.verifying_key().to_bytes());
+        proof.extend_from_slice(&signature.to_bytes());
+        proof.extend_from_slice(&data);
+        if proof.len() > MAX_PROOF_LEN {
+            return Err(SlvrError::runtime(format!(
+                "Proof too large: {} bytes (max {})",
+                proof.len(),
+                MAX_PROOF_LEN
+            )));
+        }
+        Ok(proof)
+    }
+
+    /// Verify a proof from `generate_cross_chain_proof`.
+    ///
+    /// The proof is laid out without overlap as
+    /// `pubkey(32) | sig(64) | block_height(8) | timestamp(8) | tx_id`, and
+    /// `sig` is the signer's Ed25519 signature over
+    /// `source_le(4) || target_le(4) || block_height | timestamp | tx_id`.
+    ///
+    /// The transaction must sit in the named block of the source chain with
+    /// enough confirmations, and both chains must share a consensus type.
+    /// `pubkey` must be one of the source chain's
+    /// `ChainConfig::trusted_signers` and `sig` must be a valid Ed25519
+    /// signature by that key; a valid signature by any other key is rejected.
+    /// The timestamp may lead the local clock by at most
+    /// `MAX_PROOF_CLOCK_SKEW_SECS` and trail it by at most the
+    /// `CrossChainPolicy`'s maximum proof age.
     pub fn verify_cross_chain_proof(
         &self,
         source_chain: ChainId,
         target_chain: ChainId,
         proof: &[u8],
     ) -> SlvrResult<bool> {
-        // REAL IMPLEMENTATION: Full cross-chain proof verification with SHA-512
+        use ed25519_dalek::{Signature, VerifyingKey};
 
         // 1. Validate proof format and size
-        if proof.is_empty() {
-            tracing::warn!("Empty proof provided for cross-chain verification");
+        if proof.len() > MAX_PROOF_LEN {
+            tracing::warn!("Proof too large: {} bytes (max {})", proof.len(), MAX_PROOF_LEN);
             return Ok(false);
         }
-
-        if proof.len() > 2048 {
-            tracing::warn!("Proof too large: {} bytes (max 2048)", proof.len());
+        if proof.len() <= PROOF_HEADER_LEN + 16 {
+            tracing::warn!("Proof too small: {} bytes", proof.len());
             return Ok(false);
         }
 
@@ -855,151 +1121,83 @@ impl ChainwebNetwork {
         let chains = self.chains.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
         let source = chains
             .get(&source_chain)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Source chain {} not found", source_chain),
-            })?
+            .ok_or_else(|| SlvrError::runtime(format!("Source chain {} not found", source_chain)))?
             .clone();
         let target = chains
             .get(&target_chain)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Target chain {} not found", target_chain),
-            })?
+            .ok_or_else(|| SlvrError::runtime(format!("Target chain {} not found", target_chain)))?
             .clone();
         drop(chains);
 
-        // 3. Verify proof structure: [signature (64 bytes) | data]
-        if proof.len() < 64 {
-            tracing::warn!("Proof too small: {} bytes (minimum 64)", proof.len());
+        // 3. Split the proof: [pubkey(32) | sig(64) | data]
+        let (public_key, rest) = proof.split_at(32);
+        let (signature, proof_data) = rest.split_at(64);
+        let le_u64 = |bytes: &[u8]| {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(bytes);
+            u64::from_le_bytes(word)
+        };
+        let block_height = le_u64(&proof_data[0..8]);
+        let timestamp = le_u64(&proof_data[8..16]);
+        let Ok(tx_id) = std::str::from_utf8(&proof_data[16..]) else {
+            tracing::warn!("Proof transaction id is not UTF-8");
             return Ok(false);
-        }
-
-        let signature = &proof[0..64];
-        let proof_data = &proof[64..];
-
-        // 4. Verify proof data contains valid transaction
-        // Use SHA-512 for hashing (production-grade)
-        let mut hasher = Sha512::new();
-        hasher.update(proof_data);
-        let proof_hash = hasher.finalize();
-        let proof_hash_hex = format!("{:x}", proof_hash);
+        };
 
-        // 5. Check if proof hash exists in source chain blocks
+        // 4. Check the transaction is in the named block of the source chain
         let blocks = self.blocks.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
-        let empty_blocks = vec![];
-        let source_blocks = blocks.get(&source_chain).unwrap_or(&empty_blocks);
-
-        let mut proof_exists = false;
-        let mut proof_block_height = 0u64;
-
-        for (idx, block) in source_blocks.iter().enumerate() {
-            // Verify block hash matches proof
-            if block.hash == proof_hash_hex {
-                proof_exists = true;
-                proof_block_height = idx as u64;
-                break;
-            }
-
-            // Also check if proof data is in block transactions
-            for tx in &block.transactions {
-                let mut tx_hasher = Sha512::new();
-                tx_hasher.update(tx.id.as_bytes());
-                if format!("{:x}", tx_hasher.finalize()) == proof_hash_hex {
-                    proof_exists = true;
-                    proof_block_height = idx as u64;
-                    break;
-                }
-            }
-
-            if proof_exists {
-                break;
-            }
-        }
+        let source_blocks = blocks.get(&source_chain).map(Vec::as_slice).unwrap_or_default();
+        let included = source_blocks
+            .get(block_height as usize)
+            .is_some_and(|block| block.transactions.iter().any(|tx| tx.id == tx_id));
+        let source_height = source_blocks.len() as u64;
+        drop(blocks);
 
-        if !proof_exists {
-            tracing::warn!("Proof hash not found in source chain {}", source_chain);
+        if !included {
+            tracing::warn!("Transaction {} not found in block {} of chain {}", tx_id, block_height, source_chain);
             return Ok(false);
         }
 
-        // 6. Verify confirmation count (must have at least 6 confirmations)
-        let source_height = source_blocks.len() as u64;
-        let confirmations = source_height.saturating_sub(proof_block_height);
-
-        if confirmations < 6 {
+        // 5. Verify confirmation count
+        let policy = self.cross_chain_policy;
+        let confirmations = source_height.saturating_sub(block_height);
+        if confirmations < policy.min_confirmations {
             tracing::warn!(
-                "Insufficient confirmations: {} (required: 6) for chain {}",
+                "Insufficient confirmations: {} (required: {}) for chain {}",
                 confirmations,
+                policy.min_confirmations,
                 source_chain
             );
             return Ok(false);
         }
 
-        // 7. PRODUCTION IMPLEMENTATION: Verify signature using 512-bit schemes with SHA-512
-        // This is real cryptographic signature verification for quantum-resistant schemes
-
-        // Signature size validation for 512-bit schemes:
-        // Secp512r1: 132 bytes (66-byte key)
-        // SPHINCS+: 49,856 bytes (variable, typically ~49KB)
-        // Dilithium3: 3,366 bytes
-        // For cross-chain verification, we accept variable-length signatures
-        if signature.is_empty() || signature.len() > 50000 {
-            tracing::warn!(
-                "Invalid signature length: {} (expected 1-50000 bytes for 512-bit schemes)",
-                signature.len()
-            );
+        // 6. Verify the Ed25519 signature over source || target || data, made
+        // by a signer the source chain trusts
+        let signer = hex::encode(public_key);
+        if !source
+            .trusted_signers
+            .iter()
+            .any(|trusted| trusted.eq_ignore_ascii_case(&signer))
+        {
+            tracing::warn!("Proof signer {} is not trusted by chain {}", signer, source_chain);
             return Ok(false);
         }
-
-        // Verify signature is not all zeros (basic sanity check)
-        if signature.iter().all(|&b| b == 0) {
-            tracing::warn!("Invalid signature: all zeros");
+        let mut key = [0u8; 32];
+        key.copy_from_slice(public_key);
+        let Ok(key) = VerifyingKey::from_bytes(&key) else {
+            tracing::warn!("Invalid public key in cross-chain proof");
             return Ok(false);
-        }
-
-        // Extract public key from proof (variable length for 512-bit schemes)
-        // Secp512r1: 66 bytes
-        // SPHINCS+: 32 bytes
-        // Dilithium3: 1312 bytes
-        if proof.len() < 32 {
-            tracing::warn!("Proof too short for public key extraction (minimum 32 bytes)");
+        };
+        let Ok(signature) = Signature::from_slice(signature) else {
+            tracing::warn!("Invalid signature in cross-chain proof");
+            return Ok(false);
+        };
+        if let Err(e) = key.verify_strict(&proof_message(source_chain, target_chain, proof_data), &signature) {
+            tracing::warn!("Signature verification failed: {}", e);
             return Ok(false);
         }
 
-        // For cross-chain verification, extract public key (first 66 bytes for Secp512r1)
-        let public_key_len = std::cmp::min(66, proof.len());
-        let public_key_bytes = &proof[..public_key_len];
-
-        // Reconstruct the message that was signed
-        // Format: source_chain_id || target_chain_id || proof_data
-        let mut message = Vec::new();
-        message.extend_from_slice(&source_chain.0.to_le_bytes());
-        message.extend_from_slice(&target_chain.0.to_le_bytes());
-        message.extend_from_slice(&proof[32..]);
-
-        // Verify using SHA-512 HMAC (512-bit cryptography)
-        use hmac::{Hmac, Mac};
-        use sha2::Sha512;
-
-        type HmacSha512 = Hmac<Sha512>;
-
-        let mut mac =
-            HmacSha512::new_from_slice(public_key_bytes).map_err(|_| SlvrError::RuntimeError {
-                message: "Invalid public key format for HMAC".to_string(),
-            })?;
-
-        mac.update(&message);
-
-        // Verify the signature matches the computed HMAC
-        match mac.verify_slice(signature) {
-            Ok(_) => {
-                tracing::debug!("Cross-chain proof signature verified successfully");
-            }
-            Err(e) => {
-                tracing::warn!("Signature verification failed: {}", e);
-                return Ok(false);
-            }
-        }
-
-        // 8. Verify target chain can execute this proof
+        // 7. Verify target chain can execute this proof
         // Check if both chains have compatible consensus types
         let compatible = source.consensus_type == target.consensus_type;
 
@@ -1012,49 +1210,155 @@ impl ChainwebNetwork {
             return Ok(false);
         }
 
-        // 9. Verify proof timestamp is recent (within 24 hours)
-        if proof_data.len() >= 16 {
-            let timestamp_bytes = &proof_data[8..16];
-            let timestamp = u64::from_le_bytes([
-                timestamp_bytes[0],
-                timestamp_bytes[1],
-                timestamp_bytes[2],
-                timestamp_bytes[3],
-                timestamp_bytes[4],
-                timestamp_bytes[5],
-                timestamp_bytes[6],
-                timestamp_bytes[7],
-            ]);
-
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
-
-            let age = now.saturating_sub(timestamp);
-            if age > 86400 {
-                // 24 hours
-                tracing::warn!("Proof too old: {} seconds", age);
-                return Ok(false);
-            }
+        // 8. Verify proof timestamp is recent
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        if timestamp > now.saturating_add(MAX_PROOF_CLOCK_SKEW_SECS) {
+            tracing::warn!("Proof dated {} seconds in the future", timestamp - now);
+            return Ok(false);
+        }
+        let age = now.saturating_sub(timestamp);
+        if age > policy.max_proof_age_secs {
+            tracing::warn!("Proof too old: {} seconds", age);
+            return Ok(false);
         }
 
         tracing::info!(
-            "Cross-chain proof verified: source={}, target={}, confirmations={}, hash={}",
+            "Cross-chain proof verified: source={}, target={}, confirmations={}, tx={}",
             source_chain,
             target_chain,
             confirmations,
-            &proof_hash_hex[..16]
+            tx_id
         );
 
         Ok(true)
     }
 
+    /// Record a pact continuation in a new commitment on its source chain.
+    ///
+    /// Commitments form their own hash-linked log per chain; the mined
+    /// blocks of the chain are left untouched.
+    pub fn record_continuation(&self, continuation: PactContinuation) -> SlvrResult<SpvProof> {
+        for chain in [continuation.source_chain, continuation.target_chain] {
+            if self.get_chain(chain)?.is_none() {
+                return Err(SlvrError::runtime(format!("Chain {} not found", chain)));
+            }
+        }
+
+        let mut commitments = self
+            .continuations
+            .lock()
+            .map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        let chain_blocks = commitments.entry(continuation.source_chain).or_default();
+        let mut block = Block {
+            header: BlockHeader {
+                chain_id: continuation.source_chain,
+                height: chain_blocks.len() as u64,
+                timestamp: Utc::now(),
+                parent_hash: chain_blocks.last().map(|b| b.hash.clone()).unwrap_or_default(),
+                merkle_root: merkle_root(&[continuation.hash()?]),
+                nonce: 0,
+                difficulty: 0,
+                miner: String::new(),
+            },
+            transactions: Vec::new(),
+            hash: String::new(),
+        };
+        block.hash = block.calculate_hash();
+
+        let proof = SpvProof {
+            block_height: block.header.height,
+            block_hash: block.hash.clone(),
+            continuation,
+        };
+        chain_blocks.push(block);
+        Ok(proof)
+    }
+
+    /// Check that `proof` names a commitment on its source chain whose merkle
+    /// root commits to the continuation
+    pub fn verify_continuation(&self, proof: &SpvProof) -> SlvrResult<bool> {
+        let continuation = &proof.continuation;
+        let commitments = self
+            .continuations
+            .lock()
+            .map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        let block = commitments
+            .get(&continuation.source_chain)
+            .and_then(|chain_blocks| chain_blocks.get(proof.block_height as usize));
+        Ok(match block {
+            Some(block) => {
+                block.hash == proof.block_hash
+                    && block.calculate_hash() == block.hash
+                    && block.header.merkle_root == merkle_root(&[continuation.hash()?])
+            }
+            None => false,
+        })
+    }
+
+    /// Check that `proof` may resume its continuation on `chain`: it must
+    /// target that chain, verify, and not have been resumed already
+    pub fn check_continuation(&self, chain: ChainId, proof: &SpvProof) -> SlvrResult<PactContinuation> {
+        let continuation = &proof.continuation;
+        if continuation.target_chain != chain {
+            return Err(SlvrError::runtime(format!(
+                "Continuation of pact {} targets chain {}, not {}",
+                continuation.pact_id, continuation.target_chain, chain
+            )));
+        }
+        if !self.verify_continuation(proof)? {
+            return Err(SlvrError::runtime(format!(
+                "Invalid SPV proof for pact {}",
+                continuation.pact_id
+            )));
+        }
+
+        let resumed = self
+            .resumed_continuations
+            .lock()
+            .map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        if resumed.contains(&(continuation.pact_id.clone(), continuation.step)) {
+            return Err(already_resumed(continuation));
+        }
+        Ok(continuation.clone())
+    }
+
+    /// Check `proof` as `check_continuation` does and consume it, so each
+    /// continuation resumes exactly once.
+    ///
+    /// The check and the consumption happen under one lock, so of two
+    /// callers racing on the same continuation only one gets it.
+    pub fn take_continuation(&self, chain: ChainId, proof: &SpvProof) -> SlvrResult<PactContinuation> {
+        let continuation = self.check_continuation(chain, proof)?;
+        let mut resumed = self.resumed_continuations.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        if !resumed.insert((continuation.pact_id.clone(), continuation.step)) {
+            return Err(already_resumed(&continuation));
+        }
+        Ok(continuation)
+    }
+
+    /// Give back a continuation taken by `take_continuation` whose step did
+    /// not run, so it can be resumed again
+    pub fn release_continuation(&self, continuation: &PactContinuation) -> SlvrResult<()> {
+        let mut resumed = self.resumed_continuations.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        resumed.remove(&(continuation.pact_id.clone(), continuation.step));
+        Ok(())
+    }
+
     /// Generate hash lock for atomic swap
     pub fn generate_hash_lock() -> String {
-        let mut hasher = Sha512::new();
-        hasher.update(Uuid::new_v4().to_string().as_bytes());
-        format!("{:x}", hasher.finalize())
+        Self::generate_secret_and_hash_lock().1
+    }
+
+    /// Generate a random 32-byte secret and its hash lock, the hex SHA-256
+    /// of the secret, for an atomic swap redeemed with that secret
+    pub fn generate_secret_and_hash_lock() -> (Vec<u8>, String) {
+        let secret = rand::random::<[u8; 32]>().to_vec();
+        let hash_lock = hex::encode(Sha256::digest(&secret));
+        (secret, hash_lock)
     }
 
     /// Get network statistics
@@ -1090,6 +1394,79 @@ pub struct NetworkStats {
     pub total_atomic_swaps: u64,
 }
 
+/// Builder for AtomicSwap - real production-grade builder pattern
+pub struct AtomicSwapBuilder {
+    initiator: String,
+    participant: String,
+    source_chain: ChainId,
+    target_chain: ChainId,
+    source_asset: String,
+    target_asset: String,
+    source_amount: u64,
+    target_amount: u64,
+    hash_lock: Option<String>,
+}
+
+impl AtomicSwapBuilder {
+    pub fn with_source_chain(mut self, chain: ChainId) -> Self {
+        self.source_chain = chain;
+        self
+    }
+
+    pub fn with_target_chain(mut self, chain: ChainId) -> Self {
+        self.target_chain = chain;
+        self
+    }
+
+    pub fn with_source_asset(mut self, asset: String) -> Self {
+        self.source_asset = asset;
+        self
+    }
+
+    pub fn with_target_asset(mut self, asset: String) -> Self {
+        self.target_asset = asset;
+        self
+    }
+
+    pub fn with_source_amount(mut self, amount: u64) -> Self {
+        self.source_amount = amount;
+        self
+    }
+
+    pub fn with_target_amount(mut self, amount: u64) -> Self {
+        self.target_amount = amount;
+        self
+    }
+
+    /// Lock the swap with `hash_lock` instead of a freshly generated one
+    pub fn with_hash_lock(mut self, hash_lock: String) -> Self {
+        self.hash_lock = Some(hash_lock);
+        self
+    }
+
+    pub fn build(self) -> AtomicSwap {
+        // Generate hash lock using the helper function unless one was given
+        let hash_lock = self.hash_lock.unwrap_or_else(ChainwebNetwork::generate_hash_lock);
+
+        AtomicSwap {
+            id: Uuid::new_v4().to_string(),
+            initiator: self.initiator,
+            participant: self.participant,
+            source_chain: self.source_chain,
+            target_chain: self.target_chain,
+            source_asset: self.source_asset,
+            target_asset: self.target_asset,
+            source_amount: self.source_amount,
+            target_amount: self.target_amount,
+            status: AtomicSwapStatus::Initiated,
+            hash_lock,
+            time_lock: Utc::now() + chrono::Duration::hours(24),
+            created_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1110,6 +1487,7 @@ mod tests {
             block_time_ms: 30000,
             max_block_size: 1_000_000,
             consensus_type: ConsensusType::PoW,
+            trusted_signers: Vec::new(),
         };
         assert_eq!(config.chain_id.0, 0);
     }
@@ -1125,6 +1503,7 @@ mod tests {
             block_time_ms: 30000,
             max_block_size: 1_000_000,
             consensus_type: ConsensusType::PoW,
+            trusted_signers: Vec::new(),
         };
 
         network.register_chain(config).unwrap();
@@ -1158,7 +1537,7 @@ mod tests {
 
         let swap_id = swap.id.clone();
 
-        let mut swaps = network.atomic_swaps.lock().map_err(|e| SlvrError::LockError(format!("Failed to acquire lock: {}", e)))?;
+        let mut swaps = network.atomic_swaps.lock().unwrap();
         swaps.insert(swap_id.clone(), swap);
         drop(swaps);
 
@@ -1166,87 +1545,431 @@ mod tests {
         assert!(retrieved_swap.is_some());
     }
 
+    /// Store a new swap on `network` with the given time lock
+    fn stored_swap(network: &ChainwebNetwork, time_lock: DateTime<Utc>) -> String {
+        let mut swap = network
+            .initiate_atomic_swap_builder("alice".to_string(), "bob".to_string())
+            .with_source_chain(ChainId::new(0))
+            .with_target_chain(ChainId::new(1))
+            .build();
+        swap.time_lock = time_lock;
+        let id = swap.id.clone();
+        network.atomic_swaps.lock().unwrap().insert(id.clone(), swap);
+        id
+    }
+
     #[test]
-    fn test_network_stats() {
+    fn test_refund_atomic_swap_before_expiry_is_rejected() {
         let network = ChainwebNetwork::new();
-        let config = ChainConfig {
+        let id = stored_swap(&network, Utc::now() + chrono::Duration::hours(1));
+
+        assert!(network.refund_atomic_swap(&id).is_err());
+        let swap = network.get_atomic_swap(&id).unwrap().unwrap();
+        assert_eq!(swap.status, AtomicSwapStatus::Initiated);
+        assert!(swap.completed_at.is_none());
+    }
+
+    #[test]
+    fn test_refund_atomic_swap_after_expiry() {
+        let network = ChainwebNetwork::new();
+        let id = stored_swap(&network, Utc::now() - chrono::Duration::hours(1));
+
+        network.refund_atomic_swap(&id).unwrap();
+        let swap = network.get_atomic_swap(&id).unwrap().unwrap();
+        assert_eq!(swap.status, AtomicSwapStatus::Refunded);
+        assert!(swap.completed_at.is_some());
+
+        // A refunded or completed swap cannot be refunded again
+        assert!(network.refund_atomic_swap(&id).is_err());
+        let id = stored_swap(&network, Utc::now() - chrono::Duration::hours(1));
+        network.update_atomic_swap_status(&id, AtomicSwapStatus::Completed).unwrap();
+        assert!(network.refund_atomic_swap(&id).is_err());
+        assert!(network.refund_atomic_swap("missing").is_err());
+    }
+
+    #[test]
+    fn test_redeem_atomic_swap_with_secret() {
+        let network = ChainwebNetwork::new();
+        let (secret, hash_lock) = ChainwebNetwork::generate_secret_and_hash_lock();
+        let swap = network
+            .initiate_atomic_swap_builder("alice".to_string(), "bob".to_string())
+            .with_hash_lock(hash_lock)
+            .build();
+        let id = swap.id.clone();
+        network.atomic_swaps.lock().unwrap().insert(id.clone(), swap);
+
+        let mut wrong = secret.clone();
+        wrong[0] ^= 1;
+        assert!(network.redeem_atomic_swap(&id, &wrong).is_err());
+        assert_eq!(network.get_atomic_swap(&id).unwrap().unwrap().status, AtomicSwapStatus::Initiated);
+
+        network.redeem_atomic_swap(&id, &secret).unwrap();
+        let swap = network.get_atomic_swap(&id).unwrap().unwrap();
+        assert_eq!(swap.status, AtomicSwapStatus::Completed);
+        assert!(swap.completed_at.is_some());
+        assert!(network.redeem_atomic_swap(&id, &secret).is_err());
+    }
+
+    /// A peer block at `height` holding one transaction, linked to `parent`
+    fn peer_block(height: u64, parent: &str) -> serde_json::Value {
+        mined_peer_block(height, parent, 0)
+    }
+
+    /// Like `peer_block`, with the nonce searched until the block's hash
+    /// meets `difficulty`
+    fn mined_peer_block(height: u64, parent: &str, difficulty: u64) -> serde_json::Value {
+        let tx = ChainTransaction {
+            id: format!("tx-{}", height),
             chain_id: ChainId::new(0),
-            name: "Chain 0".to_string(),
-            network_id: "silverbitcoin".to_string(),
-            peer_count: 10,
-            block_time_ms: 30000,
-            max_block_size: 1_000_000,
-            consensus_type: ConsensusType::PoW,
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            value: height,
+            data: Vec::new(),
+            gas_limit: 0,
+            gas_price: 0,
+            nonce: height,
+            signature: String::new(),
+            status: TransactionStatus::Confirmed,
+            created_at: Utc::now(),
+            confirmed_at: None,
         };
+        let tx = serde_json::to_string(&tx).unwrap();
+        let mut wire = serde_json::json!({
+            "height": height,
+            "parent_hash": parent,
+            "timestamp": Utc::now().timestamp(),
+            "difficulty": difficulty,
+            "nonce": 0,
+            "merkle_root": merkle_root(&[hex::encode(Sha512::digest(&tx))]),
+            "transactions": [tx],
+        });
+        loop {
+            wire["hash"] = wire_hash(&wire).into();
+            if meets_difficulty(wire["hash"].as_str().unwrap(), difficulty) {
+                return wire;
+            }
+            wire["nonce"] = (wire["nonce"].as_u64().unwrap() + 1).into();
+        }
+    }
 
-        network.register_chain(config).unwrap();
-        let stats = network.get_network_stats().unwrap();
-        assert_eq!(stats.chain_count, 1);
+    /// Hash of the chain 0 header a peer block describes, as sync recomputes it
+    fn wire_hash(wire: &serde_json::Value) -> String {
+        Block {
+            header: BlockHeader {
+                chain_id: ChainId::new(0),
+                height: wire["height"].as_u64().unwrap(),
+                timestamp: DateTime::from_timestamp(wire["timestamp"].as_i64().unwrap(), 0).unwrap(),
+                parent_hash: wire["parent_hash"].as_str().unwrap().to_string(),
+                merkle_root: wire["merkle_root"].as_str().unwrap().to_string(),
+                nonce: wire["nonce"].as_u64().unwrap(),
+                difficulty: wire["difficulty"].as_u64().unwrap(),
+                miner: String::new(),
+            },
+            transactions: Vec::new(),
+            hash: String::new(),
+        }
+        .calculate_hash()
     }
-}
 
-/// Builder for AtomicSwap - real production-grade builder pattern
-pub struct AtomicSwapBuilder {
-    initiator: String,
-    participant: String,
-    source_chain: ChainId,
-    target_chain: ChainId,
-    source_asset: String,
-    target_asset: String,
-    source_amount: u64,
-    target_amount: u64,
-}
+    /// Stub peer answering a single getblocks request with `blocks`,
+    /// returning its address and the request it received
+    fn serve_blocks(
+        blocks: Vec<serde_json::Value>,
+    ) -> (String, std::thread::JoinHandle<serde_json::Value>) {
+        use std::io::{Read, Write};
 
-impl AtomicSwapBuilder {
-    pub fn with_source_chain(mut self, chain: ChainId) -> Self {
-        self.source_chain = chain;
-        self
+        let response = serde_json::json!({ "jsonrpc": "2.0", "result": blocks, "id": 1 });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let peer = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![0u8; 4096];
+            let n = stream.read(&mut request).unwrap();
+            let request: serde_json::Value = serde_json::from_slice(&request[..n]).unwrap();
+            stream.write_all(response.to_string().as_bytes()).unwrap();
+            request
+        });
+        (peer, server)
     }
 
-    pub fn with_target_chain(mut self, chain: ChainId) -> Self {
-        self.target_chain = chain;
-        self
+    #[test]
+    fn test_sync_stores_blocks_from_peer() {
+        let genesis = peer_block(0, &"0".repeat(128));
+        let next = peer_block(1, genesis["hash"].as_str().unwrap());
+        let (peer, server) = serve_blocks(vec![genesis, next]);
+
+        let network = ChainwebNetwork::new();
+        network
+            .register_chain(ChainConfig {
+                chain_id: ChainId::new(0),
+                name: "Chain 0".to_string(),
+                network_id: "silverbitcoin".to_string(),
+                peer_count: 1,
+                block_time_ms: 30000,
+                max_block_size: 1_000_000,
+                consensus_type: ConsensusType::PoW,
+                trusted_signers: Vec::new(),
+            })
+            .unwrap();
+        network.connect_peer(ChainId::new(0), peer).unwrap();
+        network.sync_chain_state(ChainId::new(0)).unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(request["params"], serde_json::json!([0, 0, 100]));
+
+        let blocks = network.get_blocks(ChainId::new(0)).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].header.height, 1);
+        assert_eq!(blocks[1].header.parent_hash, blocks[0].hash);
+        assert_eq!(blocks[1].transactions[0].id, "tx-1");
     }
 
-    pub fn with_source_asset(mut self, asset: String) -> Self {
-        self.source_asset = asset;
-        self
+    /// Key chain 0 of `proof_network` trusts to sign cross-chain proofs
+    fn trusted_key() -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&[7u8; 32])
     }
 
-    pub fn with_target_asset(mut self, asset: String) -> Self {
-        self.target_asset = asset;
-        self
+    /// `network` with PoW chains 0 and 1, and `blocks` blocks on chain 0
+    /// holding `tx-0`, `tx-1`, ...
+    fn proof_network(network: ChainwebNetwork, blocks: u64) -> ChainwebNetwork {
+        for id in [0, 1] {
+            network
+                .register_chain(ChainConfig {
+                    chain_id: ChainId::new(id),
+                    name: format!("Chain {}", id),
+                    network_id: "silverbitcoin".to_string(),
+                    peer_count: 1,
+                    block_time_ms: 30000,
+                    max_block_size: 1_000_000,
+                    consensus_type: ConsensusType::PoW,
+                    trusted_signers: vec![hex::encode(trusted_key().verifying_key().to_bytes())],
+                })
+                .unwrap();
+        }
+        let mut parent = "0".repeat(128);
+        for height in 0..blocks {
+            let wire = peer_block(height, &parent);
+            let tx = serde_json::from_str(wire["transactions"][0].as_str().unwrap()).unwrap();
+            let block = Block {
+                header: BlockHeader {
+                    chain_id: ChainId::new(0),
+                    height,
+                    timestamp: DateTime::from_timestamp(wire["timestamp"].as_i64().unwrap(), 0)
+                        .unwrap(),
+                    parent_hash: parent,
+                    merkle_root: wire["merkle_root"].as_str().unwrap().to_string(),
+                    nonce: 0,
+                    difficulty: 0,
+                    miner: String::new(),
+                },
+                transactions: vec![tx],
+                hash: wire["hash"].as_str().unwrap().to_string(),
+            };
+            parent = block.hash.clone();
+            network.add_block(ChainId::new(0), block).unwrap();
+        }
+
+        network
     }
 
-    pub fn with_source_amount(mut self, amount: u64) -> Self {
-        self.source_amount = amount;
-        self
+    /// Blocks chain 0 of a fresh network holds after syncing `blocks` from a peer
+    fn synced_blocks(blocks: Vec<serde_json::Value>) -> Vec<Block> {
+        let network = proof_network(ChainwebNetwork::new(), 0);
+        let (peer, server) = serve_blocks(blocks);
+        network.connect_peer(ChainId::new(0), peer).unwrap();
+        network.sync_chain_state(ChainId::new(0)).unwrap();
+        server.join().unwrap();
+        network.get_blocks(ChainId::new(0)).unwrap()
     }
 
-    pub fn with_target_amount(mut self, amount: u64) -> Self {
-        self.target_amount = amount;
-        self
+    #[test]
+    fn test_sync_recomputes_hashes_and_checks_pow() {
+        let genesis = "0".repeat(128);
+
+        // A hash that is not the header's own is refused
+        let mut forged = peer_block(0, &genesis);
+        forged["hash"] = hex::encode(Sha512::digest("block-0")).into();
+        assert!(synced_blocks(vec![forged]).is_empty());
+
+        // Mined blocks at a real difficulty are kept
+        let mined = mined_peer_block(0, &genesis, 255);
+        let blocks = synced_blocks(vec![mined.clone()]);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].hash, mined["hash"].as_str().unwrap());
+        assert_eq!(blocks[0].header.difficulty, 255);
+
+        // An honest hash that misses the target is not
+        let mut unmined = peer_block(0, &genesis);
+        unmined["difficulty"] = u64::MAX.into();
+        unmined["hash"] = wire_hash(&unmined).into();
+        assert!(synced_blocks(vec![unmined]).is_empty());
+
+        // A transaction that is not an encoded transaction stops the sync
+        let mut malformed = peer_block(1, mined["hash"].as_str().unwrap());
+        malformed["transactions"] = serde_json::json!([malformed["transactions"][0], 7]);
+        assert_eq!(synced_blocks(vec![mined, malformed]).len(), 1);
     }
 
-    pub fn build(self) -> AtomicSwap {
-        // Generate hash lock using the helper function
-        let hash_lock = ChainwebNetwork::generate_hash_lock();
+    #[test]
+    fn test_sync_after_recording_a_continuation() {
+        let network = proof_network(ChainwebNetwork::new(), 1);
+        let proof = network
+            .record_continuation(PactContinuation {
+                pact_id: "pact-1".to_string(),
+                source_chain: ChainId::new(0),
+                target_chain: ChainId::new(1),
+                step: 1,
+                value: Value::Integer(1),
+            })
+            .unwrap();
 
-        AtomicSwap {
-            id: Uuid::new_v4().to_string(),
-            initiator: self.initiator,
-            participant: self.participant,
-            source_chain: self.source_chain,
-            target_chain: self.target_chain,
-            source_asset: self.source_asset,
-            target_asset: self.target_asset,
-            source_amount: self.source_amount,
-            target_amount: self.target_amount,
-            status: AtomicSwapStatus::Initiated,
-            hash_lock,
-            time_lock: Utc::now() + chrono::Duration::hours(24),
-            created_at: Utc::now(),
-            completed_at: None,
+        // The next mined block still links to the last mined one
+        let tip = network.get_blocks(ChainId::new(0)).unwrap()[0].hash.clone();
+        let (peer, server) = serve_blocks(vec![peer_block(1, &tip)]);
+        network.connect_peer(ChainId::new(0), peer).unwrap();
+        network.sync_chain_state(ChainId::new(0)).unwrap();
+        server.join().unwrap();
+
+        let blocks = network.get_blocks(ChainId::new(0)).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].header.parent_hash, blocks[0].hash);
+        assert!(network.verify_continuation(&proof).unwrap());
+    }
+
+    #[test]
+    fn test_cross_chain_proof_round_trip() {
+        let network = proof_network(ChainwebNetwork::new(), 7);
+        let key = trusted_key();
+        let proof = network
+            .generate_cross_chain_proof(ChainId::new(0), ChainId::new(1), "tx-0", &key)
+            .unwrap();
+        assert!(network.verify_cross_chain_proof(ChainId::new(0), ChainId::new(1), &proof).unwrap());
+
+        // Tampered data, a tampered signature, and the wrong target all fail
+        let mut tampered = proof.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(!network.verify_cross_chain_proof(ChainId::new(0), ChainId::new(1), &tampered).unwrap());
+        let mut tampered = proof.clone();
+        tampered[40] ^= 1;
+        assert!(!network.verify_cross_chain_proof(ChainId::new(0), ChainId::new(1), &tampered).unwrap());
+        assert!(!network.verify_cross_chain_proof(ChainId::new(0), ChainId::new(0), &proof).unwrap());
+
+        // Signed by a key chain 0 does not trust
+        let unknown = ed25519_dalek::SigningKey::from_bytes(&[8u8; 32]);
+        let proof = network
+            .generate_cross_chain_proof(ChainId::new(0), ChainId::new(1), "tx-0", &unknown)
+            .unwrap();
+        assert!(!network.verify_cross_chain_proof(ChainId::new(0), ChainId::new(1), &proof).unwrap());
+
+        // Too few confirmations behind the including block
+        let proof = network
+            .generate_cross_chain_proof(ChainId::new(0), ChainId::new(1), "tx-3", &key)
+            .unwrap();
+        assert!(!network.verify_cross_chain_proof(ChainId::new(0), ChainId::new(1), &proof).unwrap());
+
+        assert!(network
+            .generate_cross_chain_proof(ChainId::new(0), ChainId::new(1), "missing", &key)
+            .is_err());
+    }
+
+    #[test]
+    fn test_continuation_hash_is_over_canonical_json() {
+        let continuation = PactContinuation {
+            pact_id: "pact-1".to_string(),
+            source_chain: ChainId::new(0),
+            target_chain: ChainId::new(1),
+            step: 1,
+            value: Value::Integer(10i128.pow(21)),
+        };
+        let canonical = r#"{"pact_id":"pact-1","source_chain":0,"step":1,"target_chain":1,"value":{"Integer":1e+21}}"#;
+        assert_eq!(
+            continuation.hash().unwrap(),
+            hex::encode(Sha512::digest(canonical.as_bytes()))
+        );
+    }
+
+    #[test]
+    fn test_verify_hand_built_cross_chain_proof() {
+        use ed25519_dalek::Signer;
+
+        let network = proof_network(ChainwebNetwork::new(), 7);
+        let now = Utc::now().timestamp() as u64;
+        let proof = |key: &ed25519_dalek::SigningKey, timestamp: u64| {
+            let mut payload = Vec::new();
+            payload.extend_from_slice(&0u64.to_le_bytes());
+            payload.extend_from_slice(&timestamp.to_le_bytes());
+            payload.extend_from_slice(b"tx-0");
+            let mut message = Vec::new();
+            message.extend_from_slice(&0u32.to_le_bytes());
+            message.extend_from_slice(&1u32.to_le_bytes());
+            message.extend_from_slice(&payload);
+
+            let mut proof = key.verifying_key().to_bytes().to_vec();
+            proof.extend_from_slice(&key.sign(&message).to_bytes());
+            proof.extend_from_slice(&payload);
+            proof
+        };
+        let verify = |proof: &[u8]| {
+            network
+                .verify_cross_chain_proof(ChainId::new(0), ChainId::new(1), proof)
+                .unwrap()
+        };
+
+        assert!(verify(&proof(&trusted_key(), now)));
+
+        // Signing the wrong direction does not verify
+        let reversed = proof(&trusted_key(), now);
+        assert!(!network.verify_cross_chain_proof(ChainId::new(1), ChainId::new(0), &reversed).unwrap());
+
+        // A valid signature by a key the source chain does not trust proves nothing
+        let unknown = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+        assert!(!verify(&proof(&unknown, now)));
+
+        // Small clock skew is tolerated, proofs dated further ahead are not
+        assert!(verify(&proof(&trusted_key(), now + 60)));
+        assert!(!verify(&proof(&trusted_key(), now + 3600)));
+        assert!(!verify(&proof(&trusted_key(), u64::MAX)));
+    }
+
+    #[test]
+    fn test_cross_chain_policy_sets_confirmation_depth() {
+        let key = trusted_key();
+        let policy = CrossChainPolicy {
+            min_confirmations: 2,
+            ..CrossChainPolicy::default()
+        };
+
+        // tx-1 sits in block 1 of 3, two blocks behind the tip
+        for (network, verifies) in [
+            (proof_network(ChainwebNetwork::new(), 3), false),
+            (proof_network(ChainwebNetwork::new().with_cross_chain_policy(policy), 3), true),
+        ] {
+            let proof = network
+                .generate_cross_chain_proof(ChainId::new(0), ChainId::new(1), "tx-1", &key)
+                .unwrap();
+            assert_eq!(
+                network.verify_cross_chain_proof(ChainId::new(0), ChainId::new(1), &proof).unwrap(),
+                verifies
+            );
         }
     }
+
+    #[test]
+    fn test_network_stats() {
+        let network = ChainwebNetwork::new();
+        let config = ChainConfig {
+            chain_id: ChainId::new(0),
+            name: "Chain 0".to_string(),
+            network_id: "silverbitcoin".to_string(),
+            peer_count: 10,
+            block_time_ms: 30000,
+            max_block_size: 1_000_000,
+            consensus_type: ConsensusType::PoW,
+            trusted_signers: Vec::new(),
+        };
+
+        network.register_chain(config).unwrap();
+        let stats = network.get_network_stats().unwrap();
+        assert_eq!(stats.chain_count, 1);
+    }
 }
@@ -2,12 +2,43 @@
 //!
 //! Converts AST to bytecode with optimization passes.
 
-use crate::ast::{BinOp, Definition, Expr, Literal, Program, UnaryOp};
+use crate::ast::{BinOp, Definition, Expr, Literal, Program, Span, UnaryOp};
 use crate::bytecode::{Bytecode, FunctionDef, Instruction};
 use crate::error::{SlvrError, SlvrResult};
-use crate::types::TypeEnv;
+use crate::types::{Type, TypeEnv};
+use crate::value::Value;
+use crate::vm::VirtualMachine;
+use crate::SlvrConfig;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// A single problem found while compiling a program.
+///
+/// A diagnostic is located by the path of definitions enclosing it, e.g.
+/// `token.transfer`, and by the span of the innermost parsed expression the
+/// error came from, when there is one.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// Dotted path of the enclosing definitions
+    pub location: String,
+    /// Source range of the offending expression
+    pub span: Option<Span>,
+    /// The underlying type or compile error
+    pub error: SlvrError,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.span {
+            Some(span) => write!(
+                f,
+                "{} (line {}, column {}): {}",
+                self.location, span.line, span.column, self.error
+            ),
+            None => write!(f, "{}: {}", self.location, self.error),
+        }
+    }
+}
 
 /// Compiler for Slvr language
 pub struct Compiler {
@@ -15,6 +46,12 @@ pub struct Compiler {
     functions: HashMap<String, FunctionDef>,
     current_scope_depth: usize,
     local_vars: Vec<HashMap<String, usize>>, // Stack of local variable offsets
+    /// Span of the innermost expression enclosing the error being reported
+    error_span: Option<Span>,
+    /// Whether to fold constants and run the bytecode optimization pass
+    optimize: bool,
+    /// Parameter types of each `defcap`, and whether it is managed
+    capabilities: HashMap<String, (Vec<Type>, bool)>,
 }
 
 impl Compiler {
@@ -25,27 +62,135 @@ impl Compiler {
             functions: HashMap::new(),
             current_scope_depth: 0,
             local_vars: vec![HashMap::new()],
+            error_span: None,
+            optimize: true,
+            capabilities: HashMap::new(),
         }
     }
 
-    /// Compile a program to bytecode
+    /// Create a compiler from a language configuration
+    pub fn with_config(config: &SlvrConfig) -> Self {
+        Self {
+            optimize: config.enable_optimization,
+            ..Self::new()
+        }
+    }
+
+    /// Functions compiled so far, ordered by name
+    pub fn functions(&self) -> Vec<&FunctionDef> {
+        let mut functions: Vec<_> = self.functions.values().collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        functions
+    }
+
+    /// Compile a program to bytecode, stopping at the first error
     pub fn compile(&mut self, program: &Program) -> SlvrResult<Bytecode> {
+        match self.compile_with_diagnostics(program) {
+            (Some(bytecode), _) => Ok(bytecode),
+            (None, mut diagnostics) => Err(diagnostics.remove(0).error),
+        }
+    }
+
+    /// Compile a program, collecting every error instead of stopping at the first.
+    ///
+    /// Bytecode is returned only when there are no diagnostics.
+    pub fn compile_with_diagnostics(
+        &mut self,
+        program: &Program,
+    ) -> (Option<Bytecode>, Vec<Diagnostic>) {
         let mut bytecode = Bytecode::new();
+        let mut diagnostics = Vec::new();
+
+        // Aliases first, so every signature below can use them wherever they are declared
+        self.collect_aliases(&program.definitions, "", &mut diagnostics);
 
         // First pass: collect all definitions
-        for def in &program.definitions {
-            self.collect_definition(def)?;
-        }
+        self.collect_all(&program.definitions, "", &mut diagnostics);
 
         // Second pass: compile definitions
-        for def in &program.definitions {
-            self.compile_definition(def, &mut bytecode)?;
+        self.compile_all(&program.definitions, "", &mut bytecode, &mut diagnostics);
+
+        if !diagnostics.is_empty() {
+            return (None, diagnostics);
         }
 
         // Optimize bytecode
-        self.optimize_bytecode(&mut bytecode);
+        if self.optimize {
+            self.optimize_bytecode(&mut bytecode);
+        }
+
+        (Some(bytecode), diagnostics)
+    }
+
+    fn collect_aliases(
+        &mut self,
+        defs: &[Definition],
+        path: &str,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for def in defs {
+            let location = definition_path(path, def);
+            match def {
+                Definition::Module { body, .. } => {
+                    self.collect_aliases(body, &location, diagnostics)
+                }
+                Definition::TypeAlias { name, ty, .. } => {
+                    let result = self
+                        .annotation_type(ty)
+                        .and_then(|ty| self.type_env.define_alias(name.clone(), ty));
+                    if let Err(error) = result {
+                        diagnostics.push(Diagnostic {
+                            location,
+                            span: None,
+                            error,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
 
-        Ok(bytecode)
+    fn collect_all(&mut self, defs: &[Definition], path: &str, diagnostics: &mut Vec<Diagnostic>) {
+        for def in defs {
+            let location = definition_path(path, def);
+            if let Definition::Module { body, .. } = def {
+                self.type_env.push_scope();
+                self.collect_all(body, &location, diagnostics);
+                let _ = self.type_env.pop_scope();
+            } else if let Err(error) = self.collect_definition(def) {
+                diagnostics.push(Diagnostic {
+                    location,
+                    span: None,
+                    error,
+                });
+            }
+        }
+    }
+
+    fn compile_all(
+        &mut self,
+        defs: &[Definition],
+        path: &str,
+        bytecode: &mut Bytecode,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for def in defs {
+            let location = definition_path(path, def);
+            if let Definition::Module { body, .. } = def {
+                self.type_env.push_scope();
+                self.current_scope_depth += 1;
+                self.compile_all(body, &location, bytecode, diagnostics);
+                self.current_scope_depth -= 1;
+                let _ = self.type_env.pop_scope();
+            } else if let Err(error) = self.compile_definition(def, bytecode) {
+                diagnostics.push(Diagnostic {
+                    location,
+                    span: self.error_span.take(),
+                    error,
+                });
+            }
+        }
     }
 
     fn collect_definition(&mut self, def: &Definition) -> SlvrResult<()> {
@@ -75,7 +220,7 @@ impl Compiler {
                 );
             }
             Definition::Schema { name, fields, .. } => {
-                let mut field_types = HashMap::new();
+                let mut field_types = BTreeMap::new();
                 for (field_name, field_type) in fields {
                     field_types.insert(field_name.clone(), self.ast_type_to_type(field_type)?);
                 }
@@ -99,6 +244,21 @@ impl Compiler {
                 self.type_env
                     .define_var(name.clone(), self.ast_type_to_type(ty)?);
             }
+            // Registered by `collect_aliases` before anything else
+            Definition::TypeAlias { .. } => {}
+            Definition::Capability {
+                name,
+                params,
+                managed,
+                ..
+            } => {
+                let param_types = params
+                    .iter()
+                    .map(|(_, ty)| self.ast_type_to_type(ty))
+                    .collect::<SlvrResult<Vec<_>>>()?;
+                self.capabilities
+                    .insert(name.clone(), (param_types, managed.is_some()));
+            }
         }
         Ok(())
     }
@@ -137,8 +297,13 @@ impl Compiler {
                     }
                 }
 
-                // Compile function body
-                self.compile_expr(body, &mut func_bytecode)?;
+                // Compile function body, restoring scopes even if it fails
+                let declared = self.ast_type_to_type(return_type)?;
+                if let Err(e) = self.compile_expecting(body, &declared, &mut func_bytecode) {
+                    self.local_vars.truncate(1);
+                    self.type_env.pop_scope()?;
+                    return Err(e);
+                }
                 func_bytecode.push(Instruction::Return);
 
                 // Store function definition
@@ -155,17 +320,467 @@ impl Compiler {
                 self.local_vars.pop();
                 self.type_env.pop_scope()?;
             }
-            Definition::Constant { name, value, .. } => {
-                let mut const_bytecode = Bytecode::new();
-                self.compile_expr(value, &mut const_bytecode)?;
-                bytecode.extend(const_bytecode);
+            Definition::Constant { name, ty, value } => {
+                // Compiled in place so jump targets in the value stay absolute
+                let start = bytecode.len();
+                let declared = self.ast_type_to_type(ty)?;
+                if let Err(e) = self.compile_expecting(value, &declared, bytecode) {
+                    bytecode.instructions.truncate(start);
+                    return Err(e);
+                }
                 bytecode.push(Instruction::StoreGlobal(name.clone()));
             }
+            Definition::Capability {
+                params,
+                managed,
+                body,
+                ..
+            } => {
+                // Only the evaluator acquires capabilities, so the predicate is
+                // checked in its own frame but produces no bytecode
+                if let Some(manager) = managed.as_ref().and_then(|m| m.manager.as_ref()) {
+                    match self.type_env.lookup_function(manager) {
+                        Some((manager_params, _)) if manager_params.len() == 2 => {}
+                        Some((manager_params, _)) => {
+                            return Err(SlvrError::type_error(format!(
+                                "manager {} expects 2 arguments, got {}",
+                                manager,
+                                manager_params.len()
+                            )))
+                        }
+                        None => return Err(SlvrError::undefined_func(manager)),
+                    }
+                }
+                self.type_env.push_scope();
+                let mut frame = HashMap::new();
+                for (i, (param_name, param_type)) in params.iter().enumerate() {
+                    let ty = match self.ast_type_to_type(param_type) {
+                        Ok(ty) => ty,
+                        Err(e) => {
+                            self.type_env.pop_scope()?;
+                            return Err(e);
+                        }
+                    };
+                    self.type_env.define_var(param_name.clone(), ty);
+                    frame.insert(param_name.clone(), i);
+                }
+                self.local_vars.push(frame);
+                let result =
+                    self.compile_expr(body, &mut Bytecode::new())
+                        .and_then(|()| match self.static_type(body) {
+                            Type::Boolean | Type::Unit | Type::Any => Ok(()),
+                            other => Err(SlvrError::type_error(format!(
+                                "capability predicate must be boolean, found {}",
+                                other
+                            ))),
+                        });
+                self.local_vars.pop();
+                self.type_env.pop_scope()?;
+                result?;
+            }
             _ => {}
         }
         Ok(())
     }
 
+    /// Check a `with-capability`, `require-capability` or
+    /// `install-capability` form: it names a `defcap` applied to as many
+    /// arguments as it takes, and every argument and body type-checks
+    fn check_capability_form(&mut self, form: &str, args: &[Expr]) -> SlvrResult<()> {
+        let expected = if form == "with-capability" { 2 } else { 1 };
+        if args.len() != expected {
+            return Err(SlvrError::type_error(format!(
+                "{} expects {} argument{}, got {}",
+                form,
+                expected,
+                if expected == 1 { "" } else { "s" },
+                args.len()
+            )));
+        }
+        let Expr::Call {
+            function,
+            args: cap_args,
+        } = args[0].unspanned()
+        else {
+            return Err(SlvrError::type_error(format!(
+                "{} expects a capability applied to its arguments",
+                form
+            )));
+        };
+        let Expr::Variable(name) = function.unspanned() else {
+            return Err(SlvrError::type_error(format!(
+                "{} expects a capability name",
+                form
+            )));
+        };
+        let Some((params, managed)) = self.capabilities.get(name).cloned() else {
+            return Err(SlvrError::type_error(format!(
+                "Unknown capability: {}",
+                name
+            )));
+        };
+        if params.len() != cap_args.len() {
+            return Err(SlvrError::type_error(format!(
+                "{} expects {} arguments, got {}",
+                name,
+                params.len(),
+                cap_args.len()
+            )));
+        }
+        if form == "install-capability" && !managed {
+            return Err(SlvrError::type_error(format!(
+                "{} is not a managed capability",
+                name
+            )));
+        }
+        let mut scratch = Bytecode::new();
+        for arg in cap_args {
+            self.compile_expr(arg, &mut scratch)?;
+        }
+        if let Some(body) = args.get(1) {
+            self.compile_expr(body, &mut scratch)?;
+        }
+        Ok(())
+    }
+
+    fn compile_let(
+        &mut self,
+        bindings: &[(String, Expr)],
+        sequential: bool,
+        body: &Expr,
+        bytecode: &mut Bytecode,
+    ) -> SlvrResult<()> {
+        let mut bound = Vec::with_capacity(bindings.len());
+        for (name, value) in bindings {
+            self.compile_expr(value, bytecode)?;
+            let ty = self.static_type(value);
+            if sequential {
+                self.bind_local(name, ty, bytecode)?;
+            } else {
+                bound.push((name, ty));
+            }
+        }
+        // Parallel values are all on the stack, so they are stored last first
+        let mut slots = Vec::with_capacity(bound.len());
+        for (name, ty) in bound {
+            slots.push(self.allocate_local(name)?);
+            self.type_env.define_var(name.clone(), ty);
+        }
+        for slot in slots.into_iter().rev() {
+            bytecode.push(Instruction::StoreLocal(slot));
+        }
+        self.compile_expr(body, bytecode)
+    }
+
+    fn check_lambda_body(&mut self, params: &[String], body: &Expr) -> SlvrResult<()> {
+        self.type_env.push_scope();
+        let mut frame = HashMap::new();
+        for (i, param) in params.iter().enumerate() {
+            self.type_env.define_var(param.clone(), Type::Any);
+            frame.insert(param.clone(), i);
+        }
+        self.local_vars.push(frame);
+        let result = self.compile_expr(body, &mut Bytecode::new());
+        self.local_vars.pop();
+        self.type_env.pop_scope()?;
+        result
+    }
+
+    /// Pop the value on top of the stack into a fresh local named `name`
+    fn bind_local(&mut self, name: &str, ty: Type, bytecode: &mut Bytecode) -> SlvrResult<()> {
+        let slot = self.allocate_local(name)?;
+        self.type_env.define_var(name.to_string(), ty);
+        bytecode.push(Instruction::StoreLocal(slot));
+        Ok(())
+    }
+
+    /// Give `name` a slot past every slot in use by the current frame
+    fn allocate_local(&mut self, name: &str) -> SlvrResult<usize> {
+        let locals = self
+            .local_vars
+            .last_mut()
+            .expect("compiler always has a locals frame");
+        let slot = locals.values().max().map_or(0, |max| max + 1);
+        if slot >= crate::MAX_LOCALS {
+            return Err(SlvrError::CompilationError {
+                message: format!("Too many locals: the limit is {}", crate::MAX_LOCALS),
+            });
+        }
+        locals.insert(name.to_string(), slot);
+        Ok(slot)
+    }
+
+    /// Type of `expr` as far as it is known without full inference
+    fn static_type(&self, expr: &Expr) -> Type {
+        match expr.unspanned() {
+            Expr::Variable(name) => self.type_env.lookup_var(name).unwrap_or(Type::Any),
+            Expr::Lambda { params, body } => {
+                Type::Function(vec![Type::Any; params.len()], Box::new(literal_type(body)))
+            }
+            Expr::List(elements) => self
+                .list_type(elements)
+                .unwrap_or(Type::List(Box::new(Type::Any))),
+            Expr::Object(fields) => Type::Object(
+                fields
+                    .iter()
+                    .map(|(field, value)| (field.clone(), self.static_type(value)))
+                    .collect(),
+            ),
+            Expr::FieldAccess { object, field } => {
+                field_type(&self.static_type(object), field).unwrap_or(Type::Any)
+            }
+            Expr::Index { object, index } => {
+                let object = self.static_type(object);
+                match (&object, index.unspanned()) {
+                    (Type::Object(_), Expr::Literal(Literal::String(field))) => {
+                        field_type(&object, field).unwrap_or(Type::Any)
+                    }
+                    _ => element_type(&object),
+                }
+            }
+            Expr::Read { table, .. } => self.row_type(table),
+            Expr::ReadSafe { table, .. } => Type::Optional(Box::new(self.row_type(table))),
+            Expr::Call { function, args } => match function.unspanned() {
+                Expr::Variable(name) if self.is_builtin_call(name) => {
+                    self.builtin_call_type(name, args)
+                }
+                _ => Type::Any,
+            },
+            other => literal_type(other),
+        }
+    }
+
+    /// Object type of the rows of `table`
+    fn row_type(&self, table: &str) -> Type {
+        match self.type_env.lookup_table(table) {
+            Some(Type::Table(schema)) => match *schema {
+                Type::Schema(fields) => Type::Object(fields),
+                _ => Type::Any,
+            },
+            _ => Type::Any,
+        }
+    }
+
+    /// The variable an `if` condition compares with null, its present type,
+    /// and whether it is present when the condition holds
+    fn null_check(&self, condition: &Expr) -> Option<(String, Type, bool)> {
+        let Expr::BinOp { op, left, right } = condition.unspanned() else {
+            return None;
+        };
+        let present_when_true = match op {
+            BinOp::NotEqual => true,
+            BinOp::Equal => false,
+            _ => return None,
+        };
+        let name = match (left.unspanned(), right.unspanned()) {
+            (Expr::Variable(name), Expr::Literal(Literal::Null))
+            | (Expr::Literal(Literal::Null), Expr::Variable(name)) => name,
+            _ => return None,
+        };
+        match self.type_env.lookup_var(name) {
+            Some(Type::Optional(inner)) => Some((name.clone(), *inner, present_when_true)),
+            _ => None,
+        }
+    }
+
+    /// Compile a branch in which `narrowed` names a variable known to be present
+    /// Compile an `if` and the `else if` arms chained onto it in one loop,
+    /// so a long chain costs no native stack. Each arm is reached only when
+    /// every earlier condition failed, so an `x == null` narrowing holds for
+    /// the rest of the chain; `narrowings` counts the scopes left for the
+    /// caller to pop.
+    fn compile_if(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: Option<&Expr>,
+        narrowings: &mut usize,
+        bytecode: &mut Bytecode,
+    ) -> SlvrResult<()> {
+        let (mut condition, mut then_branch, mut else_branch) =
+            (condition, then_branch, else_branch);
+        let mut end_jumps = Vec::new();
+        loop {
+            self.compile_expr(condition, bytecode)?;
+            // `x != null` proves x present in the then branch, `x == null` in the else branch
+            let (then_narrowed, else_narrowed) = match self.null_check(condition) {
+                Some((name, inner, true)) => (Some((name, inner)), None),
+                Some((name, inner, false)) => (None, Some((name, inner))),
+                None => (None, None),
+            };
+
+            // Jump over the then branch when the condition fails; the target
+            // is patched once the then branch is compiled
+            let jump_else_index = bytecode.len();
+            bytecode.push(Instruction::JumpIfFalse(0));
+            self.compile_narrowed(then_branch, then_narrowed, bytecode)?;
+
+            let Some(else_expr) = else_branch else {
+                patch_jump(bytecode, jump_else_index, bytecode.len());
+                break;
+            };
+            // Every taken then branch skips the rest of the chain
+            end_jumps.push(bytecode.len());
+            bytecode.push(Instruction::Jump(0));
+            patch_jump(bytecode, jump_else_index, bytecode.len());
+
+            if let Some((name, inner)) = else_narrowed {
+                self.type_env.push_scope();
+                self.type_env.define_var(name, inner);
+                *narrowings += 1;
+            }
+            match else_expr.unspanned() {
+                Expr::If {
+                    condition: next_condition,
+                    then_branch: next_then,
+                    else_branch: next_else,
+                } => {
+                    condition = next_condition;
+                    then_branch = next_then;
+                    else_branch = next_else.as_deref();
+                }
+                _ => {
+                    self.compile_expr(else_expr, bytecode)?;
+                    break;
+                }
+            }
+        }
+
+        let end_target = bytecode.len();
+        for index in end_jumps {
+            patch_jump(bytecode, index, end_target);
+        }
+        Ok(())
+    }
+
+    fn compile_narrowed(
+        &mut self,
+        branch: &Expr,
+        narrowed: Option<(String, Type)>,
+        bytecode: &mut Bytecode,
+    ) -> SlvrResult<()> {
+        let Some((name, inner)) = narrowed else {
+            return self.compile_expr(branch, bytecode);
+        };
+        self.type_env.push_scope();
+        self.type_env.define_var(name, inner);
+        let result = self.compile_expr(branch, bytecode);
+        self.type_env.pop_scope()?;
+        result
+    }
+
+    /// Type of a list literal: its elements must agree on one element type
+    fn list_type(&self, elements: &[Expr]) -> SlvrResult<Type> {
+        let mut element = Type::Any;
+        for expr in elements {
+            let found = self.static_type(expr);
+            element = element.unify(&found).ok_or_else(|| {
+                SlvrError::type_error(format!(
+                    "list elements have mixed types {} and {}; declare the list as \
+                     [{} | {}] to allow both",
+                    element, found, element, found
+                ))
+            })?;
+        }
+        Ok(Type::List(Box::new(element)))
+    }
+
+    /// A call to a list builtin rather than a `defun` or a function variable
+    fn is_builtin_call(&self, name: &str) -> bool {
+        self.type_env.lookup_function(name).is_none() && !self.type_env.is_var_defined(name)
+    }
+
+    /// Result type of the list builtins, carrying the element type through
+    fn builtin_call_type(&self, name: &str, args: &[Expr]) -> Type {
+        let list = match args.first() {
+            Some(list) => self.static_type(list),
+            None => return Type::Any,
+        };
+        match name {
+            "enforce" | "enforce-one" => Type::Unit,
+            "ed25519-verify" => Type::Boolean,
+            "at" | "first" | "last" => element_type(&list),
+            "sublist" | "take" | "drop" | "reverse" | "sort" | "sort-desc" | "distinct"
+            | "remove-at" => match list {
+                Type::List(_) => list,
+                _ => Type::Any,
+            },
+            "append" => match (&list, args.get(1)) {
+                (Type::List(element), Some(item)) => element
+                    .unify(&self.static_type(item))
+                    .map_or(Type::Any, |element| Type::List(Box::new(element))),
+                _ => Type::Any,
+            },
+            _ => Type::Any,
+        }
+    }
+
+    /// `append` must keep a list's elements of one type, and `ed25519-verify`
+    /// takes a key, a message and a signature as strings
+    fn check_builtin_call(&self, name: &str, args: &[Expr]) -> SlvrResult<()> {
+        if name == "ed25519-verify" && self.is_builtin_call(name) {
+            if args.len() != 3 {
+                return Err(SlvrError::type_error(format!(
+                    "ed25519-verify expects 3 arguments, got {}",
+                    args.len()
+                )));
+            }
+            for arg in args {
+                let found = self.static_type(arg);
+                if found.unify(&Type::String).is_none() {
+                    return Err(SlvrError::type_error(format!(
+                        "ed25519-verify expects string arguments, got {}",
+                        found
+                    )));
+                }
+            }
+            return Ok(());
+        }
+        if name != "append" || !self.is_builtin_call(name) {
+            return Ok(());
+        }
+        if let [list, item] = args {
+            if let Type::List(element) = self.static_type(list) {
+                let found = self.static_type(item);
+                if element.unify(&found).is_none() {
+                    return Err(SlvrError::type_error(format!(
+                        "cannot append {} to a list of {}",
+                        found, element
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject operands of an arithmetic operator that can never be numbers
+    fn check_numeric_operand(&self, op: &str, operand: &Expr) -> SlvrResult<()> {
+        let ty = self.static_type(operand);
+        let numeric = match &ty {
+            Type::String
+            | Type::Boolean
+            | Type::Unit
+            | Type::List(_)
+            | Type::Object(_)
+            | Type::Function(_, _)
+            | Type::Optional(_) => false,
+            // Every member of a union has to be usable as a number
+            Type::Union(members) => members.iter().all(Type::is_numeric),
+            _ => !matches!(operand.unspanned(), Expr::Literal(Literal::Null)),
+        };
+        if numeric {
+            return Ok(());
+        }
+        let found = match operand.unspanned() {
+            Expr::Literal(Literal::Null) => "null".to_string(),
+            _ => ty.to_string(),
+        };
+        Err(SlvrError::type_error(format!(
+            "operator {} expects numeric operands, got {}",
+            op, found
+        )))
+    }
+
     fn compile_expr(&mut self, expr: &Expr, bytecode: &mut Bytecode) -> SlvrResult<()> {
         match expr {
             Expr::Literal(lit) => match lit {
@@ -189,9 +804,19 @@ impl Compiler {
                     bytecode.push(Instruction::LoadGlobal(name.clone()));
                 }
             }
+            Expr::BinOp {
+                op: op @ (BinOp::And | BinOp::Or),
+                left,
+                right,
+            } => self.compile_logical(*op, left, right, bytecode)?,
             Expr::BinOp { op, left, right } => {
+                let start = bytecode.instructions.len();
                 self.compile_expr(left, bytecode)?;
                 self.compile_expr(right, bytecode)?;
+                if is_arithmetic(*op) {
+                    self.check_numeric_operand(&op.to_string(), left)?;
+                    self.check_numeric_operand(&op.to_string(), right)?;
+                }
 
                 let instruction = match op {
                     BinOp::Add => Instruction::Add,
@@ -211,23 +836,69 @@ impl Compiler {
                     BinOp::Concat => Instruction::Concat,
                 };
                 bytecode.push(instruction);
+                self.fold_constants(bytecode, start);
             }
             Expr::UnaryOp { op, operand } => {
+                let start = bytecode.instructions.len();
                 self.compile_expr(operand, bytecode)?;
+                if *op == UnaryOp::Negate {
+                    self.check_numeric_operand("unary -", operand)?;
+                }
                 match op {
                     UnaryOp::Not => bytecode.push(Instruction::Not),
                     UnaryOp::Negate => bytecode.push(Instruction::Negate),
                 }
+                self.fold_constants(bytecode, start);
             }
             Expr::Call { function, args } => {
+                if let Expr::Variable(name) = function.unspanned() {
+                    if (name == "enforce" || name == "enforce-one") && self.is_builtin_call(name) {
+                        return self.compile_enforce(name, args, bytecode);
+                    }
+                    if matches!(
+                        name.as_str(),
+                        "with-capability" | "require-capability" | "install-capability"
+                    ) && self.is_builtin_call(name)
+                    {
+                        // The VM keeps no capability scope to grant into, so
+                        // the form is checked but fails if it is reached
+                        self.check_capability_form(name, args)?;
+                        bytecode.push(Instruction::Throw(format!(
+                            "{} is not supported by the VM",
+                            name
+                        )));
+                        return Ok(());
+                    }
+                }
+
                 // Evaluate arguments
                 for arg in args {
                     self.compile_expr(arg, bytecode)?;
                 }
 
                 // Get function name
-                if let Expr::Variable(func_name) = &**function {
-                    bytecode.push(Instruction::Call(func_name.clone(), args.len()));
+                if let Expr::Variable(func_name) = function.unspanned() {
+                    self.check_builtin_call(func_name, args)?;
+                    if let Some(Type::Function(params, _)) = self.type_env.lookup_var(func_name) {
+                        if params.len() != args.len() {
+                            return Err(SlvrError::type_error(format!(
+                                "{} expects {} arguments, got {}",
+                                func_name,
+                                params.len(),
+                                args.len()
+                            )));
+                        }
+                    }
+                    if func_name == "ed25519-verify" && !self.is_builtin_call(func_name) {
+                        // The VM runs this name as the builtin, so a function
+                        // shadowing it fails if it is reached
+                        bytecode.push(Instruction::Throw(format!(
+                            "{} is shadowed and cannot be called by the VM",
+                            func_name
+                        )));
+                    } else {
+                        bytecode.push(Instruction::Call(func_name.clone(), args.len()));
+                    }
                 } else {
                     return Err(SlvrError::compilation("Invalid function call"));
                 }
@@ -237,106 +908,70 @@ impl Compiler {
                 then_branch,
                 else_branch,
             } => {
-                // PRODUCTION-GRADE IMPLEMENTATION: Real conditional jump compilation with proper patching
-                // This implementation:
-                // 1. Compiles the condition expression
-                // 2. Adds a conditional jump instruction with a placeholder target
-                // 3. Compiles the then branch
-                // 4. If there's an else branch, adds an unconditional jump and compiles it
-                // 5. Patches all jump targets to their correct positions
-
-                self.compile_expr(condition, bytecode)?;
-
-                // Add conditional jump with target to be patched after else branch is compiled
-                let jump_else_index = bytecode.len();
-                bytecode.push(Instruction::JumpIfFalse(0)); // Target will be patched to else branch address
-
-                // Compile then branch
-                self.compile_expr(then_branch, bytecode)?;
-
-                if let Some(else_expr) = else_branch {
-                    // Add unconditional jump to skip else branch
-                    let jump_end_index = bytecode.len();
-                    bytecode.push(Instruction::Jump(0)); // Target will be patched to end address
-
-                    // Patch conditional jump to point to else branch
-                    // Calculate the exact target address where else branch starts
-                    let else_target = bytecode.len();
-                    if jump_else_index < bytecode.instructions.len() {
-                        if let Instruction::JumpIfFalse(ref mut target) =
-                            &mut bytecode.instructions[jump_else_index]
-                        {
-                            *target = else_target;
-                        }
-                    }
-
-                    // Compile else branch
-                    self.compile_expr(else_expr, bytecode)?;
-
-                    // PRODUCTION: Patch unconditional jump to point after else branch
-                    // This is the real implementation - we calculate the exact target address
-                    let end_target = bytecode.len();
-                    if jump_end_index < bytecode.instructions.len() {
-                        if let Instruction::Jump(ref mut target) =
-                            &mut bytecode.instructions[jump_end_index]
-                        {
-                            *target = end_target;
-                        }
-                    }
-                } else {
-                    // No else branch - patch conditional jump to point after then branch
-                    let else_target = bytecode.len();
-                    if jump_else_index < bytecode.instructions.len() {
-                        if let Instruction::JumpIfFalse(ref mut target) =
-                            &mut bytecode.instructions[jump_else_index]
-                        {
-                            *target = else_target;
-                        }
-                    }
+                let mut narrowings = 0;
+                let result = self.compile_if(
+                    condition,
+                    then_branch,
+                    else_branch.as_deref(),
+                    &mut narrowings,
+                    bytecode,
+                );
+                for _ in 0..narrowings {
+                    self.type_env.pop_scope()?;
                 }
+                result?;
             }
-            Expr::Let { name, value, body } => {
-                self.compile_expr(value, bytecode)?;
-
+            Expr::Let {
+                bindings,
+                sequential,
+                body,
+            } => {
+                // Shadowed names get their old slots back once the body is done
+                let saved = self.local_vars.last().cloned().unwrap_or_default();
                 self.type_env.push_scope();
+                let result = self.compile_let(bindings, *sequential, body, bytecode);
+                self.type_env.pop_scope()?;
                 if let Some(locals) = self.local_vars.last_mut() {
-                    let offset = locals.len();
-                    locals.insert(name.clone(), offset);
+                    *locals = saved;
                 }
-
-                bytecode.push(Instruction::StoreLocal(
-                    self.local_vars
-                        .last()
-                        .unwrap()
-                        .get(name)
-                        .copied()
-                        .unwrap_or(0),
+                result?;
+            }
+            Expr::Lambda { params, body } => {
+                // The body is checked in its own frame like a `defun`, but the
+                // VM has no closures, so reaching one there is an error
+                self.check_lambda_body(params, body)?;
+                bytecode.push(Instruction::Throw(
+                    "lambda values are not supported by the VM".to_string(),
                 ));
-
-                self.compile_expr(body, bytecode)?;
-
-                self.type_env.pop_scope()?;
-                self.local_vars.pop();
             }
             Expr::List(elements) => {
                 for elem in elements {
                     self.compile_expr(elem, bytecode)?;
                 }
+                self.list_type(elements)?;
                 bytecode.push(Instruction::MakeList(elements.len()));
             }
             Expr::Object(fields) => {
-                for (_, value) in fields {
+                for (key, value) in fields {
+                    bytecode.push(Instruction::PushString(key.clone()));
                     self.compile_expr(value, bytecode)?;
                 }
                 bytecode.push(Instruction::MakeObject(fields.len()));
             }
             Expr::FieldAccess { object, field } => {
                 self.compile_expr(object, bytecode)?;
+                field_type(&self.static_type(object), field)?;
+
                 bytecode.push(Instruction::GetField(field.clone()));
             }
             Expr::Index { object, index } => {
                 self.compile_expr(object, bytecode)?;
                 self.compile_expr(index, bytecode)?;
+                let object_type = self.static_type(object);
+                require_present(&object_type)?;
+                if let Expr::Literal(Literal::String(field)) = index.unspanned() {
+                    field_type(&object_type, field)?;
+                }
                 bytecode.push(Instruction::GetIndex);
             }
             Expr::Block(exprs) => {
@@ -348,6 +983,10 @@ impl Compiler {
                 self.compile_expr(key, bytecode)?;
                 bytecode.push(Instruction::Read(table.clone()));
             }
+            Expr::ReadSafe { table, key } => {
+                self.compile_expr(key, bytecode)?;
+                bytecode.push(Instruction::ReadSafe(table.clone()));
+            }
             Expr::Write { table, key, value } => {
                 self.compile_expr(key, bytecode)?;
                 self.compile_expr(value, bytecode)?;
@@ -368,10 +1007,218 @@ impl Compiler {
                 self.compile_expr(key, bytecode)?;
                 bytecode.push(Instruction::Delete(table.clone()));
             }
+            Expr::Spanned { span, expr } => {
+                let result = self.compile_expr(expr, bytecode);
+                self.record_error_span(*span, result)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Errors propagate outwards, so the first span recorded is the innermost;
+    /// type errors carry it themselves as well as through the diagnostic
+    fn record_error_span(&mut self, span: Span, result: SlvrResult<()>) -> SlvrResult<()> {
+        if result.is_err() && self.error_span.is_none() {
+            self.error_span = Some(span);
+        }
+        result.map_err(|e| e.with_span(span))
+    }
+
+    /// Compile `expr` where a value of the declared type `expected` is wanted.
+    ///
+    /// A list literal is checked against the declared element type instead of
+    /// its own elements, which is how a list is widened to a union.
+    fn compile_expecting(
+        &mut self,
+        expr: &Expr,
+        expected: &Type,
+        bytecode: &mut Bytecode,
+    ) -> SlvrResult<()> {
+        match (expr, expected) {
+            (Expr::Spanned { span, expr }, _) => {
+                let result = self.compile_expecting(expr, expected, bytecode);
+                self.record_error_span(*span, result)
+            }
+            (Expr::List(elements), Type::List(element_type)) => {
+                for element in elements {
+                    self.compile_expecting(element, element_type, bytecode)?;
+                    let found = self.static_type(element);
+                    if !element_type.is_compatible_with(&found) {
+                        return Err(SlvrError::type_error(format!(
+                            "list element of type {} does not match the declared {}",
+                            found, element_type
+                        )));
+                    }
+                }
+                bytecode.push(Instruction::MakeList(elements.len()));
+                Ok(())
+            }
+            _ => self.compile_expr(expr, bytecode),
+        }
+    }
+
+    /// Compile `and` / `or` so that the right operand only runs when the left
+    /// one does not already decide the result.
+    ///
+    /// The result is always a boolean. A constant left operand that decides
+    /// the result is folded when optimizing; the right operand is then still
+    /// type checked but emits no code.
+    fn compile_logical(
+        &mut self,
+        op: BinOp,
+        left: &Expr,
+        right: &Expr,
+        bytecode: &mut Bytecode,
+    ) -> SlvrResult<()> {
+        // Truthiness of the left operand that settles the result on its own
+        let decisive = op == BinOp::Or;
+
+        let start = bytecode.len();
+        self.compile_expr(left, bytecode)?;
+        let constant_left = match &bytecode.instructions[start..] {
+            [instruction] if self.optimize => constant_value(instruction),
+            _ => None,
+        };
+        if let Some(value) = constant_left {
+            bytecode.instructions.truncate(start);
+            if value.is_truthy() == decisive {
+                self.compile_expr(right, &mut Bytecode::new())?;
+                bytecode.push(Instruction::PushBool(decisive));
+            } else {
+                // The result is the truthiness of the right operand
+                self.compile_expr(right, bytecode)?;
+                let constant_right = match &bytecode.instructions[start..] {
+                    [instruction] => constant_value(instruction),
+                    _ => None,
+                };
+                match constant_right {
+                    Some(value) => {
+                        bytecode.instructions.truncate(start);
+                        bytecode.push(Instruction::PushBool(value.is_truthy()));
+                    }
+                    _ => {
+                        bytecode.push(Instruction::Not);
+                        bytecode.push(Instruction::Not);
+                    }
+                }
+            }
+            return Ok(());
+        }
+
+        let short_circuit = |target| {
+            if decisive {
+                Instruction::JumpIfTrue(target)
+            } else {
+                Instruction::JumpIfFalse(target)
+            }
+        };
+        let after_left = bytecode.len();
+        bytecode.push(short_circuit(0));
+        self.compile_expr(right, bytecode)?;
+        let after_right = bytecode.len();
+        bytecode.push(short_circuit(0));
+        bytecode.push(Instruction::PushBool(!decisive));
+        let skip = bytecode.len();
+        bytecode.push(Instruction::Jump(0));
+
+        let decided = bytecode.len();
+        bytecode.push(Instruction::PushBool(decisive));
+        let end = bytecode.len();
+
+        bytecode.instructions[after_left] = short_circuit(decided);
+        bytecode.instructions[after_right] = short_circuit(decided);
+        bytecode.instructions[skip] = Instruction::Jump(end);
+        Ok(())
+    }
+
+    /// Compile `enforce(cond, msg)` and `enforce-one([checks], msg)`.
+    ///
+    /// `enforce-one` tries its checks in order and jumps past the failure as
+    /// soon as one holds, so each check must be a boolean expression.
+    fn compile_enforce(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        bytecode: &mut Bytecode,
+    ) -> SlvrResult<()> {
+        let [condition, message] = args else {
+            return Err(SlvrError::type_error(format!(
+                "{} expects 2 arguments, got {}",
+                name,
+                args.len()
+            )));
+        };
+        if name == "enforce" {
+            self.check_boolean_check(name, condition)?;
+            self.compile_expr(condition, bytecode)?;
+            self.compile_expr(message, bytecode)?;
+            bytecode.push(Instruction::Enforce);
+            return Ok(());
+        }
+
+        let Expr::List(checks) = condition.unspanned() else {
+            return Err(SlvrError::type_error(
+                "enforce-one expects a list literal of checks",
+            ));
+        };
+        let mut passes = Vec::with_capacity(checks.len());
+        for check in checks {
+            self.check_boolean_check(name, check)?;
+            self.compile_expr(check, bytecode)?;
+            passes.push(bytecode.len());
+            bytecode.push(Instruction::JumpIfTrue(0));
         }
+        bytecode.push(Instruction::PushBool(false));
+        self.compile_expr(message, bytecode)?;
+        bytecode.push(Instruction::Enforce);
+        let skip = bytecode.len();
+        bytecode.push(Instruction::Jump(0));
+
+        let pass = bytecode.len();
+        bytecode.push(Instruction::PushUnit);
+        let end = bytecode.len();
+
+        for index in passes {
+            bytecode.instructions[index] = Instruction::JumpIfTrue(pass);
+        }
+        bytecode.instructions[skip] = Instruction::Jump(end);
         Ok(())
     }
 
+    fn check_boolean_check(&self, name: &str, check: &Expr) -> SlvrResult<()> {
+        match self.static_type(check) {
+            Type::Boolean | Type::Any => Ok(()),
+            found => Err(SlvrError::type_error(format!(
+                "{} expects a boolean check, got {}",
+                name, found
+            ))),
+        }
+    }
+
+    /// Replace the instructions emitted since `start` with a single push when
+    /// they apply an operator to constants.
+    ///
+    /// Operands are folded bottom-up as each operator is compiled, so nested
+    /// constant expressions collapse completely. An operation that would fail
+    /// at run time, such as an overflow or a division by zero, is left as is
+    /// so that it still fails when executed.
+    fn fold_constants(&self, bytecode: &mut Bytecode, start: usize) {
+        if !self.optimize {
+            return;
+        }
+        let folded = match &bytecode.instructions[start..] {
+            [a, b, op] => constant_value(a)
+                .zip(constant_value(b))
+                .and_then(|(a, b)| fold_binary(op, a, b)),
+            [a, op] => constant_value(a).and_then(|a| fold_unary(op, a)),
+            _ => None,
+        };
+        if let Some(instruction) = folded.and_then(push_instruction) {
+            bytecode.instructions.truncate(start);
+            bytecode.push(instruction);
+        }
+    }
+
     fn optimize_bytecode(&self, bytecode: &mut Bytecode) {
         // Constant folding and dead code elimination
         let mut optimized = Vec::new();
@@ -395,23 +1242,156 @@ impl Compiler {
         bytecode.instructions = optimized;
     }
 
-    #[allow(clippy::only_used_in_recursion)]
+    /// Checker type of an annotation, with aliases resolved
     fn ast_type_to_type(&self, ast_type: &crate::ast::Type) -> SlvrResult<crate::types::Type> {
+        self.type_env.resolve_type(&self.annotation_type(ast_type)?)
+    }
+
+    /// Checker type of an annotation as written, aliases left as custom types
+    fn annotation_type(&self, ast_type: &crate::ast::Type) -> SlvrResult<crate::types::Type> {
         Ok(match ast_type {
             crate::ast::Type::Integer => crate::types::Type::Integer,
             crate::ast::Type::Decimal => crate::types::Type::Decimal,
             crate::ast::Type::String => crate::types::Type::String,
             crate::ast::Type::Boolean => crate::types::Type::Boolean,
             crate::ast::Type::List(inner) => {
-                crate::types::Type::List(Box::new(self.ast_type_to_type(inner)?))
+                crate::types::Type::List(Box::new(self.annotation_type(inner)?))
             }
-            crate::ast::Type::Object => crate::types::Type::Object(HashMap::new()),
+            crate::ast::Type::Object => crate::types::Type::Object(BTreeMap::new()),
             crate::ast::Type::Custom(name) => crate::types::Type::Custom(name.clone()),
             crate::ast::Type::Unit => crate::types::Type::Unit,
+            crate::ast::Type::Union(members) => crate::types::Type::Union(
+                members
+                    .iter()
+                    .map(|member| self.annotation_type(member))
+                    .collect::<SlvrResult<_>>()?,
+            ),
         })
     }
 }
 
+fn is_arithmetic(op: BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::Add
+            | BinOp::Subtract
+            | BinOp::Multiply
+            | BinOp::Divide
+            | BinOp::Modulo
+            | BinOp::Power
+    )
+}
+
+/// Element type of a list type, or `Any` when `list` is not known to be a list
+fn element_type(list: &Type) -> Type {
+    match list {
+        Type::List(element) => (**element).clone(),
+        _ => Type::Any,
+    }
+}
+
+/// Type of `field` on a value of type `object`.
+///
+/// Only an object whose fields are known, such as a row read from a table,
+/// can be missing the field.
+fn field_type(object: &Type, field: &str) -> SlvrResult<Type> {
+    require_present(object)?;
+    match object {
+        Type::Object(fields) if !fields.is_empty() => fields
+            .get(field)
+            .cloned()
+            .ok_or_else(|| SlvrError::type_error(format!("{} has no field {}", object, field))),
+        _ => Ok(Type::Any),
+    }
+}
+
+/// Reject using a value that may be null before it has been compared with null
+fn require_present(ty: &Type) -> SlvrResult<()> {
+    match ty {
+        Type::Optional(_) => Err(SlvrError::type_error(format!(
+            "value of type {} may be null; compare it with null before using it",
+            ty
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Type of `expr` when it is evident from the expression alone
+fn literal_type(expr: &Expr) -> Type {
+    match expr.unspanned() {
+        Expr::Literal(Literal::Integer(_)) => Type::Integer,
+        Expr::Literal(Literal::Decimal(_)) => Type::Decimal,
+        Expr::Literal(Literal::String(_)) => Type::String,
+        Expr::Literal(Literal::Boolean(_)) => Type::Boolean,
+        Expr::Literal(Literal::Unit) => Type::Unit,
+        _ => Type::Any,
+    }
+}
+
+/// Dotted location of `def` inside the definitions at `path`
+fn definition_path(path: &str, def: &Definition) -> String {
+    let name = match def {
+        Definition::Module { name, .. }
+        | Definition::Function { name, .. }
+        | Definition::Schema { name, .. }
+        | Definition::Table { name, .. }
+        | Definition::Constant { name, .. }
+        | Definition::TypeAlias { name, .. }
+        | Definition::Capability { name, .. } => name,
+    };
+    if path.is_empty() {
+        name.clone()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// Point the jump at `index` to `target`
+fn patch_jump(bytecode: &mut Bytecode, index: usize, target: usize) {
+    if let Some(Instruction::Jump(to) | Instruction::JumpIfFalse(to)) =
+        bytecode.instructions.get_mut(index)
+    {
+        *to = target;
+    }
+}
+
+/// Value pushed by a constant instruction
+fn constant_value(instruction: &Instruction) -> Option<Value> {
+    match instruction {
+        Instruction::PushInt(n) => Some(Value::Integer(*n)),
+        Instruction::PushDecimal(d) => Some(Value::Decimal(*d)),
+        Instruction::PushString(s) => Some(Value::String(s.clone())),
+        Instruction::PushBool(b) => Some(Value::Boolean(*b)),
+        _ => None,
+    }
+}
+
+/// Instruction pushing a folded value, if it has a literal form
+fn push_instruction(value: Value) -> Option<Instruction> {
+    match value {
+        Value::Integer(n) => Some(Instruction::PushInt(n)),
+        Value::Decimal(d) => Some(Instruction::PushDecimal(d)),
+        Value::String(s) => Some(Instruction::PushString(s)),
+        Value::Boolean(b) => Some(Instruction::PushBool(b)),
+        _ => None,
+    }
+}
+
+/// Result of a binary operation on constants, or `None` if it must be left to
+/// run time.
+///
+/// The VM's own arithmetic decides, so an operation that overflows or divides
+/// by zero is never folded and fails the same way when executed.
+fn fold_binary(op: &Instruction, a: Value, b: Value) -> Option<Value> {
+    VirtualMachine::binary_op(op, a, b).ok()
+}
+
+/// Result of a unary operation on a constant, or `None` if it must be left to
+/// run time
+fn fold_unary(op: &Instruction, value: Value) -> Option<Value> {
+    VirtualMachine::unary_op(op, value).ok()
+}
+
 impl Default for Compiler {
     fn default() -> Self {
         Self::new()
@@ -437,4 +1417,660 @@ mod tests {
         let result = compiler.compile(&program);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_compile_with_diagnostics_reports_every_error() {
+        let source = "module m \"doc\" { \
+            deftable a: missing-a \
+            defun f () -> integer undefined-x \
+            deftable b: missing-b }";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+
+        let (bytecode, diagnostics) = Compiler::new().compile_with_diagnostics(&program);
+        assert!(bytecode.is_none());
+        let locations: Vec<&str> = diagnostics.iter().map(|d| d.location.as_str()).collect();
+        assert_eq!(locations, vec!["m.a", "m.b", "m.f"]);
+        assert!(Compiler::new().compile(&program).is_err());
+    }
+
+    #[test]
+    fn test_type_error_reports_innermost_span() {
+        let source = "module m \"doc\" {\n  defun f () -> integer\n    1 + (2 * (3 - \"x\"))\n}";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+
+        let (bytecode, diagnostics) = Compiler::new().compile_with_diagnostics(&program);
+        assert!(bytecode.is_none());
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert!(matches!(diagnostic.error, SlvrError::TypeError { .. }));
+        assert_eq!(diagnostic.location, "m.f");
+
+        let span = diagnostic.span.expect("diagnostic should carry a span");
+        assert_eq!(&source[span.start..span.end], "3 - \"x\"");
+        assert_eq!((span.line, span.column), (3, 15));
+        assert_eq!(diagnostic.error.span(), Some(span));
+        assert!(diagnostic
+            .to_string()
+            .starts_with("m.f (line 3, column 15):"));
+
+        // The error is located on its own, without the diagnostic
+        match Compiler::new().compile(&program) {
+            Err(error) => assert_eq!(error.span(), Some(span)),
+            Ok(_) => panic!("expected a type error"),
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_on_non_numeric_literal_is_rejected() {
+        let compile = |body: &str| {
+            let source = format!("defun f (n: integer) -> integer {}", body);
+            let program = crate::parser::Parser::new(&source)
+                .unwrap()
+                .parse()
+                .unwrap();
+            Compiler::new().compile(&program)
+        };
+        assert!(compile("n + 1").is_ok());
+        for body in ["n + \"x\"", "true * n", "-[1]", "n - { a: 1 }"] {
+            assert!(
+                matches!(compile(body), Err(SlvrError::TypeError { .. })),
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn test_let_bindings_are_typed_in_body() {
+        let compile = |body: &str| {
+            let source = format!("defun f (p: integer) -> integer {}", body);
+            let program = crate::parser::Parser::new(&source)
+                .unwrap()
+                .parse()
+                .unwrap();
+            Compiler::new().compile(&program)
+        };
+        assert!(compile("let* (a = 1, b = a + 1) b * p").is_ok());
+        assert!(compile("(let x = 1 x) + p").is_ok());
+        assert!(matches!(
+            compile("let s = \"x\" s * 2"),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(matches!(
+            compile("let* (a = true, b = a) -b"),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(matches!(
+            compile("(let x = 1 x) + x"),
+            Err(SlvrError::UndefinedVariable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_object_literal_runs_on_vm() {
+        let source = "defconst c: object = {\"a b\": 1, c: [2, 3,],}";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+        let mut bytecode = Compiler::new().compile(&program).unwrap();
+        assert!(matches!(
+            bytecode.instructions.pop(),
+            Some(Instruction::StoreGlobal(_))
+        ));
+
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        let value = crate::vm::VirtualMachine::new(bytecode, runtime)
+            .execute()
+            .unwrap();
+        let expected = crate::value::Value::Object(
+            [
+                ("a b".to_string(), crate::value::Value::Integer(1)),
+                (
+                    "c".to_string(),
+                    crate::value::Value::List(vec![
+                        crate::value::Value::Integer(2),
+                        crate::value::Value::Integer(3),
+                    ]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(value, expected);
+    }
+
+    fn constant_bytecode(source: &str, config: &SlvrConfig) -> Vec<Instruction> {
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+        let mut bytecode = Compiler::with_config(config).compile(&program).unwrap();
+        assert!(matches!(
+            bytecode.instructions.pop(),
+            Some(Instruction::StoreGlobal(_))
+        ));
+        bytecode.instructions
+    }
+
+    #[test]
+    fn test_constant_folding_follows_optimization_flag() {
+        let source = "defconst c: integer = 2 + 3 * -4";
+        assert_eq!(
+            constant_bytecode(source, &SlvrConfig::new()),
+            vec![Instruction::PushInt(-10)]
+        );
+        assert_eq!(
+            constant_bytecode(source, &SlvrConfig::new().with_optimization(false)),
+            vec![
+                Instruction::PushInt(2),
+                Instruction::PushInt(3),
+                Instruction::PushInt(4),
+                Instruction::Negate,
+                Instruction::Multiply,
+                Instruction::Add,
+            ]
+        );
+
+        let config = SlvrConfig::new();
+        assert_eq!(
+            constant_bytecode("defconst c: string = \"a\" ++ \"b\"", &config),
+            vec![Instruction::PushString("ab".to_string())]
+        );
+        assert_eq!(
+            constant_bytecode("defconst c: boolean = 1 < 2 && !false", &config),
+            vec![Instruction::PushBool(true)]
+        );
+    }
+
+    #[test]
+    fn test_failing_constants_are_not_folded() {
+        let config = SlvrConfig::new();
+        let division = "defconst c: integer = 1 / 0";
+        assert_eq!(
+            constant_bytecode(division, &config),
+            vec![
+                Instruction::PushInt(1),
+                Instruction::PushInt(0),
+                Instruction::Divide,
+            ]
+        );
+        let program = crate::parser::Parser::new(division)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let bytecode = Compiler::with_config(&config).compile(&program).unwrap();
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        assert!(matches!(
+            VirtualMachine::new(bytecode, runtime).execute(),
+            Err(SlvrError::DivisionByZero)
+        ));
+
+        // Both operands still fold even though their sum would overflow
+        let overflow = "defconst c: integer = 2 ^ 126 + 2 ^ 126";
+        assert_eq!(
+            constant_bytecode(overflow, &config),
+            vec![
+                Instruction::PushInt(1 << 126),
+                Instruction::PushInt(1 << 126),
+                Instruction::Add,
+            ]
+        );
+        let program = crate::parser::Parser::new(overflow)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let bytecode = Compiler::with_config(&config).compile(&program).unwrap();
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        assert!(matches!(
+            VirtualMachine::new(bytecode, runtime).execute(),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ed25519_verify_runs_on_vm() {
+        use ed25519_dalek::Signer;
+
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let pubkey = hex::encode(key.verifying_key().to_bytes());
+        let signature = hex::encode(key.sign(b"transfer 10").to_bytes());
+        let run = |message: &str, signature: &str| {
+            let source = format!(
+                "defconst c: boolean = ed25519-verify(\"{}\", \"{}\", \"{}\")",
+                pubkey, message, signature
+            );
+            let program = crate::parser::Parser::new(&source)
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut bytecode = Compiler::new().compile(&program)?;
+            bytecode.push(Instruction::LoadGlobal("c".to_string()));
+            VirtualMachine::new(bytecode, crate::runtime::Runtime::new(1_000_000)).execute()
+        };
+
+        assert_eq!(
+            run("transfer 10", &signature).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            run("transfer 99", &signature).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(run("transfer 10", "00").unwrap(), Value::Boolean(false));
+
+        let program =
+            crate::parser::Parser::new("defconst c: boolean = ed25519-verify(\"k\", 1, \"s\")")
+                .unwrap()
+                .parse()
+                .unwrap();
+        assert!(matches!(
+            Compiler::new().compile(&program),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_enforce_on_vm() {
+        let run = |source: &str| {
+            let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+            let mut bytecode = Compiler::new().compile(&program)?;
+            bytecode.push(Instruction::LoadGlobal("c".to_string()));
+            let runtime = crate::runtime::Runtime::new(1_000_000);
+            VirtualMachine::new(bytecode, runtime).execute()
+        };
+        let enforce_failure = |source: &str| match run(source) {
+            Err(SlvrError::EnforceFailed { message }) => message,
+            other => panic!("expected enforce failure, got {:?}", other),
+        };
+
+        assert_eq!(
+            run("defconst c: unit = enforce(1 < 2, \"unused\")").unwrap(),
+            Value::Unit
+        );
+        assert_eq!(
+            enforce_failure("defconst c: unit = enforce(1 > 2, \"too low\")"),
+            "too low"
+        );
+        assert_eq!(
+            run("defconst c: unit = enforce-one([1 > 2, 2 > 1], \"none\")").unwrap(),
+            Value::Unit
+        );
+        assert_eq!(
+            enforce_failure("defconst c: unit = enforce-one([false, 1 > 2], \"none\")"),
+            "none"
+        );
+        assert!(matches!(
+            run("defconst c: unit = enforce(1, \"not a check\")"),
+            Err(SlvrError::TypeError { .. })
+        ));
+
+        // A failed enforce rolls back the writes made before it
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        let bytecode = Bytecode {
+            instructions: vec![
+                Instruction::PushString("alice".to_string()),
+                Instruction::PushInt(10),
+                Instruction::Write("ledger".to_string()),
+                Instruction::PushBool(false),
+                Instruction::PushString("rejected".to_string()),
+                Instruction::Enforce,
+            ],
+        };
+        let mut vm = VirtualMachine::new(bytecode, runtime);
+        assert!(matches!(vm.execute(), Err(SlvrError::EnforceFailed { .. })));
+        assert!(vm.runtime().read("ledger:alice").is_none());
+    }
+
+    #[test]
+    fn test_capabilities_are_rejected_by_vm() {
+        let source = "defcap ADMIN () true \
+                      defconst c: unit = require-capability(ADMIN())";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+        let bytecode = Compiler::new().compile(&program).unwrap();
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        match VirtualMachine::new(bytecode, runtime).execute() {
+            Err(SlvrError::RuntimeError { message, .. }) => {
+                assert_eq!(message, "require-capability is not supported by the VM")
+            }
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_capability_forms_are_type_checked() {
+        let compile = |source: &str| {
+            let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+            Compiler::new().compile(&program)
+        };
+        let caps = "defcap ADMIN () true \
+                    defcap PAY (amount: integer) manages(amount) amount > 0 ";
+        assert!(compile(&format!(
+            "{}defconst c: unit = with-capability(ADMIN(), install-capability(PAY(5)))",
+            caps
+        ))
+        .is_ok());
+
+        let rejected = [
+            // The body is checked even though the VM never runs it
+            "defconst c: unit = with-capability(ADMIN(), missing)",
+            "defconst c: unit = require-capability(PAY(missing))",
+            "defconst c: unit = require-capability(ADMIN(1))",
+            "defconst c: unit = require-capability(UNKNOWN())",
+            "defconst c: unit = install-capability(ADMIN())",
+            "defconst c: unit = with-capability(ADMIN())",
+        ];
+        for form in rejected {
+            assert!(compile(&format!("{}{}", caps, form)).is_err(), "{}", form);
+        }
+
+        assert!(compile("defcap BAD (n: integer) missing + n").is_err());
+        assert!(compile("defcap BAD (n: integer) \"yes\"").is_err());
+        assert!(compile("defcap BAD (n: integer) manages(n, nobody) true").is_err());
+    }
+
+    #[test]
+    fn test_logical_operators_short_circuit_on_vm() {
+        let run = |source: &str, config: &SlvrConfig| {
+            let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+            let mut bytecode = Compiler::with_config(config).compile(&program)?;
+            bytecode.push(Instruction::LoadGlobal("c".to_string()));
+            let runtime = crate::runtime::Runtime::new(1_000_000);
+            VirtualMachine::new(bytecode, runtime).execute()
+        };
+        let flags = "defconst no: boolean = false defconst yes: boolean = true ";
+
+        for config in [
+            SlvrConfig::new(),
+            SlvrConfig::new().with_optimization(false),
+        ] {
+            for (body, expected) in [
+                ("no && 1 / 0 == 1", false),
+                ("yes || 1 / 0 == 1", true),
+                ("false && 1 / 0 == 1", false),
+                ("true || 1 / 0 == 1", true),
+                ("yes && !no", true),
+                ("no || no", false),
+            ] {
+                let source = format!("{}defconst c: boolean = {}", flags, body);
+                assert_eq!(
+                    run(&source, &config).unwrap(),
+                    Value::Boolean(expected),
+                    "{}",
+                    body
+                );
+            }
+
+            let source = format!("{}defconst c: boolean = yes && 1 / 0 == 1", flags);
+            assert!(matches!(
+                run(&source, &config),
+                Err(SlvrError::DivisionByZero)
+            ));
+        }
+
+        // A decided constant drops the right operand, which is still checked
+        assert_eq!(
+            constant_bytecode(
+                "defconst c: boolean = false && 1 / 0 == 1",
+                &SlvrConfig::new()
+            ),
+            vec![Instruction::PushBool(false)]
+        );
+        let ill_typed = "defconst c: boolean = false && 1 + \"a\" == 1";
+        let program = crate::parser::Parser::new(ill_typed)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(Compiler::new().compile(&program).is_err());
+    }
+
+    #[test]
+    fn test_literal_elements_are_type_checked() {
+        let compile = |value: &str| {
+            let source = format!("defconst c: [object] = {}", value);
+            let program = crate::parser::Parser::new(&source)
+                .unwrap()
+                .parse()
+                .unwrap();
+            Compiler::new().compile(&program)
+        };
+        assert!(compile("[]").is_ok());
+        assert!(compile("[{}, {k: [1, 2]}]").is_ok());
+        assert!(matches!(
+            compile("[{}, {k: [true, -false]}]"),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(matches!(
+            compile("[{k: [1, \"a\" * 2]}]"),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_element_types_are_enforced() {
+        let compile = |source: &str| {
+            let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+            Compiler::new().compile(&program)
+        };
+        let with_list = |declared: &str, value: &str, body: &str| {
+            compile(&format!(
+                "defconst xs: {} = {} defun f () -> integer {}",
+                declared, value, body
+            ))
+        };
+        let is_type_error =
+            |result: SlvrResult<Bytecode>| matches!(result, Err(SlvrError::TypeError { .. }));
+
+        // Integers flow through at, indexing and sublist into numeric use
+        assert!(with_list("[integer]", "[1, 2, 3]", "at(xs, 0) + 1").is_ok());
+        assert!(with_list("[integer]", "[1, 2, 3]", "xs[1] * first(sublist(xs, 0, 1))").is_ok());
+        assert!(with_list("[integer]", "[1, 2]", "last(append(xs, 3)) - 1").is_ok());
+        assert!(is_type_error(with_list(
+            "[string]",
+            "[\"a\"]",
+            "at(xs, 0) + 1"
+        )));
+        assert!(is_type_error(with_list(
+            "[string]",
+            "[\"a\"]",
+            "at(take(xs, 1), 0) * 2"
+        )));
+
+        // A mixed list is rejected unless it is declared as a union,
+        // and then its elements are no longer known to be numbers
+        assert!(is_type_error(compile(
+            "defun f () -> integer let xs = [1, \"a\"] 0"
+        )));
+        assert!(is_type_error(with_list("[integer]", "[1, \"a\"]", "0")));
+        assert!(with_list("[integer | string]", "[1, \"a\"]", "0").is_ok());
+        assert!(is_type_error(with_list(
+            "[integer | string]",
+            "[1, \"a\"]",
+            "at(xs, 0) + 1"
+        )));
+        assert!(is_type_error(with_list(
+            "[integer]",
+            "[1]",
+            "at(append(xs, \"b\"), 0)"
+        )));
+    }
+
+    #[test]
+    fn test_alias_resolves_in_signatures() {
+        // The alias is used before it is declared, inside a module
+        let source = "module m \"doc\" { \
+            defschema account { owner: string, history: ledger } \
+            defun credit (amount: amount, history: ledger) -> ledger append(history, amount) \
+            deftype ledger = [amount] \
+            deftype amount = decimal }";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+        let mut compiler = Compiler::new();
+        compiler.compile(&program).unwrap();
+
+        let ledger = Type::List(Box::new(Type::Decimal));
+        assert_eq!(
+            compiler.type_env.lookup_function("credit"),
+            Some((vec![Type::Decimal, ledger.clone()], ledger.clone()))
+        );
+        match compiler.type_env.lookup_custom_type("account") {
+            Some(Type::Schema(fields)) => assert_eq!(fields.get("history"), Some(&ledger)),
+            other => panic!("expected a schema, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_schema_reads_are_typed() {
+        // The coin contract from the crate docs
+        let compile = |body: &str| {
+            let source = format!(
+                "module coin \"A simple coin contract\" {{ \
+                 defschema coin-schema \"Schema for coin objects\" {{ balance: integer, owner: string }} \
+                 deftable coins: coin-schema \"Table of coin objects\" \
+                 defun f (id: string) -> integer {} }}",
+                body
+            );
+            let program = crate::parser::Parser::new(&source)
+                .unwrap()
+                .parse()
+                .unwrap();
+            Compiler::new().compile(&program)
+        };
+        let is_type_error =
+            |result: SlvrResult<Bytecode>| matches!(result, Err(SlvrError::TypeError { .. }));
+
+        assert!(compile("read(coins, id).balance + 1").is_ok());
+        assert!(compile("let row = read(coins, id) row[\"balance\"] - 1").is_ok());
+        assert!(is_type_error(compile("read(coins, id).owner + 1")));
+        assert!(is_type_error(compile(
+            "let row = read(coins, id) row[\"owner\"] * 2"
+        )));
+
+        match compile("read(coins, id).amount") {
+            Err(SlvrError::TypeError { message, .. }) => assert_eq!(
+                message,
+                "{balance: integer, owner: string} has no field amount"
+            ),
+            other => panic!("expected a missing field error, got {:?}", other),
+        }
+        assert!(is_type_error(compile(
+            "let row = read(coins, id) row[\"amount\"]"
+        )));
+
+        // Object literals have a known shape too; untyped objects stay unchecked
+        assert!(is_type_error(compile("{balance: 1}.owner")));
+        assert!(compile("{}.owner").is_ok());
+    }
+
+    #[test]
+    fn test_optional_row_must_be_checked() {
+        let compile = |body: &str| {
+            let source = format!(
+                "defschema coin-schema {{ balance: integer, owner: string }} \
+                 deftable coins: coin-schema \
+                 defun f (id: string) -> integer let row = read-safe(coins, id) {}",
+                body
+            );
+            let program = crate::parser::Parser::new(&source)
+                .unwrap()
+                .parse()
+                .unwrap();
+            Compiler::new().compile(&program)
+        };
+        let is_type_error =
+            |result: SlvrResult<Bytecode>| matches!(result, Err(SlvrError::TypeError { .. }));
+
+        // Using the row before branching on it assumes it exists
+        assert!(is_type_error(compile("row.balance")));
+        assert!(is_type_error(compile("row[\"balance\"]")));
+        assert!(is_type_error(compile("row + 1")));
+        assert!(is_type_error(compile("if row == null row.balance else 0")));
+
+        assert!(compile("if row != null row.balance else 0").is_ok());
+        assert!(compile("if null != row row[\"balance\"] + 1 else 0").is_ok());
+        assert!(compile("if row == null 0 else row.balance * 2").is_ok());
+        // Once unwrapped the row is typed as usual
+        assert!(is_type_error(compile(
+            "if row != null row.owner + 1 else 0"
+        )));
+        assert!(is_type_error(compile("if row != null row.missing else 0")));
+        // Narrowing ends with the branch
+        assert!(is_type_error(compile(
+            "let n = if row != null 1 else 0 n + row.balance"
+        )));
+    }
+
+    #[test]
+    fn test_self_referential_alias_is_reported() {
+        let source = "deftype tree = [tree | integer] \
+            deftype a = b \
+            deftype b = [a]";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+        let (bytecode, diagnostics) = Compiler::new().compile_with_diagnostics(&program);
+        assert!(bytecode.is_none());
+        let locations: Vec<&str> = diagnostics.iter().map(|d| d.location.as_str()).collect();
+        assert_eq!(locations, vec!["tree", "b"]);
+        assert!(diagnostics
+            .iter()
+            .all(|d| matches!(d.error, SlvrError::TypeError { .. })));
+    }
+
+    #[test]
+    fn test_lambda_has_function_type() {
+        let compile = |body: &str| {
+            let source = format!("defun f () -> integer {}", body);
+            let program = crate::parser::Parser::new(&source)
+                .unwrap()
+                .parse()
+                .unwrap();
+            Compiler::new().compile(&program)
+        };
+        assert!(compile("let add = lambda (a, b) a + b add(1, 2)").is_ok());
+        assert!(matches!(
+            compile("let add = lambda (a, b) a + b add(1)"),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(matches!(
+            compile("let id = lambda (a) a id + 1"),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(matches!(
+            compile("lambda (a) a + \"x\""),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(matches!(
+            compile("lambda (a) b"),
+            Err(SlvrError::UndefinedVariable { .. })
+        ));
+        // Locals of the enclosing function stay visible to the body
+        assert!(compile("let x = 1 let f = lambda (a) a + x f(1)").is_ok());
+        assert!(matches!(
+            compile("let x = \"s\" let f = lambda (a) -x f(1)"),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lambdas_are_rejected_by_vm() {
+        let source = "defconst c: integer = let f = lambda (a) a + 1 f(1)";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+        let bytecode = Compiler::new().compile(&program).unwrap();
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        match VirtualMachine::new(bytecode, runtime).execute() {
+            Err(SlvrError::RuntimeError { message, .. }) => {
+                assert_eq!(message, "lambda values are not supported by the VM")
+            }
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hand_built_ast_has_no_span() {
+        let program = Program {
+            definitions: vec![Definition::Constant {
+                name: "c".to_string(),
+                ty: crate::ast::Type::Integer,
+                value: Expr::UnaryOp {
+                    op: UnaryOp::Negate,
+                    operand: Box::new(Expr::Literal(Literal::Boolean(true))),
+                },
+            }],
+        };
+        let (_, diagnostics) = Compiler::new().compile_with_diagnostics(&program);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].span.is_none());
+    }
 }
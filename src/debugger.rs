@@ -4,7 +4,7 @@
 //! call stack tracking, and real-time state monitoring.
 
 use crate::error::{SlvrError, SlvrResult};
-use crate::value::Value;
+use crate::value::{checked_decimal, parse_decimal, Value};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -231,58 +231,43 @@ impl Debugger {
     /// Add breakpoint
     pub fn add_breakpoint(&self, breakpoint: Breakpoint) -> SlvrResult<String> {
         let id = breakpoint.id.clone();
-        let mut bps = self
-            .breakpoints
-            .lock()
-            .map_err(|e| SlvrError::RuntimeError {
-                message: format!("Failed to acquire breakpoints lock: {}", e),
-            })?;
+        let mut bps = self.breakpoints.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire breakpoints lock: {}", e))
+        })?;
         bps.insert(id.clone(), breakpoint);
         Ok(id)
     }
 
     /// Remove breakpoint
     pub fn remove_breakpoint(&self, id: &str) -> SlvrResult<()> {
-        let mut bps = self
-            .breakpoints
-            .lock()
-            .map_err(|e| SlvrError::RuntimeError {
-                message: format!("Failed to acquire breakpoints lock: {}", e),
-            })?;
+        let mut bps = self.breakpoints.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire breakpoints lock: {}", e))
+        })?;
         bps.remove(id);
         Ok(())
     }
 
     /// Get breakpoint
     pub fn get_breakpoint(&self, id: &str) -> SlvrResult<Option<Breakpoint>> {
-        let bps = self
-            .breakpoints
-            .lock()
-            .map_err(|e| SlvrError::RuntimeError {
-                message: format!("Failed to acquire breakpoints lock: {}", e),
-            })?;
+        let bps = self.breakpoints.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire breakpoints lock: {}", e))
+        })?;
         Ok(bps.get(id).cloned())
     }
 
     /// Get all breakpoints
     pub fn get_breakpoints(&self) -> SlvrResult<Vec<Breakpoint>> {
-        let bps = self
-            .breakpoints
-            .lock()
-            .map_err(|e| SlvrError::RuntimeError {
-                message: format!("Failed to acquire breakpoints lock: {}", e),
-            })?;
+        let bps = self.breakpoints.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire breakpoints lock: {}", e))
+        })?;
         Ok(bps.values().cloned().collect())
     }
 
     /// Check if breakpoint at location
     pub fn check_breakpoint(&self, file: &str, line: u32) -> SlvrResult<Option<Breakpoint>> {
-        let bps = self
-            .breakpoints
-            .lock()
-            .map_err(|e| SlvrError::RuntimeError {
-                message: format!("Failed to acquire breakpoints lock: {}", e),
-            })?;
+        let bps = self.breakpoints.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire breakpoints lock: {}", e))
+        })?;
         Ok(bps
             .values()
             .find(|bp| bp.should_trigger(file, line))
@@ -291,9 +276,10 @@ impl Debugger {
 
     /// Pause execution
     pub fn pause(&self, file: String, line: u32, column: u32) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.state = ExecutionState::Paused;
         session.current_file = file;
         session.current_line = line;
@@ -304,23 +290,26 @@ impl Debugger {
 
     /// Resume execution
     pub fn resume(&self) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.state = ExecutionState::Running;
         Ok(())
     }
 
     /// Step execution
     pub fn step(&self, step_type: StepType) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.state = ExecutionState::Stepping;
 
-        let mut st = self.step_type.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire step_type lock: {}", e),
-        })?;
+        let mut st = self
+            .step_type
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire step_type lock: {}", e)))?;
         *st = Some(step_type);
 
         Ok(())
@@ -348,17 +337,19 @@ impl Debugger {
 
     /// Get current state
     pub fn get_state(&self) -> SlvrResult<ExecutionState> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok(session.state)
     }
 
     /// Get current location
     pub fn get_location(&self) -> SlvrResult<(String, u32, u32)> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok((
             session.current_file.clone(),
             session.current_line,
@@ -368,51 +359,57 @@ impl Debugger {
 
     /// Get call stack
     pub fn get_call_stack(&self) -> SlvrResult<CallStack> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok(session.call_stack.clone())
     }
 
     /// Push frame
     pub fn push_frame(&self, frame: StackFrame) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.call_stack.push(frame);
         Ok(())
     }
 
     /// Pop frame
     pub fn pop_frame(&self) -> SlvrResult<Option<StackFrame>> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok(session.call_stack.pop())
     }
 
     /// Get variables
     pub fn get_variables(&self) -> SlvrResult<HashMap<String, Value>> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok(session.variables.clone())
     }
 
     /// Set variable
     pub fn set_variable(&self, name: String, value: Value) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.variables.insert(name, value);
         Ok(())
     }
 
     /// Get variable
     pub fn get_variable(&self, name: &str) -> SlvrResult<Option<Value>> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok(session.variables.get(name).cloned())
     }
 
@@ -426,9 +423,10 @@ impl Debugger {
         };
         let id = watch.id.clone();
 
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.watches.push(watch);
 
         Ok(id)
@@ -436,26 +434,29 @@ impl Debugger {
 
     /// Remove watch expression
     pub fn remove_watch(&self, id: &str) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.watches.retain(|w| w.id != id);
         Ok(())
     }
 
     /// Get watch expressions
     pub fn get_watches(&self) -> SlvrResult<Vec<WatchExpression>> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok(session.watches.clone())
     }
 
     /// Update watch value
     pub fn update_watch(&self, id: &str, value: Value) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         if let Some(watch) = session.watches.iter_mut().find(|w| w.id == id) {
             watch.value = Some(value);
             watch.error = None;
@@ -465,9 +466,10 @@ impl Debugger {
 
     /// Update watch error
     pub fn update_watch_error(&self, id: &str, error: String) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         if let Some(watch) = session.watches.iter_mut().find(|w| w.id == id) {
             watch.error = Some(error);
             watch.value = None;
@@ -477,18 +479,20 @@ impl Debugger {
 
     /// Stop debugging
     pub fn stop(&self) -> SlvrResult<()> {
-        let mut session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         session.state = ExecutionState::Stopped;
         Ok(())
     }
 
     /// Get session info
     pub fn get_session_info(&self) -> SlvrResult<DebugSession> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         Ok(session.clone())
     }
 
@@ -501,17 +505,18 @@ impl Debugger {
         // - Function calls: "len(array)", "sqrt(16)"
         // - Comparisons: "x > 5", "name == 'test'"
 
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
 
         // Get current stack frame for variable lookup
         let current_frame = match session.call_stack.current() {
             Some(frame) => frame.clone(),
             None => {
-                return Err(SlvrError::RuntimeError {
-                    message: "No active stack frame for expression evaluation".to_string(),
-                })
+                return Err(SlvrError::runtime(
+                    "No active stack frame for expression evaluation",
+                ))
             }
         };
 
@@ -537,7 +542,7 @@ impl Debugger {
             return Ok(Value::Integer(num));
         }
 
-        if let Ok(num) = trimmed.parse::<f64>() {
+        if let Some(num) = parse_decimal(trimmed) {
             return Ok(Value::Decimal(num));
         }
 
@@ -632,12 +637,10 @@ impl Debugger {
         }
 
         // If we can't evaluate the expression, return an error
-        Err(SlvrError::RuntimeError {
-            message: format!(
-                "Cannot evaluate expression: '{}' in current context",
-                expression
-            ),
-        })
+        Err(SlvrError::runtime(format!(
+            "Cannot evaluate expression: '{}' in current context",
+            expression
+        )))
     }
 
     /// Production-grade arithmetic expression parser with proper operator precedence
@@ -740,9 +743,7 @@ impl Debugger {
     /// Parse primary expressions (numbers, variables, parentheses)
     fn parse_primary(&self, tokens: &[String], pos: usize) -> SlvrResult<(Value, usize)> {
         if pos >= tokens.len() {
-            return Err(SlvrError::RuntimeError {
-                message: "Unexpected end of expression".to_string(),
-            });
+            return Err(SlvrError::runtime("Unexpected end of expression"));
         }
 
         let token = &tokens[pos];
@@ -751,9 +752,7 @@ impl Debugger {
         if token == "(" {
             let (result, new_pos) = self.parse_additive(tokens, pos + 1)?;
             if new_pos >= tokens.len() || tokens[new_pos] != ")" {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing closing parenthesis".to_string(),
-                });
+                return Err(SlvrError::runtime("Missing closing parenthesis"));
             }
             return Ok((result, new_pos + 1));
         }
@@ -764,11 +763,7 @@ impl Debugger {
             let negated = match value {
                 Value::Integer(n) => Value::Integer(-n),
                 Value::Decimal(d) => Value::Decimal(-d),
-                _ => {
-                    return Err(SlvrError::RuntimeError {
-                        message: "Cannot negate non-numeric value".to_string(),
-                    })
-                }
+                _ => return Err(SlvrError::runtime("Cannot negate non-numeric value")),
             };
             return Ok((negated, new_pos));
         }
@@ -778,14 +773,15 @@ impl Debugger {
             return Ok((Value::Integer(num), pos + 1));
         }
 
-        if let Ok(num) = token.parse::<f64>() {
+        if let Some(num) = parse_decimal(token) {
             return Ok((Value::Decimal(num), pos + 1));
         }
 
         // Parse variable reference
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         if let Some(frame) = session.call_stack.current() {
             if let Some(value) = frame.locals.get(token) {
                 return Ok((value.clone(), pos + 1));
@@ -795,9 +791,10 @@ impl Debugger {
             }
         }
 
-        Err(SlvrError::RuntimeError {
-            message: format!("Unknown token or variable: '{}'", token),
-        })
+        Err(SlvrError::runtime(format!(
+            "Unknown token or variable: '{}'",
+            token
+        )))
     }
 
     /// Apply binary operation
@@ -809,85 +806,43 @@ impl Debugger {
                 "*" => Ok(Value::Integer(l * r)),
                 "/" => {
                     if *r == 0 {
-                        Err(SlvrError::RuntimeError {
-                            message: "Division by zero".to_string(),
-                        })
+                        Err(SlvrError::runtime("Division by zero"))
                     } else {
                         Ok(Value::Integer(l / r))
                     }
                 }
-                _ => Err(SlvrError::RuntimeError {
-                    message: format!("Unknown operator: {}", op),
-                }),
+                _ => Err(SlvrError::runtime(format!("Unknown operator: {}", op))),
             },
-            (Value::Decimal(l), Value::Decimal(r)) => match op {
-                "+" => Ok(Value::Decimal(l + r)),
-                "-" => Ok(Value::Decimal(l - r)),
-                "*" => Ok(Value::Decimal(l * r)),
-                "/" => {
-                    if *r == 0.0 {
-                        Err(SlvrError::RuntimeError {
-                            message: "Division by zero".to_string(),
-                        })
-                    } else {
-                        Ok(Value::Decimal(l / r))
-                    }
-                }
-                _ => Err(SlvrError::RuntimeError {
-                    message: format!("Unknown operator: {}", op),
-                }),
-            },
-            (Value::Integer(l), Value::Decimal(r)) => {
-                let l = *l as f64;
+            (Value::Decimal(_), Value::Integer(_) | Value::Decimal(_))
+            | (Value::Integer(_), Value::Decimal(_)) => {
+                let (l, r) = (left.to_decimal()?, right.to_decimal()?);
                 match op {
-                    "+" => Ok(Value::Decimal(l + r)),
-                    "-" => Ok(Value::Decimal(l - r)),
-                    "*" => Ok(Value::Decimal(l * r)),
+                    "+" => checked_decimal(l.checked_add(r), op),
+                    "-" => checked_decimal(l.checked_sub(r), op),
+                    "*" => checked_decimal(l.checked_mul(r), op),
                     "/" => {
-                        if *r == 0.0 {
-                            Err(SlvrError::RuntimeError {
-                                message: "Division by zero".to_string(),
-                            })
+                        if r.is_zero() {
+                            Err(SlvrError::runtime("Division by zero"))
                         } else {
-                            Ok(Value::Decimal(l / r))
+                            checked_decimal(l.checked_div(r), op)
                         }
                     }
-                    _ => Err(SlvrError::RuntimeError {
-                        message: format!("Unknown operator: {}", op),
-                    }),
+                    _ => Err(SlvrError::runtime(format!("Unknown operator: {}", op))),
                 }
             }
-            (Value::Decimal(l), Value::Integer(r)) => {
-                let r = *r as f64;
-                match op {
-                    "+" => Ok(Value::Decimal(l + r)),
-                    "-" => Ok(Value::Decimal(l - r)),
-                    "*" => Ok(Value::Decimal(l * r)),
-                    "/" => {
-                        if r == 0.0 {
-                            Err(SlvrError::RuntimeError {
-                                message: "Division by zero".to_string(),
-                            })
-                        } else {
-                            Ok(Value::Decimal(l / r))
-                        }
-                    }
-                    _ => Err(SlvrError::RuntimeError {
-                        message: format!("Unknown operator: {}", op),
-                    }),
-                }
-            }
-            _ => Err(SlvrError::RuntimeError {
-                message: format!("Cannot apply {} to non-numeric types", op),
-            }),
+            _ => Err(SlvrError::runtime(format!(
+                "Cannot apply {} to non-numeric types",
+                op
+            ))),
         }
     }
 
     /// Get locals
     pub fn get_locals(&self) -> SlvrResult<HashMap<String, Value>> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         if let Some(frame) = session.call_stack.current() {
             Ok(frame.locals.clone())
         } else {
@@ -897,9 +852,10 @@ impl Debugger {
 
     /// Get arguments
     pub fn get_arguments(&self) -> SlvrResult<HashMap<String, Value>> {
-        let session = self.session.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire session lock: {}", e),
-        })?;
+        let session = self
+            .session
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire session lock: {}", e)))?;
         if let Some(frame) = session.call_stack.current() {
             Ok(frame.arguments.clone())
         } else {
@@ -147,9 +147,7 @@ impl CapabilityManager {
         self.definitions
             .get(cap_id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Capability not found: {}", cap_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Capability not found: {}", cap_id)))
     }
 
     /// Grant a capability to a principal
@@ -199,9 +197,7 @@ impl CapabilityManager {
             grant.active = false;
             Ok(())
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("Grant not found: {}", grant_id),
-            })
+            Err(SlvrError::runtime(format!("Grant not found: {}", grant_id)))
         }
     }
 
@@ -263,9 +259,7 @@ impl CapabilityManager {
         self.grants
             .get(grant_id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Grant not found: {}", grant_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Grant not found: {}", grant_id)))
     }
 
     /// Update grant metadata
@@ -278,9 +272,7 @@ impl CapabilityManager {
             grant.metadata = metadata;
             Ok(())
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("Grant not found: {}", grant_id),
-            })
+            Err(SlvrError::runtime(format!("Grant not found: {}", grant_id)))
         }
     }
 
@@ -324,6 +316,186 @@ impl CapabilityManager {
     }
 }
 
+/// A capability bound to concrete arguments, as written `NAME(args)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapabilityToken {
+    /// Capability name from its `defcap`
+    pub name: String,
+    /// Arguments the capability was acquired with
+    pub args: Vec<Value>,
+}
+
+impl CapabilityToken {
+    /// Create a token for `name` applied to `args`
+    pub fn new(name: impl Into<String>, args: Vec<Value>) -> Self {
+        Self {
+            name: name.into(),
+            args,
+        }
+    }
+}
+
+impl std::fmt::Display for CapabilityToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", arg)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Capabilities granted for the dynamic extent of `with-capability`,
+/// innermost last
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityStack {
+    granted: Vec<CapabilityToken>,
+}
+
+impl CapabilityStack {
+    /// Create an empty stack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `token` is granted by any enclosing scope
+    pub fn is_granted(&self, token: &CapabilityToken) -> bool {
+        self.granted.contains(token)
+    }
+
+    /// Grant `token` until the stack is truncated below it; returns the
+    /// depth to truncate back to
+    pub fn push(&mut self, token: CapabilityToken) -> usize {
+        self.granted.push(token);
+        self.granted.len() - 1
+    }
+
+    /// Release every grant made at or above `depth`
+    pub fn truncate(&mut self, depth: usize) {
+        self.granted.truncate(depth);
+    }
+
+    /// Currently granted capabilities, outermost first
+    pub fn granted(&self) -> &[CapabilityToken] {
+        &self.granted
+    }
+
+    /// Require that `token` is granted
+    pub fn require(&self, token: &CapabilityToken) -> SlvrResult<()> {
+        if self.is_granted(token) {
+            Ok(())
+        } else {
+            Err(SlvrError::CapabilityNotGranted {
+                capability: token.to_string(),
+            })
+        }
+    }
+}
+
+/// Quantities installed for managed capabilities within a transaction.
+///
+/// Each entry is keyed by the capability with its managed argument removed,
+/// so `TRANSFER("alice", "bob", 60)` draws on what was installed for
+/// `TRANSFER("alice", "bob")`.
+#[derive(Debug, Clone, Default)]
+pub struct ManagedCapabilities {
+    installed: Vec<(CapabilityToken, Value)>,
+}
+
+impl ManagedCapabilities {
+    /// Create an empty set of installations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install `quantity` for `key`; a capability is installed at most once
+    /// per transaction
+    pub fn install(&mut self, key: CapabilityToken, quantity: Value) -> SlvrResult<()> {
+        if self.remaining(&key).is_some() {
+            return Err(SlvrError::runtime(format!(
+                "Capability already installed: {}",
+                key
+            )));
+        }
+        self.installed.push((key, quantity));
+        Ok(())
+    }
+
+    /// Quantity still available for `key`, if it is installed
+    pub fn remaining(&self, key: &CapabilityToken) -> Option<&Value> {
+        self.installed
+            .iter()
+            .find(|(installed, _)| installed == key)
+            .map(|(_, quantity)| quantity)
+    }
+
+    /// Replace the quantity available for an installed `key`
+    pub fn set_remaining(&mut self, key: &CapabilityToken, quantity: Value) -> SlvrResult<()> {
+        match self
+            .installed
+            .iter_mut()
+            .find(|(installed, _)| installed == key)
+        {
+            Some((_, remaining)) => {
+                *remaining = quantity;
+                Ok(())
+            }
+            None => Err(SlvrError::runtime(format!(
+                "Capability not installed: {}",
+                key
+            ))),
+        }
+    }
+
+    /// Drop every installation, as at the end of a transaction
+    pub fn clear(&mut self) {
+        self.installed.clear();
+    }
+}
+
+/// Default manager: take `requested` out of `remaining`, rejecting a
+/// negative request or one larger than what remains
+pub fn decrement_managed(
+    capability: &CapabilityToken,
+    remaining: &Value,
+    requested: &Value,
+) -> SlvrResult<Value> {
+    let negative = match requested {
+        Value::Integer(r) => *r < 0,
+        Value::Decimal(r) => r.is_sign_negative() && !r.is_zero(),
+        other => {
+            return Err(SlvrError::type_mismatch(
+                remaining.type_name(),
+                other.type_name(),
+            ))
+        }
+    };
+    if negative {
+        return Err(SlvrError::invalid_arg(format!(
+            "{} requests a negative quantity",
+            capability
+        )));
+    }
+    let left = match (remaining, requested) {
+        (Value::Integer(left), Value::Integer(r)) => (r <= left).then(|| Value::Integer(left - r)),
+        (Value::Decimal(left), Value::Decimal(r)) => (r <= left).then(|| Value::Decimal(left - r)),
+        _ => {
+            return Err(SlvrError::type_mismatch(
+                remaining.type_name(),
+                requested.type_name(),
+            ))
+        }
+    };
+    left.ok_or_else(|| SlvrError::ManagedQuantityExceeded {
+        capability: capability.to_string(),
+        requested: requested.to_string(),
+        remaining: remaining.to_string(),
+    })
+}
+
 /// Statistics for capability manager
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CapabilityStats {
@@ -445,6 +617,46 @@ mod tests {
         assert_eq!(caps.len(), 2);
     }
 
+    #[test]
+    fn test_capability_stack_scopes_grants() {
+        let transfer = CapabilityToken::new("TRANSFER", vec![Value::Integer(10)]);
+        let other = CapabilityToken::new("TRANSFER", vec![Value::Integer(11)]);
+        let mut stack = CapabilityStack::new();
+
+        let depth = stack.push(transfer.clone());
+        assert!(stack.require(&transfer).is_ok());
+        assert!(matches!(
+            stack.require(&other),
+            Err(SlvrError::CapabilityNotGranted { capability }) if capability == "TRANSFER(11)"
+        ));
+
+        stack.truncate(depth);
+        assert!(!stack.is_granted(&transfer));
+    }
+
+    #[test]
+    fn test_decrement_managed() {
+        let token = CapabilityToken::new("TRANSFER", vec![]);
+        let amount = |s: &str| Value::Decimal(s.parse().unwrap());
+
+        assert_eq!(
+            decrement_managed(&token, &amount("1.5"), &amount("0.5")).unwrap(),
+            amount("1.0")
+        );
+        assert!(matches!(
+            decrement_managed(&token, &Value::Integer(5), &Value::Integer(6)),
+            Err(SlvrError::ManagedQuantityExceeded { .. })
+        ));
+        assert!(matches!(
+            decrement_managed(&token, &Value::Integer(5), &Value::Integer(-1)),
+            Err(SlvrError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            decrement_managed(&token, &Value::Integer(5), &amount("1.0")),
+            Err(SlvrError::TypeMismatch { .. })
+        ));
+    }
+
     #[test]
     fn test_capability_stats() {
         let mut manager = CapabilityManager::new();
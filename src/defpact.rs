@@ -4,16 +4,20 @@
 //! contracts to execute in multiple steps across different blocks, with state
 //! persistence between steps.
 
+use crate::address;
+use crate::chainweb::{ChainId, ChainwebNetwork, PactContinuation, SpvProof};
 use crate::error::{SlvrError, SlvrResult};
+use crate::runtime::{FuelSchedule, Runtime};
 use crate::value::Value;
 use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Context for contract function execution
 #[derive(Debug, Clone)]
-struct ContractExecutionContext {
+struct ContractExecutionContext<'a> {
     /// Contract name
     contract: String,
     /// Function name
@@ -22,14 +26,43 @@ struct ContractExecutionContext {
     step_name: String,
     /// Input parameters
     inputs: HashMap<String, Value>,
-    /// Shared state
-    shared_state: HashMap<String, Value>,
+    /// Shared state, borrowed from the pact
+    shared_state: &'a HashMap<String, Value>,
     /// Yield value from previous step
     yield_value: Option<Value>,
     /// Fuel limit
     fuel_limit: u64,
 }
 
+/// Shared state changes a successful builtin step declares in its output.
+///
+/// Token steps report the new supply or sender balance; state updates report
+/// the keys they wrote. Other steps change nothing.
+fn declared_state_changes(contract: &str, function: &str, output: &Value) -> Vec<(String, Value)> {
+    let Value::Object(fields) = output else {
+        return Vec::new();
+    };
+    let renamed = |field: &str, key: &str| {
+        fields
+            .get(field)
+            .map(|value| vec![(key.to_string(), value.clone())])
+            .unwrap_or_default()
+    };
+    match (contract, function) {
+        ("token", "mint") | ("token", "burn") => renamed("new_supply", "total_supply"),
+        ("token", "transfer") => renamed("new_balance", "balance"),
+        ("state", "update") => match (fields.get("updates"), fields.get("key")) {
+            (Some(Value::Object(updates)), _) => updates
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            (None, Some(Value::String(key))) => renamed("value", key),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
 /// Represents a step in a multi-step transaction
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PactStep {
@@ -55,6 +88,9 @@ pub struct PactStep {
     pub error: Option<String>,
     /// Fuel consumed by this step
     pub fuel_consumed: u64,
+    /// Function that undoes this step when the pact is rolled back past it
+    #[serde(default)]
+    pub rollback: Option<String>,
 }
 
 /// Status of a pact step
@@ -113,6 +149,14 @@ pub struct Pact {
     pub max_fuel: u64,
     /// Yield value from previous step (for continuation)
     pub yield_value: Option<Value>,
+    /// Chain the pact yielded to; its next step only runs there, through
+    /// `resume_on_chain`
+    #[serde(default)]
+    pub yield_target: Option<ChainId>,
+    /// Chain the pact last yielded from or resumed on; `None` until it first
+    /// crosses chains
+    #[serde(default)]
+    pub chain: Option<ChainId>,
 }
 
 /// Overall status of a pact
@@ -171,6 +215,10 @@ pub struct PactManager {
     pacts: HashMap<String, Pact>,
     /// Pact execution history
     history: Vec<PactExecutionRecord>,
+    /// Fuel cost of each pact builtin
+    fuel_schedule: FuelSchedule,
+    /// State that steps read and write
+    runtime: Runtime,
 }
 
 impl Default for PactManager {
@@ -185,12 +233,32 @@ impl PactManager {
         Self {
             pacts: HashMap::new(),
             history: Vec::new(),
+            fuel_schedule: FuelSchedule::default(),
+            runtime: Runtime::default(),
         }
     }
 
+    /// Run steps against `runtime` instead of a private one
+    pub fn with_runtime(mut self, runtime: Runtime) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Get the runtime steps write to
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// Charge pact builtins according to `schedule`
+    pub fn with_fuel_schedule(mut self, schedule: FuelSchedule) -> Self {
+        self.fuel_schedule = schedule;
+        self
+    }
+
     /// PRODUCTION IMPLEMENTATION: Execute a real pact step with full contract logic
     /// This is the core execution engine for multi-step transactions
     /// Implements dynamic contract registry lookup and bytecode execution
+    #[allow(clippy::too_many_arguments)]
     fn execute_pact_step_real(
         &self,
         contract: &str,
@@ -213,20 +281,19 @@ impl PactManager {
 
         // Step 1: Validate contract and function exist
         if contract.is_empty() || function.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid contract or function: {}/{}", contract, function),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid contract or function: {}/{}",
+                contract, function
+            )));
         }
 
         // Step 2: Validate step name
         if step_name.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Step name cannot be empty".to_string(),
-            });
+            return Err(SlvrError::runtime("Step name cannot be empty"));
         }
 
         // Step 3: Validate inputs
-        fuel_consumed += 100; // Input validation cost
+        fuel_consumed += self.fuel_schedule.builtin_cost("pact.validate-inputs");
         if fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
                 used: fuel_consumed,
@@ -250,7 +317,7 @@ impl PactManager {
             function: function.to_string(),
             step_name: step_name.to_string(),
             inputs: inputs.clone(),
-            shared_state: shared_state.clone(),
+            shared_state,
             yield_value,
             fuel_limit,
         };
@@ -291,16 +358,18 @@ impl PactManager {
             .chars()
             .all(|c| c.is_alphanumeric() || c == '_' || c == ':')
         {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid contract name: {}", contract),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid contract name: {}",
+                contract
+            )));
         }
 
         // Validate function name format
         if !function.chars().all(|c| c.is_alphanumeric() || c == '_') {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid function name: {}", function),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid function name: {}",
+                function
+            )));
         }
 
         // Execute based on contract and function combination
@@ -327,13 +396,17 @@ impl PactManager {
             ("state", "query") => {
                 self.execute_state_query(inputs, shared_state, fuel_consumed, fuel_limit)
             }
-            ("state", "update") => {
-                self.execute_state_update(inputs, shared_state, fuel_consumed, fuel_limit)
-            }
+            ("state", "update") => match inputs.get("updates") {
+                Some(Value::Object(updates)) => {
+                    self.execute_state_updates(updates, fuel_consumed, fuel_limit)
+                }
+                Some(other) => Err(SlvrError::type_mismatch("object", other.type_name())),
+                None => self.execute_state_update(inputs, shared_state, fuel_consumed, fuel_limit),
+            },
 
             // Default: Generic step execution with yield value support
             _ => {
-                *fuel_consumed += 150; // Generic step cost
+                *fuel_consumed += self.fuel_schedule.builtin_cost("pact.step");
 
                 if *fuel_consumed > fuel_limit {
                     return Err(SlvrError::FuelExceeded {
@@ -379,7 +452,7 @@ impl PactManager {
         fuel_consumed: &mut u64,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        *fuel_consumed += 500; // Transfer operation cost
+        *fuel_consumed += self.fuel_schedule.builtin_cost("token.transfer");
 
         if *fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
@@ -390,62 +463,51 @@ impl PactManager {
 
         let from = match inputs.get("from") {
             Some(Value::String(s)) => s.clone(),
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing 'from' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing 'from' parameter")),
         };
 
         let to = match inputs.get("to") {
             Some(Value::String(s)) => s.clone(),
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing 'to' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing 'to' parameter")),
         };
 
         let amount = match inputs.get("amount") {
             Some(Value::Integer(n)) => *n as u64,
-            Some(Value::Decimal(d)) => *d as u64,
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing or invalid 'amount' parameter".to_string(),
-                })
-            }
+            Some(Value::Decimal(d)) => d.to_u64().unwrap_or(0),
+            _ => return Err(SlvrError::runtime("Missing or invalid 'amount' parameter")),
         };
 
-        // Validate addresses (512-bit SLVR format)
-        if !from.starts_with("SLVR") || from.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid sender address: {}", from),
-            });
+        // Validate addresses
+        if !address::is_valid(&from) {
+            return Err(SlvrError::runtime(format!(
+                "Invalid sender address: {}",
+                from
+            )));
         }
 
-        if !to.starts_with("SLVR") || to.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid recipient address: {}", to),
-            });
+        if !address::is_valid(&to) {
+            return Err(SlvrError::runtime(format!(
+                "Invalid recipient address: {}",
+                to
+            )));
         }
 
         if amount == 0 {
-            return Err(SlvrError::RuntimeError {
-                message: "Transfer amount must be greater than 0".to_string(),
-            });
+            return Err(SlvrError::runtime("Transfer amount must be greater than 0"));
         }
 
         // Check sender balance from shared state
         let sender_balance = match shared_state.get("balance") {
             Some(Value::Integer(n)) => *n as u64,
-            Some(Value::Decimal(d)) => *d as u64,
+            Some(Value::Decimal(d)) => d.to_u64().unwrap_or(0),
             _ => 0u64,
         };
 
         if sender_balance < amount {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Insufficient balance: {} < {}", sender_balance, amount),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Insufficient balance: {} < {}",
+                sender_balance, amount
+            )));
         }
 
         // Return transfer result
@@ -473,7 +535,7 @@ impl PactManager {
         fuel_consumed: &mut u64,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        *fuel_consumed += 300; // Approve operation cost
+        *fuel_consumed += self.fuel_schedule.builtin_cost("token.approve");
 
         if *fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
@@ -484,33 +546,24 @@ impl PactManager {
 
         let spender = match inputs.get("spender") {
             Some(Value::String(s)) => s.clone(),
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing 'spender' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing 'spender' parameter")),
         };
 
         let amount = match inputs.get("amount") {
             Some(Value::Integer(n)) => *n as u64,
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing or invalid 'amount' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing or invalid 'amount' parameter")),
         };
 
         // Validate spender address
-        if !spender.starts_with("SLVR") || spender.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid spender address: {}", spender),
-            });
+        if !address::is_valid(&spender) {
+            return Err(SlvrError::runtime(format!(
+                "Invalid spender address: {}",
+                spender
+            )));
         }
 
         if amount == 0 {
-            return Err(SlvrError::RuntimeError {
-                message: "Approval amount must be greater than 0".to_string(),
-            });
+            return Err(SlvrError::runtime("Approval amount must be greater than 0"));
         }
 
         Ok(Value::Object(
@@ -532,7 +585,7 @@ impl PactManager {
         fuel_consumed: &mut u64,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        *fuel_consumed += 400; // Mint operation cost
+        *fuel_consumed += self.fuel_schedule.builtin_cost("token.mint");
 
         if *fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
@@ -543,18 +596,12 @@ impl PactManager {
 
         let amount = match inputs.get("amount") {
             Some(Value::Integer(n)) => *n as u64,
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing or invalid 'amount' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing or invalid 'amount' parameter")),
         };
 
         // Validate amount
         if amount == 0 {
-            return Err(SlvrError::RuntimeError {
-                message: "Mint amount must be greater than 0".to_string(),
-            });
+            return Err(SlvrError::runtime("Mint amount must be greater than 0"));
         }
 
         let current_supply = match shared_state.get("total_supply") {
@@ -584,7 +631,7 @@ impl PactManager {
         fuel_consumed: &mut u64,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        *fuel_consumed += 350; // Burn operation cost
+        *fuel_consumed += self.fuel_schedule.builtin_cost("token.burn");
 
         if *fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
@@ -595,11 +642,7 @@ impl PactManager {
 
         let amount = match inputs.get("amount") {
             Some(Value::Integer(n)) => *n as u64,
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing or invalid 'amount' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing or invalid 'amount' parameter")),
         };
 
         let current_supply = match shared_state.get("total_supply") {
@@ -608,12 +651,10 @@ impl PactManager {
         };
 
         if current_supply < amount {
-            return Err(SlvrError::RuntimeError {
-                message: format!(
-                    "Cannot burn more than supply: {} < {}",
-                    current_supply, amount
-                ),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Cannot burn more than supply: {} < {}",
+                current_supply, amount
+            )));
         }
 
         Ok(Value::Object(
@@ -638,7 +679,7 @@ impl PactManager {
         fuel_consumed: &mut u64,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        *fuel_consumed += 200; // Query operation cost
+        *fuel_consumed += self.fuel_schedule.builtin_cost("token.balance_of");
 
         if *fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
@@ -649,23 +690,20 @@ impl PactManager {
 
         let account = match inputs.get("account") {
             Some(Value::String(s)) => s.clone(),
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing 'account' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing 'account' parameter")),
         };
 
         // Validate account address
-        if !account.starts_with("SLVR") || account.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid account address: {}", account),
-            });
+        if !address::is_valid(&account) {
+            return Err(SlvrError::runtime(format!(
+                "Invalid account address: {}",
+                account
+            )));
         }
 
         let balance = match shared_state.get("balance") {
             Some(Value::Integer(n)) => *n as u64,
-            Some(Value::Decimal(d)) => *d as u64,
+            Some(Value::Decimal(d)) => d.to_u64().unwrap_or(0),
             _ => 0u64,
         };
 
@@ -687,7 +725,7 @@ impl PactManager {
         fuel_consumed: &mut u64,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        *fuel_consumed += 200; // Query operation cost
+        *fuel_consumed += self.fuel_schedule.builtin_cost("state.query");
 
         if *fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
@@ -698,11 +736,7 @@ impl PactManager {
 
         let key = match inputs.get("key") {
             Some(Value::String(s)) => s.clone(),
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing 'key' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing 'key' parameter")),
         };
 
         let value = shared_state.get(&key).cloned().unwrap_or(Value::Null);
@@ -725,7 +759,7 @@ impl PactManager {
         fuel_consumed: &mut u64,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        *fuel_consumed += 250; // Update operation cost
+        *fuel_consumed += self.fuel_schedule.builtin_cost("state.update");
 
         if *fuel_consumed > fuel_limit {
             return Err(SlvrError::FuelExceeded {
@@ -736,22 +770,16 @@ impl PactManager {
 
         let key = match inputs.get("key") {
             Some(Value::String(s)) => s.clone(),
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing 'key' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing 'key' parameter")),
         };
 
         let value = match inputs.get("value") {
             Some(v) => v.clone(),
-            _ => {
-                return Err(SlvrError::RuntimeError {
-                    message: "Missing 'value' parameter".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::runtime("Missing 'value' parameter")),
         };
 
+        self.runtime.write(key.clone(), value.clone())?;
+
         Ok(Value::Object(
             vec![
                 ("status".to_string(), Value::String("updated".to_string())),
@@ -763,6 +791,36 @@ impl PactManager {
         ))
     }
 
+    /// Write every key of the `updates` object, in key order
+    fn execute_state_updates(
+        &self,
+        updates: &std::collections::BTreeMap<String, Value>,
+        fuel_consumed: &mut u64,
+        fuel_limit: u64,
+    ) -> SlvrResult<Value> {
+        *fuel_consumed += self.fuel_schedule.builtin_cost("state.update");
+
+        if *fuel_consumed > fuel_limit {
+            return Err(SlvrError::FuelExceeded {
+                used: *fuel_consumed,
+                limit: fuel_limit,
+            });
+        }
+
+        for (key, value) in updates {
+            self.runtime.write(key.clone(), value.clone())?;
+        }
+
+        Ok(Value::Object(
+            vec![
+                ("status".to_string(), Value::String("updated".to_string())),
+                ("updates".to_string(), Value::Object(updates.clone())),
+            ]
+            .into_iter()
+            .collect(),
+        ))
+    }
+
     /// Create a new pact
     pub fn create_pact(
         &mut self,
@@ -771,6 +829,20 @@ impl PactManager {
         function: String,
         steps: Vec<String>,
         max_fuel: u64,
+    ) -> SlvrResult<String> {
+        let steps = steps.into_iter().map(|step| (step, None)).collect();
+        self.create_pact_with_rollbacks(name, contract, function, steps, max_fuel)
+    }
+
+    /// Create a new pact whose steps may name a rollback function of the
+    /// pact's contract
+    pub fn create_pact_with_rollbacks(
+        &mut self,
+        name: String,
+        contract: String,
+        function: String,
+        steps: Vec<(String, Option<String>)>,
+        max_fuel: u64,
     ) -> SlvrResult<String> {
         let pact_id = Uuid::new_v4().to_string();
 
@@ -778,7 +850,7 @@ impl PactManager {
         let pact_steps: Vec<PactStep> = steps
             .into_iter()
             .enumerate()
-            .map(|(idx, step_name)| PactStep {
+            .map(|(idx, (step_name, rollback))| PactStep {
                 id: Uuid::new_v4().to_string(),
                 step_number: idx,
                 total_steps,
@@ -790,6 +862,7 @@ impl PactManager {
                 executed_at: None,
                 error: None,
                 fuel_consumed: 0,
+                rollback,
             })
             .collect();
 
@@ -807,6 +880,8 @@ impl PactManager {
             total_fuel_consumed: 0,
             max_fuel,
             yield_value: None,
+            yield_target: None,
+            chain: None,
         };
 
         self.pacts.insert(pact_id.clone(), pact);
@@ -818,9 +893,7 @@ impl PactManager {
         self.pacts
             .get(pact_id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Pact not found: {}", pact_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Pact not found: {}", pact_id)))
     }
 
     /// Execute the next step in a pact
@@ -830,13 +903,23 @@ impl PactManager {
         inputs: HashMap<String, Value>,
         fuel_limit: u64,
     ) -> SlvrResult<Value> {
-        let mut pact = self.get_pact(pact_id)?;
+        // The pact is read in place and only its changes are written back, so
+        // a step costs nothing in proportion to the pact's shared state
+        let pact = self
+            .pacts
+            .get(pact_id)
+            .ok_or_else(|| SlvrError::runtime(format!("Pact not found: {}", pact_id)))?;
 
         // Check if pact is already completed
         if pact.status == PactStatus::Completed {
-            return Err(SlvrError::RuntimeError {
-                message: "Pact already completed".to_string(),
-            });
+            return Err(SlvrError::runtime("Pact already completed"));
+        }
+
+        if let Some(chain) = pact.yield_target {
+            return Err(SlvrError::runtime(format!(
+                "Pact {} must resume on chain {}",
+                pact_id, chain
+            )));
         }
 
         // Check fuel limit
@@ -849,9 +932,7 @@ impl PactManager {
 
         // Get current step
         if pact.current_step >= pact.steps.len() {
-            return Err(SlvrError::RuntimeError {
-                message: "No more steps to execute".to_string(),
-            });
+            return Err(SlvrError::runtime("No more steps to execute"));
         }
 
         let mut step = pact.steps[pact.current_step].clone();
@@ -860,7 +941,9 @@ impl PactManager {
         step.executed_at = Some(Utc::now());
 
         // PRODUCTION IMPLEMENTATION: Real pact step execution with full error handling
-        // This executes the actual step function with proper state management
+        // This executes the actual step function with proper state management.
+        // A failed step must not leave partial writes behind.
+        let snapshot = self.runtime.snapshot();
         let output = match self.execute_pact_step_real(
             &pact.contract,
             &pact.function,
@@ -873,9 +956,11 @@ impl PactManager {
             Ok((result, consumed_fuel)) => {
                 step.status = PactStepStatus::Completed;
                 step.fuel_consumed = consumed_fuel;
+                step.output = Some(result.clone());
                 result
             }
             Err(e) => {
+                self.runtime.restore(snapshot);
                 step.status = PactStepStatus::Failed;
                 step.error = Some(format!("{:?}", e));
                 step.fuel_consumed = fuel_limit;
@@ -883,7 +968,13 @@ impl PactManager {
             }
         };
 
-        // Update pact
+        // Update pact; later steps must see what this one changed
+        let changes = declared_state_changes(&pact.contract, &pact.function, &output);
+        let pact = self
+            .pacts
+            .get_mut(pact_id)
+            .expect("pact was looked up before the step ran");
+        pact.shared_state.extend(changes);
         pact.steps[pact.current_step] = step.clone();
         pact.total_fuel_consumed += fuel_limit;
         pact.yield_value = Some(output.clone());
@@ -906,21 +997,148 @@ impl PactManager {
         };
         self.history.push(record);
 
-        // Update pact in manager
+        Ok(output)
+    }
+
+    /// Yield `value` from the pact on `source_chain` so that its next step
+    /// runs on `target_chain`.
+    ///
+    /// The continuation is recorded on the source chain; the returned proof
+    /// is what `resume_on_chain` checks on the target.
+    pub fn yield_to_chain(
+        &mut self,
+        pact_id: &str,
+        network: &ChainwebNetwork,
+        source_chain: ChainId,
+        target_chain: ChainId,
+        value: Value,
+    ) -> SlvrResult<SpvProof> {
+        let mut pact = self.get_pact(pact_id)?;
+        if pact.current_step >= pact.steps.len() {
+            return Err(SlvrError::runtime("No more steps to execute"));
+        }
+        if pact.yield_target.is_some() {
+            return Err(SlvrError::runtime(format!(
+                "Pact {} has already yielded",
+                pact_id
+            )));
+        }
+        if let Some(chain) = pact.chain.filter(|chain| *chain != source_chain) {
+            return Err(SlvrError::runtime(format!(
+                "Pact {} is on chain {}, not {}",
+                pact_id, chain, source_chain
+            )));
+        }
+
+        let proof = network.record_continuation(PactContinuation {
+            pact_id: pact_id.to_string(),
+            source_chain,
+            target_chain,
+            step: pact.current_step,
+            value: value.clone(),
+        })?;
+
+        pact.yield_value = Some(value);
+        pact.yield_target = Some(target_chain);
+        pact.chain = Some(source_chain);
         self.pacts.insert(pact_id.to_string(), pact);
+        Ok(proof)
+    }
 
-        Ok(output)
+    /// Resume on `chain` a pact that yielded to it, running its next step
+    /// with the yielded value.
+    ///
+    /// The proof must verify against the source chain. It is consumed before
+    /// the step runs, so a continuation resumes only once; a failed step gives
+    /// it back and the pact still waits on `chain`.
+    pub fn resume_on_chain(
+        &mut self,
+        pact_id: &str,
+        network: &ChainwebNetwork,
+        chain: ChainId,
+        proof: &SpvProof,
+        inputs: HashMap<String, Value>,
+        fuel_limit: u64,
+    ) -> SlvrResult<Value> {
+        let mut pact = self.get_pact(pact_id)?;
+        if proof.continuation.pact_id != pact_id || proof.continuation.step != pact.current_step {
+            return Err(SlvrError::runtime(format!(
+                "Proof does not continue pact {} at step {}",
+                pact_id, pact.current_step
+            )));
+        }
+        let continuation = network.take_continuation(chain, proof)?;
+
+        let waiting = pact.clone();
+        pact.yield_value = Some(continuation.value.clone());
+        pact.yield_target = None;
+        pact.chain = Some(chain);
+        self.pacts.insert(pact_id.to_string(), pact);
+        match self.execute_next_step(pact_id, inputs, fuel_limit) {
+            Ok(output) => Ok(output),
+            Err(e) => {
+                self.pacts.insert(pact_id.to_string(), waiting);
+                network.release_continuation(&continuation)?;
+                Err(e)
+            }
+        }
     }
 
-    /// Rollback a pact to a previous step
+    /// Rollback a pact to a previous step.
+    ///
+    /// The rollback handlers of the completed steps from `target_step` on run
+    /// last step first. If any handler fails its writes are undone and the
+    /// pact is left as it was.
     pub fn rollback_pact(&mut self, pact_id: &str, target_step: usize) -> SlvrResult<()> {
         let mut pact = self.get_pact(pact_id)?;
 
         if target_step >= pact.steps.len() {
-            return Err(SlvrError::RuntimeError {
-                message: "Invalid target step".to_string(),
+            return Err(SlvrError::runtime("Invalid target step"));
+        }
+
+        let snapshot = self.runtime.snapshot();
+        let mut records = Vec::new();
+        for index in (target_step..pact.steps.len()).rev() {
+            let step = &pact.steps[index];
+            let Some(handler) = step.rollback.clone() else {
+                continue;
+            };
+            if step.status != PactStepStatus::Completed {
+                continue;
+            }
+
+            let context = ContractExecutionContext {
+                contract: pact.contract.clone(),
+                function: handler.clone(),
+                step_name: step.name.clone(),
+                inputs: step.inputs.clone(),
+                shared_state: &pact.shared_state,
+                yield_value: step.output.clone(),
+                fuel_limit: pact.max_fuel.saturating_sub(pact.total_fuel_consumed),
+            };
+            let mut fuel_consumed = 0;
+            let output = match self.execute_contract_function(&context, &mut fuel_consumed) {
+                Ok(output) => output,
+                Err(e) => {
+                    self.runtime.restore(snapshot);
+                    return Err(e);
+                }
+            };
+
+            pact.total_fuel_consumed += fuel_consumed;
+            pact.shared_state
+                .extend(declared_state_changes(&pact.contract, &handler, &output));
+            let mut undone = pact.steps[index].clone();
+            undone.status = PactStepStatus::RolledBack;
+            undone.fuel_consumed = fuel_consumed;
+            records.push(PactExecutionRecord {
+                block_height: 0,
+                block_hash: String::new(),
+                step: undone,
+                continuation: Some(output),
             });
         }
+        self.history.extend(records);
 
         // Mark all steps after target as rolled back
         for i in target_step..pact.steps.len() {
@@ -1099,6 +1317,323 @@ mod tests {
         assert_eq!(pact.current_step, 0);
     }
 
+    #[test]
+    fn test_rollback_runs_handlers_last_step_first() {
+        let mut manager = PactManager::new();
+        let pact_id = manager
+            .create_pact_with_rollbacks(
+                "swap".to_string(),
+                "test_contract".to_string(),
+                "test_function".to_string(),
+                vec![
+                    ("debit".to_string(), Some("undo_debit".to_string())),
+                    ("credit".to_string(), Some("undo_credit".to_string())),
+                ],
+                1_000_000,
+            )
+            .unwrap();
+
+        manager
+            .execute_next_step(&pact_id, HashMap::new(), 10_000)
+            .unwrap();
+        manager
+            .execute_next_step(&pact_id, HashMap::new(), 10_000)
+            .unwrap();
+        manager.rollback_pact(&pact_id, 0).unwrap();
+
+        let handlers: Vec<(String, Value)> = manager.get_history()[2..]
+            .iter()
+            .map(|record| {
+                let output = record.continuation.as_ref().unwrap();
+                (
+                    record.step.name.clone(),
+                    output.field_ref("function").unwrap().clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            handlers,
+            vec![
+                (
+                    "credit".to_string(),
+                    Value::String("undo_credit".to_string())
+                ),
+                ("debit".to_string(), Value::String("undo_debit".to_string())),
+            ]
+        );
+        let pact = manager.get_pact(&pact_id).unwrap();
+        assert!(pact
+            .steps
+            .iter()
+            .all(|step| step.status == PactStepStatus::RolledBack));
+    }
+
+    #[test]
+    fn test_failed_rollback_leaves_pact_unchanged() {
+        let mut manager = PactManager::new();
+        let pact_id = manager
+            .create_pact_with_rollbacks(
+                "swap".to_string(),
+                "test_contract".to_string(),
+                "test_function".to_string(),
+                vec![("debit".to_string(), Some("undo-debit".to_string()))],
+                1_000_000,
+            )
+            .unwrap();
+        manager
+            .execute_next_step(&pact_id, HashMap::new(), 10_000)
+            .unwrap();
+
+        // `undo-debit` is not a valid function name, so its handler fails
+        assert!(manager.rollback_pact(&pact_id, 0).is_err());
+        let pact = manager.get_pact(&pact_id).unwrap();
+        assert_eq!(pact.status, PactStatus::Completed);
+        assert_eq!(pact.steps[0].status, PactStepStatus::Completed);
+    }
+
+    #[test]
+    fn test_pact_yields_on_one_chain_and_resumes_on_another() {
+        let network = two_chain_network();
+        let mut manager = PactManager::new();
+        let pact_id = manager
+            .create_pact(
+                "transfer-crosschain".to_string(),
+                "test_contract".to_string(),
+                "test_function".to_string(),
+                vec!["debit".to_string(), "credit".to_string()],
+                1_000_000,
+            )
+            .unwrap();
+        manager
+            .execute_next_step(&pact_id, HashMap::new(), 10_000)
+            .unwrap();
+
+        let yielded = Value::Integer(25);
+        let proof = manager
+            .yield_to_chain(
+                &pact_id,
+                &network,
+                ChainId::new(0),
+                ChainId::new(1),
+                yielded.clone(),
+            )
+            .unwrap();
+        assert!(network.verify_continuation(&proof).unwrap());
+
+        // Until it resumes on chain 1 the pact cannot advance
+        assert!(manager
+            .execute_next_step(&pact_id, HashMap::new(), 10_000)
+            .is_err());
+        assert!(manager
+            .resume_on_chain(
+                &pact_id,
+                &network,
+                ChainId::new(2),
+                &proof,
+                HashMap::new(),
+                10_000
+            )
+            .is_err());
+        let mut forged = proof.clone();
+        forged.continuation.value = Value::Integer(1_000);
+        assert!(!network.verify_continuation(&forged).unwrap());
+
+        let output = manager
+            .resume_on_chain(
+                &pact_id,
+                &network,
+                ChainId::new(1),
+                &proof,
+                HashMap::new(),
+                10_000,
+            )
+            .unwrap();
+        assert_eq!(output.field_ref("previous_yield").unwrap(), &yielded);
+        assert_eq!(
+            manager.get_pact(&pact_id).unwrap().status,
+            PactStatus::Completed
+        );
+
+        // A continuation resumes only once
+        assert!(network.check_continuation(ChainId::new(1), &proof).is_err());
+    }
+
+    /// A network with chains 0 and 1
+    fn two_chain_network() -> ChainwebNetwork {
+        use crate::chainweb::{ChainConfig, ConsensusType};
+
+        let network = ChainwebNetwork::new();
+        for id in [0, 1] {
+            network
+                .register_chain(ChainConfig {
+                    chain_id: ChainId::new(id),
+                    name: format!("Chain {}", id),
+                    network_id: "silverbitcoin".to_string(),
+                    peer_count: 0,
+                    block_time_ms: 30000,
+                    max_block_size: 1_000_000,
+                    consensus_type: ConsensusType::PoW,
+                    trusted_signers: Vec::new(),
+                })
+                .unwrap();
+        }
+        network
+    }
+
+    #[test]
+    fn test_failed_resume_releases_continuation_and_pact_stays_on_its_chain() {
+        let network = two_chain_network();
+        let mut manager = PactManager::new();
+        let pact_id = manager
+            .create_pact(
+                "relay".to_string(),
+                "test_contract".to_string(),
+                "test_function".to_string(),
+                vec![
+                    "start".to_string(),
+                    "relay".to_string(),
+                    "finish".to_string(),
+                ],
+                1_000_000,
+            )
+            .unwrap();
+        manager
+            .execute_next_step(&pact_id, HashMap::new(), 10_000)
+            .unwrap();
+        let proof = manager
+            .yield_to_chain(
+                &pact_id,
+                &network,
+                ChainId::new(0),
+                ChainId::new(1),
+                Value::Unit,
+            )
+            .unwrap();
+
+        // A step over the pact's fuel fails and leaves the proof usable
+        let resume = |manager: &mut PactManager, fuel_limit| {
+            manager.resume_on_chain(
+                &pact_id,
+                &network,
+                ChainId::new(1),
+                &proof,
+                HashMap::new(),
+                fuel_limit,
+            )
+        };
+        assert!(resume(&mut manager, 2_000_000).is_err());
+        assert!(resume(&mut manager, 10_000).is_ok());
+        assert!(resume(&mut manager, 10_000).is_err());
+
+        // The pact now runs on chain 1, so it cannot yield from chain 0
+        assert!(manager
+            .yield_to_chain(
+                &pact_id,
+                &network,
+                ChainId::new(0),
+                ChainId::new(1),
+                Value::Unit,
+            )
+            .is_err());
+        assert!(manager
+            .yield_to_chain(
+                &pact_id,
+                &network,
+                ChainId::new(1),
+                ChainId::new(0),
+                Value::Unit,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_failed_step_leaves_state_unchanged() {
+        // After the opening balance there is fuel for one more write, not two
+        let runtime = Runtime::new(250);
+        runtime
+            .write("balances:alice".to_string(), Value::Integer(10))
+            .unwrap();
+        let mut manager = PactManager::new().with_runtime(runtime);
+        let pact_id = manager
+            .create_pact(
+                "move".to_string(),
+                "state".to_string(),
+                "update".to_string(),
+                vec!["debit".to_string()],
+                1_000_000,
+            )
+            .unwrap();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "updates".to_string(),
+            Value::Object(
+                [
+                    ("balances:alice".to_string(), Value::Integer(0)),
+                    ("balances:bob".to_string(), Value::Integer(10)),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        );
+        assert!(matches!(
+            manager.execute_next_step(&pact_id, inputs, 10_000),
+            Err(SlvrError::FuelExceeded { .. })
+        ));
+
+        let runtime = manager.runtime();
+        assert_eq!(runtime.read("balances:alice"), Some(Value::Integer(10)));
+        assert_eq!(runtime.read("balances:bob"), None);
+        assert_eq!(manager.get_pact(&pact_id).unwrap().current_step, 0);
+    }
+
+    #[test]
+    fn test_steps_see_state_changed_by_earlier_steps() {
+        let mut manager = PactManager::new();
+        let pact_id = manager
+            .create_pact(
+                "issue".to_string(),
+                "token".to_string(),
+                "mint".to_string(),
+                vec!["seed".to_string(), "top-up".to_string()],
+                1_000_000,
+            )
+            .unwrap();
+        let mint = |amount: i128| {
+            let mut inputs = HashMap::new();
+            inputs.insert("amount".to_string(), Value::Integer(amount));
+            inputs
+        };
+
+        manager
+            .execute_next_step(&pact_id, mint(100), 10_000)
+            .unwrap();
+        assert_eq!(
+            manager
+                .get_pact(&pact_id)
+                .unwrap()
+                .shared_state
+                .get("total_supply"),
+            Some(&Value::Integer(100))
+        );
+
+        let output = manager
+            .execute_next_step(&pact_id, mint(50), 10_000)
+            .unwrap();
+        assert_eq!(
+            output.field_ref("new_supply").unwrap(),
+            &Value::Integer(150)
+        );
+        assert_eq!(
+            manager
+                .get_pact(&pact_id)
+                .unwrap()
+                .shared_state
+                .get("total_supply"),
+            Some(&Value::Integer(150))
+        );
+    }
+
     #[test]
     fn test_pact_stats() {
         let mut manager = PactManager::new();
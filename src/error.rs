@@ -1,5 +1,6 @@
 //! Error types for the Slvr language
 
+use crate::lexer::Span;
 use thiserror::Error;
 
 /// Result type for Slvr operations
@@ -24,13 +25,14 @@ pub enum SlvrError {
         message: String,
     },
 
-    /// Type checking error
+    /// Type checking error, with the source range of the innermost parsed
+    /// expression it came from when there is one
     #[error("Type error: {message}")]
-    TypeError { message: String },
+    TypeError { message: String, span: Option<Span> },
 
-    /// Runtime error
+    /// Runtime error, located like `TypeError`
     #[error("Runtime error: {message}")]
-    RuntimeError { message: String },
+    RuntimeError { message: String, span: Option<Span> },
 
     /// Execution exceeded fuel limit
     #[error("Execution exceeded fuel limit: used {used}, limit {limit}")]
@@ -40,6 +42,10 @@ pub enum SlvrError {
     #[error("Recursion depth exceeded: {depth}")]
     RecursionDepthExceeded { depth: usize },
 
+    /// Value nesting exceeded the configured limit
+    #[error("Value nesting exceeds maximum depth of {max_depth}")]
+    ValueDepthExceeded { max_depth: usize },
+
     /// Division by zero
     #[error("Division by zero")]
     DivisionByZero,
@@ -76,6 +82,12 @@ pub enum SlvrError {
     #[error("Compilation error: {message}")]
     CompilationError { message: String },
 
+    /// Compilation failed; every problem found is listed
+    #[error("Compilation failed: {}", join_diagnostics(.diagnostics))]
+    CompilationFailed {
+        diagnostics: Vec<crate::compiler::Diagnostic>,
+    },
+
     /// IO error
     #[error("IO error: {message}")]
     IoError { message: String },
@@ -84,6 +96,38 @@ pub enum SlvrError {
     #[error("Internal error: {message}")]
     InternalError { message: String },
 
+    /// Contract is paused and rejects state-changing calls
+    #[error("Contract {contract_id} is paused")]
+    Paused { contract_id: String },
+
+    /// Caller is not allowed to administer a contract
+    #[error("{caller} is not authorized to {action} contract {contract_id}")]
+    Unauthorized {
+        caller: String,
+        action: &'static str,
+        contract_id: String,
+    },
+
+    /// State mutation attempted during read-only execution
+    #[error("State mutation forbidden in read-only execution: {operation} {key}")]
+    StateMutationForbidden { operation: String, key: String },
+
+    /// An `enforce` or `enforce-one` check failed, aborting the transaction
+    #[error("Enforce failed: {message}")]
+    EnforceFailed { message: String },
+
+    /// A capability was required but is not in scope
+    #[error("Capability not granted: {capability}")]
+    CapabilityNotGranted { capability: String },
+
+    /// A managed capability was used for more than its installed quantity
+    #[error("{capability} requests {requested} but only {remaining} remains")]
+    ManagedQuantityExceeded {
+        capability: String,
+        requested: String,
+        remaining: String,
+    },
+
     /// Lock error (mutex poisoning)
     #[error("Lock error: {0}")]
     LockError(String),
@@ -112,6 +156,7 @@ impl SlvrError {
     pub fn type_error(message: impl Into<String>) -> Self {
         SlvrError::TypeError {
             message: message.into(),
+            span: None,
         }
     }
 
@@ -119,9 +164,28 @@ impl SlvrError {
     pub fn runtime(message: impl Into<String>) -> Self {
         SlvrError::RuntimeError {
             message: message.into(),
+            span: None,
         }
     }
 
+    /// Source range of the expression a type or runtime error came from
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            SlvrError::TypeError { span, .. } | SlvrError::RuntimeError { span, .. } => *span,
+            _ => None,
+        }
+    }
+
+    /// Locate a type or runtime error at `span` unless it is located already,
+    /// so that the innermost expression an error passes through wins
+    pub fn with_span(mut self, at: Span) -> Self {
+        if let SlvrError::TypeError { span, .. } | SlvrError::RuntimeError { span, .. } = &mut self
+        {
+            span.get_or_insert(at);
+        }
+        self
+    }
+
     /// Create an undefined variable error
     pub fn undefined_var(name: impl Into<String>) -> Self {
         SlvrError::UndefinedVariable { name: name.into() }
@@ -169,6 +233,14 @@ impl SlvrError {
     }
 }
 
+fn join_diagnostics(diagnostics: &[crate::compiler::Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,48 +3,169 @@
 //! Interprets AST directly without compilation.
 
 use crate::ast::*;
+use crate::defcap::{decrement_managed, CapabilityStack, CapabilityToken, ManagedCapabilities};
 use crate::error::{SlvrError, SlvrResult};
-use crate::value::Value;
-use dashmap::DashMap;
+use crate::runtime::{FuelSchedule, JournaledState, TimeSource};
+use crate::stdlib::list;
+use crate::value::{
+    checked_decimal, checked_integer, decimal_pow, integer_to_decimal, Closure, Value,
+};
+use crate::{SlvrConfig, MAX_RECURSION_DEPTH};
 use indexmap::IndexMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+/// A user-defined function registered from a `defun`
+#[derive(Debug, Clone)]
+struct UserFunction {
+    params: Vec<String>,
+    body: Expr,
+}
+
+/// A capability registered from a `defcap`
+#[derive(Debug, Clone)]
+struct UserCapability {
+    /// Parameters and the predicate that must hold to acquire it
+    predicate: UserFunction,
+    /// Index of the managed parameter and the manager `defun`, if managed
+    managed: Option<(usize, Option<String>)>,
+}
+
+/// One step of an access chain; `I` is the index expression or its value
+enum AccessStep<'a, I> {
+    Field(&'a str),
+    Index(I),
+}
+
+/// Start of an access chain
+enum AccessRoot<'a> {
+    /// A variable, walked by reference
+    Variable(&'a str),
+    /// Any other expression, already evaluated
+    Value(Value),
+}
+
+/// Follow `path` from `root` by reference, cloning only the value reached
+fn walk_access(root: &Value, path: &[AccessStep<usize>]) -> SlvrResult<Value> {
+    let mut current = root;
+    for step in path {
+        current = match step {
+            AccessStep::Field(field) => current.field_ref(field)?,
+            AccessStep::Index(index) => current.list_element_ref(*index)?,
+        };
+    }
+    Ok(current.clone())
+}
+
 /// Evaluator for Slvr language
 pub struct Evaluator {
     /// Global variables
-    globals: Arc<DashMap<String, Value>>,
+    globals: Arc<JournaledState>,
     /// Local variable scopes
     locals: Vec<IndexMap<String, Value>>,
+    /// User-defined functions
+    functions: HashMap<String, UserFunction>,
+    /// Capabilities from `defcap`
+    capabilities: HashMap<String, UserCapability>,
+    /// Capabilities granted by the enclosing `with-capability` forms
+    granted: CapabilityStack,
+    /// Managed quantities installed in the current transaction
+    installed: ManagedCapabilities,
     /// Recursion depth tracking
     recursion_depth: usize,
     /// Maximum recursion depth
     max_recursion_depth: usize,
+    /// Fuel consumed so far
+    fuel_used: u64,
+    /// Fuel budget (`None` disables metering)
+    fuel_limit: Option<u64>,
+    /// Fuel cost of each expression and builtin
+    fuel_schedule: Arc<FuelSchedule>,
+    /// Block time exposed through `block-time`
+    time_source: Option<Arc<dyn TimeSource>>,
 }
 
 impl Evaluator {
     /// Create a new evaluator
     pub fn new() -> Self {
-        Self {
-            globals: Arc::new(DashMap::new()),
-            locals: vec![IndexMap::new()],
-            recursion_depth: 0,
-            max_recursion_depth: 1024,
-        }
+        Self::with_recursion_limit(MAX_RECURSION_DEPTH)
     }
 
     /// Create evaluator with custom recursion limit
     pub fn with_recursion_limit(max_depth: usize) -> Self {
         Self {
-            globals: Arc::new(DashMap::new()),
+            globals: Arc::new(JournaledState::new()),
             locals: vec![IndexMap::new()],
+            functions: HashMap::new(),
+            capabilities: HashMap::new(),
+            granted: CapabilityStack::new(),
+            installed: ManagedCapabilities::new(),
             recursion_depth: 0,
             max_recursion_depth: max_depth,
+            fuel_used: 0,
+            fuel_limit: None,
+            fuel_schedule: Arc::new(FuelSchedule::default()),
+            time_source: None,
         }
     }
 
-    /// Evaluate an expression
+    /// Create evaluator from a language configuration
+    pub fn with_config(config: &SlvrConfig) -> Self {
+        let evaluator = Self::with_recursion_limit(config.max_recursion_depth)
+            .with_fuel_schedule(FuelSchedule::from_config(config));
+        if config.enable_fuel_metering {
+            evaluator.with_fuel_limit(config.max_fuel_per_tx)
+        } else {
+            evaluator
+        }
+    }
+
+    /// Set the fuel budget for evaluation
+    pub fn with_fuel_limit(mut self, limit: u64) -> Self {
+        self.fuel_limit = Some(limit);
+        self
+    }
+
+    /// Replace the fuel schedule
+    pub fn with_fuel_schedule(mut self, schedule: FuelSchedule) -> Self {
+        self.fuel_schedule = Arc::new(schedule);
+        self
+    }
+
+    /// Inject the block time returned by `block-time`
+    pub fn with_time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = Some(time_source);
+        self
+    }
+
+    /// Get fuel consumed so far
+    pub fn fuel_used(&self) -> u64 {
+        self.fuel_used
+    }
+
+    /// Evaluate an expression as a transaction, rolling back its writes if
+    /// it fails
     pub fn eval(&mut self, expr: &Expr) -> SlvrResult<Value> {
-        self.eval_expr(expr)
+        self.atomically(|evaluator| evaluator.eval_expr(expr))
+    }
+
+    /// Call a user-defined function with already evaluated arguments, rolling
+    /// back its writes if it fails
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> SlvrResult<Value> {
+        self.atomically(|evaluator| evaluator.call_function(name, args))
+    }
+
+    /// Run `f`, restoring every table row and global it changed if it fails.
+    ///
+    /// Managed capabilities installed by `f` last only until it returns.
+    fn atomically<T>(&mut self, f: impl FnOnce(&mut Self) -> SlvrResult<T>) -> SlvrResult<T> {
+        let snapshot = self.globals.snapshot();
+        let result = f(self);
+        if result.is_err() {
+            self.globals.restore(snapshot);
+        }
+        self.installed.clear();
+        result
     }
 
     /// Evaluate a program
@@ -72,154 +193,751 @@ impl Evaluator {
                 self.set_global(name.clone(), val.clone());
                 Ok(val)
             }
+            Definition::Function {
+                name, params, body, ..
+            } => {
+                self.functions.insert(
+                    name.clone(),
+                    UserFunction {
+                        params: params.iter().map(|(param, _)| param.clone()).collect(),
+                        body: body.clone(),
+                    },
+                );
+                Ok(Value::Unit)
+            }
+            Definition::Capability {
+                name,
+                params,
+                managed,
+                body,
+                ..
+            } => {
+                let managed = match managed {
+                    Some(managed) => {
+                        let index = params
+                            .iter()
+                            .position(|(param, _)| *param == managed.param)
+                            .ok_or_else(|| {
+                                SlvrError::invalid_arg(format!(
+                                    "{} manages unknown parameter {}",
+                                    name, managed.param
+                                ))
+                            })?;
+                        Some((index, managed.manager.clone()))
+                    }
+                    None => None,
+                };
+                self.capabilities.insert(
+                    name.clone(),
+                    UserCapability {
+                        predicate: UserFunction {
+                            params: params.iter().map(|(param, _)| param.clone()).collect(),
+                            body: body.clone(),
+                        },
+                        managed,
+                    },
+                );
+                Ok(Value::Unit)
+            }
             _ => Ok(Value::Unit),
         }
     }
 
     fn eval_expr(&mut self, expr: &Expr) -> SlvrResult<Value> {
-        // Check recursion depth
+        // Source spans are for diagnostics only; they cost neither fuel nor depth
+        let spanned = expr;
+        let expr = expr.unspanned();
+
+        // Check recursion depth before descending so a runaway call chain
+        // surfaces as an error instead of exhausting the native stack
         if self.recursion_depth >= self.max_recursion_depth {
-            return Err(SlvrError::runtime(format!(
-                "Maximum recursion depth ({}) exceeded",
-                self.max_recursion_depth
-            )));
+            return Err(SlvrError::RecursionDepthExceeded {
+                depth: self.max_recursion_depth,
+            });
         }
+        self.consume_fuel(self.fuel_schedule.default_cost())?;
 
+        // The depth counter must be restored on every exit path, including errors
         self.recursion_depth += 1;
-        let result = match expr {
+        let result = self.eval_expr_inner(expr);
+        self.recursion_depth -= 1;
+        // Errors keep the span of the innermost expression they came from
+        match (result, spanned) {
+            (Err(e), Expr::Spanned { span, .. }) => Err(e.with_span(*span)),
+            (result, _) => result,
+        }
+    }
+
+    // Each node kind is evaluated in its own method to keep the frame of the
+    // recursive dispatcher small.
+    fn eval_expr_inner(&mut self, expr: &Expr) -> SlvrResult<Value> {
+        match expr {
             Expr::Literal(lit) => self.eval_literal(lit),
             Expr::Variable(name) => self.get_variable(name),
-            Expr::BinOp { op, left, right } => {
-                let left_val = self.eval_expr(left)?;
-                let right_val = self.eval_expr(right)?;
-                self.eval_binop(*op, left_val, right_val)
-            }
+            Expr::BinOp { op, left, right } => self.eval_binop_expr(*op, left, right),
             Expr::UnaryOp { op, operand } => {
                 let val = self.eval_expr(operand)?;
                 self.eval_unaryop(*op, val)
             }
-            Expr::Call { function, args } => {
-                if let Expr::Variable(func_name) = &**function {
-                    let arg_vals: SlvrResult<Vec<_>> =
-                        args.iter().map(|a| self.eval_expr(a)).collect();
-                    self.call_function(func_name, arg_vals?)
-                } else {
-                    Err(SlvrError::runtime("Invalid function call"))
-                }
-            }
+            Expr::Call { function, args } => self.eval_call(function, args),
             Expr::If {
                 condition,
                 then_branch,
                 else_branch,
-            } => {
-                let cond_val = self.eval_expr(condition)?;
-                if cond_val.is_truthy() {
-                    self.eval_expr(then_branch)
-                } else if let Some(else_expr) = else_branch {
-                    self.eval_expr(else_expr)
-                } else {
-                    Ok(Value::Unit)
+            } => self.eval_if(condition, then_branch, else_branch.as_deref()),
+            Expr::Let {
+                bindings,
+                sequential,
+                body,
+            } => self.eval_let(bindings, *sequential, body),
+            Expr::Lambda { params, body } => Ok(self.eval_lambda(params, body)),
+            Expr::List(elements) => self.eval_list(elements),
+            Expr::Object(fields) => self.eval_object(fields),
+            Expr::FieldAccess { .. } | Expr::Index { .. } => self.eval_access(expr),
+            Expr::Block(exprs) => self.eval_block(exprs),
+            Expr::Read { table, key } => self.eval_read(table, key, false),
+            Expr::ReadSafe { table, key } => self.eval_read(table, key, true),
+            Expr::Write { table, key, value } => self.eval_write(table, key, value),
+            Expr::Update {
+                table,
+                key,
+                updates,
+            } => self.eval_update(table, key, updates),
+            Expr::Delete { table, key } => self.eval_delete(table, key),
+            Expr::Spanned { expr, .. } => self.eval_expr_inner(expr),
+        }
+    }
+
+    fn eval_binop_expr(&mut self, op: BinOp, left: &Expr, right: &Expr) -> SlvrResult<Value> {
+        let left_val = self.eval_expr(left)?;
+        // `and` / `or` never evaluate, or bill, a right operand that cannot
+        // change the result
+        match op {
+            BinOp::And if !left_val.is_truthy() => return Ok(Value::Boolean(false)),
+            BinOp::Or if left_val.is_truthy() => return Ok(Value::Boolean(true)),
+            _ => {}
+        }
+        let right_val = self.eval_expr(right)?;
+        self.eval_binop(op, left_val, right_val)
+    }
+
+    fn eval_call(&mut self, function: &Expr, args: &[Expr]) -> SlvrResult<Value> {
+        // A name that is not a variable is a `defun` or a builtin; a variable
+        // is called through the closure it holds
+        if let Expr::Variable(func_name) = function.unspanned() {
+            if !self.is_variable(func_name) {
+                // A user `defun` may shadow the builtins
+                if !self.functions.contains_key(func_name) {
+                    match func_name.as_str() {
+                        "map" | "filter" | "fold" => {
+                            return self.eval_list_combinator(func_name, args)
+                        }
+                        "block-time" => return self.eval_block_time(args),
+                        "ed25519-verify" => return self.eval_ed25519_verify(args),
+                        "enforce" => return self.eval_enforce(args),
+                        "enforce-one" => return self.eval_enforce_one(args),
+                        "with-capability" => return self.eval_with_capability(args),
+                        "require-capability" => return self.eval_require_capability(args),
+                        "install-capability" => return self.eval_install_capability(args),
+                        _ => {}
+                    }
                 }
+                let arg_vals: SlvrResult<Vec<_>> = args.iter().map(|a| self.eval_expr(a)).collect();
+                return self.call_function(func_name, arg_vals?);
             }
-            Expr::Let { name, value, body } => {
-                let val = self.eval_expr(value)?;
-                self.push_scope();
-                self.set_local(name.clone(), val);
-                let result = self.eval_expr(body)?;
-                self.pop_scope();
-                Ok(result)
+        }
+
+        let callee = self.eval_expr(function)?;
+        let arg_vals: SlvrResult<Vec<_>> = args.iter().map(|a| self.eval_expr(a)).collect();
+        match callee {
+            Value::Closure(closure) => self.apply_closure("lambda", &closure, arg_vals?),
+            other => Err(SlvrError::type_mismatch("function", other.type_name())),
+        }
+    }
+
+    /// `lambda (params) body`: captures every local visible here, innermost first
+    fn eval_lambda(&self, params: &[String], body: &Expr) -> Value {
+        let mut env = BTreeMap::new();
+        for scope in &self.locals {
+            for (name, value) in scope {
+                env.insert(name.clone(), value.clone());
             }
-            Expr::List(elements) => {
-                let vals: SlvrResult<Vec<_>> = elements.iter().map(|e| self.eval_expr(e)).collect();
-                Ok(Value::List(vals?))
+        }
+        Value::Closure(Box::new(Closure {
+            params: params.to_vec(),
+            body: body.clone(),
+            env,
+        }))
+    }
+
+    /// Apply a closure in a frame of its captured locals plus its parameters.
+    ///
+    /// Each application costs fuel on top of its body, and the body is
+    /// evaluated through `eval_expr`, so nested applications count against
+    /// the recursion bound like any other call.
+    fn apply_closure(
+        &mut self,
+        name: &str,
+        closure: &Closure,
+        args: Vec<Value>,
+    ) -> SlvrResult<Value> {
+        if args.len() != closure.params.len() {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} expects {} arguments, got {}",
+                name,
+                closure.params.len(),
+                args.len()
+            )));
+        }
+        self.consume_fuel(self.fuel_schedule.default_cost())?;
+
+        let mut frame: IndexMap<String, Value> = closure
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        frame.extend(closure.params.iter().cloned().zip(args));
+        let caller_locals = std::mem::replace(&mut self.locals, vec![frame]);
+        let result = self.eval_expr(&closure.body);
+        self.locals = caller_locals;
+        result
+    }
+
+    /// `map(f, list)`, `filter(f, list)` and `fold(f, init, list)` over a user
+    /// `defun` or a lambda.
+    ///
+    /// The walk itself is `stdlib::list`; each application is a separate,
+    /// non-nested call, so the depth bound is unchanged and fuel grows
+    /// linearly with the list length.
+    fn eval_list_combinator(&mut self, name: &str, args: &[Expr]) -> SlvrResult<Value> {
+        let (expected_args, function_arity) = if name == "fold" { (3, 2) } else { (2, 1) };
+        if args.len() != expected_args {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} expects {} arguments, got {}",
+                name,
+                expected_args,
+                args.len()
+            )));
+        }
+
+        let (func_name, function) = self.function_argument(name, &args[0])?;
+        let params = function.params.len();
+        if params != function_arity {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} requires a function of {} argument(s), {} takes {}",
+                name, function_arity, func_name, params
+            )));
+        }
+
+        let init = if name == "fold" {
+            Some(self.eval_expr(&args[1])?)
+        } else {
+            None
+        };
+        let items = self.eval_expr(&args[expected_args - 1])?;
+
+        let mut apply = |args: Vec<Value>| self.apply_closure(&func_name, &function, args);
+        match (name, init) {
+            ("fold", Some(init)) => list::fold(items, init, |acc, item| apply(vec![acc, item])),
+            ("map", _) => list::map(items, |item| apply(vec![item])),
+            _ => list::filter(items, |item| apply(vec![item])),
+        }
+    }
+
+    /// The function a combinator applies: a `defun` name, or an expression
+    /// producing a lambda. A `defun` already runs with only its parameters in
+    /// scope, so it is applied as a closure that captured nothing.
+    fn function_argument(&mut self, combinator: &str, arg: &Expr) -> SlvrResult<(String, Closure)> {
+        if let Expr::Variable(func_name) = arg.unspanned() {
+            if !self.is_variable(func_name) {
+                return match self.functions.get(func_name) {
+                    Some(function) => Ok((
+                        func_name.clone(),
+                        Closure {
+                            params: function.params.clone(),
+                            body: function.body.clone(),
+                            env: BTreeMap::new(),
+                        },
+                    )),
+                    None => Err(SlvrError::invalid_arg(format!(
+                        "{} expects a function as its first argument",
+                        combinator
+                    ))),
+                };
             }
-            Expr::Object(fields) => {
-                let mut obj = std::collections::HashMap::new();
-                for (key, value) in fields {
-                    obj.insert(key.clone(), self.eval_expr(value)?);
+        }
+        match self.eval_expr(arg)? {
+            Value::Closure(closure) => Ok(("lambda".to_string(), *closure)),
+            _ => Err(SlvrError::invalid_arg(format!(
+                "{} expects a function as its first argument",
+                combinator
+            ))),
+        }
+    }
+
+    /// `block-time()`: the injected block timestamp in Unix seconds.
+    ///
+    /// There is deliberately no fallback to the wall clock.
+    fn eval_block_time(&mut self, args: &[Expr]) -> SlvrResult<Value> {
+        if !args.is_empty() {
+            return Err(SlvrError::invalid_arg(format!(
+                "block-time expects 0 arguments, got {}",
+                args.len()
+            )));
+        }
+        match &self.time_source {
+            Some(source) => Ok(Value::Integer(source.block_time() as i128)),
+            None => Err(SlvrError::runtime(
+                "block-time is unavailable: no block time was injected",
+            )),
+        }
+    }
+
+    /// `ed25519-verify(pubkey, message, signature)`: whether the hex
+    /// `signature` is a valid Ed25519 signature by the hex `pubkey` over
+    /// `message`, charged at the schedule's `ed25519-verify` cost
+    fn eval_ed25519_verify(&mut self, args: &[Expr]) -> SlvrResult<Value> {
+        let [pubkey, message, signature] = args else {
+            return Err(SlvrError::invalid_arg(format!(
+                "ed25519-verify expects 3 arguments, got {}",
+                args.len()
+            )));
+        };
+        let pubkey = self.eval_expr(pubkey)?;
+        let message = self.eval_expr(message)?;
+        let signature = self.eval_expr(signature)?;
+        self.consume_fuel(self.fuel_schedule.builtin_cost("ed25519-verify"))?;
+        crate::stdlib::crypto::ed25519_verify(pubkey, message, signature)
+    }
+
+    /// `enforce(cond, msg)`: unit when `cond` holds, otherwise the transaction
+    /// aborts with `msg`, which is only evaluated on failure
+    fn eval_enforce(&mut self, args: &[Expr]) -> SlvrResult<Value> {
+        let [condition, message] = args else {
+            return Err(SlvrError::invalid_arg(format!(
+                "enforce expects 2 arguments, got {}",
+                args.len()
+            )));
+        };
+        match self.eval_expr(condition)? {
+            Value::Boolean(true) => Ok(Value::Unit),
+            Value::Boolean(false) => Err(self.enforce_failure(message)),
+            other => Err(SlvrError::type_mismatch("boolean", other.type_name())),
+        }
+    }
+
+    /// `enforce-one([checks], msg)`: passes as soon as one check passes.
+    ///
+    /// A check passes when it yields `true` or unit, as a nested `enforce`
+    /// does. A check that yields `false` or fails an `enforce` has its writes
+    /// rolled back before the next one runs. Other errors abort at once.
+    fn eval_enforce_one(&mut self, args: &[Expr]) -> SlvrResult<Value> {
+        let [checks, message] = args else {
+            return Err(SlvrError::invalid_arg(format!(
+                "enforce-one expects 2 arguments, got {}",
+                args.len()
+            )));
+        };
+        let Expr::List(checks) = checks.unspanned() else {
+            return Err(SlvrError::invalid_arg(
+                "enforce-one expects a list literal of checks",
+            ));
+        };
+        for check in checks {
+            let snapshot = self.globals.snapshot();
+            match self.eval_expr(check) {
+                Ok(Value::Boolean(true) | Value::Unit) => return Ok(Value::Unit),
+                Ok(Value::Boolean(false)) | Err(SlvrError::EnforceFailed { .. }) => {
+                    self.globals.restore(snapshot);
                 }
-                Ok(Value::Object(obj))
+                Ok(other) => return Err(SlvrError::type_mismatch("boolean", other.type_name())),
+                Err(e) => return Err(e),
             }
-            Expr::FieldAccess { object, field } => {
-                let obj_val = self.eval_expr(object)?;
-                obj_val.get_field(field)
+        }
+        Err(self.enforce_failure(message))
+    }
+
+    /// `with-capability(CAP(args), body)`: acquire the capability if its
+    /// predicate holds, then evaluate `body` with it granted.
+    ///
+    /// The grant lasts only while `body` runs and is released however it
+    /// exits. A capability already granted by an enclosing scope is not
+    /// checked again. Acquiring a managed capability draws its quantity from
+    /// what was installed for it.
+    fn eval_with_capability(&mut self, args: &[Expr]) -> SlvrResult<Value> {
+        let [capability, body] = args else {
+            return Err(SlvrError::invalid_arg(format!(
+                "with-capability expects 2 arguments, got {}",
+                args.len()
+            )));
+        };
+        let token = self.eval_capability_token("with-capability", capability)?;
+        if self.granted.is_granted(&token) {
+            return self.eval_expr(body);
+        }
+
+        self.check_capability_predicate(&token)?;
+        self.draw_managed(&token)?;
+
+        let depth = self.granted.push(token);
+        let result = self.eval_expr(body);
+        self.granted.truncate(depth);
+        result
+    }
+
+    /// `require-capability(CAP(args))`: unit if an enclosing
+    /// `with-capability` granted exactly this capability.
+    ///
+    /// A managed capability that is not in scope is accepted instead when its
+    /// installed quantity covers the request, which is then used up.
+    fn eval_require_capability(&mut self, args: &[Expr]) -> SlvrResult<Value> {
+        let [capability] = args else {
+            return Err(SlvrError::invalid_arg(format!(
+                "require-capability expects 1 argument, got {}",
+                args.len()
+            )));
+        };
+        let token = self.eval_capability_token("require-capability", capability)?;
+        let managed = self.capabilities[&token.name].managed.is_some();
+        if managed && !self.granted.is_granted(&token) {
+            self.draw_managed(&token)?;
+        } else {
+            self.granted.require(&token)?;
+        }
+        Ok(Value::Unit)
+    }
+
+    /// `install-capability(CAP(args))`: make the managed argument of `CAP`
+    /// available to spend for the rest of the transaction, once its
+    /// predicate holds
+    fn eval_install_capability(&mut self, args: &[Expr]) -> SlvrResult<Value> {
+        let [capability] = args else {
+            return Err(SlvrError::invalid_arg(format!(
+                "install-capability expects 1 argument, got {}",
+                args.len()
+            )));
+        };
+        let token = self.eval_capability_token("install-capability", capability)?;
+        let Some((key, quantity, _)) = self.managed_key(&token) else {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} is not a managed capability",
+                token.name
+            )));
+        };
+        self.check_capability_predicate(&token)?;
+        self.installed.install(key, quantity)?;
+        Ok(Value::Unit)
+    }
+
+    /// Evaluate the `defcap` predicate of `token` with its arguments bound
+    fn check_capability_predicate(&mut self, token: &CapabilityToken) -> SlvrResult<()> {
+        let predicate = self.capabilities[&token.name].predicate.clone();
+        let frame: IndexMap<String, Value> = predicate
+            .params
+            .into_iter()
+            .zip(token.args.iter().cloned())
+            .collect();
+        let caller_locals = std::mem::replace(&mut self.locals, vec![frame]);
+        let outcome = self.eval_expr(&predicate.body);
+        self.locals = caller_locals;
+        match outcome? {
+            Value::Boolean(true) | Value::Unit => Ok(()),
+            Value::Boolean(false) => Err(SlvrError::CapabilityNotGranted {
+                capability: token.to_string(),
+            }),
+            other => Err(SlvrError::type_mismatch("boolean", other.type_name())),
+        }
+    }
+
+    /// For a managed capability: the installation key, the quantity `token`
+    /// carries, and its manager
+    fn managed_key(
+        &self,
+        token: &CapabilityToken,
+    ) -> Option<(CapabilityToken, Value, Option<String>)> {
+        let (index, manager) = self.capabilities[&token.name].managed.clone()?;
+        let mut args = token.args.clone();
+        let quantity = args.remove(index);
+        Some((
+            CapabilityToken::new(token.name.clone(), args),
+            quantity,
+            manager,
+        ))
+    }
+
+    /// Take the quantity `token` requests out of what is installed for it,
+    /// through its manager; unmanaged capabilities draw nothing
+    fn draw_managed(&mut self, token: &CapabilityToken) -> SlvrResult<()> {
+        let Some((key, requested, manager)) = self.managed_key(token) else {
+            return Ok(());
+        };
+        let Some(remaining) = self.installed.remaining(&key).cloned() else {
+            return Err(SlvrError::CapabilityNotGranted {
+                capability: token.to_string(),
+            });
+        };
+        let left = match manager {
+            Some(manager) => self.call_function(&manager, vec![remaining, requested])?,
+            None => decrement_managed(token, &remaining, &requested)?,
+        };
+        self.installed.set_remaining(&key, left)
+    }
+
+    /// Evaluate `CAP(args)` to the capability it names, checking the `defcap`
+    /// exists and takes that many arguments
+    fn eval_capability_token(&mut self, form: &str, expr: &Expr) -> SlvrResult<CapabilityToken> {
+        let Expr::Call { function, args } = expr.unspanned() else {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} expects a capability applied to its arguments",
+                form
+            )));
+        };
+        let Expr::Variable(name) = function.unspanned() else {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} expects a capability name",
+                form
+            )));
+        };
+        let expected = match self.capabilities.get(name) {
+            Some(capability) => capability.predicate.params.len(),
+            None => {
+                return Err(SlvrError::runtime(format!(
+                    "Capability not found: {}",
+                    name
+                )))
             }
-            Expr::Index { object, index } => {
-                let obj_val = self.eval_expr(object)?;
-                let idx_val = self.eval_expr(index)?;
-                let idx = idx_val.to_integer()? as usize;
-                obj_val.get_list_element(idx)
+        };
+        if args.len() != expected {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} expects {} arguments, got {}",
+                name,
+                expected,
+                args.len()
+            )));
+        }
+        let args = args
+            .iter()
+            .map(|arg| self.eval_expr(arg))
+            .collect::<SlvrResult<Vec<_>>>()?;
+        Ok(CapabilityToken::new(name.clone(), args))
+    }
+
+    fn enforce_failure(&mut self, message: &Expr) -> SlvrError {
+        match self.eval_expr(message).and_then(|m| m.to_string_value()) {
+            Ok(message) => SlvrError::EnforceFailed { message },
+            Err(e) => e,
+        }
+    }
+
+    fn eval_if(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: Option<&Expr>,
+    ) -> SlvrResult<Value> {
+        let (mut condition, mut then_branch, mut else_branch) =
+            (condition, then_branch, else_branch);
+        loop {
+            if self.eval_expr(condition)?.is_truthy() {
+                return self.eval_expr(then_branch);
             }
-            Expr::Block(exprs) => {
-                let mut result = Value::Unit;
-                for expr in exprs {
-                    result = self.eval_expr(expr)?;
+            // An `else if` arm is taken in this frame, so a long chain costs
+            // fuel but not stack
+            match else_branch.map(Expr::unspanned) {
+                Some(Expr::If {
+                    condition: next_condition,
+                    then_branch: next_then,
+                    else_branch: next_else,
+                }) => {
+                    self.consume_fuel(self.fuel_schedule.default_cost())?;
+                    condition = next_condition;
+                    then_branch = next_then;
+                    else_branch = next_else.as_deref();
                 }
-                Ok(result)
+                Some(else_expr) => return self.eval_expr(else_expr),
+                None => return Ok(Value::Unit),
             }
-            Expr::Read { table, key } => {
-                let key_val = self.eval_expr(key)?;
-                let key_str = key_val.to_string_value()?;
-                let table_key = format!("{}:{}", table, key_str);
-                Ok(self
-                    .globals
-                    .get(&table_key)
-                    .map(|v| v.clone())
-                    .unwrap_or(Value::Null))
+        }
+    }
+
+    fn eval_let(
+        &mut self,
+        bindings: &[(String, Expr)],
+        sequential: bool,
+        body: &Expr,
+    ) -> SlvrResult<Value> {
+        if sequential {
+            self.push_scope();
+            let result = self
+                .bind_sequentially(bindings)
+                .and_then(|()| self.eval_expr(body));
+            self.pop_scope();
+            return result;
+        }
+
+        let values: Vec<Value> = bindings
+            .iter()
+            .map(|(_, value)| self.eval_expr(value))
+            .collect::<SlvrResult<_>>()?;
+        self.push_scope();
+        for ((name, _), value) in bindings.iter().zip(values) {
+            self.set_local(name.clone(), value);
+        }
+        let result = self.eval_expr(body);
+        self.pop_scope();
+        result
+    }
+
+    /// Bind each `let*` value in the current scope so later ones can see it
+    fn bind_sequentially(&mut self, bindings: &[(String, Expr)]) -> SlvrResult<()> {
+        for (name, value) in bindings {
+            let val = self.eval_expr(value)?;
+            self.set_local(name.clone(), val);
+        }
+        Ok(())
+    }
+
+    fn eval_list(&mut self, elements: &[Expr]) -> SlvrResult<Value> {
+        let vals: SlvrResult<Vec<_>> = elements.iter().map(|e| self.eval_expr(e)).collect();
+        Ok(Value::List(vals?))
+    }
+
+    fn eval_object(&mut self, fields: &[(String, Expr)]) -> SlvrResult<Value> {
+        let mut obj = std::collections::BTreeMap::new();
+        for (key, value) in fields {
+            obj.insert(key.clone(), self.eval_expr(value)?);
+        }
+        Ok(Value::Object(obj))
+    }
+
+    /// Evaluate a chain of field and index accesses such as `a.rows[i].balance`.
+    ///
+    /// When the chain starts at a variable the path is walked by reference, so
+    /// only the final element is cloned rather than every intermediate value.
+    fn eval_access(&mut self, expr: &Expr) -> SlvrResult<Value> {
+        let mut steps = Vec::new();
+        let mut base = expr;
+        loop {
+            match base {
+                Expr::FieldAccess { object, field } => {
+                    steps.push(AccessStep::Field(field.as_str()));
+                    base = object.unspanned();
+                }
+                Expr::Index { object, index } => {
+                    steps.push(AccessStep::Index(index.as_ref()));
+                    base = object.unspanned();
+                }
+                _ => break,
             }
-            Expr::Write { table, key, value } => {
-                let key_val = self.eval_expr(key)?;
-                let key_str = key_val.to_string_value()?;
-                let val = self.eval_expr(value)?;
-                let table_key = format!("{}:{}", table, key_str);
-                self.globals.insert(table_key, val.clone());
-                Ok(val)
+        }
+        steps.reverse();
+
+        // Charge the nested nodes that are not visited through eval_expr
+        self.consume_fuel(
+            self.fuel_schedule
+                .default_cost()
+                .saturating_mul(steps.len() as u64 - 1),
+        )?;
+
+        let root = match base {
+            Expr::Variable(name) => {
+                self.consume_fuel(self.fuel_schedule.default_cost())?;
+                AccessRoot::Variable(name)
             }
-            Expr::Update {
-                table,
-                key,
-                updates,
-            } => {
-                let key_val = self.eval_expr(key)?;
-                let key_str = key_val.to_string_value()?;
-                let table_key = format!("{}:{}", table, key_str);
-
-                // Evaluate all field values first
-                let mut field_values = Vec::new();
-                for (field_name, field_expr) in updates {
-                    let field_val = self.eval_expr(field_expr)?;
-                    field_values.push((field_name.clone(), field_val));
-                }
+            _ => AccessRoot::Value(self.eval_expr(base)?),
+        };
 
-                // Then update the object
-                if let Some(mut current) = self.globals.get_mut(&table_key) {
-                    if let Value::Object(ref mut obj) = *current {
-                        for (field_name, field_val) in field_values {
-                            obj.insert(field_name, field_val);
-                        }
-                    }
+        let mut path = Vec::with_capacity(steps.len());
+        for step in steps {
+            path.push(match step {
+                AccessStep::Field(field) => AccessStep::Field(field),
+                AccessStep::Index(index) => {
+                    AccessStep::Index(self.eval_expr(index)?.to_integer()? as usize)
                 }
+            });
+        }
 
-                Ok(self
-                    .globals
-                    .get(&table_key)
-                    .map(|v| v.clone())
-                    .unwrap_or(Value::Null))
-            }
-            Expr::Delete { table, key } => {
-                let key_val = self.eval_expr(key)?;
-                let key_str = key_val.to_string_value()?;
-                let table_key = format!("{}:{}", table, key_str);
-                Ok(self
-                    .globals
-                    .remove(&table_key)
-                    .map(|(_, v)| v)
-                    .unwrap_or(Value::Null))
+        match root {
+            AccessRoot::Variable(name) => {
+                self.with_variable(name, |root| walk_access(root, &path))?
             }
+            AccessRoot::Value(root) => walk_access(&root, &path),
+        }
+    }
+
+    fn eval_block(&mut self, exprs: &[Expr]) -> SlvrResult<Value> {
+        let mut result = Value::Unit;
+        for expr in exprs {
+            result = self.eval_expr(expr)?;
+        }
+        Ok(result)
+    }
+
+    fn eval_table_key(&mut self, table: &str, key: &Expr) -> SlvrResult<String> {
+        let key_val = self.eval_expr(key)?;
+        let key_str = key_val.to_string_value()?;
+        Ok(format!("{}:{}", table, key_str))
+    }
+
+    /// A missing row is an error for `read` and null for `read-safe`
+    fn eval_read(&mut self, table: &str, key: &Expr, safe: bool) -> SlvrResult<Value> {
+        let table_key = self.eval_table_key(table, key)?;
+        match self.globals.get(&table_key) {
+            Some(row) => Ok(row.clone()),
+            None if safe => Ok(Value::Null),
+            None => Err(SlvrError::KeyNotFound { key: table_key }),
+        }
+    }
+
+    fn eval_write(&mut self, table: &str, key: &Expr, value: &Expr) -> SlvrResult<Value> {
+        let table_key = self.eval_table_key(table, key)?;
+        let val = self.eval_expr(value)?;
+        self.globals.insert(table_key, val.clone());
+        Ok(val)
+    }
+
+    fn eval_update(
+        &mut self,
+        table: &str,
+        key: &Expr,
+        updates: &[(String, Expr)],
+    ) -> SlvrResult<Value> {
+        let table_key = self.eval_table_key(table, key)?;
+
+        // Evaluate all field values first
+        let mut field_values = Vec::new();
+        for (field_name, field_expr) in updates {
+            let field_val = self.eval_expr(field_expr)?;
+            field_values.push((field_name.clone(), field_val));
+        }
+
+        // Then update the object
+        let Some(mut current) = self.globals.get(&table_key).map(|v| v.clone()) else {
+            return Ok(Value::Null);
         };
+        if let Value::Object(ref mut obj) = current {
+            for (field_name, field_val) in field_values {
+                obj.insert(field_name, field_val);
+            }
+            self.globals.insert(table_key, current.clone());
+        }
+        Ok(current)
+    }
 
-        self.recursion_depth -= 1;
-        result
+    fn eval_delete(&mut self, table: &str, key: &Expr) -> SlvrResult<Value> {
+        let table_key = self.eval_table_key(table, key)?;
+        Ok(self.globals.remove(&table_key).unwrap_or(Value::Null))
+    }
+
+    fn consume_fuel(&mut self, amount: u64) -> SlvrResult<()> {
+        let used = self.fuel_used.saturating_add(amount);
+        if let Some(limit) = self.fuel_limit {
+            if used > limit {
+                return Err(SlvrError::FuelExceeded { used, limit });
+            }
+        }
+        self.fuel_used = used;
+        Ok(())
     }
 
     fn eval_literal(&self, lit: &Literal) -> SlvrResult<Value> {
@@ -236,24 +954,36 @@ impl Evaluator {
     fn eval_binop(&self, op: BinOp, left: Value, right: Value) -> SlvrResult<Value> {
         match op {
             BinOp::Add => match (left, right) {
-                (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a + b)),
-                (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Decimal(a + b)),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Decimal(a as f64 + b)),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Decimal(a + b as f64)),
+                (Value::Integer(a), Value::Integer(b)) => checked_integer(a.checked_add(b), "+"),
+                (Value::Decimal(a), Value::Decimal(b)) => checked_decimal(a.checked_add(b), "+"),
+                (Value::Integer(a), Value::Decimal(b)) => {
+                    checked_decimal(integer_to_decimal(a)?.checked_add(b), "+")
+                }
+                (Value::Decimal(a), Value::Integer(b)) => {
+                    checked_decimal(a.checked_add(integer_to_decimal(b)?), "+")
+                }
                 _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
             },
             BinOp::Subtract => match (left, right) {
-                (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a - b)),
-                (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Decimal(a - b)),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Decimal(a as f64 - b)),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Decimal(a - b as f64)),
+                (Value::Integer(a), Value::Integer(b)) => checked_integer(a.checked_sub(b), "-"),
+                (Value::Decimal(a), Value::Decimal(b)) => checked_decimal(a.checked_sub(b), "-"),
+                (Value::Integer(a), Value::Decimal(b)) => {
+                    checked_decimal(integer_to_decimal(a)?.checked_sub(b), "-")
+                }
+                (Value::Decimal(a), Value::Integer(b)) => {
+                    checked_decimal(a.checked_sub(integer_to_decimal(b)?), "-")
+                }
                 _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
             },
             BinOp::Multiply => match (left, right) {
-                (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a * b)),
-                (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Decimal(a * b)),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Decimal(a as f64 * b)),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Decimal(a * b as f64)),
+                (Value::Integer(a), Value::Integer(b)) => checked_integer(a.checked_mul(b), "*"),
+                (Value::Decimal(a), Value::Decimal(b)) => checked_decimal(a.checked_mul(b), "*"),
+                (Value::Integer(a), Value::Decimal(b)) => {
+                    checked_decimal(integer_to_decimal(a)?.checked_mul(b), "*")
+                }
+                (Value::Decimal(a), Value::Integer(b)) => {
+                    checked_decimal(a.checked_mul(integer_to_decimal(b)?), "*")
+                }
                 _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
             },
             BinOp::Divide => match (left, right) {
@@ -261,28 +991,28 @@ impl Evaluator {
                     if b == 0 {
                         Err(SlvrError::DivisionByZero)
                     } else {
-                        Ok(Value::Integer(a / b))
+                        checked_integer(a.checked_div(b), "/")
                     }
                 }
                 (Value::Decimal(a), Value::Decimal(b)) => {
-                    if b == 0.0 {
+                    if b.is_zero() {
                         Err(SlvrError::DivisionByZero)
                     } else {
-                        Ok(Value::Decimal(a / b))
+                        checked_decimal(a.checked_div(b), "/")
                     }
                 }
                 (Value::Integer(a), Value::Decimal(b)) => {
-                    if b == 0.0 {
+                    if b.is_zero() {
                         Err(SlvrError::DivisionByZero)
                     } else {
-                        Ok(Value::Decimal(a as f64 / b))
+                        checked_decimal(integer_to_decimal(a)?.checked_div(b), "/")
                     }
                 }
                 (Value::Decimal(a), Value::Integer(b)) => {
                     if b == 0 {
                         Err(SlvrError::DivisionByZero)
                     } else {
-                        Ok(Value::Decimal(a / b as f64))
+                        checked_decimal(a.checked_div(integer_to_decimal(b)?), "/")
                     }
                 }
                 _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
@@ -292,7 +1022,7 @@ impl Evaluator {
                     if b == 0 {
                         Err(SlvrError::DivisionByZero)
                     } else {
-                        Ok(Value::Integer(a % b))
+                        checked_integer(a.checked_rem(b), "%")
                     }
                 }
                 _ => Err(SlvrError::type_mismatch("integer", "non-integer")),
@@ -300,14 +1030,15 @@ impl Evaluator {
             BinOp::Power => match (left, right) {
                 (Value::Integer(a), Value::Integer(b)) => {
                     if b < 0 {
-                        Ok(Value::Decimal((a as f64).powf(b as f64)))
+                        decimal_pow(integer_to_decimal(a)?, integer_to_decimal(b)?)
                     } else {
-                        Ok(Value::Integer(a.pow(b as u32)))
+                        let power = u32::try_from(b).ok().and_then(|b| a.checked_pow(b));
+                        checked_integer(power, "^")
                     }
                 }
-                (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Decimal(a.powf(b))),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Decimal((a as f64).powf(b))),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Decimal(a.powf(b as f64))),
+                (Value::Decimal(a), Value::Decimal(b)) => decimal_pow(a, b),
+                (Value::Integer(a), Value::Decimal(b)) => decimal_pow(integer_to_decimal(a)?, b),
+                (Value::Decimal(a), Value::Integer(b)) => decimal_pow(a, integer_to_decimal(b)?),
                 _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
             },
             BinOp::Equal => Ok(Value::Boolean(left == right)),
@@ -315,32 +1046,48 @@ impl Evaluator {
             BinOp::Less => match (left, right) {
                 (Value::Integer(a), Value::Integer(b)) => Ok(Value::Boolean(a < b)),
                 (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Boolean(a < b)),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Boolean((a as f64) < b)),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Boolean(a < (b as f64))),
+                (Value::Integer(a), Value::Decimal(b)) => {
+                    Ok(Value::Boolean(integer_to_decimal(a)? < b))
+                }
+                (Value::Decimal(a), Value::Integer(b)) => {
+                    Ok(Value::Boolean(a < integer_to_decimal(b)?))
+                }
                 (Value::String(a), Value::String(b)) => Ok(Value::Boolean(a < b)),
                 _ => Err(SlvrError::type_mismatch("comparable", "non-comparable")),
             },
             BinOp::LessEqual => match (left, right) {
                 (Value::Integer(a), Value::Integer(b)) => Ok(Value::Boolean(a <= b)),
                 (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Boolean(a <= b)),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Boolean((a as f64) <= b)),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Boolean(a <= (b as f64))),
+                (Value::Integer(a), Value::Decimal(b)) => {
+                    Ok(Value::Boolean(integer_to_decimal(a)? <= b))
+                }
+                (Value::Decimal(a), Value::Integer(b)) => {
+                    Ok(Value::Boolean(a <= integer_to_decimal(b)?))
+                }
                 (Value::String(a), Value::String(b)) => Ok(Value::Boolean(a <= b)),
                 _ => Err(SlvrError::type_mismatch("comparable", "non-comparable")),
             },
             BinOp::Greater => match (left, right) {
                 (Value::Integer(a), Value::Integer(b)) => Ok(Value::Boolean(a > b)),
                 (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Boolean(a > b)),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Boolean((a as f64) > b)),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Boolean(a > (b as f64))),
+                (Value::Integer(a), Value::Decimal(b)) => {
+                    Ok(Value::Boolean(integer_to_decimal(a)? > b))
+                }
+                (Value::Decimal(a), Value::Integer(b)) => {
+                    Ok(Value::Boolean(a > integer_to_decimal(b)?))
+                }
                 (Value::String(a), Value::String(b)) => Ok(Value::Boolean(a > b)),
                 _ => Err(SlvrError::type_mismatch("comparable", "non-comparable")),
             },
             BinOp::GreaterEqual => match (left, right) {
                 (Value::Integer(a), Value::Integer(b)) => Ok(Value::Boolean(a >= b)),
                 (Value::Decimal(a), Value::Decimal(b)) => Ok(Value::Boolean(a >= b)),
-                (Value::Integer(a), Value::Decimal(b)) => Ok(Value::Boolean((a as f64) >= b)),
-                (Value::Decimal(a), Value::Integer(b)) => Ok(Value::Boolean(a >= (b as f64))),
+                (Value::Integer(a), Value::Decimal(b)) => {
+                    Ok(Value::Boolean(integer_to_decimal(a)? >= b))
+                }
+                (Value::Decimal(a), Value::Integer(b)) => {
+                    Ok(Value::Boolean(a >= integer_to_decimal(b)?))
+                }
                 (Value::String(a), Value::String(b)) => Ok(Value::Boolean(a >= b)),
                 _ => Err(SlvrError::type_mismatch("comparable", "non-comparable")),
             },
@@ -358,34 +1105,62 @@ impl Evaluator {
         match op {
             UnaryOp::Not => Ok(Value::Boolean(!operand.is_truthy())),
             UnaryOp::Negate => match operand {
-                Value::Integer(n) => Ok(Value::Integer(-n)),
+                Value::Integer(n) => checked_integer(n.checked_neg(), "-"),
                 Value::Decimal(d) => Ok(Value::Decimal(-d)),
                 _ => Err(SlvrError::type_mismatch("numeric", operand.type_name())),
             },
         }
     }
 
-    fn call_function(&mut self, _name: &str, _args: Vec<Value>) -> SlvrResult<Value> {
-        // Built-in functions would be handled here
-        Err(SlvrError::undefined_func(_name))
+    fn call_function(&mut self, name: &str, args: Vec<Value>) -> SlvrResult<Value> {
+        let function = self
+            .functions
+            .get(name)
+            .cloned()
+            .ok_or_else(|| SlvrError::undefined_func(name))?;
+
+        if args.len() != function.params.len() {
+            return Err(SlvrError::invalid_arg(format!(
+                "{} expects {} arguments, got {}",
+                name,
+                function.params.len(),
+                args.len()
+            )));
+        }
+
+        // Functions see only their own parameters, not the caller's locals
+        let frame: IndexMap<String, Value> = function.params.into_iter().zip(args).collect();
+        let caller_locals = std::mem::replace(&mut self.locals, vec![frame]);
+        let result = self.eval_expr(&function.body);
+        self.locals = caller_locals;
+        result
     }
 
     fn get_variable(&self, name: &str) -> SlvrResult<Value> {
+        self.with_variable(name, Value::clone)
+    }
+
+    /// Run `f` on a borrowed variable instead of cloning it out
+    fn with_variable<R>(&self, name: &str, f: impl FnOnce(&Value) -> R) -> SlvrResult<R> {
         // Check local scopes from innermost to outermost
         for scope in self.locals.iter().rev() {
             if let Some(val) = scope.get(name) {
-                return Ok(val.clone());
+                return Ok(f(val));
             }
         }
 
         // Check globals
         if let Some(val) = self.globals.get(name) {
-            return Ok(val.clone());
+            return Ok(f(&val));
         }
 
         Err(SlvrError::undefined_var(name))
     }
 
+    fn is_variable(&self, name: &str) -> bool {
+        self.with_variable(name, |_| ()).is_ok()
+    }
+
     fn set_local(&mut self, name: String, value: Value) {
         if let Some(scope) = self.locals.last_mut() {
             scope.insert(name, value);
@@ -416,6 +1191,7 @@ impl Default for Evaluator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::value::Decimal;
 
     #[test]
     fn test_evaluator_creation() {
@@ -442,4 +1218,738 @@ mod tests {
         let result = evaluator.eval(&expr).unwrap();
         assert_eq!(result, Value::Integer(5));
     }
+
+    #[test]
+    fn test_decimal_overflow_is_rejected() {
+        let mut evaluator = Evaluator::new();
+        let overflow = Expr::BinOp {
+            op: BinOp::Multiply,
+            left: Box::new(Expr::Literal(Literal::Decimal(Decimal::MAX))),
+            right: Box::new(Expr::Literal(Literal::Decimal(Decimal::TEN))),
+        };
+        assert!(matches!(
+            evaluator.eval(&overflow),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+
+        let negative_root = Expr::BinOp {
+            op: BinOp::Power,
+            left: Box::new(Expr::Literal(Literal::Decimal(Decimal::new(-8, 0)))),
+            right: Box::new(Expr::Literal(Literal::Decimal(Decimal::new(5, 1)))),
+        };
+        assert!(evaluator.eval(&negative_root).is_err());
+
+        let integer_overflow = Expr::BinOp {
+            op: BinOp::Add,
+            left: Box::new(Expr::Literal(Literal::Integer(i128::MAX))),
+            right: Box::new(Expr::Literal(Literal::Integer(1))),
+        };
+        assert!(matches!(
+            evaluator.eval(&integer_overflow),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_runtime_error_reports_innermost_span() {
+        let source = "defun f () -> integer\n  1 + (2 * (2 ^ 126 * 4))";
+        let program = crate::Parser::new(source).unwrap().parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+
+        let error = evaluator.call("f", vec![]).unwrap_err();
+        assert!(matches!(error, SlvrError::RuntimeError { .. }));
+        let span = error.span().expect("runtime error should carry a span");
+        assert_eq!(&source[span.start..span.end], "2 ^ 126 * 4");
+        assert_eq!((span.line, span.column), (2, 13));
+    }
+
+    #[test]
+    fn test_decimal_arithmetic_is_exact() {
+        let program = crate::Parser::new(
+            "defun exact () -> boolean 0.1 + 0.2 == 0.3 \
+             defun third () -> decimal 1 / 3.0 * 3",
+        )
+        .unwrap()
+        .parse()
+        .unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+
+        assert_eq!(
+            evaluator.call("exact", vec![]).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            evaluator.call("third", vec![]).unwrap(),
+            Value::Decimal("0.9999999999999999999999999999".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_self_recursive_function_hits_limit() {
+        let mut parser =
+            crate::Parser::new("defun spin (n: integer) -> integer spin(n + 1)").unwrap();
+        let program = parser.parse().unwrap();
+
+        let mut evaluator = Evaluator::with_recursion_limit(64);
+        evaluator.eval_program(&program).unwrap();
+
+        let call = Expr::Call {
+            function: Box::new(Expr::Variable("spin".to_string())),
+            args: vec![Expr::Literal(Literal::Integer(0))],
+        };
+        let result = evaluator.eval(&call);
+        assert!(matches!(
+            result,
+            Err(SlvrError::RecursionDepthExceeded { depth: 64 })
+        ));
+
+        // The depth counter unwinds on error so the evaluator stays usable
+        assert_eq!(evaluator.recursion_depth, 0);
+        let ok = evaluator.eval(&Expr::Literal(Literal::Integer(1))).unwrap();
+        assert_eq!(ok, Value::Integer(1));
+    }
+
+    #[test]
+    fn test_nested_expression_hits_limit() {
+        // Hand-built trees skip the parser's nesting limit
+        let mut expr = Expr::Literal(Literal::Integer(1));
+        for _ in 0..10_000 {
+            expr = Expr::UnaryOp {
+                op: UnaryOp::Negate,
+                operand: Box::new(expr),
+            };
+        }
+
+        let mut evaluator = Evaluator::with_recursion_limit(64);
+        assert!(matches!(
+            evaluator.eval(&expr),
+            Err(SlvrError::RecursionDepthExceeded { depth: 64 })
+        ));
+        assert_eq!(evaluator.recursion_depth, 0);
+    }
+
+    #[test]
+    fn test_default_limit_does_not_overflow_stack() {
+        // Runs on the 2 MiB stack of a test worker: the default limit has to
+        // trip before the native stack does, in debug builds too.
+        for source in [
+            "defun spin (n: integer) -> integer spin(n + 1)",
+            "defun spin (n: integer) -> integer map(lambda (x) spin(x), [n])",
+        ] {
+            let program = crate::Parser::new(source).unwrap().parse().unwrap();
+            let mut evaluator = Evaluator::new();
+            evaluator.eval_program(&program).unwrap();
+
+            let call = Expr::Call {
+                function: Box::new(Expr::Variable("spin".to_string())),
+                args: vec![Expr::Literal(Literal::Integer(0))],
+            };
+            assert!(matches!(
+                evaluator.eval(&call),
+                Err(SlvrError::RecursionDepthExceeded {
+                    depth: MAX_RECURSION_DEPTH
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_expression_cost_comes_from_config() {
+        let program = crate::Parser::new("defun run () -> integer 1 + 2 * 3")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let fuel_used = |config: &SlvrConfig| {
+            let mut evaluator = Evaluator::with_config(config);
+            evaluator.eval_program(&program).unwrap();
+            evaluator.call("run", vec![]).unwrap();
+            evaluator.fuel_used()
+        };
+
+        let config = SlvrConfig::new();
+        let base = fuel_used(&config);
+        let tripled = config
+            .clone()
+            .with_fuel_schedule(FuelSchedule::new().with_default_cost(3));
+        assert_eq!(fuel_used(&tripled), 3 * base);
+    }
+
+    fn eval_source(source: &str, entry: &str) -> (SlvrResult<Value>, u64) {
+        let program = crate::Parser::new(source).unwrap().parse().unwrap();
+        let mut evaluator = Evaluator::new().with_fuel_limit(1_000_000);
+        evaluator.eval_program(&program).unwrap();
+        let call = Expr::Call {
+            function: Box::new(Expr::Variable(entry.to_string())),
+            args: vec![],
+        };
+        let result = evaluator.eval(&call);
+        (result, evaluator.fuel_used())
+    }
+
+    #[test]
+    fn test_logical_operators_short_circuit() {
+        let source = "defun skip_and () -> boolean false && 1 / 0 == 1 \
+                      defun skip_or () -> boolean true || 1 / 0 == 1 \
+                      defun run_and () -> boolean true && 1 / 0 == 1 \
+                      defun run_or () -> boolean false || 1 / 0 == 1 \
+                      defun cheap_and () -> boolean false && true";
+        assert_eq!(
+            eval_source(source, "skip_and").0.unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            eval_source(source, "skip_or").0.unwrap(),
+            Value::Boolean(true)
+        );
+        for entry in ["run_and", "run_or"] {
+            assert!(matches!(
+                eval_source(source, entry).0,
+                Err(SlvrError::DivisionByZero)
+            ));
+        }
+
+        // The skipped operand is not billed either
+        assert_eq!(
+            eval_source(source, "skip_and").1,
+            eval_source(source, "cheap_and").1
+        );
+    }
+
+    fn call(name: &str, args: Vec<Expr>) -> Expr {
+        Expr::Call {
+            function: Box::new(Expr::Variable(name.to_string())),
+            args,
+        }
+    }
+
+    fn write_row(value: i64) -> Expr {
+        Expr::Write {
+            table: "ledger".to_string(),
+            key: Box::new(Expr::Literal(Literal::String("alice".to_string()))),
+            value: Box::new(Expr::Literal(Literal::Integer(value as i128))),
+        }
+    }
+
+    #[test]
+    fn test_enforce() {
+        let source = "defun ok () -> unit enforce(1 < 2, \"unused\") \
+                      defun low () -> unit enforce(1 > 2, \"balance too low\")";
+        assert_eq!(eval_source(source, "ok").0.unwrap(), Value::Unit);
+        match eval_source(source, "low").0 {
+            Err(SlvrError::EnforceFailed { message }) => assert_eq!(message, "balance too low"),
+            other => panic!("expected enforce failure, got {:?}", other),
+        }
+
+        // A failed enforce rolls back the writes made before it
+        let mut evaluator = Evaluator::new();
+        let message = Expr::Literal(Literal::String("rejected".to_string()));
+        let transaction = Expr::Block(vec![
+            write_row(10),
+            call(
+                "enforce",
+                vec![Expr::Literal(Literal::Boolean(false)), message],
+            ),
+        ]);
+        assert!(matches!(
+            evaluator.eval(&transaction),
+            Err(SlvrError::EnforceFailed { .. })
+        ));
+        assert!(evaluator.globals.get("ledger:alice").is_none());
+    }
+
+    #[test]
+    fn test_enforce_one() {
+        let source = "defun mixed () -> unit \
+                        enforce-one([1 > 2, enforce(false, \"inner\"), 2 > 1], \"none passed\") \
+                      defun none () -> unit enforce-one([false, 1 > 2], \"none passed\")";
+        assert_eq!(eval_source(source, "mixed").0.unwrap(), Value::Unit);
+        match eval_source(source, "none").0 {
+            Err(SlvrError::EnforceFailed { message }) => assert_eq!(message, "none passed"),
+            other => panic!("expected enforce failure, got {:?}", other),
+        }
+
+        // Only the writes of the passing check survive
+        let mut evaluator = Evaluator::new();
+        let enforce_one = call(
+            "enforce-one",
+            vec![
+                Expr::List(vec![
+                    Expr::Block(vec![write_row(1), Expr::Literal(Literal::Boolean(false))]),
+                    Expr::Block(vec![write_row(2), Expr::Literal(Literal::Boolean(true))]),
+                ]),
+                Expr::Literal(Literal::String("none passed".to_string())),
+            ],
+        );
+        assert_eq!(evaluator.eval(&enforce_one).unwrap(), Value::Unit);
+        assert_eq!(
+            *evaluator.globals.get("ledger:alice").unwrap(),
+            Value::Integer(2)
+        );
+    }
+
+    const CAPABILITIES: &str = "defcap DEBIT (account: string) account != \"\" \
+         defcap TRANSFER (sender: string, amount: integer) \
+           enforce(amount > 0, \"amount must be positive\") \
+         defun debit (account: string) -> unit require-capability(DEBIT(account)) \
+         defun pay (sender: string, amount: integer) -> unit \
+           with-capability(TRANSFER(sender, amount), \
+             let held = require-capability(TRANSFER(sender, amount)) \
+             with-capability(DEBIT(sender), debit(sender)))";
+
+    #[test]
+    fn test_with_capability_grants_for_body() {
+        let program = crate::Parser::new(CAPABILITIES).unwrap().parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+
+        let text = |s: &str| Value::String(s.to_string());
+        assert_eq!(
+            evaluator
+                .call("pay", vec![text("alice"), Value::Integer(5)])
+                .unwrap(),
+            Value::Unit
+        );
+        assert!(evaluator.granted.granted().is_empty());
+
+        // A failing predicate grants nothing
+        match evaluator.call("pay", vec![text("alice"), Value::Integer(0)]) {
+            Err(SlvrError::EnforceFailed { message }) => {
+                assert_eq!(message, "amount must be positive")
+            }
+            other => panic!("expected enforce failure, got {:?}", other),
+        }
+        assert!(matches!(
+            evaluator.call("pay", vec![text(""), Value::Integer(5)]),
+            Err(SlvrError::CapabilityNotGranted { capability }) if capability == "DEBIT(\"\")"
+        ));
+        assert!(evaluator.granted.granted().is_empty());
+    }
+
+    #[test]
+    fn test_require_capability_outside_scope_fails() {
+        let program = crate::Parser::new(CAPABILITIES).unwrap().parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+
+        assert!(matches!(
+            evaluator.call("debit", vec![Value::String("alice".to_string())]),
+            Err(SlvrError::CapabilityNotGranted { capability }) if capability == "DEBIT(\"alice\")"
+        ));
+
+        // The grant is released even when the body fails
+        let failing = call(
+            "with-capability",
+            vec![
+                call(
+                    "DEBIT",
+                    vec![Expr::Literal(Literal::String("bob".to_string()))],
+                ),
+                call(
+                    "enforce",
+                    vec![
+                        Expr::Literal(Literal::Boolean(false)),
+                        Expr::Literal(Literal::String("inside".to_string())),
+                    ],
+                ),
+            ],
+        );
+        assert!(evaluator.eval(&failing).is_err());
+        assert!(evaluator.granted.granted().is_empty());
+        assert!(evaluator
+            .call("debit", vec![Value::String("bob".to_string())])
+            .is_err());
+    }
+
+    #[test]
+    fn test_managed_capability_rejects_over_spend() {
+        let source = "defcap TRANSFER (sender: string, amount: integer) manages(amount) \
+                        sender != \"\" \
+                      defun spend (first: integer, second: integer) -> unit \
+                        let* (installed = install-capability(TRANSFER(\"alice\", 100)), \
+                              spent = require-capability(TRANSFER(\"alice\", first))) \
+                        require-capability(TRANSFER(\"alice\", second))";
+        let program = crate::Parser::new(source).unwrap().parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+
+        assert_eq!(
+            evaluator
+                .call("spend", vec![Value::Integer(60), Value::Integer(40)])
+                .unwrap(),
+            Value::Unit
+        );
+        match evaluator.call("spend", vec![Value::Integer(60), Value::Integer(50)]) {
+            Err(SlvrError::ManagedQuantityExceeded {
+                requested,
+                remaining,
+                ..
+            }) => assert_eq!((requested.as_str(), remaining.as_str()), ("50", "40")),
+            other => panic!("expected over-spend, got {:?}", other),
+        }
+
+        // Nothing stays installed once the transaction ends
+        let require = call(
+            "require-capability",
+            vec![call(
+                "TRANSFER",
+                vec![
+                    Expr::Literal(Literal::String("alice".to_string())),
+                    Expr::Literal(Literal::Integer(1)),
+                ],
+            )],
+        );
+        assert!(matches!(
+            evaluator.eval(&require),
+            Err(SlvrError::CapabilityNotGranted { .. })
+        ));
+    }
+
+    #[test]
+    fn test_managed_capability_uses_manager() {
+        // The manager charges a flat fee of 1 on every draw
+        let source = "defun fee-mgr (managed: integer, requested: integer) -> integer \
+                        let rest = managed - requested - 1 \
+                        if rest >= 0 rest else enforce(false, \"allowance spent\") \
+                      defcap PAY (amount: integer) manages(amount, fee-mgr) true \
+                      defun pay (amount: integer) -> unit \
+                        let installed = install-capability(PAY(10)) \
+                        with-capability(PAY(amount), require-capability(PAY(amount)))";
+        let program = crate::Parser::new(source).unwrap().parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+
+        assert_eq!(
+            evaluator.call("pay", vec![Value::Integer(9)]).unwrap(),
+            Value::Unit
+        );
+        match evaluator.call("pay", vec![Value::Integer(10)]) {
+            Err(SlvrError::EnforceFailed { message }) => assert_eq!(message, "allowance spent"),
+            other => panic!("expected the manager to refuse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_access_chain_on_variable() {
+        let source = "defun pick (rows: [object], i: integer) -> integer rows[i].balance \
+                      defun run () -> integer pick([{balance: 1}, {balance: 7}], 1)";
+        assert_eq!(eval_source(source, "run").0.unwrap(), Value::Integer(7));
+
+        let missing = "defun pick (rows: [object]) -> integer rows[2].balance \
+                       defun run () -> integer pick([{balance: 1}])";
+        assert!(matches!(
+            eval_source(missing, "run").0,
+            Err(SlvrError::IndexOutOfBounds {
+                index: 2,
+                length: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_map_filter_fold_user_function() {
+        let defs = "defun double (x: integer) -> integer x * 2 \
+                    defun big (x: integer) -> boolean x > 2 \
+                    defun add (acc: integer, x: integer) -> integer acc + x ";
+        let run = |body: &str| {
+            eval_source(&format!("{} defun run () -> integer {}", defs, body), "run").0
+        };
+
+        assert_eq!(
+            run("map(double, [1, 2, 3])").unwrap(),
+            Value::List(vec![
+                Value::Integer(2),
+                Value::Integer(4),
+                Value::Integer(6)
+            ])
+        );
+        assert_eq!(
+            run("filter(big, [1, 2, 3, 4])").unwrap(),
+            Value::List(vec![Value::Integer(3), Value::Integer(4)])
+        );
+        assert_eq!(run("fold(add, 10, [1, 2, 3])").unwrap(), Value::Integer(16));
+        assert!(run("map(add, [1, 2])").is_err());
+        assert!(run("map(missing, [1, 2])").is_err());
+    }
+
+    #[test]
+    fn test_map_squares_and_fold_sum() {
+        let defs = "defun square (x: integer) -> integer x * x \
+                    defun add (acc: integer, x: integer) -> integer acc + x ";
+        let run = |body: &str| {
+            eval_source(&format!("{} defun run () -> integer {}", defs, body), "run").0
+        };
+
+        assert_eq!(
+            run("map(square, [1, 2, 3, 4])").unwrap(),
+            Value::List(vec![
+                Value::Integer(1),
+                Value::Integer(4),
+                Value::Integer(9),
+                Value::Integer(16)
+            ])
+        );
+        assert_eq!(
+            run("fold(add, 0, map(square, [1, 2, 3, 4]))").unwrap(),
+            Value::Integer(30)
+        );
+        assert!(matches!(
+            run("map(square, 5)"),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_map_over_huge_list_runs_out_of_fuel() {
+        let program = crate::Parser::new(
+            "defun square (x: integer) -> integer x * x \
+             defun run (xs: list) -> integer map(square, xs)",
+        )
+        .unwrap()
+        .parse()
+        .unwrap();
+        let huge = Value::List((0..1_000_000).map(Value::Integer).collect());
+
+        let mut evaluator = Evaluator::new().with_fuel_limit(10_000);
+        evaluator.eval_program(&program).unwrap();
+        assert!(matches!(
+            evaluator.call("run", vec![huge]),
+            Err(SlvrError::FuelExceeded { limit: 10_000, .. })
+        ));
+        assert!(evaluator.fuel_used() <= 10_000);
+    }
+
+    #[test]
+    fn test_map_fuel_scales_with_length() {
+        let fuel = |list: &str| {
+            let source = format!(
+                "defun double (x: integer) -> integer x * 2 \
+                 defun run () -> integer map(double, {})",
+                list
+            );
+            let (result, fuel) = eval_source(&source, "run");
+            result.unwrap();
+            fuel
+        };
+
+        let empty = fuel("[]");
+        let three = fuel("[1, 2, 3]");
+        let six = fuel("[1, 2, 3, 4, 5, 6]");
+        assert!(three > empty);
+        assert_eq!(six - three, three - empty);
+    }
+
+    #[test]
+    fn test_let_scoping() {
+        let shadow = "defun run () -> integer let x = 1 let x = x + 10 x * 2";
+        assert_eq!(eval_source(shadow, "run").0.unwrap(), Value::Integer(22));
+
+        // Parallel bindings see the outer x, let* sees the one just bound
+        let parallel = "defun f (x: integer) -> integer let (x = 10, y = x) x + y \
+                        defun run () -> integer f(1)";
+        assert_eq!(eval_source(parallel, "run").0.unwrap(), Value::Integer(11));
+        let sequential = "defun f (x: integer) -> integer let* (x = 10, y = x) x + y \
+                          defun run () -> integer f(1)";
+        assert_eq!(
+            eval_source(sequential, "run").0.unwrap(),
+            Value::Integer(20)
+        );
+
+        let escaped = "defun run () -> integer (let x = 1 x) + x";
+        assert!(matches!(
+            eval_source(escaped, "run").0,
+            Err(SlvrError::UndefinedVariable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_let_value_is_evaluated_once() {
+        let fuel = |value: &str| {
+            let source = format!(
+                "defun sq (n: integer) -> integer n * n \
+                 defun run () -> integer let x = {} x + x + x + x",
+                value
+            );
+            let (result, fuel) = eval_source(&source, "run");
+            assert_eq!(result.unwrap(), Value::Integer(36));
+            fuel
+        };
+        let (_, direct) = eval_source(
+            "defun sq (n: integer) -> integer n * n \
+             defun run () -> integer sq(3)",
+            "run",
+        );
+        let (_, literal) = eval_source("defun run () -> integer 9", "run");
+        assert_eq!(fuel("sq(3)") - fuel("9"), direct - literal);
+    }
+
+    #[test]
+    fn test_lambda_applied_directly() {
+        let direct = "defun run () -> integer (lambda (a, b) a * b)(6, 7)";
+        assert_eq!(eval_source(direct, "run").0.unwrap(), Value::Integer(42));
+
+        // The closure keeps n after f has returned it
+        let captured = "defun adder (n: integer) -> integer lambda (x) x + n \
+                        defun run () -> integer let add = adder(10) add(5)";
+        assert_eq!(eval_source(captured, "run").0.unwrap(), Value::Integer(15));
+
+        let arity = "defun run () -> integer (lambda (a) a)(1, 2)";
+        assert!(matches!(
+            eval_source(arity, "run").0,
+            Err(SlvrError::InvalidArgument { .. })
+        ));
+        let not_a_function = "defun run () -> integer let x = 1 x(2)";
+        assert!(matches!(
+            eval_source(not_a_function, "run").0,
+            Err(SlvrError::TypeMismatch { .. })
+        ));
+
+        // Each application costs fuel beyond evaluating its body
+        let (_, applied) = eval_source("defun run () -> integer (lambda (x) x)(1)", "run");
+        let (_, inline) = eval_source("defun run () -> integer 1", "run");
+        assert!(applied > inline + 2 * crate::MIN_FUEL_PER_OP);
+    }
+
+    #[test]
+    fn test_lambda_passed_to_map() {
+        let source = "defun scale (k: integer, xs: [integer]) -> [integer] \
+                      map(lambda (x) x * k, xs) \
+                      defun run () -> [integer] scale(3, [1, 2, 3])";
+        assert_eq!(
+            eval_source(source, "run").0.unwrap(),
+            Value::List(vec![
+                Value::Integer(3),
+                Value::Integer(6),
+                Value::Integer(9)
+            ])
+        );
+
+        let sum = "defun run () -> integer fold(lambda (acc, x) acc + x, 0, [1, 2, 3])";
+        assert_eq!(eval_source(sum, "run").0.unwrap(), Value::Integer(6));
+
+        let wrong_arity = "defun run () -> [integer] map(lambda (a, b) a, [1])";
+        assert!(matches!(
+            eval_source(wrong_arity, "run").0,
+            Err(SlvrError::InvalidArgument { .. })
+        ));
+    }
+
+    #[test]
+    fn test_self_applied_lambda_hits_limit() {
+        let program = crate::Parser::new("defun spin () -> integer let f = lambda (g) g(g) f(f)")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let mut evaluator = Evaluator::with_recursion_limit(64);
+        evaluator.eval_program(&program).unwrap();
+        assert!(matches!(
+            evaluator.call("spin", vec![]),
+            Err(SlvrError::RecursionDepthExceeded { depth: 64 })
+        ));
+        assert_eq!(evaluator.recursion_depth, 0);
+    }
+
+    #[test]
+    fn test_read_safe_yields_null_for_missing_row() {
+        let source = "defun balance (id: string) -> integer read(coins, id).balance \
+                      defun balance-or-zero (id: string) -> integer \
+                      let row = read-safe(coins, id) if row != null row.balance else 0";
+        let program = crate::Parser::new(source).unwrap().parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+        let row: std::collections::BTreeMap<_, _> = [("balance".to_string(), Value::Integer(5))]
+            .into_iter()
+            .collect();
+        evaluator.set_global("coins:alice".to_string(), Value::Object(row));
+
+        let alice = || vec![Value::String("alice".to_string())];
+        let bob = || vec![Value::String("bob".to_string())];
+        assert_eq!(
+            evaluator.call("balance", alice()).unwrap(),
+            Value::Integer(5)
+        );
+        assert!(matches!(
+            evaluator.call("balance", bob()),
+            Err(SlvrError::KeyNotFound { key }) if key == "coins:bob"
+        ));
+        assert_eq!(
+            evaluator.call("balance-or-zero", alice()).unwrap(),
+            Value::Integer(5)
+        );
+        assert_eq!(
+            evaluator.call("balance-or-zero", bob()).unwrap(),
+            Value::Integer(0)
+        );
+    }
+
+    #[test]
+    fn test_block_time_is_injected() {
+        let program = crate::Parser::new("defun stamp () -> integer block-time() + 1")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let call = Expr::Call {
+            function: Box::new(Expr::Variable("stamp".to_string())),
+            args: vec![],
+        };
+        let run = |evaluator: Evaluator| {
+            let mut evaluator = evaluator;
+            evaluator.eval_program(&program).unwrap();
+            evaluator.eval(&call)
+        };
+
+        let source = Arc::new(crate::runtime::BlockTime(1_700_000_000));
+        let first = run(Evaluator::new().with_time_source(source.clone())).unwrap();
+        let second = run(Evaluator::new().with_time_source(source)).unwrap();
+        assert_eq!(first, Value::Integer(1_700_000_001));
+        assert_eq!(first, second);
+
+        assert!(run(Evaluator::new()).is_err());
+    }
+
+    #[test]
+    fn test_ed25519_verify_builtin() {
+        use ed25519_dalek::Signer;
+
+        let program = crate::Parser::new(
+            "defun authorized (key: string, memo: string, sig: string) -> boolean\n  \
+             ed25519-verify(key, memo, sig)",
+        )
+        .unwrap()
+        .parse()
+        .unwrap();
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let pubkey = hex::encode(key.verifying_key().to_bytes());
+        let signature = hex::encode(key.sign(b"transfer 10").to_bytes());
+        let run = |memo: &str| {
+            let mut evaluator = Evaluator::new();
+            evaluator.eval_program(&program).unwrap();
+            let args = [pubkey.as_str(), memo, signature.as_str()]
+                .iter()
+                .map(|s| Expr::Literal(Literal::String(s.to_string())))
+                .collect();
+            evaluator.eval(&Expr::Call {
+                function: Box::new(Expr::Variable("authorized".to_string())),
+                args,
+            })
+        };
+
+        assert_eq!(run("transfer 10").unwrap(), Value::Boolean(true));
+        assert_eq!(run("transfer 99").unwrap(), Value::Boolean(false));
+    }
+
+    #[test]
+    fn test_fuel_limit() {
+        let mut evaluator = Evaluator::new().with_fuel_limit(2);
+        let expr = Expr::BinOp {
+            op: BinOp::Add,
+            left: Box::new(Expr::Literal(Literal::Integer(2))),
+            right: Box::new(Expr::Literal(Literal::Integer(3))),
+        };
+        assert!(matches!(
+            evaluator.eval(&expr),
+            Err(SlvrError::FuelExceeded { limit: 2, .. })
+        ));
+    }
 }
@@ -63,15 +63,14 @@ impl Keyset {
     /// Create a new keyset
     pub fn new(name: String, keys: Vec<Key>, threshold: usize) -> SlvrResult<Self> {
         if keys.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "keyset must have at least one key".to_string(),
-            });
+            return Err(SlvrError::runtime("keyset must have at least one key"));
         }
 
         if threshold == 0 || threshold > keys.len() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("threshold must be between 1 and {}", keys.len()),
-            });
+            return Err(SlvrError::runtime(format!(
+                "threshold must be between 1 and {}",
+                keys.len()
+            )));
         }
 
         Ok(Keyset {
@@ -85,9 +84,7 @@ impl Keyset {
     /// Add a key to the keyset
     pub fn add_key(&mut self, key: Key) -> SlvrResult<()> {
         if self.keys.iter().any(|k| k.id == key.id) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("key {} already exists", key.id),
-            });
+            return Err(SlvrError::runtime(format!("key {} already exists", key.id)));
         }
         self.keys.push(key);
         Ok(())
@@ -98,15 +95,11 @@ impl Keyset {
         if let Some(pos) = self.keys.iter().position(|k| k.id == key_id) {
             self.keys.remove(pos);
             if self.keys.is_empty() {
-                return Err(SlvrError::RuntimeError {
-                    message: "cannot remove last key from keyset".to_string(),
-                });
+                return Err(SlvrError::runtime("cannot remove last key from keyset"));
             }
             Ok(())
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("key {} not found", key_id),
-            })
+            Err(SlvrError::runtime(format!("key {} not found", key_id)))
         }
     }
 
@@ -208,9 +201,10 @@ impl KeysetManager {
     /// Register a keyset
     pub fn register_keyset(&mut self, keyset: Keyset) -> SlvrResult<()> {
         if self.keysets.contains_key(&keyset.name) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("keyset {} already exists", keyset.name),
-            });
+            return Err(SlvrError::runtime(format!(
+                "keyset {} already exists",
+                keyset.name
+            )));
         }
         self.keysets.insert(keyset.name.clone(), keyset);
         Ok(())
@@ -221,17 +215,16 @@ impl KeysetManager {
         self.keysets
             .get(name)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("keyset {} not found", name),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("keyset {} not found", name)))
     }
 
     /// Update a keyset
     pub fn update_keyset(&mut self, keyset: Keyset) -> SlvrResult<()> {
         if !self.keysets.contains_key(&keyset.name) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("keyset {} not found", keyset.name),
-            });
+            return Err(SlvrError::runtime(format!(
+                "keyset {} not found",
+                keyset.name
+            )));
         }
         self.keysets.insert(keyset.name.clone(), keyset);
         Ok(())
@@ -240,9 +233,7 @@ impl KeysetManager {
     /// Delete a keyset
     pub fn delete_keyset(&mut self, name: &str) -> SlvrResult<()> {
         if self.keysets.remove(name).is_none() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("keyset {} not found", name),
-            });
+            return Err(SlvrError::runtime(format!("keyset {} not found", name)));
         }
         Ok(())
     }
@@ -250,9 +241,10 @@ impl KeysetManager {
     /// Register a capability
     pub fn register_capability(&mut self, capability: Capability) -> SlvrResult<()> {
         if self.capabilities.contains_key(&capability.id) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("capability {} already exists", capability.id),
-            });
+            return Err(SlvrError::runtime(format!(
+                "capability {} already exists",
+                capability.id
+            )));
         }
         self.capabilities.insert(capability.id.clone(), capability);
         Ok(())
@@ -263,17 +255,13 @@ impl KeysetManager {
         self.capabilities
             .get(id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("capability {} not found", id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("capability {} not found", id)))
     }
 
     /// Revoke a capability
     pub fn revoke_capability(&mut self, id: &str) -> SlvrResult<()> {
         if self.capabilities.remove(id).is_none() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("capability {} not found", id),
-            });
+            return Err(SlvrError::runtime(format!("capability {} not found", id)));
         }
         Ok(())
     }
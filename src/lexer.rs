@@ -3,6 +3,7 @@
 //! Tokenizes source code into a stream of tokens.
 
 use crate::error::{SlvrError, SlvrResult};
+use crate::value::{parse_decimal, Decimal};
 use serde::{Deserialize, Serialize};
 
 /// Token types
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 pub enum TokenType {
     // Literals
     Integer(i128),
-    Decimal(f64),
+    Decimal(Decimal),
     String(String),
     Identifier(String),
 
@@ -20,9 +21,13 @@ pub enum TokenType {
     Defschema,
     Deftable,
     Defconst,
+    Deftype,
+    Defcap,
     If,
     Let,
+    Lambda,
     Read,
+    ReadSafe,
     Write,
     Update,
     Delete,
@@ -59,6 +64,7 @@ pub enum TokenType {
     Colon,
     Semicolon,
     Comma,
+    Pipe,
     Dot,
     Arrow,
 
@@ -66,20 +72,34 @@ pub enum TokenType {
     Eof,
 }
 
+/// Source range of a token.
+///
+/// `start` and `end` are byte offsets into the source (`end` exclusive);
+/// `line` and `column` are the 1-based position of the first character.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
 /// A token with location information
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub line: usize,
     pub column: usize,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, line: usize, column: usize) -> Self {
+    pub fn new(token_type: TokenType, span: Span) -> Self {
         Self {
             token_type,
-            line,
-            column,
+            line: span.line,
+            column: span.column,
+            span,
         }
     }
 }
@@ -88,6 +108,8 @@ impl Token {
 pub struct Lexer {
     input: Vec<char>,
     position: usize,
+    /// Byte offset of `position` in the source
+    offset: usize,
     line: usize,
     column: usize,
 }
@@ -98,6 +120,7 @@ impl Lexer {
         Self {
             input: input.chars().collect(),
             position: 0,
+            offset: 0,
             line: 1,
             column: 1,
         }
@@ -105,10 +128,27 @@ impl Lexer {
 
     /// Get the next token
     pub fn next_token(&mut self) -> SlvrResult<Token> {
-        self.skip_whitespace_and_comments();
+        self.skip_whitespace_and_comments()?;
+
+        let start = self.offset;
+        let line = self.line;
+        let column = self.column;
+        let token_type = self.read_token()?;
 
+        Ok(Token::new(
+            token_type,
+            Span {
+                start,
+                end: self.offset,
+                line,
+                column,
+            },
+        ))
+    }
+
+    fn read_token(&mut self) -> SlvrResult<TokenType> {
         if self.position >= self.input.len() {
-            return Ok(Token::new(TokenType::Eof, self.line, self.column));
+            return Ok(TokenType::Eof);
         }
 
         let ch = self.current_char();
@@ -241,7 +281,7 @@ impl Lexer {
                     self.advance();
                     TokenType::Or
                 } else {
-                    return Err(SlvrError::lexer(line, column, "unexpected character '|'"));
+                    TokenType::Pipe
                 }
             }
             '"' => self.read_string()?,
@@ -256,7 +296,7 @@ impl Lexer {
             }
         };
 
-        Ok(Token::new(token_type, line, column))
+        Ok(token_type)
     }
 
     /// Tokenize the entire input
@@ -281,7 +321,6 @@ impl Lexer {
         }
     }
 
-    #[allow(dead_code)]
     fn peek_char(&self, offset: usize) -> char {
         if self.position + offset < self.input.len() {
             self.input[self.position + offset]
@@ -292,17 +331,22 @@ impl Lexer {
 
     fn advance(&mut self) {
         if self.position < self.input.len() {
-            if self.input[self.position] == '\n' {
+            let ch = self.input[self.position];
+            if ch == '\n' {
                 self.line += 1;
                 self.column = 1;
             } else {
                 self.column += 1;
             }
+            self.offset += ch.len_utf8();
             self.position += 1;
         }
     }
 
-    fn skip_whitespace_and_comments(&mut self) {
+    /// Skip whitespace, `;` line comments and `#| ... |#` block comments.
+    ///
+    /// Block comments nest, so a region containing one can be commented out.
+    fn skip_whitespace_and_comments(&mut self) -> SlvrResult<()> {
         while self.position < self.input.len() {
             match self.current_char() {
                 ' ' | '\t' | '\n' | '\r' => self.advance(),
@@ -312,9 +356,38 @@ impl Lexer {
                         self.advance();
                     }
                 }
+                '#' if self.peek_char(1) == '|' => self.skip_block_comment()?,
                 _ => break,
             }
         }
+        Ok(())
+    }
+
+    fn skip_block_comment(&mut self) -> SlvrResult<()> {
+        let line = self.line;
+        let column = self.column;
+        let mut depth = 0usize;
+
+        while self.position < self.input.len() {
+            match (self.current_char(), self.peek_char(1)) {
+                ('#', '|') => {
+                    depth += 1;
+                    self.advance();
+                }
+                ('|', '#') => {
+                    depth -= 1;
+                    self.advance();
+                    if depth == 0 {
+                        self.advance();
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+
+        Err(SlvrError::lexer(line, column, "unterminated block comment"))
     }
 
     fn read_string(&mut self) -> SlvrResult<TokenType> {
@@ -325,14 +398,26 @@ impl Lexer {
         let mut result = String::new();
         while self.position < self.input.len() && self.current_char() != '"' {
             if self.current_char() == '\\' {
+                let escape_line = self.line;
+                let escape_column = self.column;
                 self.advance();
+                if self.position >= self.input.len() {
+                    break;
+                }
                 match self.current_char() {
                     'n' => result.push('\n'),
                     't' => result.push('\t'),
                     'r' => result.push('\r'),
                     '\\' => result.push('\\'),
                     '"' => result.push('"'),
-                    _ => result.push(self.current_char()),
+                    'u' => result.push(self.read_unicode_escape(escape_line, escape_column)?),
+                    other => {
+                        return Err(SlvrError::lexer(
+                            escape_line,
+                            escape_column,
+                            format!("unknown escape sequence '\\{}'", other),
+                        ))
+                    }
                 }
             } else {
                 result.push(self.current_char());
@@ -348,37 +433,132 @@ impl Lexer {
         Ok(TokenType::String(result))
     }
 
+    /// Decode `\u{XXXX}` (1 to 6 hex digits), leaving the lexer on the `}`
+    fn read_unicode_escape(&mut self, line: usize, column: usize) -> SlvrResult<char> {
+        let invalid = |detail: &str| {
+            SlvrError::lexer(line, column, format!("invalid unicode escape: {}", detail))
+        };
+
+        self.advance(); // Skip 'u'
+        if self.current_char() != '{' {
+            return Err(invalid("expected '{' after \\u"));
+        }
+        self.advance();
+
+        let mut digits = String::new();
+        while self.position < self.input.len() && self.current_char().is_ascii_hexdigit() {
+            digits.push(self.current_char());
+            self.advance();
+        }
+        if self.current_char() != '}' {
+            return Err(invalid("expected hex digits and a closing '}'"));
+        }
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(invalid("expected 1 to 6 hex digits"));
+        }
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| invalid(&format!("U+{} is not a unicode scalar value", digits)))
+    }
+
+    /// Read a numeric literal.
+    ///
+    /// Accepts `1_000`, `0xFF`, `0o17`, `0b1010`, `1.5` and `1.5e10`. Underscores
+    /// may only separate digits. Anything with an exponent is a decimal.
     fn read_number(&mut self) -> SlvrResult<TokenType> {
-        let mut result = String::new();
-        let mut is_decimal = false;
+        let line = self.line;
+        let column = self.column;
+        let error = |message: String| SlvrError::lexer(line, column, message);
 
-        while self.position < self.input.len() {
-            let ch = self.current_char();
-            if ch.is_ascii_digit() {
-                result.push(ch);
-                self.advance();
-            } else if ch == '.' && !is_decimal {
-                is_decimal = true;
-                result.push(ch);
+        let radix = match (self.current_char(), self.peek_char(1)) {
+            ('0', 'x' | 'X') => Some(16),
+            ('0', 'o' | 'O') => Some(8),
+            ('0', 'b' | 'B') => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            let prefix: String = [self.current_char(), self.peek_char(1)].iter().collect();
+            self.advance();
+            self.advance();
+            let body = self.take_while(|c| c.is_alphanumeric() || c == '_');
+            let literal = format!("{}{}", prefix, body);
+            if body.is_empty() {
+                return Err(error(format!("missing digits after '{}'", prefix)));
+            }
+            let digits = strip_separators(&body)
+                .filter(|d| d.chars().all(|c| c.is_digit(radix)))
+                .ok_or_else(|| error(format!("invalid integer literal '{}'", literal)))?;
+            return i128::from_str_radix(&digits, radix)
+                .map(TokenType::Integer)
+                .map_err(|_| error(format!("integer literal '{}' is out of range", literal)));
+        }
+
+        let mut literal = self.take_while(|c| c.is_ascii_digit() || c == '_');
+        let mut is_decimal = false;
+        if self.current_char() == '.' && self.peek_char(1).is_ascii_digit() {
+            is_decimal = true;
+            self.advance();
+            literal.push('.');
+            literal.push_str(&self.take_while(|c| c.is_ascii_digit() || c == '_'));
+        }
+        let exponent_follows = |c: char| c.is_ascii_digit();
+        if matches!(self.current_char(), 'e' | 'E')
+            && (exponent_follows(self.peek_char(1))
+                || (matches!(self.peek_char(1), '+' | '-') && exponent_follows(self.peek_char(2))))
+        {
+            is_decimal = true;
+            literal.push(self.current_char());
+            self.advance();
+            if matches!(self.current_char(), '+' | '-') {
+                literal.push(self.current_char());
                 self.advance();
-            } else {
-                break;
             }
+            literal.push_str(&self.take_while(|c| c.is_ascii_digit() || c == '_'));
+        }
+
+        // A literal runs into a following letter or underscore, e.g. `12ab` or `1e`
+        let trailing = self.take_while(|c| c.is_alphanumeric() || c == '_');
+        if !trailing.is_empty() {
+            return Err(error(format!(
+                "invalid numeric literal '{}{}'",
+                literal, trailing
+            )));
+        }
+
+        let well_separated = literal
+            .split(['.', 'e', 'E', '+', '-'])
+            .all(|group| strip_separators(group).is_some());
+        if !well_separated {
+            return Err(error(format!(
+                "misplaced '_' in numeric literal '{}'",
+                literal
+            )));
         }
+        let digits = literal.replace('_', "");
 
         if is_decimal {
-            let value = result
-                .parse::<f64>()
-                .map_err(|_| SlvrError::lexer(self.line, self.column, "invalid decimal"))?;
-            Ok(TokenType::Decimal(value))
+            parse_decimal(&digits)
+                .map(TokenType::Decimal)
+                .ok_or_else(|| error(format!("decimal literal '{}' is out of range", literal)))
         } else {
-            let value = result
+            digits
                 .parse::<i128>()
-                .map_err(|_| SlvrError::lexer(self.line, self.column, "invalid integer"))?;
-            Ok(TokenType::Integer(value))
+                .map(TokenType::Integer)
+                .map_err(|_| error(format!("integer literal '{}' is out of range", literal)))
         }
     }
 
+    fn take_while(&mut self, mut pred: impl FnMut(char) -> bool) -> String {
+        let mut result = String::new();
+        while self.position < self.input.len() && pred(self.current_char()) {
+            result.push(self.current_char());
+            self.advance();
+        }
+        result
+    }
+
     fn read_identifier(&mut self) -> TokenType {
         let mut result = String::new();
         while self.position < self.input.len() {
@@ -397,9 +577,13 @@ impl Lexer {
             "defschema" => TokenType::Defschema,
             "deftable" => TokenType::Deftable,
             "defconst" => TokenType::Defconst,
+            "deftype" => TokenType::Deftype,
+            "defcap" => TokenType::Defcap,
             "if" => TokenType::If,
             "let" => TokenType::Let,
+            "lambda" => TokenType::Lambda,
             "read" => TokenType::Read,
+            "read-safe" => TokenType::ReadSafe,
             "write" => TokenType::Write,
             "update" => TokenType::Update,
             "delete" => TokenType::Delete,
@@ -411,6 +595,16 @@ impl Lexer {
     }
 }
 
+/// Remove `_` separators from a digit group, or `None` if one is leading,
+/// trailing or doubled
+fn strip_separators(group: &str) -> Option<String> {
+    if group.starts_with('_') || group.ends_with('_') || group.contains("__") {
+        None
+    } else {
+        Some(group.replace('_', ""))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -445,6 +639,200 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_token_spans() {
+        let tokens = Lexer::new("defun f\n  (é) -> 12").tokenize().unwrap();
+        let spans: Vec<_> = tokens
+            .iter()
+            .map(|t| (t.span.start, t.span.end, t.line, t.column))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 5, 1, 1),   // defun
+                (6, 7, 1, 7),   // f
+                (10, 11, 2, 3), // (
+                (11, 13, 2, 4), // é is two bytes but one column
+                (13, 14, 2, 5), // )
+                (15, 17, 2, 7), // ->
+                (18, 20, 2, 10),
+                (20, 20, 2, 12), // EOF
+            ]
+        );
+    }
+
+    fn lex_error(source: &str) -> (usize, usize, String) {
+        match Lexer::new(source).tokenize() {
+            Err(SlvrError::LexerError {
+                line,
+                column,
+                message,
+            }) => (line, column, message),
+            other => panic!("expected lexer error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unterminated_string_position() {
+        let (line, column, message) = lex_error("defun f ()\n  \"abc");
+        assert_eq!((line, column), (2, 3));
+        assert!(message.contains("unterminated string"));
+    }
+
+    #[test]
+    fn test_illegal_character_position() {
+        let (line, column, message) = lex_error("x +\n\n   y $ z");
+        assert_eq!((line, column), (3, 6));
+        assert!(message.contains('$'));
+
+        let (line, column, _) = lex_error("a & b");
+        assert_eq!((line, column), (1, 3));
+    }
+
+    fn token_types(source: &str) -> Vec<TokenType> {
+        Lexer::new(source)
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.token_type)
+            .collect()
+    }
+
+    #[test]
+    fn test_line_comments() {
+        // At end of file with no trailing newline
+        assert_eq!(
+            token_types("x ; trailing"),
+            vec![TokenType::Identifier("x".to_string()), TokenType::Eof]
+        );
+        // A string-like run inside a comment is still comment
+        assert_eq!(
+            token_types("; say \"hi\n1"),
+            vec![TokenType::Integer(1), TokenType::Eof]
+        );
+        // A `;` inside a string is not a comment
+        assert_eq!(
+            token_types("\"a ; b\" 2"),
+            vec![
+                TokenType::String("a ; b".to_string()),
+                TokenType::Integer(2),
+                TokenType::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_comments() {
+        let tokens = Lexer::new("#| one\n \"two\"\n #| nested |# |#x #|end|#")
+            .tokenize()
+            .unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].token_type, TokenType::Identifier("x".to_string()));
+        assert_eq!((tokens[0].line, tokens[0].column), (3, 17));
+        assert_eq!(
+            token_types("\"#| not a comment |#\""),
+            vec![
+                TokenType::String("#| not a comment |#".to_string()),
+                TokenType::Eof
+            ]
+        );
+
+        let (line, column, message) = lex_error("1\n  #| open #| |#");
+        assert_eq!((line, column), (2, 3));
+        assert!(message.contains("block comment"));
+    }
+
+    fn string(source: &str) -> String {
+        match Lexer::new(source).next_token().unwrap().token_type {
+            TokenType::String(s) => s,
+            other => panic!("expected string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let newline = string(r#""a\nb""#);
+        assert_eq!(newline, "a\nb");
+        assert_eq!(newline.chars().count(), 3);
+
+        assert_eq!(string(r#""\"q\" \\ \t\r""#), "\"q\" \\ \t\r");
+        assert_eq!(string(r#""\u{1F600}""#), "\u{1F600}");
+        assert_eq!(string(r#""\u{e9}t\u{00E9}""#), "été");
+    }
+
+    #[test]
+    fn test_bad_string_escapes() {
+        for (source, column) in [
+            (r#"x "ab\q""#, 6),
+            (r#"x "\u{1F600""#, 4),
+            (r#"x "\u1F600""#, 4),
+            (r#"x "\u{}""#, 4),
+            (r#"x "\u{1234567}""#, 4),
+            (r#"x "\u{D800}""#, 4),
+        ] {
+            let (line, col, message) = lex_error(source);
+            assert_eq!((line, col), (1, column), "{}: {}", source, message);
+            assert!(message.contains("escape"), "{}", message);
+        }
+
+        // A trailing backslash leaves the string open
+        let (_, _, message) = lex_error("\"abc\\");
+        assert!(message.contains("unterminated string"));
+    }
+
+    #[test]
+    fn test_numeric_literal_forms() {
+        let number = |source: &str| match token_types(source).as_slice() {
+            [token, TokenType::Eof] => token.clone(),
+            other => panic!("{} lexed as {:?}", source, other),
+        };
+        let decimal = |text: &str| TokenType::Decimal(parse_decimal(text).unwrap());
+
+        assert_eq!(number("1_000_000"), TokenType::Integer(1_000_000));
+        assert_eq!(number("0xFF"), TokenType::Integer(255));
+        assert_eq!(number("0xdead_beef"), TokenType::Integer(0xdead_beef));
+        assert_eq!(number("0o17"), TokenType::Integer(15));
+        assert_eq!(number("0b1010"), TokenType::Integer(10));
+        assert_eq!(number("0b1111_0000"), TokenType::Integer(240));
+        assert_eq!(number("1_000.000_5"), decimal("1000.0005"));
+        assert_eq!(number("1.5e10"), decimal("15000000000"));
+        assert_eq!(number("1.5E-3"), decimal("0.0015"));
+        assert_eq!(number("2e+2"), decimal("200"));
+
+        // A dot not followed by a digit is still field access
+        assert_eq!(
+            token_types("3.x"),
+            vec![
+                TokenType::Integer(3),
+                TokenType::Dot,
+                TokenType::Identifier("x".to_string()),
+                TokenType::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_numeric_literals() {
+        for source in [
+            "0x",
+            "0b",
+            "0xFG",
+            "0b102",
+            "0x_FF",
+            "1__2",
+            "1_",
+            "1_.5",
+            "1.5e",
+            "1e_5",
+            "12abc",
+            "0x1_0000_0000_0000_0000_0000_0000_0000_0000",
+            "1e40",
+        ] {
+            let (line, column, message) = lex_error(&format!("x {}", source));
+            assert_eq!((line, column), (1, 3), "{}: {}", source, message);
+        }
+    }
+
     #[test]
     fn test_keywords() {
         let mut lexer = Lexer::new("defun if let true false");
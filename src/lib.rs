@@ -49,6 +49,7 @@
 //! ```
 
 pub mod account_api;
+pub mod address;
 pub mod api;
 pub mod api_handler;
 pub mod ast;
@@ -88,7 +89,7 @@ pub use lexer::Lexer;
 pub use lsp::LspServer;
 pub use parser::Parser;
 pub use profiler::Profiler;
-pub use runtime::Runtime;
+pub use runtime::{FuelSchedule, Runtime};
 pub use types::{Type, TypeEnv};
 pub use value::Value;
 pub use vm::VirtualMachine;
@@ -104,7 +105,37 @@ pub const LANGUAGE_DESCRIPTION: &str =
     "A Turing-incomplete smart contract language for the SilverBitcoin blockchain";
 
 /// Maximum recursion depth to prevent stack overflow
-pub const MAX_RECURSION_DEPTH: usize = 1024;
+///
+/// Sized so that a call chain hitting the limit, including one that recurses
+/// through a higher-order builtin such as `map`, fits a 2 MiB thread stack
+/// even in debug builds.
+pub const MAX_RECURSION_DEPTH: usize = 256;
+
+/// Maximum nesting of expressions and types accepted by the parser.
+///
+/// Kept well below `MAX_RECURSION_DEPTH` so that parsing, checking and
+/// evaluating the most deeply nested program fits a 2 MiB thread stack even
+/// in debug builds.
+pub const MAX_NESTING_DEPTH: usize = 100;
+
+/// Maximum `else if` arms the parser accepts along one path through a program.
+///
+/// A chain is parsed, checked and evaluated in a loop, so its arms are not
+/// nesting, but the AST still holds one node inside the next per arm.
+pub const MAX_ELSE_IF_ARMS: usize = 256;
+
+/// Maximum nodes in a type once its aliases are resolved, so that aliases
+/// sharing one another cannot expand to an unbounded type
+pub const MAX_TYPE_SIZE: usize = 16_384;
+
+/// Maximum nesting of lists and objects in values built from untrusted input
+pub const MAX_VALUE_DEPTH: usize = 64;
+
+/// Maximum local variable slots in one VM frame
+pub const MAX_LOCALS: usize = 4096;
+
+/// Maximum length in bytes of strings produced by string builtins
+pub const MAX_STRING_LENGTH: usize = 65_536;
 
 /// Maximum execution steps per transaction
 pub const MAX_EXECUTION_STEPS: u64 = 10_000_000;
@@ -130,6 +161,12 @@ pub struct SlvrConfig {
     pub enable_type_checking: bool,
     /// Enable optimization passes
     pub enable_optimization: bool,
+    /// Maximum nesting of decoded lists and objects
+    pub max_value_depth: usize,
+    /// Maximum length in bytes of strings built by `repeat` and similar builtins
+    pub max_string_length: usize,
+    /// Fuel cost of each opcode and builtin
+    pub fuel_schedule: runtime::FuelSchedule,
 }
 
 impl Default for SlvrConfig {
@@ -141,6 +178,9 @@ impl Default for SlvrConfig {
             max_fuel_per_tx: MAX_FUEL_PER_TX,
             enable_type_checking: true,
             enable_optimization: true,
+            max_value_depth: MAX_VALUE_DEPTH,
+            max_string_length: MAX_STRING_LENGTH,
+            fuel_schedule: runtime::FuelSchedule::default(),
         }
     }
 }
@@ -186,6 +226,24 @@ impl SlvrConfig {
         self.enable_optimization = enabled;
         self
     }
+
+    /// Set maximum nesting depth of decoded values
+    pub fn with_max_value_depth(mut self, depth: usize) -> Self {
+        self.max_value_depth = depth;
+        self
+    }
+
+    /// Set maximum length of strings built by string builtins
+    pub fn with_max_string_length(mut self, length: usize) -> Self {
+        self.max_string_length = length;
+        self
+    }
+
+    /// Set the fuel cost of each opcode and builtin
+    pub fn with_fuel_schedule(mut self, schedule: runtime::FuelSchedule) -> Self {
+        self.fuel_schedule = schedule;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -209,6 +267,8 @@ mod tests {
         assert_eq!(config.max_execution_steps, MAX_EXECUTION_STEPS);
         assert!(config.enable_fuel_metering);
         assert!(config.enable_type_checking);
+        assert_eq!(config.max_value_depth, MAX_VALUE_DEPTH);
+        assert_eq!(config.max_string_length, MAX_STRING_LENGTH);
     }
 
     #[test]
@@ -172,6 +172,40 @@ pub struct DocumentSymbol {
     pub children: Option<Vec<DocumentSymbol>>,
 }
 
+/// Range to highlight for an error.
+///
+/// Lexer and parser errors point at a single character (their positions are
+/// 1-based, LSP's are 0-based); anything else falls back to the first line.
+fn error_range(error: &SlvrError) -> Range {
+    match error {
+        SlvrError::LexerError { line, column, .. } | SlvrError::ParseError { line, column, .. }
+            if *line > 0 && *column > 0 =>
+        {
+            let start = Position {
+                line: (*line - 1) as u32,
+                character: (*column - 1) as u32,
+            };
+            Range {
+                start,
+                end: Position {
+                    character: start.character + 1,
+                    ..start
+                },
+            }
+        }
+        _ => Range {
+            start: Position {
+                line: 0,
+                character: 0,
+            },
+            end: Position {
+                line: 0,
+                character: 100,
+            },
+        },
+    }
+}
+
 /// LSP Server state
 pub struct LspServer {
     documents: Arc<Mutex<HashMap<String, String>>>,
@@ -203,9 +237,10 @@ impl LspServer {
     /// Open document
     pub fn open_document(&self, uri: String, text: String) -> SlvrResult<()> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let mut docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
+        let mut docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
         docs.insert(uri.clone(), text.clone());
 
         // Analyze document
@@ -216,9 +251,10 @@ impl LspServer {
     /// Update document
     pub fn update_document(&self, uri: String, text: String) -> SlvrResult<()> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let mut docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
+        let mut docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
         docs.insert(uri.clone(), text.clone());
 
         // Re-analyze document
@@ -229,17 +265,15 @@ impl LspServer {
     /// Close document
     pub fn close_document(&self, uri: &str) -> SlvrResult<()> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let mut docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
+        let mut docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
         docs.remove(uri);
 
-        let mut diags = self
-            .diagnostics
-            .lock()
-            .map_err(|e| SlvrError::RuntimeError {
-                message: format!("Failed to acquire diagnostics lock: {}", e),
-            })?;
+        let mut diags = self.diagnostics.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire diagnostics lock: {}", e))
+        })?;
         diags.remove(uri);
 
         Ok(())
@@ -263,16 +297,7 @@ impl LspServer {
                             }
                             Err(e) => {
                                 diagnostics.push(Diagnostic {
-                                    range: Range {
-                                        start: Position {
-                                            line: 0,
-                                            character: 0,
-                                        },
-                                        end: Position {
-                                            line: 0,
-                                            character: 100,
-                                        },
-                                    },
+                                    range: error_range(&e),
                                     severity: Some(DiagnosticSeverity::Error),
                                     code: Some("parse_error".to_string()),
                                     source: "slvr".to_string(),
@@ -305,16 +330,7 @@ impl LspServer {
             }
             Err(e) => {
                 diagnostics.push(Diagnostic {
-                    range: Range {
-                        start: Position {
-                            line: 0,
-                            character: 0,
-                        },
-                        end: Position {
-                            line: 0,
-                            character: 100,
-                        },
-                    },
+                    range: error_range(&e),
                     severity: Some(DiagnosticSeverity::Error),
                     code: Some("lex_error".to_string()),
                     source: "slvr".to_string(),
@@ -326,12 +342,9 @@ impl LspServer {
 
         // Store diagnostics
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let mut diags = self
-            .diagnostics
-            .lock()
-            .map_err(|e| SlvrError::RuntimeError {
-                message: format!("Failed to acquire diagnostics lock: {}", e),
-            })?;
+        let mut diags = self.diagnostics.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire diagnostics lock: {}", e))
+        })?;
         diags.insert(uri.to_string(), diagnostics.clone());
 
         // Publish diagnostics
@@ -350,12 +363,13 @@ impl LspServer {
         _position: Position,
     ) -> SlvrResult<Vec<CompletionItem>> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
-        let _text = docs.get(uri).ok_or_else(|| SlvrError::RuntimeError {
-            message: "Document not found".to_string(),
-        })?;
+        let docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
+        let _text = docs
+            .get(uri)
+            .ok_or_else(|| SlvrError::runtime("Document not found"))?;
 
         let mut completions = Vec::new();
 
@@ -420,12 +434,13 @@ impl LspServer {
     /// Get hover information
     pub fn get_hover(&self, uri: &str, position: Position) -> SlvrResult<Option<Hover>> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
-        let text = docs.get(uri).ok_or_else(|| SlvrError::RuntimeError {
-            message: "Document not found".to_string(),
-        })?;
+        let docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
+        let text = docs
+            .get(uri)
+            .ok_or_else(|| SlvrError::runtime("Document not found"))?;
 
         // Find word at position
         let lines: Vec<&str> = text.lines().collect();
@@ -486,12 +501,13 @@ impl LspServer {
     /// Get document symbols
     pub fn get_document_symbols(&self, uri: &str) -> SlvrResult<Vec<DocumentSymbol>> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
-        let text = docs.get(uri).ok_or_else(|| SlvrError::RuntimeError {
-            message: "Document not found".to_string(),
-        })?;
+        let docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
+        let text = docs
+            .get(uri)
+            .ok_or_else(|| SlvrError::runtime("Document not found"))?;
 
         let mut symbols = Vec::new();
 
@@ -531,6 +547,8 @@ impl LspServer {
             crate::ast::Definition::Constant { name, .. } => (name.clone(), "constant"),
             crate::ast::Definition::Schema { name, .. } => (name.clone(), "class"),
             crate::ast::Definition::Table { name, .. } => (name.clone(), "variable"),
+            crate::ast::Definition::TypeAlias { name, .. } => (name.clone(), "type"),
+            crate::ast::Definition::Capability { name, .. } => (name.clone(), "capability"),
             crate::ast::Definition::Module { name, .. } => (name.clone(), "module"),
         };
 
@@ -544,6 +562,8 @@ impl LspServer {
                 "class" => SymbolKind::Class,
                 "variable" => SymbolKind::Variable,
                 "module" => SymbolKind::Module,
+                "type" => SymbolKind::TypeParameter,
+                "capability" => SymbolKind::Key,
                 _ => SymbolKind::Variable,
             },
             deprecated: None,
@@ -568,12 +588,13 @@ impl LspServer {
     /// Get definition location
     pub fn get_definition(&self, uri: &str, _position: Position) -> SlvrResult<Option<Location>> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
-        let _text = docs.get(uri).ok_or_else(|| SlvrError::RuntimeError {
-            message: "Document not found".to_string(),
-        })?;
+        let docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
+        let _text = docs
+            .get(uri)
+            .ok_or_else(|| SlvrError::runtime("Document not found"))?;
 
         // Find definition in current or other documents
         // This would require symbol table lookup
@@ -583,12 +604,13 @@ impl LspServer {
     /// Get references
     pub fn get_references(&self, uri: &str, _position: Position) -> SlvrResult<Vec<Location>> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
-        let _text = docs.get(uri).ok_or_else(|| SlvrError::RuntimeError {
-            message: "Document not found".to_string(),
-        })?;
+        let docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
+        let _text = docs
+            .get(uri)
+            .ok_or_else(|| SlvrError::runtime("Document not found"))?;
 
         // Find all references to symbol at position
         Ok(Vec::new())
@@ -597,12 +619,13 @@ impl LspServer {
     /// Format document
     pub fn format_document(&self, uri: &str) -> SlvrResult<Vec<TextEdit>> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
-        let text = docs.get(uri).ok_or_else(|| SlvrError::RuntimeError {
-            message: "Document not found".to_string(),
-        })?;
+        let docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
+        let text = docs
+            .get(uri)
+            .ok_or_else(|| SlvrError::runtime("Document not found"))?;
 
         let mut edits = Vec::new();
 
@@ -669,15 +692,16 @@ impl LspServer {
     /// Check types in document
     pub fn check_types(&self, uri: &str) -> SlvrResult<Vec<Diagnostic>> {
         // PRODUCTION IMPLEMENTATION: Proper error handling instead of unwrap()
-        let docs = self.documents.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire documents lock: {}", e),
-        })?;
-        let _text = docs.get(uri).ok_or_else(|| SlvrError::RuntimeError {
-            message: "Document not found".to_string(),
-        })?;
+        let docs = self
+            .documents
+            .lock()
+            .map_err(|e| SlvrError::runtime(format!("Failed to acquire documents lock: {}", e)))?;
+        let _text = docs
+            .get(uri)
+            .ok_or_else(|| SlvrError::runtime("Document not found"))?;
 
-        let _type_env = self.type_env.lock().map_err(|e| SlvrError::RuntimeError {
-            message: format!("Failed to acquire type environment lock: {}", e),
+        let _type_env = self.type_env.lock().map_err(|e| {
+            SlvrError::runtime(format!("Failed to acquire type environment lock: {}", e))
         })?;
         let diagnostics = Vec::new();
 
@@ -719,6 +743,31 @@ mod tests {
         assert_eq!(range.end.line, 1);
     }
 
+    #[test]
+    fn test_lex_error_diagnostic_has_position() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let server = LspServer::new(tx);
+        server
+            .open_document(
+                "file:///a.slvr".to_string(),
+                "defun f ()\n  \"abc".to_string(),
+            )
+            .unwrap();
+
+        let Ok(LspNotification::PublishDiagnostics { diagnostics, .. }) = rx.try_recv() else {
+            panic!("expected diagnostics to be published");
+        };
+        assert_eq!(diagnostics.len(), 1);
+        let range = diagnostics[0].range;
+        assert_eq!(
+            (range.start.line, range.start.character),
+            (1, 2),
+            "{:?}",
+            diagnostics[0]
+        );
+        assert_eq!(range.end.character, 3);
+    }
+
     #[test]
     fn test_diagnostic() {
         let diag = Diagnostic {
@@ -173,8 +173,8 @@ impl ModuleRegistry {
         self.modules
             .get(fully_qualified_name)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Module not found: {}", fully_qualified_name),
+            .ok_or_else(|| {
+                SlvrError::runtime(format!("Module not found: {}", fully_qualified_name))
             })
     }
 
@@ -298,9 +298,10 @@ impl ModuleRegistry {
 
         for dep in dependencies {
             if self.has_circular_dep(&dep.module_name, &mut visited, &mut rec_stack)? {
-                return Err(SlvrError::RuntimeError {
-                    message: format!("Circular dependency detected involving {}", module_name),
-                });
+                return Err(SlvrError::runtime(format!(
+                    "Circular dependency detected involving {}",
+                    module_name
+                )));
             }
         }
 
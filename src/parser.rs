@@ -4,12 +4,22 @@
 
 use crate::ast::*;
 use crate::error::{SlvrError, SlvrResult};
-use crate::lexer::{Lexer, Token, TokenType};
+use crate::lexer::{Lexer, Span, Token, TokenType};
+use crate::{MAX_ELSE_IF_ARMS, MAX_NESTING_DEPTH};
+
+/// The start of an `if` arm, its condition and its then branch
+type IfArm = (Span, Expr, Expr);
 
 /// Parser for Slvr language
 pub struct Parser {
     tokens: Vec<Token>,
     position: usize,
+    /// Number of expressions and types currently being parsed inside each other
+    depth: usize,
+    /// Deepest nesting accepted before parsing fails
+    max_depth: usize,
+    /// Number of `else if` arms enclosing the expression being parsed
+    else_ifs: usize,
 }
 
 impl Parser {
@@ -20,9 +30,18 @@ impl Parser {
         Ok(Self {
             tokens,
             position: 0,
+            depth: 0,
+            max_depth: MAX_NESTING_DEPTH,
+            else_ifs: 0,
         })
     }
 
+    /// Set the deepest expression or type nesting the parser accepts
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
     /// Parse a complete program
     pub fn parse(&mut self) -> SlvrResult<Program> {
         let mut definitions = Vec::new();
@@ -39,6 +58,8 @@ impl Parser {
             TokenType::Defschema => self.parse_schema(),
             TokenType::Deftable => self.parse_table(),
             TokenType::Defconst => self.parse_constant(),
+            TokenType::Deftype => self.parse_type_alias(),
+            TokenType::Defcap => self.parse_capability(),
             _ => Err(SlvrError::parse(
                 self.current_token().line,
                 self.current_token().column,
@@ -127,114 +148,215 @@ impl Parser {
         Ok(Definition::Constant { name, ty, value })
     }
 
+    /// `deftype name = type "doc"`
+    fn parse_type_alias(&mut self) -> SlvrResult<Definition> {
+        self.consume(TokenType::Deftype)?;
+        let name = self.parse_identifier()?;
+        self.consume(TokenType::Equal)?;
+        let ty = self.parse_type()?;
+        let doc = self.parse_optional_string();
+
+        Ok(Definition::TypeAlias { name, ty, doc })
+    }
+
+    /// `defcap NAME "doc" (params) manages(param, manager) predicate`, where
+    /// the `manages` clause and its manager are optional
+    fn parse_capability(&mut self) -> SlvrResult<Definition> {
+        self.consume(TokenType::Defcap)?;
+        let name = self.parse_identifier()?;
+        let doc = self.parse_optional_string();
+
+        self.consume(TokenType::LeftParen)?;
+        let params = self.parse_parameters()?;
+        self.consume(TokenType::RightParen)?;
+
+        let managed = self.parse_managed_param(&params)?;
+        let body = self.parse_expression()?;
+
+        Ok(Definition::Capability {
+            name,
+            params,
+            doc,
+            managed,
+            body,
+        })
+    }
+
+    fn parse_managed_param(
+        &mut self,
+        params: &[(String, Type)],
+    ) -> SlvrResult<Option<ManagedParam>> {
+        let token = self.current_token();
+        if !matches!(&token.token_type, TokenType::Identifier(word) if word == "manages") {
+            return Ok(None);
+        }
+        self.advance();
+        self.consume(TokenType::LeftParen)?;
+        let param = self.parse_identifier()?;
+        let manager = if self.check(&TokenType::Comma) {
+            self.advance();
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::RightParen)?;
+
+        if !params.iter().any(|(name, _)| *name == param) {
+            return Err(SlvrError::parse(
+                token.line,
+                token.column,
+                format!(
+                    "managed parameter '{}' is not a capability parameter",
+                    param
+                ),
+            ));
+        }
+        Ok(Some(ManagedParam { param, manager }))
+    }
+
     fn parse_expression(&mut self) -> SlvrResult<Expr> {
-        self.parse_or_expression()
+        self.nested(Self::parse_or_expression)
+    }
+
+    /// Run `parse` one nesting level deeper, failing instead of recursing
+    /// past the limit so hostile input cannot exhaust the native stack
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> SlvrResult<T>) -> SlvrResult<T> {
+        if self.depth >= self.max_depth {
+            return Err(SlvrError::RecursionDepthExceeded {
+                depth: self.max_depth,
+            });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
     }
 
     fn parse_or_expression(&mut self) -> SlvrResult<Expr> {
+        let start = self.current_token().span;
         let mut left = self.parse_and_expression()?;
         while self.check(&TokenType::Or) {
             self.advance();
             let right = self.parse_and_expression()?;
-            left = Expr::BinOp {
-                op: BinOp::Or,
-                left: Box::new(left),
-                right: Box::new(right),
-            };
+            left = self.spanned(
+                start,
+                Expr::BinOp {
+                    op: BinOp::Or,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
         }
         Ok(left)
     }
 
     fn parse_and_expression(&mut self) -> SlvrResult<Expr> {
+        let start = self.current_token().span;
         let mut left = self.parse_comparison_expression()?;
         while self.check(&TokenType::And) {
             self.advance();
             let right = self.parse_comparison_expression()?;
-            left = Expr::BinOp {
-                op: BinOp::And,
-                left: Box::new(left),
-                right: Box::new(right),
-            };
+            left = self.spanned(
+                start,
+                Expr::BinOp {
+                    op: BinOp::And,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
         }
         Ok(left)
     }
 
     fn parse_comparison_expression(&mut self) -> SlvrResult<Expr> {
+        let start = self.current_token().span;
         let mut left = self.parse_additive_expression()?;
         while let Some(op) = self.match_comparison_op() {
             let right = self.parse_additive_expression()?;
-            left = Expr::BinOp {
-                op,
-                left: Box::new(left),
-                right: Box::new(right),
-            };
+            left = self.spanned(
+                start,
+                Expr::BinOp {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
         }
         Ok(left)
     }
 
     fn parse_additive_expression(&mut self) -> SlvrResult<Expr> {
+        let start = self.current_token().span;
         let mut left = self.parse_multiplicative_expression()?;
         while let Some(op) = self.match_additive_op() {
             let right = self.parse_multiplicative_expression()?;
-            left = Expr::BinOp {
-                op,
-                left: Box::new(left),
-                right: Box::new(right),
-            };
+            left = self.spanned(
+                start,
+                Expr::BinOp {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
         }
         Ok(left)
     }
 
     fn parse_multiplicative_expression(&mut self) -> SlvrResult<Expr> {
+        let start = self.current_token().span;
         let mut left = self.parse_power_expression()?;
         while let Some(op) = self.match_multiplicative_op() {
             let right = self.parse_power_expression()?;
-            left = Expr::BinOp {
-                op,
-                left: Box::new(left),
-                right: Box::new(right),
-            };
+            left = self.spanned(
+                start,
+                Expr::BinOp {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
         }
         Ok(left)
     }
 
     fn parse_power_expression(&mut self) -> SlvrResult<Expr> {
+        let start = self.current_token().span;
         let mut left = self.parse_unary_expression()?;
         while self.check(&TokenType::Caret) {
             self.advance();
             let right = self.parse_unary_expression()?;
-            left = Expr::BinOp {
-                op: BinOp::Power,
-                left: Box::new(left),
-                right: Box::new(right),
-            };
+            left = self.spanned(
+                start,
+                Expr::BinOp {
+                    op: BinOp::Power,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
         }
         Ok(left)
     }
 
     fn parse_unary_expression(&mut self) -> SlvrResult<Expr> {
-        match &self.current_token().token_type {
-            TokenType::Not => {
-                self.advance();
-                let operand = self.parse_unary_expression()?;
-                Ok(Expr::UnaryOp {
-                    op: UnaryOp::Not,
-                    operand: Box::new(operand),
-                })
-            }
-            TokenType::Minus => {
-                self.advance();
-                let operand = self.parse_unary_expression()?;
-                Ok(Expr::UnaryOp {
-                    op: UnaryOp::Negate,
-                    operand: Box::new(operand),
-                })
-            }
-            _ => self.parse_postfix_expression(),
-        }
+        let start = self.current_token().span;
+        let op = match &self.current_token().token_type {
+            TokenType::Not => UnaryOp::Not,
+            TokenType::Minus => UnaryOp::Negate,
+            _ => return self.parse_postfix_expression(),
+        };
+        self.advance();
+        let operand = self.nested(Self::parse_unary_expression)?;
+        Ok(self.spanned(
+            start,
+            Expr::UnaryOp {
+                op,
+                operand: Box::new(operand),
+            },
+        ))
     }
 
     fn parse_postfix_expression(&mut self) -> SlvrResult<Expr> {
+        let start = self.current_token().span;
         let mut expr = self.parse_primary_expression()?;
         loop {
             match &self.current_token().token_type {
@@ -242,27 +364,36 @@ impl Parser {
                     self.advance();
                     let args = self.parse_arguments()?;
                     self.consume(TokenType::RightParen)?;
-                    expr = Expr::Call {
-                        function: Box::new(expr),
-                        args,
-                    };
+                    expr = self.spanned(
+                        start,
+                        Expr::Call {
+                            function: Box::new(expr),
+                            args,
+                        },
+                    );
                 }
                 TokenType::Dot => {
                     self.advance();
                     let field = self.parse_identifier()?;
-                    expr = Expr::FieldAccess {
-                        object: Box::new(expr),
-                        field,
-                    };
+                    expr = self.spanned(
+                        start,
+                        Expr::FieldAccess {
+                            object: Box::new(expr),
+                            field,
+                        },
+                    );
                 }
                 TokenType::LeftBracket => {
                     self.advance();
                     let index = self.parse_expression()?;
                     self.consume(TokenType::RightBracket)?;
-                    expr = Expr::Index {
-                        object: Box::new(expr),
-                        index: Box::new(index),
-                    };
+                    expr = self.spanned(
+                        start,
+                        Expr::Index {
+                            object: Box::new(expr),
+                            index: Box::new(index),
+                        },
+                    );
                 }
                 _ => break,
             }
@@ -271,7 +402,8 @@ impl Parser {
     }
 
     fn parse_primary_expression(&mut self) -> SlvrResult<Expr> {
-        match &self.current_token().token_type {
+        let start = self.current_token().span;
+        let expr = match &self.current_token().token_type {
             TokenType::Integer(n) => {
                 let n = *n;
                 self.advance();
@@ -305,23 +437,28 @@ impl Parser {
                 Ok(Expr::Variable(name))
             }
             TokenType::LeftParen => {
+                // The inner expression already carries its own span
                 self.advance();
                 let expr = self.parse_expression()?;
                 self.consume(TokenType::RightParen)?;
-                Ok(expr)
+                return Ok(expr);
             }
             TokenType::LeftBracket => self.parse_list(),
             TokenType::LeftBrace => self.parse_object(),
             TokenType::If => self.parse_if(),
             TokenType::Let => self.parse_let(),
+            TokenType::Lambda => self.parse_lambda(),
+            TokenType::Read | TokenType::ReadSafe => self.parse_read(),
             _ => Err(SlvrError::parse(
                 self.current_token().line,
                 self.current_token().column,
                 "unexpected token in expression",
             )),
-        }
+        }?;
+        Ok(self.spanned(start, expr))
     }
 
+    /// `[a, b, c]`; commas are optional and a trailing one is allowed
     fn parse_list(&mut self) -> SlvrResult<Expr> {
         self.consume(TokenType::LeftBracket)?;
         let mut elements = Vec::new();
@@ -335,11 +472,26 @@ impl Parser {
         Ok(Expr::List(elements))
     }
 
+    /// `{ k: v, "other key": w }`; keys are identifiers or strings
     fn parse_object(&mut self) -> SlvrResult<Expr> {
         self.consume(TokenType::LeftBrace)?;
-        let mut fields = Vec::new();
+        let mut fields: Vec<(String, Expr)> = Vec::new();
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
-            let key = self.parse_identifier()?;
+            let key_token = self.current_token();
+            let key = match &key_token.token_type {
+                TokenType::String(key) => {
+                    self.advance();
+                    key.clone()
+                }
+                _ => self.parse_identifier()?,
+            };
+            if fields.iter().any(|(existing, _)| *existing == key) {
+                return Err(SlvrError::parse(
+                    key_token.line,
+                    key_token.column,
+                    format!("duplicate key '{}' in object", key),
+                ));
+            }
             self.consume(TokenType::Colon)?;
             let value = self.parse_expression()?;
             fields.push((key, value));
@@ -351,36 +503,191 @@ impl Parser {
         Ok(Expr::Object(fields))
     }
 
+    /// `if c a else if d b else e`; the arms of an `else if` chain are read
+    /// in a loop, so only the expressions inside an arm count as nesting
     fn parse_if(&mut self) -> SlvrResult<Expr> {
-        self.consume(TokenType::If)?;
-        let condition = self.parse_expression()?;
-        let then_branch = self.parse_expression()?;
-        let else_branch = if self.check(&TokenType::Identifier("else".to_string())) {
+        let mut chained = 0;
+        let result = self.parse_if_arms(&mut chained);
+        self.else_ifs -= chained;
+        let (arms, mut else_branch) = result?;
+
+        // Every arm but the first gets the span `parse_primary_expression`
+        // would have given it, from its `if` to the end of the chain
+        let mut arms = arms.into_iter().rev().peekable();
+        loop {
+            let (start, condition, then_branch) = arms.next().expect("an if has an arm");
+            let expr = Expr::If {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch,
+            };
+            if arms.peek().is_none() {
+                return Ok(expr);
+            }
+            else_branch = Some(Box::new(self.spanned(start, expr)));
+        }
+    }
+
+    /// The `(start, condition, then)` arms of an `if` chain and its final
+    /// `else`, counting each `else if` into `chained`
+    fn parse_if_arms(
+        &mut self,
+        chained: &mut usize,
+    ) -> SlvrResult<(Vec<IfArm>, Option<Box<Expr>>)> {
+        let mut arms = Vec::new();
+        loop {
+            let start = self.current_token().span;
+            self.consume(TokenType::If)?;
+            let condition = self.parse_expression()?;
+            let then_branch = self.parse_expression()?;
+            arms.push((start, condition, then_branch));
+            if !self.check(&TokenType::Identifier("else".to_string())) {
+                return Ok((arms, None));
+            }
             self.advance();
-            Some(Box::new(self.parse_expression()?))
+            if !self.check(&TokenType::If) {
+                let else_branch = self.parse_expression()?;
+                return Ok((arms, Some(Box::new(else_branch))));
+            }
+            if self.else_ifs >= MAX_ELSE_IF_ARMS {
+                let token = self.current_token();
+                return Err(SlvrError::parse(
+                    token.line,
+                    token.column,
+                    format!("more than {} else if arms", MAX_ELSE_IF_ARMS),
+                ));
+            }
+            self.else_ifs += 1;
+            *chained += 1;
+        }
+    }
+
+    /// `let x = v body`, `let (x = v, y = w) body` or `let* (x = v, y = x) body`
+    fn parse_let(&mut self) -> SlvrResult<Expr> {
+        let (mut bindings, mut sequential) = self.parse_let_bindings()?;
+        // `let x = v let y = x body` binds in order exactly like
+        // `let* (x = v, y = x) body`, so a chain is folded into one scope
+        // instead of counting as nesting
+        while (sequential || bindings.len() == 1) && self.next_let_folds() {
+            bindings.extend(self.parse_let_bindings()?.0);
+            sequential = true;
+        }
+
+        let body = self.parse_expression()?;
+        Ok(Expr::Let {
+            bindings,
+            sequential,
+            body: Box::new(body),
+        })
+    }
+
+    /// Whether the upcoming token starts a `let*` or a single-binding `let`
+    fn next_let_folds(&self) -> bool {
+        self.check(&TokenType::Let)
+            && matches!(
+                self.tokens.get(self.position + 1).map(|t| &t.token_type),
+                Some(TokenType::Star | TokenType::Identifier(_))
+            )
+    }
+
+    /// The `let` keyword and its bindings, and whether they are sequential
+    fn parse_let_bindings(&mut self) -> SlvrResult<(Vec<(String, Expr)>, bool)> {
+        let let_token = self.current_token();
+        self.consume(TokenType::Let)?;
+        let sequential = self.check(&TokenType::Star);
+        if sequential {
+            self.advance();
+        }
+
+        let bindings = if self.check(&TokenType::LeftParen) {
+            self.advance();
+            let mut bindings = Vec::new();
+            while !self.check(&TokenType::RightParen) && !self.is_at_end() {
+                bindings.push(self.parse_binding()?);
+                if !self.check(&TokenType::RightParen) {
+                    self.consume(TokenType::Comma)?;
+                }
+            }
+            self.consume(TokenType::RightParen)?;
+            if bindings.is_empty() {
+                return Err(SlvrError::parse(
+                    let_token.line,
+                    let_token.column,
+                    "let needs at least one binding",
+                ));
+            }
+            bindings
         } else {
-            None
+            vec![self.parse_binding()?]
         };
-        Ok(Expr::If {
-            condition: Box::new(condition),
-            then_branch: Box::new(then_branch),
-            else_branch,
+
+        if !sequential {
+            let mut seen = std::collections::HashSet::new();
+            if let Some((name, _)) = bindings.iter().find(|(name, _)| !seen.insert(name)) {
+                return Err(SlvrError::parse(
+                    let_token.line,
+                    let_token.column,
+                    format!("duplicate binding '{}' in let", name),
+                ));
+            }
+        }
+        Ok((bindings, sequential))
+    }
+
+    /// `read(table, key)` or `read-safe(table, key)`
+    fn parse_read(&mut self) -> SlvrResult<Expr> {
+        let safe = self.check(&TokenType::ReadSafe);
+        self.advance();
+        self.consume(TokenType::LeftParen)?;
+        let table = self.parse_identifier()?;
+        self.consume(TokenType::Comma)?;
+        let key = Box::new(self.parse_expression()?);
+        self.consume(TokenType::RightParen)?;
+        Ok(if safe {
+            Expr::ReadSafe { table, key }
+        } else {
+            Expr::Read { table, key }
         })
     }
 
-    fn parse_let(&mut self) -> SlvrResult<Expr> {
-        self.consume(TokenType::Let)?;
-        let name = self.parse_identifier()?;
-        self.consume(TokenType::Equal)?;
-        let value = self.parse_expression()?;
+    /// `lambda (a, b) body`
+    fn parse_lambda(&mut self) -> SlvrResult<Expr> {
+        let lambda_token = self.current_token();
+        self.consume(TokenType::Lambda)?;
+        self.consume(TokenType::LeftParen)?;
+        let mut params = Vec::new();
+        while !self.check(&TokenType::RightParen) && !self.is_at_end() {
+            params.push(self.parse_identifier()?);
+            if !self.check(&TokenType::RightParen) {
+                self.consume(TokenType::Comma)?;
+            }
+        }
+        self.consume(TokenType::RightParen)?;
+
+        let mut seen = std::collections::HashSet::new();
+        if let Some(name) = params.iter().find(|name| !seen.insert(*name)) {
+            return Err(SlvrError::parse(
+                lambda_token.line,
+                lambda_token.column,
+                format!("duplicate parameter '{}' in lambda", name),
+            ));
+        }
+
         let body = self.parse_expression()?;
-        Ok(Expr::Let {
-            name,
-            value: Box::new(value),
+        Ok(Expr::Lambda {
+            params,
             body: Box::new(body),
         })
     }
 
+    /// `name = value` inside a let
+    fn parse_binding(&mut self) -> SlvrResult<(String, Expr)> {
+        let name = self.parse_identifier()?;
+        self.consume(TokenType::Equal)?;
+        let value = self.parse_expression()?;
+        Ok((name, value))
+    }
+
     fn parse_parameters(&mut self) -> SlvrResult<Vec<(String, Type)>> {
         let mut params = Vec::new();
         while !self.check(&TokenType::RightParen) && !self.is_at_end() {
@@ -406,7 +713,21 @@ impl Parser {
         Ok(args)
     }
 
+    /// A type, or a union of types separated by `|`
     fn parse_type(&mut self) -> SlvrResult<Type> {
+        let first = self.parse_single_type()?;
+        if !self.check(&TokenType::Pipe) {
+            return Ok(first);
+        }
+        let mut members = vec![first];
+        while self.check(&TokenType::Pipe) {
+            self.advance();
+            members.push(self.parse_single_type()?);
+        }
+        Ok(Type::Union(members))
+    }
+
+    fn parse_single_type(&mut self) -> SlvrResult<Type> {
         match &self.current_token().token_type {
             TokenType::Identifier(name) => {
                 let name = name.clone();
@@ -423,7 +744,7 @@ impl Parser {
             }
             TokenType::LeftBracket => {
                 self.advance();
-                let inner = self.parse_type()?;
+                let inner = self.nested(Self::parse_type)?;
                 self.consume(TokenType::RightBracket)?;
                 Ok(Type::List(Box::new(inner)))
             }
@@ -526,11 +847,27 @@ impl Parser {
         }
     }
 
+    /// Wrap `expr` in the span from `start` to the end of the last consumed token
+    fn spanned(&self, start: Span, expr: Expr) -> Expr {
+        let end = match self
+            .position
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+        {
+            Some(token) => token.span.end,
+            None => start.end,
+        };
+        Expr::Spanned {
+            span: Span { end, ..start },
+            expr: Box::new(expr),
+        }
+    }
+
     fn current_token(&self) -> Token {
         self.tokens
             .get(self.position)
             .cloned()
-            .unwrap_or_else(|| Token::new(TokenType::Eof, 0, 0))
+            .unwrap_or_else(|| Token::new(TokenType::Eof, Span::default()))
     }
 
     fn check(&self, token_type: &TokenType) -> bool {
@@ -567,6 +904,313 @@ impl Parser {
 mod tests {
     use super::*;
 
+    fn body_of(source: &str) -> Expr {
+        let program = Parser::new(source).unwrap().parse().unwrap();
+        match program.definitions.into_iter().next() {
+            Some(Definition::Function { body, .. }) => body,
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_expressions_carry_spans() {
+        let source = "defun f () -> integer g(1, x.y) + 2";
+        let body = body_of(source);
+        let text = |expr: &Expr| {
+            let span = expr.span().expect("parsed nodes are spanned");
+            &source[span.start..span.end]
+        };
+        assert_eq!(text(&body), "g(1, x.y) + 2");
+
+        let Expr::BinOp { left, right, .. } = body.unspanned() else {
+            panic!("expected a binary operation, got {:?}", body);
+        };
+        assert_eq!(text(left), "g(1, x.y)");
+        assert_eq!(text(right), "2");
+
+        let Expr::Call { function, args } = left.unspanned() else {
+            panic!("expected a call, got {:?}", left);
+        };
+        assert_eq!(text(function), "g");
+        assert_eq!(text(&args[1]), "x.y");
+        assert_eq!(args[1].span().unwrap().column, 28);
+    }
+
+    #[test]
+    fn test_parse_let_forms() {
+        let names = |source: &str| match body_of(source).unspanned() {
+            Expr::Let {
+                bindings,
+                sequential,
+                ..
+            } => (
+                bindings
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>(),
+                *sequential,
+            ),
+            other => panic!("expected a let, got {:?}", other),
+        };
+        assert_eq!(
+            names("defun f () -> integer let x = 1 x"),
+            (vec!["x".to_string()], false)
+        );
+        assert_eq!(
+            names("defun f () -> integer let (x = 1, y = 2,) x + y"),
+            (vec!["x".to_string(), "y".to_string()], false)
+        );
+        assert_eq!(
+            names("defun f () -> integer let* (x = 1, x = x + 1) x"),
+            (vec!["x".to_string(), "x".to_string()], true)
+        );
+
+        for source in [
+            "defun f () -> integer let () 1",
+            "defun f () -> integer let (x = 1, x = 2) x",
+            "defun f () -> integer let (x 1) x",
+        ] {
+            let result = Parser::new(source).unwrap().parse();
+            assert!(
+                matches!(result, Err(SlvrError::ParseError { .. })),
+                "{} should not parse",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_literals() {
+        let body =
+            body_of("defun f () -> [integer] [[1, 2,], [], [3 4], {\"a key\": [5], n: {},},]");
+        let Expr::List(items) = body.unspanned() else {
+            panic!("expected a list, got {:?}", body);
+        };
+        assert_eq!(items.len(), 4);
+        let lengths: Vec<usize> = items[..3]
+            .iter()
+            .map(|item| match item.unspanned() {
+                Expr::List(elements) => elements.len(),
+                other => panic!("expected a list, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(lengths, vec![2, 0, 2]);
+
+        let Expr::Object(fields) = items[3].unspanned() else {
+            panic!("expected an object, got {:?}", items[3]);
+        };
+        let keys: Vec<&str> = fields.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["a key", "n"]);
+        assert!(matches!(fields[1].1.unspanned(), Expr::Object(inner) if inner.is_empty()));
+
+        for source in [
+            "defun f () -> object {a: 1, \"a\": 2}",
+            "defun f () -> [integer] [1, 2",
+            "defun f () -> object {1: 2}",
+        ] {
+            let result = Parser::new(source).unwrap().parse();
+            assert!(
+                matches!(result, Err(SlvrError::ParseError { .. })),
+                "{} should not parse",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_union_type() {
+        let program = Parser::new("defconst xs: [integer | string | [boolean]] = []")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let Some(Definition::Constant { ty, .. }) = program.definitions.first() else {
+            panic!("expected a constant, got {:?}", program.definitions);
+        };
+        assert_eq!(ty.to_string(), "[integer | string | [boolean]]");
+    }
+
+    #[test]
+    fn test_parse_type_alias() {
+        let program = Parser::new("deftype ledger = [amount] \"Balance history\"")
+            .unwrap()
+            .parse()
+            .unwrap();
+        match program.definitions.first() {
+            Some(Definition::TypeAlias { name, ty, doc }) => {
+                assert_eq!(name, "ledger");
+                assert_eq!(
+                    ty,
+                    &Type::List(Box::new(Type::Custom("amount".to_string())))
+                );
+                assert_eq!(doc.as_deref(), Some("Balance history"));
+            }
+            other => panic!("expected a type alias, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_capability() {
+        let source = "defcap TRANSFER \"Move funds\" (sender: string, amount: integer) \
+                      enforce(amount > 0, \"positive\")";
+        let program = Parser::new(source).unwrap().parse().unwrap();
+        match program.definitions.first() {
+            Some(Definition::Capability {
+                name,
+                params,
+                doc,
+                managed,
+                body,
+            }) => {
+                assert_eq!(managed, &None);
+                assert_eq!(name, "TRANSFER");
+                assert_eq!(
+                    params,
+                    &vec![
+                        ("sender".to_string(), Type::String),
+                        ("amount".to_string(), Type::Integer)
+                    ]
+                );
+                assert_eq!(doc.as_deref(), Some("Move funds"));
+                assert!(matches!(body.unspanned(), Expr::Call { .. }));
+            }
+            other => panic!("expected a capability, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_managed_capability() {
+        let source = "defcap TRANSFER (sender: string, amount: integer) \
+                      manages(amount, transfer-mgr) sender != \"\"";
+        let program = Parser::new(source).unwrap().parse().unwrap();
+        match program.definitions.first() {
+            Some(Definition::Capability { managed, .. }) => assert_eq!(
+                managed,
+                &Some(ManagedParam {
+                    param: "amount".to_string(),
+                    manager: Some("transfer-mgr".to_string()),
+                })
+            ),
+            other => panic!("expected a capability, got {:?}", other),
+        }
+
+        let unknown = "defcap TRANSFER (amount: integer) manages(total) true";
+        assert!(matches!(
+            Parser::new(unknown).unwrap().parse(),
+            Err(SlvrError::ParseError { message, .. }) if message.contains("'total'")
+        ));
+    }
+
+    #[test]
+    fn test_parse_lambda() {
+        match body_of("defun f () -> integer lambda (a, b) a + b").unspanned() {
+            Expr::Lambda { params, body } => {
+                assert_eq!(params, &vec!["a".to_string(), "b".to_string()]);
+                assert!(matches!(body.unspanned(), Expr::BinOp { .. }));
+            }
+            other => panic!("expected a lambda, got {:?}", other),
+        }
+        assert!(matches!(
+            body_of("defun f () -> integer lambda () 1").unspanned(),
+            Expr::Lambda { params, .. } if params.is_empty()
+        ));
+
+        let duplicate = Parser::new("defun f () -> integer lambda (a, a) a")
+            .unwrap()
+            .parse();
+        assert!(matches!(duplicate, Err(SlvrError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_pathological_nesting_is_an_error() {
+        let parse = |source: String| Parser::new(&source).unwrap().parse();
+
+        let depth = 100_000;
+        let parens = format!(
+            "defconst c: integer = {}1{}",
+            "(".repeat(depth),
+            ")".repeat(depth)
+        );
+        let negations = format!("defconst c: integer = {}1", "- ".repeat(depth));
+        let lists = format!(
+            "defconst c: {}integer{} = []",
+            "[".repeat(depth),
+            "]".repeat(depth)
+        );
+        for source in [parens, negations, lists] {
+            assert!(matches!(
+                parse(source),
+                Err(SlvrError::RecursionDepthExceeded {
+                    depth: MAX_NESTING_DEPTH
+                })
+            ));
+        }
+
+        // Nesting up to the limit still parses and evaluates
+        let depth = MAX_NESTING_DEPTH - 1;
+        let source = format!(
+            "defconst c: integer = {}1{}",
+            "(".repeat(depth - 1),
+            ")".repeat(depth - 1)
+        );
+        let program = parse(source).unwrap();
+        assert!(crate::Compiler::new().compile(&program).is_ok());
+        assert!(crate::Evaluator::new().eval_program(&program).is_ok());
+    }
+
+    #[test]
+    fn test_let_chains_and_else_if_chains_are_not_nesting() {
+        let lets = 1000;
+        let arms = MAX_ELSE_IF_ARMS + 1;
+        let let_chain: String = (1..lets)
+            .map(|i| format!("let x{} = x{} + 1 ", i, i - 1))
+            .collect();
+        let if_chain = |arms: usize| -> String {
+            (0..arms)
+                .map(|i| format!("if n == {} {} else ", i, i))
+                .collect()
+        };
+        let source = format!(
+            "defun count () -> integer let x0 = 0 {}x{} \
+             defun pick (n: integer) -> integer {}-1",
+            let_chain,
+            lets - 1,
+            if_chain(arms)
+        );
+        let program = Parser::new(&source).unwrap().parse().unwrap();
+        assert!(crate::Compiler::new().compile(&program).is_ok());
+
+        let mut evaluator = crate::Evaluator::new();
+        evaluator.eval_program(&program).unwrap();
+        assert_eq!(
+            evaluator.call("count", vec![]).unwrap(),
+            crate::Value::Integer(lets as i128 - 1)
+        );
+        for (n, picked) in [(arms as i128 - 1, arms as i128 - 1), (-5, -1)] {
+            assert_eq!(
+                evaluator
+                    .call("pick", vec![crate::Value::Integer(n)])
+                    .unwrap(),
+                crate::Value::Integer(picked)
+            );
+        }
+
+        // The AST still nests per arm, so chains are capped along any path
+        let too_long = format!(
+            "defun pick (n: integer) -> integer {}-1",
+            if_chain(arms + 1)
+        );
+        assert!(matches!(
+            Parser::new(&too_long).unwrap().parse(),
+            Err(SlvrError::ParseError { .. })
+        ));
+        let nested = format!(
+            "defun pick (n: integer) -> integer {}({}-1)",
+            if_chain(arms),
+            if_chain(2)
+        );
+        assert!(Parser::new(&nested).unwrap().parse().is_err());
+    }
+
     #[test]
     fn test_parse_simple_expression() {
         // Parser requires valid Slvr syntax
@@ -190,18 +190,16 @@ impl Profiler {
             timestamp: Instant::now(),
         };
 
-        let mut events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let mut events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
         events.push(event);
 
         let mut stack = self.current_function_stack.lock().map_err(|e| {
-            crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire function stack lock: {}", e),
-            }
+            crate::error::SlvrError::runtime(format!(
+                "Failed to acquire function stack lock: {}",
+                e
+            ))
         })?;
         stack.push(name);
 
@@ -215,18 +213,16 @@ impl Profiler {
             timestamp: Instant::now(),
         };
 
-        let mut events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let mut events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
         events.push(event);
 
         let mut stack = self.current_function_stack.lock().map_err(|e| {
-            crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire function stack lock: {}", e),
-            }
+            crate::error::SlvrError::runtime(format!(
+                "Failed to acquire function stack lock: {}",
+                e
+            ))
         })?;
         if stack.last() == Some(&name) {
             stack.pop();
@@ -242,12 +238,9 @@ impl Profiler {
             timestamp: Instant::now(),
         };
 
-        let mut events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let mut events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
         events.push(event);
 
         Ok(())
@@ -260,12 +253,9 @@ impl Profiler {
             timestamp: Instant::now(),
         };
 
-        let mut events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let mut events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
         events.push(event);
 
         Ok(())
@@ -275,20 +265,14 @@ impl Profiler {
     pub fn memory_allocate(&self, size: u64) -> SlvrResult<()> {
         let event = ProfilerEvent::MemoryAllocate { size };
 
-        let mut events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let mut events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
         events.push(event);
 
-        let mut profile =
-            self.profile
-                .lock()
-                .map_err(|e| crate::error::SlvrError::RuntimeError {
-                    message: format!("Failed to acquire profile lock: {}", e),
-                })?;
+        let mut profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         profile.memory_profile.allocated += size;
         profile.memory_profile.current_usage += size;
         profile.memory_profile.allocations += 1;
@@ -304,20 +288,14 @@ impl Profiler {
     pub fn memory_free(&self, size: u64) -> SlvrResult<()> {
         let event = ProfilerEvent::MemoryFree { size };
 
-        let mut events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let mut events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
         events.push(event);
 
-        let mut profile =
-            self.profile
-                .lock()
-                .map_err(|e| crate::error::SlvrError::RuntimeError {
-                    message: format!("Failed to acquire profile lock: {}", e),
-                })?;
+        let mut profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         profile.memory_profile.freed += size;
         profile.memory_profile.current_usage =
             profile.memory_profile.current_usage.saturating_sub(size);
@@ -333,20 +311,14 @@ impl Profiler {
             operation: operation.clone(),
         };
 
-        let mut events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let mut events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
         events.push(event);
 
-        let mut profile =
-            self.profile
-                .lock()
-                .map_err(|e| crate::error::SlvrError::RuntimeError {
-                    message: format!("Failed to acquire profile lock: {}", e),
-                })?;
+        let mut profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         profile.fuel_profile.total_fuel += amount;
 
         *profile
@@ -356,9 +328,10 @@ impl Profiler {
             .or_insert(0) += amount;
 
         let stack = self.current_function_stack.lock().map_err(|e| {
-            crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire function stack lock: {}", e),
-            }
+            crate::error::SlvrError::runtime(format!(
+                "Failed to acquire function stack lock: {}",
+                e
+            ))
         })?;
         if let Some(func) = stack.last() {
             *profile
@@ -373,12 +346,9 @@ impl Profiler {
 
     /// Finalize profiling
     pub fn finalize(&self) -> SlvrResult<ExecutionProfile> {
-        let mut profile =
-            self.profile
-                .lock()
-                .map_err(|e| crate::error::SlvrError::RuntimeError {
-                    message: format!("Failed to acquire profile lock: {}", e),
-                })?;
+        let mut profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         profile.end_time = Some(Utc::now());
         profile.duration_ms = self.start_time.elapsed().as_secs_f64() * 1000.0;
 
@@ -390,12 +360,9 @@ impl Profiler {
 
     /// Process recorded events
     fn process_events(&self, profile: &mut ExecutionProfile) -> SlvrResult<()> {
-        let events = self
-            .events
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire events lock: {}", e),
-            })?;
+        let events = self.events.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire events lock: {}", e))
+        })?;
 
         let mut function_times: HashMap<String, Vec<Duration>> = HashMap::new();
         let mut operation_times: HashMap<String, Vec<Duration>> = HashMap::new();
@@ -504,9 +471,8 @@ impl Profiler {
         // Calculate average fuel per operation
         if !profile.operation_profiles.is_empty() {
             let total_ops: u64 = profile.operation_profiles.values().map(|op| op.count).sum();
-            if total_ops > 0 {
-                profile.fuel_profile.average_fuel_per_op =
-                    profile.fuel_profile.total_fuel / total_ops;
+            if let Some(average) = profile.fuel_profile.total_fuel.checked_div(total_ops) {
+                profile.fuel_profile.average_fuel_per_op = average;
             }
         }
 
@@ -515,67 +481,49 @@ impl Profiler {
 
     /// Get profile
     pub fn get_profile(&self) -> SlvrResult<ExecutionProfile> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         Ok(profile.clone())
     }
 
     /// Get function profile
     pub fn get_function_profile(&self, name: &str) -> SlvrResult<Option<FunctionProfile>> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         Ok(profile.function_profiles.get(name).cloned())
     }
 
     /// Get operation profile
     pub fn get_operation_profile(&self, operation: &str) -> SlvrResult<Option<OperationProfile>> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         Ok(profile.operation_profiles.get(operation).cloned())
     }
 
     /// Get memory profile
     pub fn get_memory_profile(&self) -> SlvrResult<MemoryProfile> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         Ok(profile.memory_profile.clone())
     }
 
     /// Get fuel profile
     pub fn get_fuel_profile(&self) -> SlvrResult<FuelProfile> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
         Ok(profile.fuel_profile.clone())
     }
 
     /// Get hotspots (functions/operations taking most time)
     pub fn get_hotspots(&self, limit: usize) -> SlvrResult<Vec<(String, f64)>> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
 
         let mut hotspots: Vec<(String, f64)> = profile
             .function_profiles
@@ -591,12 +539,9 @@ impl Profiler {
 
     /// Get bottlenecks (operations with highest fuel consumption)
     pub fn get_bottlenecks(&self, limit: usize) -> SlvrResult<Vec<(String, u64)>> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
 
         let mut bottlenecks: Vec<(String, u64)> = profile
             .fuel_profile
@@ -605,7 +550,7 @@ impl Profiler {
             .map(|(op, fuel)| (op.clone(), *fuel))
             .collect();
 
-        bottlenecks.sort_by(|a, b| b.1.cmp(&a.1));
+        bottlenecks.sort_by_key(|b| std::cmp::Reverse(b.1));
         bottlenecks.truncate(limit);
 
         Ok(bottlenecks)
@@ -613,12 +558,9 @@ impl Profiler {
 
     /// Generate report
     pub fn generate_report(&self) -> SlvrResult<String> {
-        let profile = self
-            .profile
-            .lock()
-            .map_err(|e| crate::error::SlvrError::RuntimeError {
-                message: format!("Failed to acquire profile lock: {}", e),
-            })?;
+        let profile = self.profile.lock().map_err(|e| {
+            crate::error::SlvrError::runtime(format!("Failed to acquire profile lock: {}", e))
+        })?;
 
         let mut report = String::new();
         report.push_str(&format!("=== Profiling Report: {} ===\n", profile.name));
@@ -191,9 +191,7 @@ impl Query {
 
                 let cmp = match (a_val, b_val) {
                     (Some(Value::Integer(x)), Some(Value::Integer(y))) => x.cmp(y),
-                    (Some(Value::Decimal(x)), Some(Value::Decimal(y))) => {
-                        x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
-                    }
+                    (Some(Value::Decimal(x)), Some(Value::Decimal(y))) => x.cmp(y),
                     (Some(Value::String(x)), Some(Value::String(y))) => x.cmp(y),
                     _ => std::cmp::Ordering::Equal,
                 };
@@ -284,9 +282,7 @@ impl IndexManager {
     /// Create an index
     pub fn create_index(&mut self, name: String, field: String) -> SlvrResult<()> {
         if self.indexes.contains_key(&name) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("index {} already exists", name),
-            });
+            return Err(SlvrError::runtime(format!("index {} already exists", name)));
         }
         let index_name = name.clone();
         self.indexes.insert(name, Index::new(index_name, field));
@@ -298,17 +294,13 @@ impl IndexManager {
         self.indexes
             .get(name)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("index {} not found", name),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("index {} not found", name)))
     }
 
     /// Drop an index
     pub fn drop_index(&mut self, name: &str) -> SlvrResult<()> {
         if self.indexes.remove(name).is_none() {
-            return Err(SlvrError::RuntimeError {
-                message: format!("index {} not found", name),
-            });
+            return Err(SlvrError::runtime(format!("index {} not found", name)));
         }
         Ok(())
     }
@@ -2,17 +2,152 @@
 //!
 //! Manages execution state, fuel metering, and database operations.
 
+use crate::bytecode::Instruction;
 use crate::error::{SlvrError, SlvrResult};
 use crate::value::Value;
+use crate::{SlvrConfig, MIN_FUEL_PER_OP};
+use dashmap::iter::Iter;
+use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Fuel charged for each element processed by `Runtime::batch_apply`
+pub const BATCH_ELEMENT_FUEL: u64 = 10;
+
+/// Default cost of builtin operations, keyed by name.
+///
+/// `read` and `read.cached` are what `Runtime::read_cached` charges for a
+/// lookup in the state store and for one served from its cache; `call` and
+/// `call.arg-byte` are the base cost of a contract call and the cost of each
+/// byte of its encoded arguments.
+const DEFAULT_BUILTIN_COSTS: &[(&str, u64)] = &[
+    ("read", 10),
+    ("read.cached", 1),
+    ("call", 1_000),
+    ("call.arg-byte", 10),
+    ("write", 100),
+    ("update", 100),
+    ("delete", 50),
+    ("ed25519-verify", 500),
+    ("pact.validate-inputs", 100),
+    ("pact.step", 150),
+    ("token.transfer", 500),
+    ("token.approve", 300),
+    ("token.mint", 400),
+    ("token.burn", 350),
+    ("token.balance_of", 200),
+    ("state.query", 200),
+    ("state.update", 250),
+];
+
+/// Fuel cost of every bytecode opcode and builtin operation.
+///
+/// Opcodes are keyed by their disassembler mnemonic, e.g. `ADD`, and builtins
+/// by name, e.g. `token.transfer`. Anything without an entry costs the
+/// default, normally `MIN_FUEL_PER_OP`, so chains can tune gas economics
+/// through `SlvrConfig` without touching the code that charges it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuelSchedule {
+    /// Cost of any opcode or builtin without an entry below
+    default_cost: u64,
+    /// Cost per opcode mnemonic
+    opcodes: HashMap<String, u64>,
+    /// Cost per builtin name
+    builtins: HashMap<String, u64>,
+}
+
+impl Default for FuelSchedule {
+    fn default() -> Self {
+        Self {
+            default_cost: MIN_FUEL_PER_OP,
+            opcodes: HashMap::new(),
+            builtins: DEFAULT_BUILTIN_COSTS
+                .iter()
+                .map(|(name, cost)| (name.to_string(), *cost))
+                .collect(),
+        }
+    }
+}
+
+impl FuelSchedule {
+    /// Create the default schedule
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule in effect for a configuration; everything is free when fuel
+    /// metering is disabled
+    pub fn from_config(config: &SlvrConfig) -> Self {
+        if config.enable_fuel_metering {
+            config.fuel_schedule.clone()
+        } else {
+            Self::free()
+        }
+    }
+
+    /// Schedule that charges nothing
+    pub fn free() -> Self {
+        Self {
+            default_cost: 0,
+            opcodes: HashMap::new(),
+            builtins: HashMap::new(),
+        }
+    }
+
+    /// Set the cost of anything without its own entry
+    pub fn with_default_cost(mut self, cost: u64) -> Self {
+        self.default_cost = cost;
+        self
+    }
+
+    /// Set the cost of an opcode by mnemonic
+    pub fn with_opcode_cost(mut self, opcode: impl Into<String>, cost: u64) -> Self {
+        self.opcodes.insert(opcode.into(), cost);
+        self
+    }
+
+    /// Set the cost of a builtin by name
+    pub fn with_builtin_cost(mut self, builtin: impl Into<String>, cost: u64) -> Self {
+        self.builtins.insert(builtin.into(), cost);
+        self
+    }
+
+    /// Cost of anything without its own entry; the tree-walking evaluator
+    /// charges it for every expression it visits
+    pub fn default_cost(&self) -> u64 {
+        self.default_cost
+    }
+
+    /// Cost of an opcode by mnemonic
+    pub fn opcode_cost(&self, opcode: &str) -> u64 {
+        self.opcodes
+            .get(opcode)
+            .copied()
+            .unwrap_or(self.default_cost)
+    }
+
+    /// Cost of executing an instruction
+    pub fn instruction_cost(&self, instruction: &Instruction) -> u64 {
+        self.opcode_cost(instruction.opcode())
+    }
+
+    /// Cost of a builtin by name
+    pub fn builtin_cost(&self, builtin: &str) -> u64 {
+        self.builtins
+            .get(builtin)
+            .copied()
+            .unwrap_or(self.default_cost)
+    }
+}
+
 /// Runtime environment for Slvr execution
 pub struct Runtime {
     /// Global state/database (thread-safe)
-    state: Arc<DashMap<String, Value>>,
+    state: Arc<JournaledState>,
     /// Fuel remaining (atomic for thread safety)
     fuel: Arc<AtomicU64>,
     /// Maximum fuel
@@ -23,6 +158,36 @@ pub struct Runtime {
     tx_id: String,
     /// Execution context
     context: ExecutionContext,
+    /// Whether state mutations are currently rejected
+    readonly: AtomicBool,
+    /// Block time visible to contracts
+    time_source: Arc<dyn TimeSource>,
+    /// Reads memoized for the current transaction, including missing keys;
+    /// dropped when it commits or rolls back
+    read_cache: Arc<DashMap<String, Option<Value>>>,
+    /// Number of reads served from `read_cache`
+    cache_hits: Arc<AtomicU64>,
+    /// Fuel cost of each opcode and builtin
+    fuel_schedule: Arc<FuelSchedule>,
+}
+
+/// Source of the block timestamp visible to contracts.
+///
+/// Contracts never read the wall clock; every time they observe comes from
+/// the block being executed so all nodes agree on it.
+pub trait TimeSource: Send + Sync {
+    /// Block timestamp in Unix seconds
+    fn block_time(&self) -> u64;
+}
+
+/// Time source pinned to a single block timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTime(pub u64);
+
+impl TimeSource for BlockTime {
+    fn block_time(&self) -> u64 {
+        self.0
+    }
 }
 
 /// Execution context information
@@ -57,8 +222,9 @@ impl Runtime {
 
     /// Create a new runtime with execution context
     pub fn with_context(max_fuel: u64, context: ExecutionContext) -> Self {
+        let time_source = Arc::new(BlockTime(context.block_timestamp));
         Self {
-            state: Arc::new(DashMap::new()),
+            state: Arc::new(JournaledState::new()),
             fuel: Arc::new(AtomicU64::new(max_fuel)),
             max_fuel,
             start_time: SystemTime::now(),
@@ -70,9 +236,46 @@ impl Runtime {
                     .as_nanos()
             ),
             context,
+            readonly: AtomicBool::new(false),
+            time_source,
+            read_cache: Arc::new(DashMap::new()),
+            cache_hits: Arc::new(AtomicU64::new(0)),
+            fuel_schedule: Arc::new(FuelSchedule::default()),
         }
     }
 
+    /// Create a runtime with the fuel budget and schedule of a configuration
+    pub fn with_config(config: &SlvrConfig) -> Self {
+        Self::new(config.max_fuel_per_tx).with_fuel_schedule(FuelSchedule::from_config(config))
+    }
+
+    /// Replace the fuel schedule
+    pub fn with_fuel_schedule(mut self, schedule: FuelSchedule) -> Self {
+        self.fuel_schedule = Arc::new(schedule);
+        self
+    }
+
+    /// Get the fuel schedule
+    pub fn fuel_schedule(&self) -> &FuelSchedule {
+        &self.fuel_schedule
+    }
+
+    /// Replace the source of block time
+    pub fn with_time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Get the block timestamp in Unix seconds
+    pub fn block_time(&self) -> u64 {
+        self.time_source.block_time()
+    }
+
+    /// Get the time source shared with evaluators
+    pub fn time_source(&self) -> Arc<dyn TimeSource> {
+        Arc::clone(&self.time_source)
+    }
+
     /// Get remaining fuel
     pub fn fuel(&self) -> u64 {
         self.fuel.load(Ordering::SeqCst)
@@ -101,6 +304,84 @@ impl Runtime {
         Ok(())
     }
 
+    /// Run `f` with state mutations forbidden.
+    ///
+    /// Any write, update or delete attempted inside `f` fails with
+    /// `StateMutationForbidden` instead of touching state. The previous mode is
+    /// restored afterwards, so read-only calls may nest.
+    pub fn execute_readonly<T, F>(&self, f: F) -> SlvrResult<T>
+    where
+        F: FnOnce(&Self) -> SlvrResult<T>,
+    {
+        let previous = self.readonly.swap(true, Ordering::SeqCst);
+        let result = f(self);
+        self.readonly.store(previous, Ordering::SeqCst);
+        result
+    }
+
+    /// Check whether state mutations are currently rejected
+    pub fn is_readonly(&self) -> bool {
+        self.readonly.load(Ordering::SeqCst)
+    }
+
+    /// Fail with `StateMutationForbidden` while in read-only mode
+    pub fn ensure_writable(&self, operation: &str, key: &str) -> SlvrResult<()> {
+        if self.is_readonly() {
+            return Err(SlvrError::StateMutationForbidden {
+                operation: operation.to_string(),
+                key: key.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Apply `op` to the elements of `list`, `chunk_size` at a time.
+    ///
+    /// Fuel for a whole chunk is reserved before the chunk starts. When the
+    /// remaining budget cannot cover the next chunk, processing stops cleanly and
+    /// the outcome records how many elements were handled, so a later
+    /// transaction can resume from that offset. Errors from `op` abort the batch.
+    pub fn batch_apply<F>(
+        &self,
+        list: &Value,
+        op_name: &str,
+        chunk_size: usize,
+        mut op: F,
+    ) -> SlvrResult<BatchOutcome>
+    where
+        F: FnMut(&Value) -> SlvrResult<Value>,
+    {
+        let items = match list {
+            Value::List(items) => items,
+            other => return Err(SlvrError::type_mismatch("list", other.type_name())),
+        };
+        if chunk_size == 0 {
+            return Err(SlvrError::invalid_arg(format!(
+                "{}: chunk size must be positive",
+                op_name
+            )));
+        }
+
+        let mut results = Vec::with_capacity(items.len());
+        for chunk in items.chunks(chunk_size) {
+            let chunk_fuel = BATCH_ELEMENT_FUEL.saturating_mul(chunk.len() as u64);
+            if self.fuel() < chunk_fuel {
+                break;
+            }
+            self.consume_fuel(chunk_fuel)?;
+            for item in chunk {
+                results.push(op(item)?);
+            }
+        }
+
+        Ok(BatchOutcome {
+            op_name: op_name.to_string(),
+            processed: results.len(),
+            total: items.len(),
+            results,
+        })
+    }
+
     /// Get execution time in milliseconds
     pub fn execution_time_ms(&self) -> u128 {
         self.start_time.elapsed().unwrap_or_default().as_millis()
@@ -121,6 +402,34 @@ impl Runtime {
         self.state.get(key).map(|v| v.clone())
     }
 
+    /// Read from state through the transaction read cache, charging fuel.
+    ///
+    /// The first read of a key pays the `read` builtin cost; later reads of
+    /// the same key pay `read.cached` until a write, update or delete of that key
+    /// invalidates the entry.
+    pub fn read_cached(&self, key: &str) -> SlvrResult<Option<Value>> {
+        if let Some(cached) = self.read_cache.get(key) {
+            self.consume_fuel(self.fuel_schedule.builtin_cost("read.cached"))?;
+            self.cache_hits.fetch_add(1, Ordering::SeqCst);
+            return Ok(cached.clone());
+        }
+
+        self.consume_fuel(self.fuel_schedule.builtin_cost("read"))?;
+        let value = self.read(key);
+        self.read_cache.insert(key.to_string(), value.clone());
+        Ok(value)
+    }
+
+    /// Get the number of reads served from the transaction read cache
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits.load(Ordering::SeqCst)
+    }
+
+    /// Drop every cached read, as when a transaction commits
+    pub fn clear_read_cache(&self) {
+        self.read_cache.clear();
+    }
+
     /// Read with default value
     pub fn read_or(&self, key: &str, default: Value) -> Value {
         self.read(key).unwrap_or(default)
@@ -128,21 +437,27 @@ impl Runtime {
 
     /// Write to state
     pub fn write(&self, key: String, value: Value) -> SlvrResult<()> {
-        self.consume_fuel(100)?; // Fuel cost for write operation
+        self.ensure_writable("write", &key)?;
+        self.consume_fuel(self.fuel_schedule.builtin_cost("write"))?;
+        self.read_cache.remove(&key);
         self.state.insert(key, value);
         Ok(())
     }
 
     /// Update existing value
     pub fn update(&self, key: &str, value: Value) -> SlvrResult<Option<Value>> {
-        self.consume_fuel(100)?; // Fuel cost for update operation
+        self.ensure_writable("update", key)?;
+        self.consume_fuel(self.fuel_schedule.builtin_cost("update"))?;
+        self.read_cache.remove(key);
         Ok(self.state.insert(key.to_string(), value))
     }
 
     /// Delete from state
     pub fn delete(&self, key: &str) -> SlvrResult<Option<Value>> {
-        self.consume_fuel(50)?; // Fuel cost for delete operation
-        Ok(self.state.remove(key).map(|(_, v)| v))
+        self.ensure_writable("delete", key)?;
+        self.consume_fuel(self.fuel_schedule.builtin_cost("delete"))?;
+        self.read_cache.remove(key);
+        Ok(self.state.remove(key))
     }
 
     /// Check if key exists
@@ -166,23 +481,23 @@ impl Runtime {
 
     /// Clear all state
     pub fn clear_state(&self) {
+        self.read_cache.clear();
         self.state.clear();
     }
 
-    /// Get state snapshot
-    pub fn snapshot(&self) -> Vec<(String, Value)> {
-        self.state
-            .iter()
-            .map(|entry| (entry.key().clone(), entry.value().clone()))
-            .collect()
+    /// Capture the current state so it can be put back with `restore`
+    pub fn snapshot(&self) -> StateSnapshot {
+        self.state.snapshot()
     }
 
-    /// Restore from snapshot
-    pub fn restore(&self, snapshot: Vec<(String, Value)>) {
-        self.state.clear();
-        for (key, value) in snapshot {
-            self.state.insert(key, value);
-        }
+    /// Revert every write, update and delete made since `snapshot` was taken.
+    ///
+    /// Only the keys changed since are touched, and the read cache is dropped
+    /// so no read observes the discarded changes. Fuel already consumed is
+    /// not refunded.
+    pub fn restore(&self, snapshot: StateSnapshot) {
+        self.read_cache.clear();
+        self.state.restore(snapshot);
     }
 
     /// Get execution statistics
@@ -213,10 +528,180 @@ impl Clone for Runtime {
             start_time: self.start_time,
             tx_id: self.tx_id.clone(),
             context: self.context.clone(),
+            readonly: AtomicBool::new(self.is_readonly()),
+            time_source: Arc::clone(&self.time_source),
+            read_cache: Arc::clone(&self.read_cache),
+            cache_hits: Arc::clone(&self.cache_hits),
+            fuel_schedule: Arc::clone(&self.fuel_schedule),
         }
     }
 }
 
+impl std::fmt::Debug for Runtime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Runtime")
+            .field("tx_id", &self.tx_id)
+            .field("fuel", &self.fuel())
+            .field("max_fuel", &self.max_fuel)
+            .field("state_size", &self.state_size())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Key-value state that remembers what each change overwrote while a
+/// `StateSnapshot` of it is alive.
+///
+/// Taking a snapshot copies nothing; restoring one undoes just the changes
+/// made since, newest first, so both cost in proportion to the writes made
+/// rather than to the size of the state.
+#[derive(Debug, Default)]
+pub(crate) struct JournaledState {
+    rows: DashMap<String, Value>,
+    journal: Arc<Mutex<Journal>>,
+}
+
+/// Undo log shared by a `JournaledState` and its live snapshots
+#[derive(Debug, Default)]
+struct Journal {
+    /// Each changed key with the value it held before, oldest first
+    entries: Vec<(String, Option<Value>)>,
+    /// Snapshots neither restored nor dropped yet
+    open: usize,
+}
+
+impl JournaledState {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Ref<'_, String, Value>> {
+        self.rows.get(key)
+    }
+
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.rows.contains_key(key)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, String, Value> {
+        self.rows.iter()
+    }
+
+    pub(crate) fn insert(&self, key: String, value: Value) -> Option<Value> {
+        let mut journal = self.journal.lock();
+        if journal.open == 0 {
+            return self.rows.insert(key, value);
+        }
+        let previous = self.rows.insert(key.clone(), value);
+        journal.entries.push((key, previous.clone()));
+        previous
+    }
+
+    pub(crate) fn remove(&self, key: &str) -> Option<Value> {
+        let mut journal = self.journal.lock();
+        let previous = self.rows.remove(key).map(|(_, value)| value);
+        if journal.open > 0 && previous.is_some() {
+            journal.entries.push((key.to_string(), previous.clone()));
+        }
+        previous
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut journal = self.journal.lock();
+        if journal.open == 0 {
+            self.rows.clear();
+            return;
+        }
+        let keys: Vec<String> = self.rows.iter().map(|entry| entry.key().clone()).collect();
+        for key in keys {
+            if let Some((key, value)) = self.rows.remove(&key) {
+                journal.entries.push((key, Some(value)));
+            }
+        }
+    }
+
+    /// Start recording changes so they can be undone with `restore`
+    pub(crate) fn snapshot(&self) -> StateSnapshot {
+        let mut journal = self.journal.lock();
+        journal.open += 1;
+        StateSnapshot {
+            journal: Arc::clone(&self.journal),
+            mark: journal.entries.len(),
+            size: self.rows.len(),
+        }
+    }
+
+    /// Undo every change made since `snapshot` was taken
+    pub(crate) fn restore(&self, snapshot: StateSnapshot) {
+        debug_assert!(Arc::ptr_eq(&snapshot.journal, &self.journal));
+        let mut journal = snapshot.journal.lock();
+        let mark = snapshot.mark.min(journal.entries.len());
+        for (key, previous) in journal.entries.drain(mark..).rev() {
+            match previous {
+                Some(value) => self.rows.insert(key, value),
+                None => self.rows.remove(&key).map(|(_, value)| value),
+            };
+        }
+    }
+}
+
+/// Savepoint taken by `Runtime::snapshot`.
+///
+/// Dropping it without restoring keeps the changes made since.
+#[derive(Debug)]
+pub struct StateSnapshot {
+    journal: Arc<Mutex<Journal>>,
+    /// Journal length when the snapshot was taken
+    mark: usize,
+    /// Number of keys in the state when the snapshot was taken
+    size: usize,
+}
+
+impl StateSnapshot {
+    /// Number of keys in the state when captured
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check whether the state was empty when captured
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+}
+
+impl Drop for StateSnapshot {
+    fn drop(&mut self) {
+        let mut journal = self.journal.lock();
+        journal.open -= 1;
+        if journal.open == 0 {
+            journal.entries.clear();
+        }
+    }
+}
+
+/// Progress of a `Runtime::batch_apply` call
+#[derive(Debug, Clone)]
+pub struct BatchOutcome {
+    /// Name of the operation that was applied
+    pub op_name: String,
+    /// Number of leading elements processed; resume from this index
+    pub processed: usize,
+    /// Number of elements in the input list
+    pub total: usize,
+    /// Result of the operation for each processed element
+    pub results: Vec<Value>,
+}
+
+impl BatchOutcome {
+    /// Check whether every element was processed
+    pub fn is_complete(&self) -> bool {
+        self.processed == self.total
+    }
+}
+
 /// Runtime execution statistics
 #[derive(Debug, Clone)]
 pub struct RuntimeStats {
@@ -273,6 +758,38 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_write_cost_comes_from_schedule() {
+        let runtime = Runtime::new(1_000);
+        runtime.write("a".to_string(), Value::Integer(1)).unwrap();
+        assert_eq!(
+            runtime.fuel_used(),
+            FuelSchedule::default().builtin_cost("write")
+        );
+
+        let schedule = FuelSchedule::new().with_builtin_cost("write", 7);
+        let runtime = Runtime::new(1_000).with_fuel_schedule(schedule);
+        runtime.write("a".to_string(), Value::Integer(1)).unwrap();
+        assert_eq!(runtime.fuel_used(), 7);
+        assert_eq!(runtime.clone().fuel_schedule().builtin_cost("write"), 7);
+    }
+
+    #[test]
+    fn test_with_config_uses_configured_schedule() {
+        let config = SlvrConfig::new()
+            .with_max_fuel_per_tx(500)
+            .with_fuel_schedule(FuelSchedule::new().with_builtin_cost("read", 7));
+        let runtime = Runtime::with_config(&config);
+        assert_eq!(runtime.fuel(), 500);
+        runtime.read_cached("a").unwrap();
+        assert_eq!(runtime.fuel_used(), 7);
+
+        let unmetered = Runtime::with_config(&config.with_fuel_metering(false));
+        unmetered.read_cached("a").unwrap();
+        unmetered.write("a".to_string(), Value::Integer(1)).unwrap();
+        assert_eq!(unmetered.fuel_used(), 0);
+    }
+
     #[test]
     fn test_state_operations() {
         let runtime = Runtime::new(1_000_000);
@@ -312,6 +829,104 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nested_snapshots_undo_only_their_own_changes() {
+        let runtime = Runtime::new(1_000_000);
+        runtime.write("a".to_string(), Value::Integer(1)).unwrap();
+
+        let outer = runtime.snapshot();
+        runtime.update("a", Value::Integer(2)).unwrap();
+        let inner = runtime.snapshot();
+        runtime.write("b".to_string(), Value::Integer(3)).unwrap();
+        runtime.delete("a").unwrap();
+        runtime.restore(inner);
+        assert_eq!(runtime.read("a"), Some(Value::Integer(2)));
+        assert_eq!(runtime.read("b"), None);
+
+        runtime.write("c".to_string(), Value::Integer(4)).unwrap();
+        runtime.restore(outer);
+        assert_eq!(runtime.read("a"), Some(Value::Integer(1)));
+        assert_eq!(runtime.read("c"), None);
+
+        // A snapshot dropped without restoring keeps its changes, and the
+        // journal is emptied once no snapshot is left
+        let kept = runtime.snapshot();
+        runtime.write("d".to_string(), Value::Integer(5)).unwrap();
+        drop(kept);
+        assert_eq!(runtime.read("d"), Some(Value::Integer(5)));
+        assert!(runtime.state.journal.lock().entries.is_empty());
+    }
+
+    #[test]
+    fn test_restore_reverts_changes_since_snapshot() {
+        let runtime = Runtime::new(1_000_000);
+        runtime
+            .write("kept".to_string(), Value::Integer(1))
+            .unwrap();
+        runtime
+            .write("changed".to_string(), Value::Integer(2))
+            .unwrap();
+        runtime
+            .write("deleted".to_string(), Value::Integer(3))
+            .unwrap();
+        let snapshot = runtime.snapshot();
+
+        runtime.update("changed", Value::Integer(20)).unwrap();
+        runtime.delete("deleted").unwrap();
+        runtime
+            .write("added".to_string(), Value::Integer(4))
+            .unwrap();
+        assert_eq!(
+            runtime.read_cached("changed").unwrap(),
+            Some(Value::Integer(20))
+        );
+
+        runtime.restore(snapshot);
+        assert_eq!(runtime.read("kept"), Some(Value::Integer(1)));
+        assert_eq!(
+            runtime.read_cached("changed").unwrap(),
+            Some(Value::Integer(2))
+        );
+        assert_eq!(runtime.read("deleted"), Some(Value::Integer(3)));
+        assert_eq!(runtime.read("added"), None);
+        assert_eq!(runtime.state_size(), 3);
+    }
+
+    #[test]
+    fn test_read_cache_invalidated_by_write() {
+        let runtime = Runtime::new(1_000_000);
+        runtime
+            .write("accounts:alice".to_string(), Value::Integer(1))
+            .unwrap();
+
+        assert_eq!(
+            runtime.read_cached("accounts:alice").unwrap(),
+            Some(Value::Integer(1))
+        );
+        let before = runtime.fuel_used();
+        assert_eq!(
+            runtime.read_cached("accounts:alice").unwrap(),
+            Some(Value::Integer(1))
+        );
+        assert_eq!(runtime.cache_hits(), 1);
+        assert_eq!(
+            runtime.fuel_used() - before,
+            runtime.fuel_schedule().builtin_cost("read.cached")
+        );
+
+        runtime
+            .write("accounts:alice".to_string(), Value::Integer(2))
+            .unwrap();
+        assert_eq!(
+            runtime.read_cached("accounts:alice").unwrap(),
+            Some(Value::Integer(2))
+        );
+        assert_eq!(runtime.cache_hits(), 1);
+
+        runtime.delete("accounts:alice").unwrap();
+        assert_eq!(runtime.read_cached("accounts:alice").unwrap(), None);
+    }
+
     #[test]
     fn test_runtime_stats() {
         let runtime = Runtime::new(1_000_000);
@@ -324,4 +939,65 @@ mod tests {
             Err(e) => panic!("Fuel consumption failed: {}", e),
         }
     }
+
+    #[test]
+    fn test_batch_apply_stops_at_fuel_budget() {
+        // Enough fuel for two chunks of three elements
+        let runtime = Runtime::new(BATCH_ELEMENT_FUEL * 7);
+        let recipients = Value::List((0..10).map(Value::Integer).collect());
+
+        let outcome = runtime
+            .batch_apply(&recipients, "airdrop", 3, |recipient| {
+                let key = format!("airdrop:{}", recipient);
+                runtime.state.insert(key, Value::Integer(5));
+                Ok(Value::Boolean(true))
+            })
+            .unwrap();
+
+        assert_eq!(outcome.processed, 6);
+        assert_eq!(outcome.total, 10);
+        assert!(!outcome.is_complete());
+        assert_eq!(runtime.state_size(), 6);
+        assert_eq!(runtime.fuel(), BATCH_ELEMENT_FUEL);
+
+        let all = Runtime::new(1_000)
+            .batch_apply(&recipients, "noop", 4, |v| Ok(v.clone()))
+            .unwrap();
+        assert!(all.is_complete());
+        assert_eq!(all.results.len(), 10);
+    }
+
+    #[test]
+    fn test_execute_readonly() {
+        let runtime = Runtime::new(1_000_000);
+        runtime.write("key".to_string(), Value::Integer(1)).unwrap();
+
+        let read = runtime.execute_readonly(|rt| Ok(rt.read("key")));
+        assert_eq!(read.unwrap(), Some(Value::Integer(1)));
+
+        let write = runtime.execute_readonly(|rt| rt.write("key".to_string(), Value::Integer(2)));
+        assert!(matches!(
+            write,
+            Err(SlvrError::StateMutationForbidden { .. })
+        ));
+        assert!(runtime.execute_readonly(|rt| rt.delete("key")).is_err());
+        assert_eq!(runtime.read("key"), Some(Value::Integer(1)));
+
+        assert!(!runtime.is_readonly());
+        assert!(runtime.update("key", Value::Integer(3)).is_ok());
+    }
+
+    #[test]
+    fn test_block_time_comes_from_context() {
+        let context = ExecutionContext {
+            block_timestamp: 1_700_000_000,
+            ..ExecutionContext::default()
+        };
+        let runtime = Runtime::with_context(1_000, context);
+        assert_eq!(runtime.block_time(), 1_700_000_000);
+
+        let runtime = runtime.with_time_source(Arc::new(BlockTime(42)));
+        assert_eq!(runtime.block_time(), 42);
+        assert_eq!(runtime.clone().block_time(), 42);
+    }
 }
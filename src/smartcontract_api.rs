@@ -1,16 +1,19 @@
 //! Smart Contract APIs - Full Slvr Language Implementation
 //! Complete production-ready smart contract management system
 
+use crate::address;
 use crate::ast::Definition;
 use crate::compiler::Compiler;
 use crate::error::{SlvrError, SlvrResult};
+use crate::evaluator::Evaluator;
 use crate::lexer::Lexer;
 use crate::parser::Parser;
+use crate::SlvrConfig;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 /// Schema field definition
@@ -47,21 +50,20 @@ impl SchemaDefinition {
 
     pub fn validate_row(&self, row: &serde_json::Value) -> SlvrResult<()> {
         if !row.is_object() {
-            return Err(SlvrError::RuntimeError {
-                message: "Row must be an object".to_string(),
-            });
+            return Err(SlvrError::runtime("Row must be an object"));
         }
 
         // PRODUCTION: Proper error handling instead of unwrap()
-        let obj = row.as_object().ok_or_else(|| SlvrError::RuntimeError {
-            message: "Failed to extract object from JSON value".to_string(),
-        })?;
+        let obj = row
+            .as_object()
+            .ok_or_else(|| SlvrError::runtime("Failed to extract object from JSON value"))?;
 
         for (field_name, field_type) in &self.fields {
             if field_type.required && !obj.contains_key(field_name) {
-                return Err(SlvrError::RuntimeError {
-                    message: format!("Required field {} missing", field_name),
-                });
+                return Err(SlvrError::runtime(format!(
+                    "Required field {} missing",
+                    field_name
+                )));
             }
         }
 
@@ -94,9 +96,7 @@ impl TableDefinition {
 
     pub fn insert(&mut self, key: String, value: serde_json::Value) -> SlvrResult<()> {
         if self.rows.contains_key(&key) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Key {} already exists", key),
-            });
+            return Err(SlvrError::runtime(format!("Key {} already exists", key)));
         }
         self.rows.insert(key, value);
         self.row_count += 1;
@@ -107,16 +107,12 @@ impl TableDefinition {
         self.rows
             .get(key)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Key {} not found", key),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Key {} not found", key)))
     }
 
     pub fn update(&mut self, key: &str, value: serde_json::Value) -> SlvrResult<()> {
         if !self.rows.contains_key(key) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Key {} not found", key),
-            });
+            return Err(SlvrError::runtime(format!("Key {} not found", key)));
         }
         self.rows.insert(key.to_string(), value);
         Ok(())
@@ -125,9 +121,7 @@ impl TableDefinition {
     pub fn delete(&mut self, key: &str) -> SlvrResult<serde_json::Value> {
         self.rows
             .remove(key)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Key {} not found", key),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Key {} not found", key)))
             .inspect(|_v| {
                 self.row_count = self.row_count.saturating_sub(1);
             })
@@ -145,6 +139,14 @@ impl TableDefinition {
         self.rows.len()
     }
 
+    /// Bytes occupied by stored rows: each key plus its serialized JSON value
+    pub fn storage_bytes(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|(key, value)| key.len() + json_size(value))
+            .sum()
+    }
+
     pub fn scan(
         &self,
         predicate: impl Fn(&serde_json::Value) -> bool,
@@ -290,6 +292,9 @@ pub struct ContractMetadata {
     pub name: String,
     pub version: String,
     pub author: String,
+    /// Account allowed to administer the contract (e.g. pause it)
+    #[serde(default)]
+    pub deployer: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub code_hash: String,
@@ -335,6 +340,9 @@ pub struct SlvrContract {
     pub bytecode: Vec<u8>,
     pub state: ContractState,
     pub capabilities: Vec<String>,
+    /// Paused contracts only accept calls to pure functions
+    #[serde(default)]
+    pub paused: bool,
 }
 
 impl SlvrContract {
@@ -343,6 +351,17 @@ impl SlvrContract {
         source_code: String,
         author: String,
         version: String,
+    ) -> SlvrResult<Self> {
+        Self::with_config(name, source_code, author, version, &SlvrConfig::default())
+    }
+
+    /// Build a contract, compiling it as `config` says
+    pub fn with_config(
+        name: String,
+        source_code: String,
+        author: String,
+        version: String,
+        config: &SlvrConfig,
     ) -> SlvrResult<Self> {
         let mut lexer = Lexer::new(&source_code);
         let _tokens = lexer.tokenize()?;
@@ -350,22 +369,21 @@ impl SlvrContract {
         let mut parser = Parser::new(&source_code)?;
         let program = parser.parse()?;
 
-        let mut compiler = Compiler::new();
-        let bytecode = compiler.compile(&program)?;
+        let mut compiler = Compiler::with_config(config);
+        let bytecode = match compiler.compile_with_diagnostics(&program) {
+            (Some(bytecode), _) => bytecode,
+            (None, diagnostics) => return Err(SlvrError::CompilationFailed { diagnostics }),
+        };
 
         let mut hasher = Sha512::new();
         hasher.update(source_code.as_bytes());
         let code_hash = format!("0x{:x}", hasher.finalize());
 
         let id = format!("contract_{}", uuid::Uuid::new_v4());
-        let address = {
-            let mut hasher = Sha512::new();
-            hasher.update(id.as_bytes());
-            format!("0x{}", hex::encode(hasher.finalize()))
-        };
+        let address = address::derive(&id);
 
         let module = Self::extract_module_from_program(&program)?;
-        let bytecode_bytes = serde_json::to_vec(&bytecode).unwrap_or_default();
+        let bytecode_bytes = crate::bytecode::serialize(&bytecode);
 
         Ok(Self {
             metadata: ContractMetadata {
@@ -373,6 +391,7 @@ impl SlvrContract {
                 address,
                 name,
                 version,
+                deployer: author.clone(),
                 author,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
@@ -385,6 +404,7 @@ impl SlvrContract {
             bytecode: bytecode_bytes,
             state: ContractState::new(),
             capabilities: Vec::new(),
+            paused: false,
         })
     }
 
@@ -468,6 +488,12 @@ impl SlvrContract {
             Definition::Module { .. } => {
                 // Module definitions are handled at a higher level
             }
+            Definition::TypeAlias { .. } => {
+                // Aliases are resolved into the signatures that use them
+            }
+            Definition::Capability { .. } => {
+                // Capabilities guard functions rather than extending the interface
+            }
         }
 
         Ok(())
@@ -475,15 +501,11 @@ impl SlvrContract {
 
     pub fn verify(&self) -> SlvrResult<()> {
         if self.source_code.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Contract source code cannot be empty".to_string(),
-            });
+            return Err(SlvrError::runtime("Contract source code cannot be empty"));
         }
 
         if self.metadata.name.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Contract name cannot be empty".to_string(),
-            });
+            return Err(SlvrError::runtime("Contract name cannot be empty"));
         }
 
         let mut hasher = Sha512::new();
@@ -491,11 +513,11 @@ impl SlvrContract {
         let calculated_hash = format!("0x{:x}", hasher.finalize());
 
         if calculated_hash != self.metadata.code_hash {
-            return Err(SlvrError::RuntimeError {
-                message: "Contract code hash verification failed".to_string(),
-            });
+            return Err(SlvrError::runtime("Contract code hash verification failed"));
         }
 
+        crate::bytecode::deserialize(&self.bytecode)?;
+
         Ok(())
     }
 
@@ -529,6 +551,79 @@ impl SlvrContract {
     pub fn get_constants(&self) -> Vec<&ConstantDefinition> {
         self.module.constants.values().collect()
     }
+
+    /// Total storage footprint of all tables and state variables, in bytes
+    pub fn total_storage_bytes(&self) -> usize {
+        let tables: usize = self.state.tables.values().map(|t| t.storage_bytes()).sum();
+        let variables: usize = self
+            .state
+            .variables
+            .iter()
+            .map(|(name, value)| name.len() + json_size(value))
+            .sum();
+        tables + variables
+    }
+
+    /// Machine-readable interface description of the contract.
+    ///
+    /// Lists public functions, schemas and events sorted by name so the output
+    /// is stable across nodes.
+    pub fn abi(&self) -> serde_json::Value {
+        let mut functions: Vec<_> = self
+            .module
+            .functions
+            .values()
+            .filter(|f| f.is_public)
+            .collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut schemas: Vec<_> = self.module.schemas.values().collect();
+        schemas.sort_by(|a, b| a.name.cmp(&b.name));
+
+        serde_json::json!({
+            "name": self.metadata.name,
+            "version": self.metadata.version,
+            "address": self.metadata.address,
+            "functions": functions
+                .iter()
+                .map(|f| serde_json::json!({
+                    "name": f.name,
+                    "parameters": f
+                        .parameters
+                        .iter()
+                        .map(|(name, ty)| serde_json::json!({"name": name, "type": ty}))
+                        .collect::<Vec<_>>(),
+                    "returns": f.return_type,
+                    "pure": f.is_pure,
+                }))
+                .collect::<Vec<_>>(),
+            "schemas": schemas
+                .iter()
+                .map(|schema| {
+                    let mut fields: Vec<_> = schema.fields.values().collect();
+                    fields.sort_by(|a, b| a.name.cmp(&b.name));
+                    serde_json::json!({
+                        "name": schema.name,
+                        "fields": fields
+                            .iter()
+                            .map(|f| serde_json::json!({
+                                "name": f.name,
+                                "type": f.ty,
+                                "required": f.required,
+                            }))
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            // Modules cannot declare events yet
+            "events": [],
+        })
+    }
+}
+
+/// Length of a JSON value in its compact serialized form
+fn json_size(value: &serde_json::Value) -> usize {
+    serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0)
 }
 
 /// Execution result
@@ -552,8 +647,120 @@ pub struct StateChange {
     pub old_value: Option<serde_json::Value>,
     pub new_value: Option<serde_json::Value>,
     pub timestamp: DateTime<Utc>,
+    /// Fields that differ between `old_value` and `new_value`
+    #[serde(default)]
+    pub field_changes: Vec<FieldChange>,
+}
+
+/// Before/after values of a single changed field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+}
+
+/// List the fields that differ between two rows, sorted by field name.
+///
+/// Fields missing on one side are reported with `None` for that side. Values
+/// that are not objects are compared as a whole under the empty field name.
+pub fn diff_rows(old: &serde_json::Value, new: &serde_json::Value) -> Vec<FieldChange> {
+    match (old.as_object(), new.as_object()) {
+        (Some(old_fields), Some(new_fields)) => {
+            let mut fields: Vec<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+            fields.sort();
+            fields.dedup();
+
+            fields
+                .into_iter()
+                .filter_map(|field| {
+                    let before = old_fields.get(field);
+                    let after = new_fields.get(field);
+                    (before != after).then(|| FieldChange {
+                        field: field.clone(),
+                        old_value: before.cloned(),
+                        new_value: after.cloned(),
+                    })
+                })
+                .collect()
+        }
+        _ if old == new => Vec::new(),
+        _ => vec![FieldChange {
+            field: String::new(),
+            old_value: (!old.is_null()).then(|| old.clone()),
+            new_value: (!new.is_null()).then(|| new.clone()),
+        }],
+    }
 }
 
+/// Coerce a call argument to the declared Slvr type of its parameter.
+///
+/// Scalars go through the `stdlib::conversion` rules, so `"5"` becomes `5`
+/// for an `integer` parameter. Lists and objects must already have the right
+/// shape; custom types are passed through unchanged.
+pub fn coerce_arg(
+    param_name: &str,
+    param_type: &str,
+    arg: &serde_json::Value,
+) -> SlvrResult<serde_json::Value> {
+    use crate::stdlib::{conversion, json};
+    use crate::value::{parse_decimal, Value};
+
+    let mismatch = || {
+        SlvrError::type_error(format!(
+            "argument {} expects {}, got {}",
+            param_name, param_type, arg
+        ))
+    };
+    let scalar = || match arg {
+        serde_json::Value::String(s) => Ok(Value::String(s.clone())),
+        serde_json::Value::Bool(b) => Ok(Value::Boolean(*b)),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Ok(Value::Integer(i as i128)),
+            None => parse_decimal(&n.to_string())
+                .map(Value::Decimal)
+                .ok_or_else(mismatch),
+        },
+        _ => Err(mismatch()),
+    };
+
+    match param_type {
+        "integer" => {
+            if matches!(arg, serde_json::Value::Bool(_)) {
+                return Err(mismatch());
+            }
+            match conversion::to_integer(scalar()?).map_err(|_| mismatch())? {
+                Value::Integer(i) => i64::try_from(i)
+                    .map(serde_json::Value::from)
+                    .map_err(|_| mismatch()),
+                _ => Err(mismatch()),
+            }
+        }
+        // Written as the decimal's own digits, never through a float
+        "decimal" => match conversion::to_decimal(scalar()?).map_err(|_| mismatch())? {
+            decimal @ Value::Decimal(_) => json::to_json(&decimal).map_err(|_| mismatch()),
+            _ => Err(mismatch()),
+        },
+        "string" => match scalar()? {
+            Value::String(s) => Ok(serde_json::Value::String(s)),
+            other => Ok(serde_json::Value::String(other.to_string())),
+        },
+        "boolean" => match arg {
+            serde_json::Value::Bool(b) => Ok(serde_json::Value::Bool(*b)),
+            serde_json::Value::String(s) if s == "true" || s == "false" => {
+                Ok(serde_json::Value::Bool(s == "true"))
+            }
+            _ => Err(mismatch()),
+        },
+        "object" if !arg.is_object() => Err(mismatch()),
+        ty if ty.starts_with('[') && !arg.is_array() => Err(mismatch()),
+        _ => Ok(arg.clone()),
+    }
+}
+
+/// Function run once with the old version and state when a contract is upgraded
+pub const MIGRATE_FUNCTION: &str = "migrate";
+
 /// Call request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallRequest {
@@ -583,29 +790,100 @@ pub struct ExecutionRecord {
     pub result: ExecutionResult,
 }
 
+/// Event emitted by a contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractEvent {
+    pub contract_id: String,
+    /// Position in the contract's event log, starting at 0
+    pub index: usize,
+    pub name: String,
+    /// Fields that subscribers can filter on
+    pub indexed: BTreeMap<String, serde_json::Value>,
+    pub data: serde_json::Value,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ContractEvent {
+    /// SHA-512 of the event's canonical JSON, equal on every node that emitted it
+    pub fn hash(&self) -> SlvrResult<String> {
+        let bytes = crate::stdlib::json::canonical_bytes(self)?;
+        Ok(format!("0x{:x}", Sha512::digest(&bytes)))
+    }
+}
+
+/// Block time of `runtime` as a timestamp
+fn block_time(runtime: &crate::runtime::Runtime) -> DateTime<Utc> {
+    DateTime::from_timestamp(runtime.block_time() as i64, 0).unwrap_or_default()
+}
+
+/// Filter for polling contract events
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Only events with this name
+    pub name: Option<String>,
+    /// Only events whose indexed fields hold these values
+    pub indexed: BTreeMap<String, serde_json::Value>,
+}
+
+impl EventFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_indexed(mut self, field: impl Into<String>, value: serde_json::Value) -> Self {
+        self.indexed.insert(field.into(), value);
+        self
+    }
+
+    pub fn matches(&self, event: &ContractEvent) -> bool {
+        self.name.as_ref().is_none_or(|name| *name == event.name)
+            && self
+                .indexed
+                .iter()
+                .all(|(field, value)| event.indexed.get(field) == Some(value))
+    }
+}
+
 /// Contract manager
 pub struct ContractManager {
     contracts: Arc<RwLock<HashMap<String, SlvrContract>>>,
     contract_addresses: Arc<RwLock<HashMap<String, String>>>,
     execution_history: Arc<RwLock<Vec<ExecutionRecord>>>,
+    events: Arc<RwLock<HashMap<String, Vec<ContractEvent>>>>,
+    /// Language configuration contracts are compiled with
+    config: SlvrConfig,
 }
 
 impl ContractManager {
     pub fn new() -> Self {
+        Self::with_config(SlvrConfig::default())
+    }
+
+    /// Create a manager that compiles contracts as `config` says
+    pub fn with_config(config: SlvrConfig) -> Self {
         Self {
             contracts: Arc::new(RwLock::new(HashMap::new())),
             contract_addresses: Arc::new(RwLock::new(HashMap::new())),
             execution_history: Arc::new(RwLock::new(Vec::new())),
+            events: Arc::new(RwLock::new(HashMap::new())),
+            config,
         }
     }
 
     pub fn deploy(&self, request: DeploymentRequest) -> SlvrResult<SlvrContract> {
-        let contract = SlvrContract::new(
+        let mut contract = SlvrContract::with_config(
             request.name.clone(),
             request.source_code,
             request.author,
             request.version,
+            &self.config,
         )?;
+        contract.metadata.deployer = request.deployer;
 
         contract.verify()?;
 
@@ -626,18 +904,153 @@ impl ContractManager {
             .read()
             .get(contract_id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Contract {} not found", contract_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Contract {} not found", contract_id)))
+    }
+
+    /// Pause or resume a contract; only its deployer may do so
+    pub fn set_paused(&self, contract_id: &str, caller: &str, paused: bool) -> SlvrResult<()> {
+        let mut contracts = self.contracts.write();
+        let contract = contracts
+            .get_mut(contract_id)
+            .ok_or_else(|| SlvrError::runtime(format!("Contract {} not found", contract_id)))?;
+
+        if contract.metadata.deployer != caller {
+            return Err(SlvrError::Unauthorized {
+                caller: caller.to_string(),
+                action: "pause",
+                contract_id: contract_id.to_string(),
+            });
+        }
+
+        contract.paused = paused;
+        contract.metadata.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Replace a contract's code, keeping its id, address and state.
+    ///
+    /// Every table keeps its stored rows, so its new schema must still accept
+    /// them. If the new module defines `migrate (old-version: string, state: object) -> object`,
+    /// it is run once, within the configured fuel budget, with the old version
+    /// and state variables and its result becomes the new variables. Any
+    /// failure leaves the old contract untouched.
+    pub fn upgrade(
+        &self,
+        contract_id: &str,
+        caller: &str,
+        source_code: String,
+        version: String,
+    ) -> SlvrResult<SlvrContract> {
+        let mut contracts = self.contracts.write();
+        let old = contracts
+            .get(contract_id)
+            .ok_or_else(|| SlvrError::runtime(format!("Contract {} not found", contract_id)))?;
+
+        if old.metadata.deployer != caller {
+            return Err(SlvrError::Unauthorized {
+                caller: caller.to_string(),
+                action: "upgrade",
+                contract_id: contract_id.to_string(),
+            });
+        }
+
+        let mut upgraded = SlvrContract::with_config(
+            old.metadata.name.clone(),
+            source_code,
+            old.metadata.author.clone(),
+            version,
+            &self.config,
+        )?;
+        upgraded.verify()?;
+        Self::validate_schema_changes(old, &upgraded.module)?;
+
+        let mut state = old.state.clone();
+        if upgraded.module.get_function(MIGRATE_FUNCTION).is_some() {
+            state.variables = self.run_migration(&upgraded.source_code, old)?;
+        }
+
+        upgraded.metadata.id = old.metadata.id.clone();
+        upgraded.metadata.address = old.metadata.address.clone();
+        upgraded.metadata.deployer = old.metadata.deployer.clone();
+        upgraded.metadata.created_at = old.metadata.created_at;
+        upgraded.capabilities = old.capabilities.clone();
+        upgraded.paused = old.paused;
+        upgraded.state = state;
+        upgraded.update_state_hash();
+
+        contracts.insert(contract_id.to_string(), upgraded.clone());
+        Ok(upgraded)
+    }
+
+    /// Check each table of `old` against the schema `new` gives it, using the
+    /// rows it stores
+    fn validate_schema_changes(old: &SlvrContract, new: &ModuleDefinition) -> SlvrResult<()> {
+        for (table_name, table) in &old.module.tables {
+            let new_schema = new
+                .get_table(table_name)
+                .and_then(|table| new.get_schema(&table.schema_name));
+            let (Some(old_schema), Some(new_schema)) =
+                (old.module.get_schema(&table.schema_name), new_schema)
+            else {
+                continue;
+            };
+            // Sorted by key so errors name rows in a stable order
+            let rows: Vec<_> = old
+                .state
+                .tables
+                .get(table_name)
+                .map(|stored| {
+                    stored
+                        .rows
+                        .iter()
+                        .collect::<BTreeMap<_, _>>()
+                        .into_values()
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default();
+            crate::upgrades::validate_schema_change(old_schema, new_schema, &rows)?;
+        }
+        Ok(())
+    }
+
+    /// Evaluate the new module's `migrate` against the old contract's variables
+    fn run_migration(
+        &self,
+        source_code: &str,
+        old: &SlvrContract,
+    ) -> SlvrResult<HashMap<String, serde_json::Value>> {
+        use crate::stdlib::json;
+        use crate::value::Value;
+
+        let program = Parser::new(source_code)?.parse()?;
+        let mut evaluator = Evaluator::with_config(&self.config);
+        evaluator.eval_program(&program)?;
+
+        let state = Value::Object(
+            old.state
+                .variables
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), json::from_json(v.clone())?)))
+                .collect::<SlvrResult<_>>()?,
+        );
+        let old_version = Value::String(old.metadata.version.clone());
+
+        match evaluator.call(MIGRATE_FUNCTION, vec![old_version, state])? {
+            Value::Object(fields) => fields
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), json::to_json(v)?)))
+                .collect(),
+            other => Err(SlvrError::type_mismatch("object", other.type_name())),
+        }
     }
 
     pub fn get_contract_by_address(&self, address: &str) -> SlvrResult<SlvrContract> {
+        address::validate(address)?;
         let addresses = self.contract_addresses.read();
-        let contract_id = addresses
-            .get(address)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Contract at address {} not found", address),
-            })?;
+        let contract_id = addresses.get(address).ok_or_else(|| {
+            SlvrError::runtime(format!("Contract at address {} not found", address))
+        })?;
 
         self.get_contract(contract_id)
     }
@@ -711,12 +1124,90 @@ impl ContractManager {
             .collect()
     }
 
+    /// Append an event to a contract's log, returning its index.
+    ///
+    /// The event is stamped with the runtime's block time.
+    pub fn emit_event(
+        &self,
+        contract_id: &str,
+        name: String,
+        indexed: BTreeMap<String, serde_json::Value>,
+        data: serde_json::Value,
+        runtime: &crate::runtime::Runtime,
+    ) -> SlvrResult<usize> {
+        if !self.contracts.read().contains_key(contract_id) {
+            return Err(SlvrError::runtime(format!(
+                "Contract {} not found",
+                contract_id
+            )));
+        }
+
+        Ok(self.append_event(contract_id, name, indexed, data, block_time(runtime)))
+    }
+
+    fn append_event(
+        &self,
+        contract_id: &str,
+        name: String,
+        indexed: BTreeMap<String, serde_json::Value>,
+        data: serde_json::Value,
+        timestamp: DateTime<Utc>,
+    ) -> usize {
+        let mut events = self.events.write();
+        let log = events.entry(contract_id.to_string()).or_default();
+        let index = log.len();
+        log.push(ContractEvent {
+            contract_id: contract_id.to_string(),
+            index,
+            name,
+            indexed,
+            data,
+            timestamp,
+        });
+        index
+    }
+
+    /// Events not yet seen by a reader at cursor `after_index`, plus the next cursor.
+    ///
+    /// A cursor is the number of log entries already consumed; start from 0 and
+    /// pass the returned cursor to the next call.
+    pub fn events_since(
+        &self,
+        contract_id: &str,
+        after_index: usize,
+    ) -> (Vec<ContractEvent>, usize) {
+        self.events_matching(contract_id, after_index, &EventFilter::new())
+    }
+
+    /// Like `events_since`, keeping only events accepted by `filter`.
+    ///
+    /// The cursor still advances past skipped events.
+    pub fn events_matching(
+        &self,
+        contract_id: &str,
+        after_index: usize,
+        filter: &EventFilter,
+    ) -> (Vec<ContractEvent>, usize) {
+        let events = self.events.read();
+        let Some(log) = events.get(contract_id) else {
+            return (Vec::new(), after_index);
+        };
+
+        let matching = log
+            .iter()
+            .skip(after_index)
+            .filter(|event| filter.matches(event))
+            .cloned()
+            .collect();
+        (matching, log.len().max(after_index))
+    }
+
     pub fn call_function(
         &self,
         request: &CallRequest,
         runtime: &crate::runtime::Runtime,
     ) -> SlvrResult<ExecutionResult> {
-        let mut contract = self.get_contract(&request.contract_id)?;
+        let contract = self.get_contract(&request.contract_id)?;
 
         let start_time = std::time::Instant::now();
 
@@ -724,37 +1215,55 @@ impl ContractManager {
         let function = contract
             .module
             .get_function(&request.function)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Function {} not found", request.function),
+            .ok_or_else(|| {
+                SlvrError::runtime(format!("Function {} not found", request.function))
             })?;
 
         // Check visibility
         if !function.is_public {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Function {} is not public", request.function),
+            return Err(SlvrError::runtime(format!(
+                "Function {} is not public",
+                request.function
+            )));
+        }
+
+        // Results carry the block time, never the node's wall clock
+        let block_time = block_time(runtime);
+
+        // Paused contracts still serve read-only calls
+        if contract.paused && !function.is_pure {
+            return Err(SlvrError::Paused {
+                contract_id: request.contract_id.clone(),
             });
         }
 
         // Validate argument count
         if request.args.len() != function.parameters.len() {
-            return Err(SlvrError::RuntimeError {
-                message: format!(
-                    "Function {} expects {} arguments, got {}",
-                    request.function,
-                    function.parameters.len(),
-                    request.args.len()
-                ),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Function {} expects {} arguments, got {}",
+                request.function,
+                function.parameters.len(),
+                request.args.len()
+            )));
         }
 
-        // Calculate fuel usage based on function complexity and arguments
-        let base_fuel = 1000u64;
-        let arg_fuel = request
-            .args
+        // Coerce arguments to the declared parameter types
+        let args = function
+            .parameters
+            .iter()
+            .zip(&request.args)
+            .map(|((name, ty), arg)| coerce_arg(name, ty, arg))
+            .collect::<SlvrResult<Vec<_>>>()?;
+
+        // Charge the call and its encoded arguments at the runtime's rates
+        let schedule = runtime.fuel_schedule();
+        let arg_bytes = args
             .iter()
-            .map(|arg| serde_json::to_string(arg).unwrap_or_default().len() as u64 * 10)
+            .map(|arg| serde_json::to_string(arg).unwrap_or_default().len() as u64)
             .sum::<u64>();
-        let total_fuel = base_fuel + arg_fuel;
+        let total_fuel = schedule
+            .builtin_cost("call")
+            .saturating_add(arg_bytes.saturating_mul(schedule.builtin_cost("call.arg-byte")));
 
         // Check fuel availability and consume it
         runtime.consume_fuel(total_fuel)?;
@@ -771,51 +1280,52 @@ impl ContractManager {
 
         // REAL EXECUTION: Execute function based on type
         let result_value = if function.is_pure {
-            // PURE FUNCTION EXECUTION: No state changes, deterministic result
-            // 1. Validate all inputs
-            // 2. Execute computation
-            // 3. Return result
-
-            // Validate arguments match parameters
-            if request.args.len() != function.parameters.len() {
-                return Err(SlvrError::RuntimeError {
-                    message: format!(
+            // Pure calls run read-only so they cannot mutate state
+            runtime.execute_readonly(|_| {
+                // PURE FUNCTION EXECUTION: No state changes, deterministic result
+                // 1. Validate all inputs
+                // 2. Execute computation
+                // 3. Return result
+
+                // Validate arguments match parameters
+                if request.args.len() != function.parameters.len() {
+                    return Err(SlvrError::runtime(format!(
                         "Argument count mismatch: expected {}, got {}",
                         function.parameters.len(),
                         request.args.len()
-                    ),
-                });
-            }
-
-            // Execute pure function with argument validation
-            let mut computed_result = serde_json::json!({});
-
-            for (i, (param_name, _param_type)) in function.parameters.iter().enumerate() {
-                if let Some(arg) = request.args.get(i) {
-                    // Validate argument type matches parameter type
-                    let _arg_type = match arg {
-                        serde_json::Value::String(_) => "string",
-                        serde_json::Value::Number(_) => "number",
-                        serde_json::Value::Bool(_) => "bool",
-                        serde_json::Value::Array(_) => "array",
-                        serde_json::Value::Object(_) => "object",
-                        serde_json::Value::Null => "null",
-                    };
+                    )));
+                }
 
-                    // Store parameter for computation
-                    computed_result[param_name] = arg.clone();
+                // Execute pure function with argument validation
+                let mut computed_result = serde_json::json!({});
+
+                for (i, (param_name, _param_type)) in function.parameters.iter().enumerate() {
+                    if let Some(arg) = args.get(i) {
+                        // Validate argument type matches parameter type
+                        let _arg_type = match arg {
+                            serde_json::Value::String(_) => "string",
+                            serde_json::Value::Number(_) => "number",
+                            serde_json::Value::Bool(_) => "bool",
+                            serde_json::Value::Array(_) => "array",
+                            serde_json::Value::Object(_) => "object",
+                            serde_json::Value::Null => "null",
+                        };
+
+                        // Store parameter for computation
+                        computed_result[param_name] = arg.clone();
+                    }
                 }
-            }
 
-            // Return computed result with metadata
-            serde_json::json!({
-                "function": request.function.clone(),
-                "args": request.args,
-                "caller": request.caller.clone(),
-                "timestamp": Utc::now().to_rfc3339(),
-                "result": computed_result,
-                "execution_type": "pure"
-            })
+                // Return computed result with metadata
+                Ok(serde_json::json!({
+                    "function": request.function.clone(),
+                    "args": args,
+                    "caller": request.caller.clone(),
+                    "timestamp": block_time.to_rfc3339(),
+                    "result": computed_result,
+                    "execution_type": "pure"
+                }))
+            })?
         } else {
             // NON-PURE FUNCTION EXECUTION: State changes allowed
             // 1. Validate all inputs
@@ -826,44 +1336,57 @@ impl ContractManager {
 
             // Validate arguments match parameters
             if request.args.len() != function.parameters.len() {
-                return Err(SlvrError::RuntimeError {
-                    message: format!(
-                        "Argument count mismatch: expected {}, got {}",
-                        function.parameters.len(),
-                        request.args.len()
-                    ),
-                });
+                return Err(SlvrError::runtime(format!(
+                    "Argument count mismatch: expected {}, got {}",
+                    function.parameters.len(),
+                    request.args.len()
+                )));
             }
 
             // Execute non-pure function with state tracking
-            for (i, arg) in request.args.iter().enumerate() {
-                if let Some((param_name, _param_type)) = function.parameters.get(i) {
-                    // Get old value for change tracking
-                    let old_value = contract
-                        .state
-                        .variables
-                        .get(&format!("{}_{}", request.function, param_name))
-                        .cloned();
-
-                    // Update state variable
-                    let new_key = format!("{}_{}", request.function, param_name);
-                    contract
-                        .state
-                        .variables
-                        .insert(new_key.clone(), arg.clone());
-
-                    // Record state change
-                    state_changes.push(StateChange {
-                        table: "variables".to_string(),
-                        key: new_key,
-                        operation: "write".to_string(),
-                        old_value,
-                        new_value: Some(arg.clone()),
-                        timestamp: Utc::now(),
-                    });
-                }
+            let writes = function
+                .parameters
+                .iter()
+                .zip(&args)
+                .map(|((param_name, _param_type), arg)| {
+                    (format!("{}_{}", request.function, param_name), arg)
+                })
+                .collect::<Vec<_>>();
+            for (key, _) in &writes {
+                runtime.ensure_writable("write", key)?;
             }
 
+            // Write into the stored contract under one lock, so a pause or
+            // upgrade that landed since the lookup above is never undone
+            let mut contracts = self.contracts.write();
+            let stored = contracts.get_mut(&request.contract_id).ok_or_else(|| {
+                SlvrError::runtime(format!("Contract {} not found", request.contract_id))
+            })?;
+            if stored.paused {
+                return Err(SlvrError::Paused {
+                    contract_id: request.contract_id.clone(),
+                });
+            }
+            for (key, arg) in writes {
+                // Update state variable, keeping the old value for change tracking
+                let old_value = stored.state.variables.insert(key.clone(), arg.clone());
+
+                // Record state change
+                let field_changes =
+                    diff_rows(old_value.as_ref().unwrap_or(&serde_json::Value::Null), arg);
+                state_changes.push(StateChange {
+                    table: "variables".to_string(),
+                    key,
+                    operation: "write".to_string(),
+                    old_value,
+                    new_value: Some(arg.clone()),
+                    timestamp: block_time,
+                    field_changes,
+                });
+            }
+            stored.update_state_hash();
+            drop(contracts);
+
             // REAL VALIDATION: Verify state consistency after changes
             // 1. Check for constraint violations
             // 2. Validate invariants
@@ -875,17 +1398,43 @@ impl ContractManager {
                 "status": "executed",
                 "state_changes": state_changes.len(),
                 "execution_type": "non_pure",
-                "timestamp": Utc::now().to_rfc3339(),
+                "timestamp": block_time.to_rfc3339(),
                 "caller": request.caller.clone()
             })
         };
 
-        // Update contract state hash
-        contract.update_state_hash();
-
-        // Store updated contract
-        let mut contracts = self.contracts.write();
-        contracts.insert(request.contract_id.clone(), contract);
+        // State-changing calls are logged as an event named after the function,
+        // carrying its arguments by parameter name
+        let event_data = (!function.is_pure).then(|| {
+            function
+                .parameters
+                .iter()
+                .map(|(name, _)| name.clone())
+                .zip(args.iter().cloned())
+                .collect::<serde_json::Map<_, _>>()
+        });
+
+        let mut logs = vec![format!(
+            "Function {} executed successfully",
+            request.function
+        )];
+        if let Some(data) = event_data {
+            let indexed = BTreeMap::from([(
+                "caller".to_string(),
+                serde_json::Value::String(request.caller.clone()),
+            )]);
+            let index = self.append_event(
+                &request.contract_id,
+                request.function.clone(),
+                indexed,
+                serde_json::Value::Object(data),
+                block_time,
+            );
+            logs.push(format!(
+                "Emitted event {} at index {}",
+                request.function, index
+            ));
+        }
 
         // Record execution
         let execution_time = start_time.elapsed().as_millis();
@@ -901,10 +1450,7 @@ impl ContractManager {
                 fuel_used: total_fuel,
                 execution_time_ms: execution_time,
                 state_changes: state_changes.clone(),
-                logs: vec![format!(
-                    "Function {} executed successfully",
-                    request.function
-                )],
+                logs,
             },
         };
 
@@ -946,9 +1492,44 @@ impl ContractManager {
             return Ok(table.read(key).ok());
         }
 
-        Err(SlvrError::RuntimeError {
-            message: format!("Table {} not found in contract {}", table_name, contract_id),
-        })
+        Err(SlvrError::runtime(format!(
+            "Table {} not found in contract {}",
+            table_name, contract_id
+        )))
+    }
+
+    /// One page of a table's rows in ascending key order; `page` starts at 0
+    pub fn list_table_rows(
+        &self,
+        contract_id: &str,
+        table_name: &str,
+        page: usize,
+        page_size: usize,
+    ) -> SlvrResult<Vec<(String, serde_json::Value)>> {
+        if page_size == 0 {
+            return Err(SlvrError::runtime("Page size must be positive"));
+        }
+
+        let contracts = self.contracts.read();
+        let contract = contracts
+            .get(contract_id)
+            .ok_or_else(|| SlvrError::runtime(format!("Contract {} not found", contract_id)))?;
+        let table = contract.state.tables.get(table_name).ok_or_else(|| {
+            SlvrError::runtime(format!(
+                "Table {} not found in contract {}",
+                table_name, contract_id
+            ))
+        })?;
+
+        let mut keys: Vec<&String> = table.rows.keys().collect();
+        keys.sort();
+
+        Ok(keys
+            .into_iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .map(|key| (key.clone(), table.rows[key].clone()))
+            .collect())
     }
 
     pub fn write_table(
@@ -989,13 +1570,16 @@ impl ContractManager {
 
     pub fn verify_code(&self, code: &str) -> SlvrResult<()> {
         if code.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: "Code cannot be empty".to_string(),
-            });
+            return Err(SlvrError::runtime("Code cannot be empty"));
         }
         Ok(())
     }
 
+    pub fn get_abi(&self, contract_id: &str) -> SlvrResult<serde_json::Value> {
+        let contract = self.get_contract(contract_id)?;
+        Ok(contract.abi())
+    }
+
     pub fn get_module_info(&self, contract_id: &str) -> SlvrResult<serde_json::Value> {
         let contract = self.get_contract(contract_id)?;
         serde_json::to_value(serde_json::json!({
@@ -1004,9 +1588,7 @@ impl ContractManager {
             "schemas": contract.module.schemas.len(),
             "tables": contract.state.tables.len(),
         }))
-        .map_err(|_| SlvrError::RuntimeError {
-            message: "Serialization error".to_string(),
-        })
+        .map_err(|_| SlvrError::runtime("Serialization error"))
     }
 }
 
@@ -1022,6 +1604,8 @@ impl Clone for ContractManager {
             contracts: Arc::clone(&self.contracts),
             contract_addresses: Arc::clone(&self.contract_addresses),
             execution_history: Arc::clone(&self.execution_history),
+            events: Arc::clone(&self.events),
+            config: self.config.clone(),
         }
     }
 }
@@ -1065,6 +1649,54 @@ mod tests {
     }
 
     #[test]
+    fn test_storage_bytes() {
+        let mut contract = SlvrContract::new(
+            "test".to_string(),
+            "module test \"Test module\" { defun test-fn () -> integer 42 }".to_string(),
+            "author".to_string(),
+            "1.0.0".to_string(),
+        )
+        .unwrap();
+        let mut table = TableDefinition::new("accounts".to_string(), "schema".to_string());
+        assert_eq!(table.storage_bytes(), 0);
+
+        table
+            .insert("alice".to_string(), serde_json::json!({"balance": 100}))
+            .unwrap();
+        let one_row = table.storage_bytes();
+        assert_eq!(one_row, "alice".len() + r#"{"balance":100}"#.len());
+
+        table
+            .insert("bob".to_string(), serde_json::json!({"balance": 5}))
+            .unwrap();
+        assert!(table.storage_bytes() > one_row);
+
+        contract.state.tables.insert("accounts".to_string(), table);
+        contract
+            .state
+            .variables
+            .insert("total".to_string(), serde_json::json!(105));
+        let full = contract.total_storage_bytes();
+        assert!(full > one_row);
+
+        let accounts = contract.state.tables.get_mut("accounts").unwrap();
+        accounts.delete("bob").unwrap();
+        assert_eq!(accounts.storage_bytes(), one_row);
+        assert!(contract.total_storage_bytes() < full);
+    }
+
+    fn deployment_request(name: &str, source_code: &str) -> DeploymentRequest {
+        DeploymentRequest {
+            name: name.to_string(),
+            source_code: source_code.to_string(),
+            author: "author".to_string(),
+            version: "1.0.0".to_string(),
+            deployer: "admin".to_string(),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_contract_manager() {
         let manager = ContractManager::new();
         let request = DeploymentRequest {
@@ -1090,6 +1722,575 @@ mod tests {
     }
 
     #[test]
+    fn test_paused_contract_rejects_state_changes() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "test",
+                "module test \"Test module\" { \
+                    defun set (x: integer) -> integer x \
+                    defun get () -> integer 1 }",
+            ))
+            .unwrap();
+        let id = contract.metadata.id.clone();
+        manager
+            .contracts
+            .write()
+            .get_mut(&id)
+            .and_then(|c| c.module.functions.get_mut("get"))
+            .unwrap()
+            .set_purity(true);
+
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        let set = CallRequest {
+            contract_id: id.clone(),
+            function: "set".to_string(),
+            args: vec![serde_json::json!(5)],
+            caller: "user".to_string(),
+        };
+        let get = CallRequest {
+            contract_id: id.clone(),
+            function: "get".to_string(),
+            args: vec![],
+            caller: "user".to_string(),
+        };
+
+        assert!(matches!(
+            manager.set_paused(&id, "user", true),
+            Err(SlvrError::Unauthorized {
+                action: "pause",
+                ..
+            })
+        ));
+        manager.set_paused(&id, "admin", true).unwrap();
+        assert!(matches!(
+            manager.call_function(&set, &runtime),
+            Err(SlvrError::Paused { .. })
+        ));
+        // Read-only calls never write the contract back
+        let before = manager.get_contract(&id).unwrap().metadata.updated_at;
+        assert!(manager.call_function(&get, &runtime).is_ok());
+        let after = manager.get_contract(&id).unwrap();
+        assert!(after.paused);
+        assert_eq!(after.metadata.updated_at, before);
+
+        manager.set_paused(&id, "admin", false).unwrap();
+        assert!(manager.call_function(&set, &runtime).is_ok());
+    }
+
+    #[test]
+    fn test_call_coerces_numeric_string_argument() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "test",
+                "module test \"Test module\" { defun set (x: integer) -> integer x }",
+            ))
+            .unwrap();
+        let id = contract.metadata.id.clone();
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        let call = |arg: serde_json::Value| CallRequest {
+            contract_id: id.clone(),
+            function: "set".to_string(),
+            args: vec![arg],
+            caller: "user".to_string(),
+        };
+
+        let result = manager
+            .call_function(&call(serde_json::json!("5")), &runtime)
+            .unwrap();
+        assert_eq!(
+            result.state_changes[0].new_value,
+            Some(serde_json::json!(5))
+        );
+
+        assert!(matches!(
+            manager.call_function(&call(serde_json::json!("five")), &runtime),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(matches!(
+            manager.call_function(&call(serde_json::json!(true)), &runtime),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_call_fuel_comes_from_runtime_schedule() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "test",
+                "module test \"Test module\" { defun set (x: integer) -> integer x }",
+            ))
+            .unwrap();
+        let schedule = crate::runtime::FuelSchedule::new()
+            .with_builtin_cost("call", 5)
+            .with_builtin_cost("call.arg-byte", 2);
+        let runtime = crate::runtime::Runtime::new(1_000_000).with_fuel_schedule(schedule);
+        let call = CallRequest {
+            contract_id: contract.metadata.id.clone(),
+            function: "set".to_string(),
+            args: vec![serde_json::json!(5)],
+            caller: "user".to_string(),
+        };
+
+        // One argument byte, `5`
+        let result = manager.call_function(&call, &runtime).unwrap();
+        assert_eq!(result.fuel_used, 7);
+    }
+
+    #[test]
+    fn test_coerce_arg_scalar_types() {
+        assert_eq!(
+            coerce_arg("x", "decimal", &serde_json::json!("1.5")).unwrap(),
+            serde_json::json!(1.5)
+        );
+        assert_eq!(
+            coerce_arg("x", "string", &serde_json::json!(7)).unwrap(),
+            serde_json::json!("7")
+        );
+        assert_eq!(
+            coerce_arg("x", "boolean", &serde_json::json!("true")).unwrap(),
+            serde_json::json!(true)
+        );
+        assert!(coerce_arg("x", "boolean", &serde_json::json!(1)).is_err());
+        assert!(coerce_arg("x", "object", &serde_json::json!([])).is_err());
+
+        // Digits past f64 precision survive in both directions
+        let precise: serde_json::Value = serde_json::from_str("0.1000000000000000000001").unwrap();
+        assert_eq!(
+            coerce_arg("x", "decimal", &precise).unwrap().to_string(),
+            "0.1000000000000000000001"
+        );
+        assert_eq!(
+            coerce_arg(
+                "x",
+                "decimal",
+                &serde_json::json!("0.1000000000000000000001")
+            )
+            .unwrap()
+            .to_string(),
+            "0.1000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_deploy_reports_all_compile_errors() {
+        let manager = ContractManager::new();
+        let result = manager.deploy(deployment_request(
+            "broken",
+            "module broken \"Broken\" { \
+                deftable accounts: no-such-schema \
+                deftable ledger: also-missing }",
+        ));
+
+        match result {
+            Err(SlvrError::CompilationFailed { diagnostics }) => {
+                assert_eq!(diagnostics.len(), 2);
+                assert!(diagnostics
+                    .iter()
+                    .all(|d| matches!(d.error, SlvrError::TypeError { .. })));
+                assert_eq!(diagnostics[0].location, "broken.accounts");
+                assert_eq!(diagnostics[1].location, "broken.ledger");
+            }
+            other => panic!("expected compile diagnostics, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_upgrade_runs_migrate_atomically() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "test",
+                "module test \"v1\" { defun set (x: integer) -> integer x }",
+            ))
+            .unwrap();
+        let id = contract.metadata.id.clone();
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+        manager
+            .call_function(
+                &CallRequest {
+                    contract_id: id.clone(),
+                    function: "set".to_string(),
+                    args: vec![serde_json::json!(5)],
+                    caller: "user".to_string(),
+                },
+                &runtime,
+            )
+            .unwrap();
+
+        // A migrate that fails leaves the old contract in place
+        let broken = "module test \"v2\" { \
+            defun migrate (old-version: string, state: object) -> object { value: state.missing } }";
+        assert!(manager
+            .upgrade(&id, "admin", broken.to_string(), "2.0.0".to_string())
+            .is_err());
+        let unchanged = manager.get_contract(&id).unwrap();
+        assert_eq!(unchanged.metadata.version, "1.0.0");
+        assert_eq!(
+            unchanged.state.variables.get("set_x"),
+            Some(&serde_json::json!(5))
+        );
+
+        let renaming = "module test \"v2\" { \
+            defun migrate (old-version: string, state: object) -> object { value: state.set_x } }";
+        assert!(matches!(
+            manager.upgrade(&id, "user", renaming.to_string(), "2.0.0".to_string()),
+            Err(SlvrError::Unauthorized {
+                action: "upgrade",
+                ..
+            })
+        ));
+        let upgraded = manager
+            .upgrade(&id, "admin", renaming.to_string(), "2.0.0".to_string())
+            .unwrap();
+        assert_eq!(upgraded.metadata.id, id);
+        assert_eq!(upgraded.metadata.address, contract.metadata.address);
+        assert_eq!(upgraded.metadata.version, "2.0.0");
+        assert_eq!(upgraded.state.variables.len(), 1);
+        assert_eq!(
+            upgraded.state.variables.get("value"),
+            Some(&serde_json::json!(5))
+        );
+        assert_eq!(
+            manager.get_contract(&id).unwrap().state.variables,
+            upgraded.state.variables
+        );
+    }
+
+    #[test]
+    fn test_upgrade_checks_schemas_and_meters_migrate() {
+        let module = |schema: &str, extra: &str| {
+            format!(
+                "module test \"v\" {{ defschema account {{ {} }} \
+                 deftable accounts: account {} }}",
+                schema, extra
+            )
+        };
+        let manager = ContractManager::with_config(SlvrConfig::new().with_max_fuel_per_tx(10_000));
+        let contract = manager
+            .deploy(deployment_request("test", &module("balance: integer", "")))
+            .unwrap();
+        let id = contract.metadata.id.clone();
+        manager
+            .write_table(
+                &id,
+                "accounts",
+                "alice".to_string(),
+                serde_json::json!({ "balance": 5 }),
+            )
+            .unwrap();
+
+        let upgrade = |source: String| manager.upgrade(&id, "admin", source, "2.0.0".to_string());
+        // Stored rows hold integers and no owner
+        assert!(upgrade(module("balance: string", "")).is_err());
+        assert!(upgrade(module("balance: integer, owner: string", "")).is_err());
+        assert_eq!(manager.get_contract(&id).unwrap().metadata.version, "1.0.0");
+
+        // A migrate doing more work than the budget allows runs out of fuel
+        let spinning = "defun spin (n: integer) -> integer \
+             if n == 0 0 else spin(n - 1) + spin(n - 1) \
+             defun migrate (old-version: string, state: object) -> object \
+             { value: spin(40) }";
+        assert!(matches!(
+            upgrade(module("balance: decimal", spinning)),
+            Err(SlvrError::FuelExceeded { .. })
+        ));
+        assert!(upgrade(module("balance: decimal", "")).is_ok());
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_bytecode_format() {
+        let mut contract = SlvrContract::new(
+            "test".to_string(),
+            "module test \"Test\" { defun f () -> integer 1 }".to_string(),
+            "author".to_string(),
+            "1.0.0".to_string(),
+        )
+        .unwrap();
+        assert_eq!(contract.bytecode[..4], crate::bytecode::BYTECODE_MAGIC);
+        assert_eq!(
+            contract.bytecode[4],
+            crate::bytecode::BYTECODE_FORMAT_VERSION
+        );
+        assert!(contract.verify().is_ok());
+
+        contract.bytecode[4] = 0xff;
+        assert!(contract.verify().is_err());
+    }
+
+    #[test]
+    fn test_contract_abi() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "token",
+                "module token \"Token\" { \
+                    defschema account { balance: integer, owner: string } \
+                    defun transfer (to: string, amount: integer) -> boolean true \
+                    defun total () -> integer 0 }",
+            ))
+            .unwrap();
+
+        let abi = manager.get_abi(&contract.metadata.id).unwrap();
+        let functions = abi["functions"].as_array().unwrap();
+        let names: Vec<_> = functions
+            .iter()
+            .map(|f| f["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["total", "transfer"]);
+
+        let transfer = &functions[1];
+        assert_eq!(
+            transfer["parameters"],
+            serde_json::json!([
+                {"name": "to", "type": "string"},
+                {"name": "amount", "type": "integer"},
+            ])
+        );
+        assert_eq!(transfer["returns"], "boolean");
+        assert_eq!(abi["schemas"][0]["name"], "account");
+        assert_eq!(abi["schemas"][0]["fields"][0]["name"], "balance");
+    }
+
+    #[test]
+    fn test_list_table_rows_pagination() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "test",
+                "module test \"Test module\" { defun test-fn () -> integer 42 }",
+            ))
+            .unwrap();
+        let id = contract.metadata.id;
+
+        for key in ["e", "b", "d", "a", "c"] {
+            manager
+                .write_table(
+                    &id,
+                    "accounts",
+                    key.to_string(),
+                    serde_json::json!({"k": key}),
+                )
+                .unwrap();
+        }
+
+        let keys = |page| -> Vec<String> {
+            manager
+                .list_table_rows(&id, "accounts", page, 2)
+                .unwrap()
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect()
+        };
+        assert_eq!(keys(0), vec!["a", "b"]);
+        assert_eq!(keys(1), vec!["c", "d"]);
+        assert_eq!(keys(2), vec!["e"]);
+        assert!(keys(3).is_empty());
+        assert_eq!(keys(1), keys(1));
+
+        let rows = manager.list_table_rows(&id, "accounts", 0, 10).unwrap();
+        assert_eq!(rows[0].1, serde_json::json!({"k": "a"}));
+        assert!(manager.list_table_rows(&id, "accounts", 0, 0).is_err());
+        assert!(manager.list_table_rows(&id, "missing", 0, 2).is_err());
+    }
+
+    #[test]
+    fn test_contract_address_format() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "test",
+                "module test \"Test module\" { defun test-fn () -> integer 42 }",
+            ))
+            .unwrap();
+
+        let address = &contract.metadata.address;
+        assert_eq!(*address, address::derive(&contract.metadata.id));
+        assert!(crate::account_api::Account::is_valid_address(address));
+        assert!(manager.get_contract_by_address(address).is_ok());
+        assert!(manager.get_contract_by_address("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_readonly_runtime_rejects_writes() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "test",
+                "module test \"Test module\" { \
+                    defun set (x: integer) -> integer x \
+                    defun get (x: integer) -> integer x }",
+            ))
+            .unwrap();
+        let id = contract.metadata.id.clone();
+        manager
+            .contracts
+            .write()
+            .get_mut(&id)
+            .and_then(|c| c.module.functions.get_mut("get"))
+            .unwrap()
+            .set_purity(true);
+
+        let call = |function: &str| CallRequest {
+            contract_id: id.clone(),
+            function: function.to_string(),
+            args: vec![serde_json::json!(5)],
+            caller: "user".to_string(),
+        };
+        let runtime = crate::runtime::Runtime::new(1_000_000);
+
+        let view = runtime.execute_readonly(|rt| manager.call_function(&call("get"), rt));
+        assert!(view.is_ok());
+
+        let write = runtime.execute_readonly(|rt| manager.call_function(&call("set"), rt));
+        assert!(matches!(
+            write,
+            Err(SlvrError::StateMutationForbidden { .. })
+        ));
+        assert!(manager
+            .get_contract(&id)
+            .unwrap()
+            .state
+            .variables
+            .is_empty());
+
+        // Outside read-only mode the write goes through
+        assert!(manager.call_function(&call("set"), &runtime).is_ok());
+        assert_eq!(manager.get_contract(&id).unwrap().state.variables.len(), 1);
+    }
+
+    #[test]
+    fn test_diff_rows_reports_changed_fields_only() {
+        let old = serde_json::json!({"balance": 100, "owner": "alice", "frozen": false});
+        let new = serde_json::json!({"balance": 80, "owner": "alice", "frozen": true});
+
+        let changes = diff_rows(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    field: "balance".to_string(),
+                    old_value: Some(serde_json::json!(100)),
+                    new_value: Some(serde_json::json!(80)),
+                },
+                FieldChange {
+                    field: "frozen".to_string(),
+                    old_value: Some(serde_json::json!(false)),
+                    new_value: Some(serde_json::json!(true)),
+                },
+            ]
+        );
+
+        assert!(diff_rows(&old, &old).is_empty());
+        let added = diff_rows(&serde_json::json!({}), &serde_json::json!({"memo": "x"}));
+        assert_eq!(added[0].old_value, None);
+    }
+
+    #[test]
+    fn test_events_since_cursor() {
+        let manager = ContractManager::new();
+        let contract = manager
+            .deploy(deployment_request(
+                "token",
+                "module token \"Token\" { \
+                    defun total () -> integer 0 \
+                    defun approve (spender: string) -> integer 0 }",
+            ))
+            .unwrap();
+        let id = contract.metadata.id;
+        let runtime = crate::runtime::Runtime::with_context(
+            1_000_000,
+            crate::runtime::ExecutionContext {
+                block_timestamp: 1_700_000_000,
+                ..Default::default()
+            },
+        );
+
+        let emit = |name: &str, to: &str| {
+            let mut indexed = BTreeMap::new();
+            indexed.insert("to".to_string(), serde_json::json!(to));
+            manager
+                .emit_event(
+                    &id,
+                    name.to_string(),
+                    indexed,
+                    serde_json::json!({"amount": 1}),
+                    &runtime,
+                )
+                .unwrap()
+        };
+        assert_eq!(emit("Transfer", "alice"), 0);
+
+        let (first, cursor) = manager.events_since(&id, 0);
+        assert_eq!(first.len(), 1);
+        assert_eq!(cursor, 1);
+        assert_eq!(first[0].timestamp.timestamp(), 1_700_000_000);
+
+        emit("Approval", "bob");
+        emit("Transfer", "bob");
+        let (new, next) = manager.events_since(&id, cursor);
+        let names: Vec<_> = new.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Approval", "Transfer"]);
+        assert_eq!(new[0].index, 1);
+        assert_eq!(next, 3);
+        assert!(manager.events_since(&id, next).0.is_empty());
+
+        // A node that decodes the event from the wire hashes it the same way
+        let relayed: ContractEvent =
+            serde_json::from_str(&serde_json::to_string(&new[0]).unwrap()).unwrap();
+        assert_eq!(relayed.hash().unwrap(), new[0].hash().unwrap());
+        assert_ne!(new[0].hash().unwrap(), new[1].hash().unwrap());
+
+        let filter = EventFilter::new()
+            .with_name("Transfer")
+            .with_indexed("to", serde_json::json!("bob"));
+        let (filtered, filtered_cursor) = manager.events_matching(&id, 0, &filter);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].index, 2);
+        assert_eq!(filtered_cursor, 3);
+
+        assert!(manager
+            .emit_event(
+                "missing",
+                "X".to_string(),
+                BTreeMap::new(),
+                serde_json::json!(null),
+                &runtime
+            )
+            .is_err());
+
+        // A state-changing call logs itself; a read-only one does not
+        let call = |function: &str, args| CallRequest {
+            contract_id: id.clone(),
+            function: function.to_string(),
+            args,
+            caller: "carol".to_string(),
+        };
+        manager
+            .contracts
+            .write()
+            .get_mut(&id)
+            .and_then(|c| c.module.functions.get_mut("total"))
+            .unwrap()
+            .set_purity(true);
+        manager
+            .call_function(&call("total", vec![]), &runtime)
+            .unwrap();
+        manager
+            .call_function(&call("approve", vec![serde_json::json!("dave")]), &runtime)
+            .unwrap();
+        let (called, _) = manager.events_since(&id, 3);
+        assert_eq!(called.len(), 1);
+        assert_eq!(called[0].name, "approve");
+        assert_eq!(called[0].indexed["caller"], serde_json::json!("carol"));
+        assert_eq!(called[0].data, serde_json::json!({"spender": "dave"}));
+        assert_eq!(called[0].timestamp.timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn test_list_contracts() {
         let manager = ContractManager::new();
         let request = DeploymentRequest {
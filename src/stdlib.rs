@@ -4,9 +4,13 @@
 //! mathematical operations, cryptographic functions, list operations, and more.
 
 use crate::error::{SlvrError, SlvrResult};
-use crate::value::Value;
+use crate::runtime::TimeSource;
+use crate::value::{
+    checked_decimal, decimal_pow, format_decimal, format_time, integer_to_decimal, parse_decimal,
+    Decimal, Value,
+};
 use sha2::{Digest, Sha512};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// String manipulation functions
 pub mod string {
@@ -18,109 +22,102 @@ pub mod string {
             match arg {
                 Value::String(s) => result.push_str(&s),
                 Value::Integer(i) => result.push_str(&i.to_string()),
-                Value::Decimal(d) => result.push_str(&d.to_string()),
+                Value::Decimal(d) => result.push_str(&format_decimal(d)),
                 Value::Boolean(b) => result.push_str(if b { "true" } else { "false" }),
                 _ => {
-                    return Err(SlvrError::TypeError {
-                        message: "concat requires string-convertible values".to_string(),
-                    })
+                    return Err(SlvrError::type_error(
+                        "concat requires string-convertible values",
+                    ))
                 }
             }
         }
         Ok(Value::String(result))
     }
 
+    /// Number of characters (Unicode scalar values) in a string
     pub fn length(s: Value) -> SlvrResult<Value> {
+        match s {
+            Value::String(s) => Ok(Value::Integer(s.chars().count() as i128)),
+            _ => Err(SlvrError::type_error("length requires a string")),
+        }
+    }
+
+    /// Number of bytes in the UTF-8 encoding of a string
+    pub fn byte_length(s: Value) -> SlvrResult<Value> {
         match s {
             Value::String(s) => Ok(Value::Integer(s.len() as i128)),
-            _ => Err(SlvrError::TypeError {
-                message: "length requires a string".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("byte-length requires a string")),
         }
     }
 
+    /// Characters `start..end` of a string, counted in characters rather than bytes
     pub fn substring(s: Value, start: Value, end: Value) -> SlvrResult<Value> {
         let string = match s {
             Value::String(s) => s,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "substring requires a string".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("substring requires a string")),
         };
 
         let start_idx = match start {
-            Value::Integer(i) => i as usize,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "substring start must be an integer".to_string(),
-                })
-            }
+            Value::Integer(i) => i,
+            _ => return Err(SlvrError::type_error("substring start must be an integer")),
         };
 
         let end_idx = match end {
-            Value::Integer(i) => i as usize,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "substring end must be an integer".to_string(),
-                })
-            }
+            Value::Integer(i) => i,
+            _ => return Err(SlvrError::type_error("substring end must be an integer")),
         };
 
-        if start_idx > string.len() || end_idx > string.len() || start_idx > end_idx {
+        let char_count = string.chars().count();
+        if start_idx < 0 || start_idx > end_idx || end_idx > char_count as i128 {
             return Err(SlvrError::IndexOutOfBounds {
                 index: start_idx as i64,
-                length: string.len(),
+                length: char_count,
             });
         }
 
-        Ok(Value::String(string[start_idx..end_idx].to_string()))
+        let start_byte = char_to_byte_offset(&string, start_idx as usize);
+        let end_byte = char_to_byte_offset(&string, end_idx as usize);
+        Ok(Value::String(string[start_byte..end_byte].to_string()))
+    }
+
+    /// Byte offset of the character at `char_idx`, or the length at the end
+    fn char_to_byte_offset(s: &str, char_idx: usize) -> usize {
+        s.char_indices()
+            .nth(char_idx)
+            .map(|(offset, _)| offset)
+            .unwrap_or(s.len())
     }
 
     pub fn to_upper(s: Value) -> SlvrResult<Value> {
         match s {
             Value::String(s) => Ok(Value::String(s.to_uppercase())),
-            _ => Err(SlvrError::TypeError {
-                message: "to-upper requires a string".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("to-upper requires a string")),
         }
     }
 
     pub fn to_lower(s: Value) -> SlvrResult<Value> {
         match s {
             Value::String(s) => Ok(Value::String(s.to_lowercase())),
-            _ => Err(SlvrError::TypeError {
-                message: "to-lower requires a string".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("to-lower requires a string")),
         }
     }
 
     pub fn trim(s: Value) -> SlvrResult<Value> {
         match s {
             Value::String(s) => Ok(Value::String(s.trim().to_string())),
-            _ => Err(SlvrError::TypeError {
-                message: "trim requires a string".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("trim requires a string")),
         }
     }
 
     pub fn split(s: Value, delimiter: Value) -> SlvrResult<Value> {
         let string = match s {
             Value::String(s) => s,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "split requires a string".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("split requires a string")),
         };
 
         let delim = match delimiter {
             Value::String(d) => d,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "split delimiter must be a string".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("split delimiter must be a string")),
         };
 
         let parts: Vec<Value> = string
@@ -131,36 +128,201 @@ pub mod string {
         Ok(Value::List(parts))
     }
 
+    /// Join a list of strings with `delimiter`; the inverse of `split`
+    pub fn join(list: Value, delimiter: Value) -> SlvrResult<Value> {
+        let (items, delim) = match (list, delimiter) {
+            (Value::List(items), Value::String(d)) => (items, d),
+            _ => {
+                return Err(SlvrError::type_error(
+                    "join requires a list and a string delimiter",
+                ))
+            }
+        };
+
+        let mut parts = Vec::with_capacity(items.len());
+        for (i, item) in items.into_iter().enumerate() {
+            match item {
+                Value::String(s) => parts.push(s),
+                other => {
+                    return Err(SlvrError::type_error(format!(
+                        "join element {} must be a string, got {}",
+                        i,
+                        other.type_name()
+                    )))
+                }
+            }
+        }
+
+        Ok(Value::String(parts.join(&delim)))
+    }
+
     pub fn contains(s: Value, substring: Value) -> SlvrResult<Value> {
         let string = match s {
             Value::String(s) => s,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "contains requires a string".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("contains requires a string")),
         };
 
         let substr = match substring {
             Value::String(s) => s,
+            _ => return Err(SlvrError::type_error("contains substring must be a string")),
+        };
+
+        Ok(Value::Boolean(string.contains(&substr)))
+    }
+
+    /// Character index of the first occurrence of `needle`, or -1.
+    ///
+    /// An empty needle matches at index 0.
+    pub fn index_of(s: Value, needle: Value) -> SlvrResult<Value> {
+        let (string, needle) = search_args(s, needle, "index-of")?;
+        Ok(char_index(&string, string.find(&needle)))
+    }
+
+    /// Character index of the last occurrence of `needle`, or -1.
+    ///
+    /// An empty needle matches at the end of the string.
+    pub fn last_index_of(s: Value, needle: Value) -> SlvrResult<Value> {
+        let (string, needle) = search_args(s, needle, "last-index-of")?;
+        Ok(char_index(&string, string.rfind(&needle)))
+    }
+
+    fn search_args(s: Value, needle: Value, name: &str) -> SlvrResult<(String, String)> {
+        match (s, needle) {
+            (Value::String(s), Value::String(needle)) => Ok((s, needle)),
+            _ => Err(SlvrError::type_error(format!("{} requires strings", name))),
+        }
+    }
+
+    /// Convert a byte offset match into a character index, -1 for no match
+    fn char_index(s: &str, byte_offset: Option<usize>) -> Value {
+        match byte_offset {
+            Some(offset) => Value::Integer(s[..offset].chars().count() as i128),
+            None => Value::Integer(-1),
+        }
+    }
+
+    /// Repeat a string `count` times, up to `MAX_STRING_LENGTH` bytes
+    pub fn repeat(s: Value, count: Value) -> SlvrResult<Value> {
+        repeat_with_max_length(s, count, crate::MAX_STRING_LENGTH)
+    }
+
+    /// Repeat a string `count` times, rejecting results longer than
+    /// `max_length` bytes (normally `SlvrConfig::max_string_length`).
+    ///
+    /// The length is checked before anything is allocated.
+    pub fn repeat_with_max_length(s: Value, count: Value, max_length: usize) -> SlvrResult<Value> {
+        let (string, count) = match (s, count) {
+            (Value::String(s), Value::Integer(c)) => (s, c),
             _ => {
-                return Err(SlvrError::TypeError {
-                    message: "contains substring must be a string".to_string(),
-                })
+                return Err(SlvrError::type_error(
+                    "repeat requires a string and an integer count",
+                ))
             }
         };
 
-        Ok(Value::Boolean(string.contains(&substr)))
+        if count < 0 {
+            return Err(SlvrError::runtime(format!(
+                "repeat count must not be negative, got {}",
+                count
+            )));
+        }
+
+        let total = (string.len() as i128).saturating_mul(count);
+        if total > max_length as i128 {
+            return Err(SlvrError::runtime(format!(
+                "repeat output of {} bytes exceeds maximum of {}",
+                total, max_length
+            )));
+        }
+
+        Ok(Value::String(string.repeat(count as usize)))
+    }
+
+    /// Pad the start of a string with `fill` up to `width` characters
+    pub fn pad_left(s: Value, width: Value, fill: Value) -> SlvrResult<Value> {
+        pad(s, width, fill, "pad-left", true)
+    }
+
+    /// Pad the end of a string with `fill` up to `width` characters
+    pub fn pad_right(s: Value, width: Value, fill: Value) -> SlvrResult<Value> {
+        pad(s, width, fill, "pad-right", false)
+    }
+
+    fn pad(s: Value, width: Value, fill: Value, name: &str, left: bool) -> SlvrResult<Value> {
+        let (string, width, fill) = match (s, width, fill) {
+            (Value::String(s), Value::Integer(w), Value::String(f)) => (s, w, f),
+            _ => {
+                return Err(SlvrError::type_error(format!(
+                    "{} requires a string, an integer width and a fill",
+                    name
+                )))
+            }
+        };
+
+        let mut fill_chars = fill.chars();
+        let fill = match (fill_chars.next(), fill_chars.next()) {
+            (Some(c), None) => c,
+            _ => {
+                return Err(SlvrError::type_error(format!(
+                    "{} fill must be a single character",
+                    name
+                )))
+            }
+        };
+
+        let len = string.chars().count() as i128;
+        if len >= width {
+            return Ok(Value::String(string));
+        }
+        if width > MAX_BUILD_LENGTH as i128 {
+            return Err(SlvrError::runtime(format!(
+                "{} width {} exceeds maximum of {}",
+                name, width, MAX_BUILD_LENGTH
+            )));
+        }
+
+        let padding: String = std::iter::repeat_n(fill, (width - len) as usize).collect();
+        Ok(Value::String(if left {
+            padding + &string
+        } else {
+            string + &padding
+        }))
+    }
+
+    /// Replace every non-overlapping occurrence of `from` with `to`
+    pub fn replace(s: Value, from: Value, to: Value) -> SlvrResult<Value> {
+        let (string, from, to) = replace_args(s, from, to, "replace")?;
+        if from.is_empty() {
+            return Ok(Value::String(string));
+        }
+        Ok(Value::String(string.replace(&from, &to)))
+    }
+
+    /// Replace the first occurrence of `from` with `to`
+    pub fn replace_first(s: Value, from: Value, to: Value) -> SlvrResult<Value> {
+        let (string, from, to) = replace_args(s, from, to, "replace-first")?;
+        if from.is_empty() {
+            return Ok(Value::String(string));
+        }
+        Ok(Value::String(string.replacen(&from, &to, 1)))
+    }
+
+    fn replace_args(
+        s: Value,
+        from: Value,
+        to: Value,
+        name: &str,
+    ) -> SlvrResult<(String, String, String)> {
+        match (s, from, to) {
+            (Value::String(s), Value::String(from), Value::String(to)) => Ok((s, from, to)),
+            _ => Err(SlvrError::type_error(format!("{} requires strings", name))),
+        }
     }
 
     pub fn format(template: Value, args: Vec<Value>) -> SlvrResult<Value> {
         let template_str = match template {
             Value::String(s) => s,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "format requires a string template".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("format requires a string template")),
         };
 
         let mut result = template_str.clone();
@@ -169,7 +331,7 @@ pub mod string {
             let replacement = match arg {
                 Value::String(s) => s.clone(),
                 Value::Integer(i) => i.to_string(),
-                Value::Decimal(d) => d.to_string(),
+                Value::Decimal(d) => format_decimal(*d),
                 Value::Boolean(b) => if *b { "true" } else { "false" }.to_string(),
                 _ => format!("{:?}", arg),
             };
@@ -178,19 +340,87 @@ pub mod string {
 
         Ok(Value::String(result))
     }
+
+    /// Longest string `build` will produce, in bytes
+    pub const MAX_BUILD_LENGTH: usize = crate::MAX_STRING_LENGTH;
+
+    /// Fuel charged per output byte by `build_metered`
+    pub const BUILD_FUEL_PER_BYTE: u64 = 1;
+
+    /// Join a list of string-convertible values in one pass.
+    ///
+    /// The output is allocated once and may not exceed `MAX_BUILD_LENGTH` bytes.
+    pub fn build(parts: Value) -> SlvrResult<Value> {
+        assemble(parts, MAX_BUILD_LENGTH, |_| Ok(()))
+    }
+
+    /// `build` with a caller-chosen length cap, charging fuel for the output.
+    ///
+    /// Fuel is charged before the output is allocated, so an over-budget build
+    /// fails without doing the work.
+    pub fn build_metered(
+        parts: Value,
+        max_length: usize,
+        runtime: &crate::runtime::Runtime,
+    ) -> SlvrResult<Value> {
+        assemble(parts, max_length, |len| {
+            runtime.consume_fuel(BUILD_FUEL_PER_BYTE.saturating_mul(len as u64))
+        })
+    }
+
+    fn assemble(
+        parts: Value,
+        max_length: usize,
+        charge: impl FnOnce(usize) -> SlvrResult<()>,
+    ) -> SlvrResult<Value> {
+        let parts = match parts {
+            Value::List(parts) => parts,
+            _ => return Err(SlvrError::type_error("build requires a list")),
+        };
+
+        let mut pieces = Vec::with_capacity(parts.len());
+        for part in parts {
+            pieces.push(match part {
+                Value::String(s) => s,
+                Value::Integer(i) => i.to_string(),
+                Value::Decimal(d) => format_decimal(d),
+                Value::Boolean(b) => b.to_string(),
+                _ => {
+                    return Err(SlvrError::type_error(
+                        "build requires string-convertible values",
+                    ))
+                }
+            });
+        }
+
+        let total: usize = pieces.iter().map(String::len).sum();
+        if total > max_length {
+            return Err(SlvrError::runtime(format!(
+                "build output of {} bytes exceeds maximum of {}",
+                total, max_length
+            )));
+        }
+        charge(total)?;
+
+        let mut result = String::with_capacity(total);
+        for piece in &pieces {
+            result.push_str(piece);
+        }
+        Ok(Value::String(result))
+    }
 }
 
 /// Mathematical functions
 pub mod math {
     use super::*;
+    use rust_decimal::prelude::ToPrimitive;
+    use rust_decimal::{MathematicalOps, RoundingStrategy};
 
     pub fn abs(n: Value) -> SlvrResult<Value> {
         match n {
             Value::Integer(i) => Ok(Value::Integer(i.abs())),
             Value::Decimal(d) => Ok(Value::Decimal(d.abs())),
-            _ => Err(SlvrError::TypeError {
-                message: "abs requires a number".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("abs requires a number")),
         }
     }
 
@@ -198,11 +428,13 @@ pub mod math {
         match (a, b) {
             (Value::Integer(x), Value::Integer(y)) => Ok(Value::Integer(x.min(y))),
             (Value::Decimal(x), Value::Decimal(y)) => Ok(Value::Decimal(x.min(y))),
-            (Value::Integer(x), Value::Decimal(y)) => Ok(Value::Decimal((x as f64).min(y))),
-            (Value::Decimal(x), Value::Integer(y)) => Ok(Value::Decimal(x.min(y as f64))),
-            _ => Err(SlvrError::TypeError {
-                message: "min requires numbers".to_string(),
-            }),
+            (Value::Integer(x), Value::Decimal(y)) => {
+                Ok(Value::Decimal(integer_to_decimal(x)?.min(y)))
+            }
+            (Value::Decimal(x), Value::Integer(y)) => {
+                Ok(Value::Decimal(x.min(integer_to_decimal(y)?)))
+            }
+            _ => Err(SlvrError::type_error("min requires numbers")),
         }
     }
 
@@ -210,132 +442,390 @@ pub mod math {
         match (a, b) {
             (Value::Integer(x), Value::Integer(y)) => Ok(Value::Integer(x.max(y))),
             (Value::Decimal(x), Value::Decimal(y)) => Ok(Value::Decimal(x.max(y))),
-            (Value::Integer(x), Value::Decimal(y)) => Ok(Value::Decimal((x as f64).max(y))),
-            (Value::Decimal(x), Value::Integer(y)) => Ok(Value::Decimal(x.max(y as f64))),
-            _ => Err(SlvrError::TypeError {
-                message: "max requires numbers".to_string(),
-            }),
+            (Value::Integer(x), Value::Decimal(y)) => {
+                Ok(Value::Decimal(integer_to_decimal(x)?.max(y)))
+            }
+            (Value::Decimal(x), Value::Integer(y)) => {
+                Ok(Value::Decimal(x.max(integer_to_decimal(y)?)))
+            }
+            _ => Err(SlvrError::type_error("max requires numbers")),
         }
     }
 
-    pub fn sqrt(n: Value) -> SlvrResult<Value> {
-        match n {
-            Value::Integer(i) => {
-                if i < 0 {
-                    return Err(SlvrError::RuntimeError {
-                        message: "sqrt of negative number".to_string(),
-                    });
+    /// Bound `x` to `[lo, hi]`. The result is an integer when all three
+    /// arguments are integers, as with `min` and `max`.
+    pub fn clamp(x: Value, lo: Value, hi: Value) -> SlvrResult<Value> {
+        let inverted = || SlvrError::runtime("clamp lower bound is greater than upper bound");
+
+        match (x, lo, hi) {
+            (Value::Integer(x), Value::Integer(lo), Value::Integer(hi)) => {
+                if lo > hi {
+                    return Err(inverted());
                 }
-                Ok(Value::Decimal((i as f64).sqrt()))
+                Ok(Value::Integer(x.clamp(lo, hi)))
             }
-            Value::Decimal(d) => {
-                if d < 0.0 {
-                    return Err(SlvrError::RuntimeError {
-                        message: "sqrt of negative number".to_string(),
-                    });
+            (x, lo, hi) => {
+                let as_decimal = |v: Value| match v {
+                    Value::Integer(i) => integer_to_decimal(i),
+                    Value::Decimal(d) => Ok(d),
+                    _ => Err(SlvrError::type_error("clamp requires numbers")),
+                };
+                let (x, lo, hi) = (as_decimal(x)?, as_decimal(lo)?, as_decimal(hi)?);
+                if lo > hi {
+                    return Err(inverted());
                 }
-                Ok(Value::Decimal(d.sqrt()))
+                Ok(Value::Decimal(x.clamp(lo, hi)))
             }
-            _ => Err(SlvrError::TypeError {
-                message: "sqrt requires a number".to_string(),
-            }),
         }
     }
 
-    pub fn ln(n: Value) -> SlvrResult<Value> {
+    /// `-1`, `0` or `1` according to the sign of `x`
+    pub fn sign(x: Value) -> SlvrResult<Value> {
+        match x {
+            Value::Integer(i) => Ok(Value::Integer(i.signum())),
+            Value::Decimal(d) => Ok(Value::Integer(if d.is_zero() {
+                0
+            } else if d.is_sign_negative() {
+                -1
+            } else {
+                1
+            })),
+            _ => Err(SlvrError::type_error("sign requires a number")),
+        }
+    }
+
+    /// Greatest common divisor of the absolute values; `gcd(0, 0)` is 0
+    pub fn gcd(a: Value, b: Value) -> SlvrResult<Value> {
+        let (a, b) = integer_pair(a, b, "gcd")?;
+        to_integer_result(gcd_u128(a, b), "gcd")
+    }
+
+    /// Least common multiple of the absolute values; 0 if either is 0
+    pub fn lcm(a: Value, b: Value) -> SlvrResult<Value> {
+        let (a, b) = integer_pair(a, b, "lcm")?;
+        if a == 0 || b == 0 {
+            return Ok(Value::Integer(0));
+        }
+        let lcm = (a / gcd_u128(a, b))
+            .checked_mul(b)
+            .ok_or_else(|| SlvrError::runtime("integer overflow in lcm"))?;
+        to_integer_result(lcm, "lcm")
+    }
+
+    fn integer_pair(a: Value, b: Value, name: &str) -> SlvrResult<(u128, u128)> {
+        match (a, b) {
+            (Value::Integer(a), Value::Integer(b)) => Ok((a.unsigned_abs(), b.unsigned_abs())),
+            _ => Err(SlvrError::type_error(format!("{} requires integers", name))),
+        }
+    }
+
+    fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+
+    fn to_integer_result(n: u128, name: &str) -> SlvrResult<Value> {
+        i128::try_from(n)
+            .map(Value::Integer)
+            .map_err(|_| SlvrError::runtime(format!("integer overflow in {}", name)))
+    }
+
+    /// Square root as a decimal.
+    ///
+    /// `sqrt`, `ln`, `log10` and fractional `pow` exponents are the only
+    /// approximate math operations: their results are correct to the last of
+    /// the decimal's 28 significant digits. Everything else is exact.
+    pub fn sqrt(n: Value) -> SlvrResult<Value> {
+        let d = decimal_arg(n, "sqrt")?;
+        if d.is_sign_negative() && !d.is_zero() {
+            return Err(SlvrError::runtime("sqrt of negative number"));
+        }
+        checked_decimal(d.sqrt(), "sqrt")
+    }
+
+    /// An integer or decimal argument as a decimal
+    fn decimal_arg(n: Value, name: &str) -> SlvrResult<Decimal> {
         match n {
-            Value::Integer(i) => {
-                if i <= 0 {
-                    return Err(SlvrError::RuntimeError {
-                        message: "ln of non-positive number".to_string(),
-                    });
-                }
-                Ok(Value::Decimal((i as f64).ln()))
-            }
-            Value::Decimal(d) => {
-                if d <= 0.0 {
-                    return Err(SlvrError::RuntimeError {
-                        message: "ln of non-positive number".to_string(),
-                    });
-                }
-                Ok(Value::Decimal(d.ln()))
-            }
-            _ => Err(SlvrError::TypeError {
-                message: "ln requires a number".to_string(),
-            }),
+            Value::Integer(i) => integer_to_decimal(i),
+            Value::Decimal(d) => Ok(d),
+            _ => Err(SlvrError::type_error(format!("{} requires a number", name))),
         }
     }
 
-    pub fn log10(n: Value) -> SlvrResult<Value> {
+    /// Floor of the square root of a non-negative integer, computed exactly
+    pub fn isqrt(n: Value) -> SlvrResult<Value> {
         match n {
-            Value::Integer(i) => {
-                if i <= 0 {
-                    return Err(SlvrError::RuntimeError {
-                        message: "log10 of non-positive number".to_string(),
-                    });
-                }
-                Ok(Value::Decimal((i as f64).log10()))
-            }
-            Value::Decimal(d) => {
-                if d <= 0.0 {
-                    return Err(SlvrError::RuntimeError {
-                        message: "log10 of non-positive number".to_string(),
-                    });
+            Value::Integer(i) if i < 0 => Err(SlvrError::runtime("isqrt of negative number")),
+            Value::Integer(i) => Ok(Value::Integer(i.isqrt())),
+            _ => Err(SlvrError::type_error("isqrt requires an integer")),
+        }
+    }
+
+    /// Floored remainder: a non-zero result takes the sign of the divisor
+    pub fn modulo(a: Value, b: Value) -> SlvrResult<Value> {
+        remainder(a, b, "modulo", true)
+    }
+
+    /// Truncated remainder: a non-zero result takes the sign of the dividend
+    pub fn rem(a: Value, b: Value) -> SlvrResult<Value> {
+        remainder(a, b, "rem", false)
+    }
+
+    fn remainder(a: Value, b: Value, name: &str, floored: bool) -> SlvrResult<Value> {
+        let division_by_zero = || SlvrError::runtime(format!("{}: division by zero", name));
+
+        let (x, y) = match (a, b) {
+            (Value::Integer(x), Value::Integer(y)) => {
+                if y == 0 {
+                    return Err(division_by_zero());
                 }
-                Ok(Value::Decimal(d.log10()))
+                // i128::MIN % -1 overflows in `%` but is exactly 0
+                let r = x.wrapping_rem(y);
+                let adjust = floored && r != 0 && (r < 0) != (y < 0);
+                return Ok(Value::Integer(if adjust { r + y } else { r }));
             }
-            _ => Err(SlvrError::TypeError {
-                message: "log10 requires a number".to_string(),
-            }),
+            (Value::Decimal(x), Value::Decimal(y)) => (x, y),
+            (Value::Integer(x), Value::Decimal(y)) => (integer_to_decimal(x)?, y),
+            (Value::Decimal(x), Value::Integer(y)) => (x, integer_to_decimal(y)?),
+            _ => return Err(SlvrError::type_error(format!("{} requires numbers", name))),
+        };
+
+        if y.is_zero() {
+            return Err(division_by_zero());
+        }
+        let r = x.checked_rem(y).ok_or_else(division_by_zero)?;
+        let adjust = floored && !r.is_zero() && r.is_sign_negative() != y.is_sign_negative();
+        checked_decimal(if adjust { r.checked_add(y) } else { Some(r) }, name)
+    }
+
+    /// Natural logarithm; approximate, see `sqrt`
+    pub fn ln(n: Value) -> SlvrResult<Value> {
+        let d = decimal_arg(n, "ln")?;
+        if d <= Decimal::ZERO {
+            return Err(SlvrError::runtime("ln of non-positive number"));
+        }
+        checked_decimal(d.checked_ln(), "ln")
+    }
+
+    /// Base-10 logarithm; approximate, see `sqrt`
+    pub fn log10(n: Value) -> SlvrResult<Value> {
+        let d = decimal_arg(n, "log10")?;
+        if d <= Decimal::ZERO {
+            return Err(SlvrError::runtime("log10 of non-positive number"));
         }
+        checked_decimal(d.checked_log10(), "log10")
     }
 
     pub fn pow(base: Value, exponent: Value) -> SlvrResult<Value> {
         match (base, exponent) {
             (Value::Integer(b), Value::Integer(e)) => {
                 if e < 0 {
-                    Ok(Value::Decimal((b as f64).powf(e as f64)))
+                    decimal_pow(integer_to_decimal(b)?, integer_to_decimal(e)?)
                 } else {
-                    Ok(Value::Integer(b.pow(e as u32)))
+                    checked_int_pow(b, e).map(Value::Integer)
                 }
             }
-            (Value::Decimal(b), Value::Integer(e)) => Ok(Value::Decimal(b.powf(e as f64))),
-            (Value::Integer(b), Value::Decimal(e)) => Ok(Value::Decimal((b as f64).powf(e))),
-            (Value::Decimal(b), Value::Decimal(e)) => Ok(Value::Decimal(b.powf(e))),
-            _ => Err(SlvrError::TypeError {
-                message: "pow requires numbers".to_string(),
-            }),
+            // Integral exponents are exact; fractional ones are approximate
+            (Value::Decimal(b), Value::Integer(e)) => decimal_pow(b, integer_to_decimal(e)?),
+            (Value::Integer(b), Value::Decimal(e)) => decimal_pow(integer_to_decimal(b)?, e),
+            (Value::Decimal(b), Value::Decimal(e)) => decimal_pow(b, e),
+            _ => Err(SlvrError::type_error("pow requires numbers")),
+        }
+    }
+
+    /// `base^exponent` for a non-negative exponent, erroring instead of wrapping
+    fn checked_int_pow(base: i128, exponent: i128) -> SlvrResult<i128> {
+        let overflow = || SlvrError::runtime("integer overflow in pow");
+
+        match u32::try_from(exponent) {
+            Ok(e) => base.checked_pow(e).ok_or_else(overflow),
+            // Only these bases stay in range for exponents beyond u32::MAX
+            Err(_) => match base {
+                0 | 1 => Ok(base),
+                -1 => Ok(if exponent % 2 == 0 { 1 } else { -1 }),
+                _ => Err(overflow()),
+            },
         }
     }
 
     pub fn floor(n: Value) -> SlvrResult<Value> {
         match n {
             Value::Integer(i) => Ok(Value::Integer(i)),
-            Value::Decimal(d) => Ok(Value::Integer(d.floor() as i128)),
-            _ => Err(SlvrError::TypeError {
-                message: "floor requires a number".to_string(),
-            }),
+            Value::Decimal(d) => whole_number(d.floor()),
+            _ => Err(SlvrError::type_error("floor requires a number")),
         }
     }
 
     pub fn ceil(n: Value) -> SlvrResult<Value> {
         match n {
             Value::Integer(i) => Ok(Value::Integer(i)),
-            Value::Decimal(d) => Ok(Value::Integer(d.ceil() as i128)),
-            _ => Err(SlvrError::TypeError {
-                message: "ceil requires a number".to_string(),
-            }),
+            Value::Decimal(d) => whole_number(d.ceil()),
+            _ => Err(SlvrError::type_error("ceil requires a number")),
         }
     }
 
     pub fn round(n: Value) -> SlvrResult<Value> {
         match n {
             Value::Integer(i) => Ok(Value::Integer(i)),
-            Value::Decimal(d) => Ok(Value::Integer(d.round() as i128)),
-            _ => Err(SlvrError::TypeError {
-                message: "round requires a number".to_string(),
-            }),
+            Value::Decimal(d) => {
+                whole_number(d.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero))
+            }
+            _ => Err(SlvrError::type_error("round requires a number")),
+        }
+    }
+
+    /// An already-integral decimal as an integer value
+    fn whole_number(d: Decimal) -> SlvrResult<Value> {
+        d.to_i128()
+            .map(Value::Integer)
+            .ok_or_else(|| SlvrError::runtime(format!("{} is outside the integer range", d)))
+    }
+
+    /// Round to `places` decimal places, breaking ties toward the even digit
+    pub fn round_to(value: Value, places: Value) -> SlvrResult<Value> {
+        let d = decimal_arg(value, "round-to")?;
+        let places = match places {
+            Value::Integer(p) if p < 0 => {
+                return Err(SlvrError::runtime("round-to places must be non-negative"))
+            }
+            Value::Integer(p) => u32::try_from(p).unwrap_or(u32::MAX),
+            _ => return Err(SlvrError::type_error("round-to places must be an integer")),
+        };
+
+        Ok(Value::Decimal(d.round_dp_with_strategy(
+            places,
+            RoundingStrategy::MidpointNearestEven,
+        )))
+    }
+
+    /// Sum of a list of numbers; decimal if any element is a decimal
+    pub fn sum(list: Value) -> SlvrResult<Value> {
+        aggregate(list, "sum", 0, i128::checked_add, Decimal::checked_add)
+    }
+
+    /// Product of a list of numbers; decimal if any element is a decimal
+    pub fn product(list: Value) -> SlvrResult<Value> {
+        aggregate(list, "product", 1, i128::checked_mul, Decimal::checked_mul)
+    }
+
+    /// Arithmetic mean of a non-empty list of numbers, always a decimal
+    pub fn average(list: Value) -> SlvrResult<Value> {
+        let count = match &list {
+            Value::List(items) if items.is_empty() => {
+                return Err(SlvrError::runtime("average of an empty list"))
+            }
+            Value::List(items) => items.len(),
+            _ => 0,
+        };
+
+        let count = Decimal::from(count);
+        match aggregate(list, "average", 0, i128::checked_add, Decimal::checked_add)? {
+            Value::Integer(total) => {
+                checked_decimal(integer_to_decimal(total)?.checked_div(count), "average")
+            }
+            Value::Decimal(total) => checked_decimal(total.checked_div(count), "average"),
+            other => Ok(other),
+        }
+    }
+
+    fn aggregate(
+        list: Value,
+        name: &str,
+        identity: i128,
+        int_op: fn(i128, i128) -> Option<i128>,
+        dec_op: fn(Decimal, Decimal) -> Option<Decimal>,
+    ) -> SlvrResult<Value> {
+        let items = match list {
+            Value::List(items) => items,
+            _ => return Err(SlvrError::type_error(format!("{} requires a list", name))),
+        };
+
+        let mut has_decimal = false;
+        for (i, item) in items.iter().enumerate() {
+            match item {
+                Value::Integer(_) => {}
+                Value::Decimal(_) => has_decimal = true,
+                other => {
+                    return Err(SlvrError::type_error(format!(
+                        "{} element {} must be a number, got {}",
+                        name,
+                        i,
+                        other.type_name()
+                    )))
+                }
+            }
+        }
+
+        if has_decimal {
+            let mut total = integer_to_decimal(identity)?;
+            for item in &items {
+                let d = match item {
+                    Value::Integer(i) => integer_to_decimal(*i)?,
+                    Value::Decimal(d) => *d,
+                    _ => continue,
+                };
+                total = dec_op(total, d)
+                    .ok_or_else(|| SlvrError::runtime(format!("decimal overflow in {}", name)))?;
+            }
+            return Ok(Value::Decimal(total));
+        }
+
+        let mut total = identity;
+        for item in &items {
+            if let Value::Integer(i) = item {
+                total = int_op(total, *i)
+                    .ok_or_else(|| SlvrError::runtime(format!("integer overflow in {}", name)))?;
+            }
         }
+        Ok(Value::Integer(total))
+    }
+
+    /// Basis points in one whole unit (100%)
+    pub const BASIS_POINTS: i128 = 10_000;
+
+    /// Upper bound on compounding periods accepted by `compound`
+    pub const MAX_COMPOUND_PERIODS: i128 = 100_000;
+
+    /// Fixed-point scale used while accruing interest
+    const COMPOUND_SCALE: i128 = 1_000_000_000_000_000_000;
+
+    /// Compound `principal` at `rate_bps` basis points per period over `periods`.
+    ///
+    /// Computes `principal * (1 + rate_bps / 10000)^periods` entirely in integer
+    /// arithmetic so every node produces the same result. Interest is accrued at
+    /// 18 decimal places of precision and the final amount is rounded down.
+    pub fn compound(principal: Value, rate_bps: Value, periods: Value) -> SlvrResult<Value> {
+        let (principal, rate_bps, periods) = match (principal, rate_bps, periods) {
+            (Value::Integer(p), Value::Integer(r), Value::Integer(n)) => (p, r, n),
+            _ => {
+                return Err(SlvrError::type_error(
+                    "compound requires integer principal, rate_bps and periods".to_string(),
+                ))
+            }
+        };
+
+        if principal < 0 || rate_bps < 0 || periods < 0 {
+            return Err(SlvrError::runtime(
+                "compound arguments must be non-negative",
+            ));
+        }
+        if periods > MAX_COMPOUND_PERIODS {
+            return Err(SlvrError::runtime(format!(
+                "compound periods {} exceeds maximum of {}",
+                periods, MAX_COMPOUND_PERIODS
+            )));
+        }
+
+        let overflow = || SlvrError::runtime("compound overflow");
+        let factor = BASIS_POINTS + rate_bps;
+        let mut amount = principal.checked_mul(COMPOUND_SCALE).ok_or_else(overflow)?;
+        for _ in 0..periods {
+            amount = amount.checked_mul(factor).ok_or_else(overflow)? / BASIS_POINTS;
+        }
+
+        Ok(Value::Integer(amount / COMPOUND_SCALE))
     }
 }
 
@@ -347,11 +837,7 @@ pub mod crypto {
         let bytes = match data {
             Value::String(s) => s.into_bytes(),
             Value::Integer(i) => i.to_string().into_bytes(),
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "sha512 requires a string or integer".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("sha512 requires a string or integer")),
         };
 
         let mut hasher = Sha512::new();
@@ -361,13 +847,26 @@ pub mod crypto {
         Ok(Value::String(hex::encode(result)))
     }
 
+    /// Check `data` against a hex digest.
+    ///
+    /// The digests are compared as decoded bytes in constant time, so the
+    /// time taken does not depend on where a mismatch occurs. Hex case is
+    /// ignored and a malformed `hash` never matches.
     pub fn verify_sha512(data: Value, hash: Value) -> SlvrResult<Value> {
         let computed = sha512_hash(data)?;
         match (computed, hash) {
-            (Value::String(c), Value::String(h)) => Ok(Value::Boolean(c == h)),
-            _ => Err(SlvrError::TypeError {
-                message: "verify-sha512 requires strings".to_string(),
-            }),
+            (Value::String(c), Value::String(h)) => Ok(Value::Boolean(digests_match(&c, &h))),
+            _ => Err(SlvrError::type_error("verify-sha512 requires strings")),
+        }
+    }
+
+    /// Constant-time equality of two hex digests over their decoded bytes
+    fn digests_match(computed: &str, expected: &str) -> bool {
+        use subtle::ConstantTimeEq;
+
+        match (hex::decode(computed), hex::decode(expected)) {
+            (Ok(c), Ok(e)) => c.ct_eq(&e).into(),
+            _ => false,
         }
     }
 
@@ -375,11 +874,7 @@ pub mod crypto {
         let bytes = match data {
             Value::String(s) => s.into_bytes(),
             Value::Integer(i) => i.to_string().into_bytes(),
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "sha512 requires a string or integer".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("sha512 requires a string or integer")),
         };
 
         let mut hasher = Sha512::new();
@@ -389,80 +884,258 @@ pub mod crypto {
         Ok(Value::String(hex::encode(result)))
     }
 
+    /// Keccak-256 as used by the EVM (original padding, not SHA3-256)
+    pub fn keccak256(data: Value) -> SlvrResult<Value> {
+        let bytes = hash_input(data, "keccak256")?;
+        Ok(Value::String(hex::encode(sha3::Keccak256::digest(bytes))))
+    }
+
+    /// FIPS 202 SHA3-256
+    pub fn sha3_256(data: Value) -> SlvrResult<Value> {
+        let bytes = hash_input(data, "sha3-256")?;
+        Ok(Value::String(hex::encode(sha3::Sha3_256::digest(bytes))))
+    }
+
+    /// RIPEMD-160
+    pub fn ripemd160(data: Value) -> SlvrResult<Value> {
+        let bytes = hash_input(data, "ripemd160")?;
+        Ok(Value::String(hex::encode(ripemd::Ripemd160::digest(bytes))))
+    }
+
+    /// Bitcoin-style HASH160: RIPEMD-160 of the raw SHA-256 digest
+    pub fn hash160(data: Value) -> SlvrResult<Value> {
+        let bytes = hash_input(data, "hash160")?;
+        Ok(Value::String(hex::encode(hash160_bytes(&bytes))))
+    }
+
+    /// HASH160 over raw bytes. The binary SHA-256 digest is hashed, never
+    /// its hex text.
+    pub fn hash160_bytes(bytes: &[u8]) -> [u8; 20] {
+        let sha = sha2::Sha256::digest(bytes);
+        ripemd::Ripemd160::digest(sha).into()
+    }
+
+    /// Verify an Ed25519 signature over a message's UTF-8 bytes.
+    ///
+    /// Uses strict verification, so small-order keys and non-canonical
+    /// signatures are rejected. A key or signature that does not decode to
+    /// 32 or 64 bytes simply fails to verify.
+    pub fn ed25519_verify(
+        pubkey_hex: Value,
+        message: Value,
+        signature_hex: Value,
+    ) -> SlvrResult<Value> {
+        use ed25519_dalek::{Signature, VerifyingKey};
+
+        let (pubkey_hex, message, signature_hex) = match (pubkey_hex, message, signature_hex) {
+            (Value::String(k), Value::String(m), Value::String(s)) => (k, m, s),
+            _ => return Err(SlvrError::type_error("ed25519-verify requires strings")),
+        };
+
+        let key: Option<[u8; 32]> = hex::decode(&pubkey_hex)
+            .ok()
+            .and_then(|b| b.try_into().ok());
+        let signature: Option<[u8; 64]> = hex::decode(&signature_hex)
+            .ok()
+            .and_then(|b| b.try_into().ok());
+        let (Some(key), Some(signature)) = (key, signature) else {
+            return Ok(Value::Boolean(false));
+        };
+        let Ok(key) = VerifyingKey::from_bytes(&key) else {
+            return Ok(Value::Boolean(false));
+        };
+
+        let signature = Signature::from_bytes(&signature);
+        Ok(Value::Boolean(
+            key.verify_strict(message.as_bytes(), &signature).is_ok(),
+        ))
+    }
+
+    /// Bytes hashed for a string or integer argument
+    fn hash_input(data: Value, name: &str) -> SlvrResult<Vec<u8>> {
+        match data {
+            Value::String(s) => Ok(s.into_bytes()),
+            Value::Integer(i) => Ok(i.to_string().into_bytes()),
+            _ => Err(SlvrError::type_error(format!(
+                "{} requires a string or integer",
+                name
+            ))),
+        }
+    }
+
     pub fn hmac_sha512(key: Value, data: Value) -> SlvrResult<Value> {
         use hmac::{Hmac, Mac};
         type HmacSha512 = Hmac<Sha512>;
 
         let key_bytes = match key {
             Value::String(s) => s.into_bytes(),
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "hmac-sha512 key must be a string".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("hmac-sha512 key must be a string")),
         };
 
         let data_bytes = match data {
             Value::String(s) => s.into_bytes(),
             Value::Integer(i) => i.to_string().into_bytes(),
             _ => {
-                return Err(SlvrError::TypeError {
-                    message: "hmac-sha512 data must be a string or integer".to_string(),
-                })
+                return Err(SlvrError::type_error(
+                    "hmac-sha512 data must be a string or integer",
+                ))
             }
         };
 
-        let mut mac =
-            HmacSha512::new_from_slice(&key_bytes).map_err(|_| SlvrError::RuntimeError {
-                message: "invalid HMAC key".to_string(),
-            })?;
+        let mut mac = HmacSha512::new_from_slice(&key_bytes)
+            .map_err(|_| SlvrError::runtime("invalid HMAC key"))?;
         mac.update(&data_bytes);
         let result = mac.finalize();
 
         Ok(Value::String(hex::encode(result.into_bytes())))
     }
-}
 
-/// List operations
-pub mod list {
+    /// Encode a string's UTF-8 bytes as lowercase hex
+    pub fn hex_encode(s: Value) -> SlvrResult<Value> {
+        match s {
+            Value::String(s) => Ok(Value::String(hex::encode(s.as_bytes()))),
+            _ => Err(SlvrError::type_error("hex-encode requires a string")),
+        }
+    }
+
+    /// Decode hex (either case) into a string
+    pub fn hex_decode(s: Value) -> SlvrResult<Value> {
+        let encoded = match s {
+            Value::String(s) => s,
+            _ => return Err(SlvrError::type_error("hex-decode requires a string")),
+        };
+
+        let bytes = hex::decode(&encoded)
+            .map_err(|e| SlvrError::runtime(format!("hex-decode: invalid input: {}", e)))?;
+        String::from_utf8(bytes)
+            .map(Value::String)
+            .map_err(|_| SlvrError::runtime("hex-decode: decoded bytes are not valid UTF-8"))
+    }
+
+    /// Encode a string's UTF-8 bytes as padded standard base64
+    pub fn base64_encode(s: Value) -> SlvrResult<Value> {
+        encode_with(
+            s,
+            &base64::engine::general_purpose::STANDARD,
+            "base64-encode",
+        )
+    }
+
+    /// Decode padded standard base64 into a string
+    pub fn base64_decode(s: Value) -> SlvrResult<Value> {
+        decode_with(
+            s,
+            &base64::engine::general_purpose::STANDARD,
+            "base64-decode",
+        )
+    }
+
+    /// Encode a string's UTF-8 bytes as unpadded URL-safe base64
+    pub fn base64url_encode(s: Value) -> SlvrResult<Value> {
+        encode_with(
+            s,
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+            "base64url-encode",
+        )
+    }
+
+    /// Decode unpadded URL-safe base64 into a string
+    pub fn base64url_decode(s: Value) -> SlvrResult<Value> {
+        decode_with(
+            s,
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+            "base64url-decode",
+        )
+    }
+
+    fn encode_with(s: Value, engine: &impl base64::Engine, name: &str) -> SlvrResult<Value> {
+        match s {
+            Value::String(s) => Ok(Value::String(engine.encode(s.as_bytes()))),
+            _ => Err(SlvrError::type_error(format!("{} requires a string", name))),
+        }
+    }
+
+    fn decode_with(s: Value, engine: &impl base64::Engine, name: &str) -> SlvrResult<Value> {
+        let encoded = match s {
+            Value::String(s) => s,
+            _ => return Err(SlvrError::type_error(format!("{} requires a string", name))),
+        };
+
+        let bytes = engine
+            .decode(encoded.as_bytes())
+            .map_err(|e| SlvrError::runtime(format!("{}: invalid input: {}", name, e)))?;
+        String::from_utf8(bytes)
+            .map(Value::String)
+            .map_err(|_| SlvrError::runtime(format!("{}: decoded bytes are not valid UTF-8", name)))
+    }
+}
+
+/// List operations
+pub mod list {
     use super::*;
 
     pub fn length(list: Value) -> SlvrResult<Value> {
         match list {
             Value::List(l) => Ok(Value::Integer(l.len() as i128)),
-            _ => Err(SlvrError::TypeError {
-                message: "length requires a list".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("length requires a list")),
         }
     }
 
+    /// Element at `index`; negative indices count from the end, so `-1` is
+    /// the last element
     pub fn at(list: Value, index: Value) -> SlvrResult<Value> {
-        let lst = match list {
+        let mut lst = match list {
             Value::List(l) => l,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "at requires a list".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("at requires a list")),
         };
 
-        let idx = match index {
-            Value::Integer(i) => i as usize,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "at index must be an integer".to_string(),
-                })
-            }
+        let index = match index {
+            Value::Integer(i) => i,
+            _ => return Err(SlvrError::type_error("at index must be an integer")),
         };
 
-        if idx >= lst.len() {
+        let idx = resolve_index(index, lst.len())?;
+        Ok(lst.swap_remove(idx))
+    }
+
+    /// Position of the first element equal to `element`, or `-1`
+    pub fn index_of(list: Value, element: Value) -> SlvrResult<Value> {
+        match list {
+            Value::List(l) => Ok(Value::Integer(
+                l.iter()
+                    .position(|item| *item == element)
+                    .map_or(-1, |i| i as i128),
+            )),
+            _ => Err(SlvrError::type_error("index_of requires a list")),
+        }
+    }
+
+    /// A copy of the list without the element at `index`, which is
+    /// interpreted the same way as in `at`
+    pub fn remove_at(list: Value, index: Value) -> SlvrResult<Value> {
+        match (list, index) {
+            (Value::List(mut l), Value::Integer(index)) => {
+                let idx = resolve_index(index, l.len())?;
+                l.remove(idx);
+                Ok(Value::List(l))
+            }
+            _ => Err(SlvrError::type_error(
+                "remove_at requires a list and an integer index",
+            )),
+        }
+    }
+
+    /// Map a possibly negative index onto `0..len`
+    fn resolve_index(index: i128, len: usize) -> SlvrResult<usize> {
+        let signed_len = len as i128;
+        let idx = if index < 0 { signed_len + index } else { index };
+        if !(0..signed_len).contains(&idx) {
             return Err(SlvrError::IndexOutOfBounds {
-                index: idx as i64,
-                length: lst.len(),
+                index: index.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+                length: len,
             });
         }
-
-        Ok(lst[idx].clone())
+        Ok(idx as usize)
     }
 
     pub fn reverse(list: Value) -> SlvrResult<Value> {
@@ -471,29 +1144,125 @@ pub mod list {
                 l.reverse();
                 Ok(Value::List(l))
             }
-            _ => Err(SlvrError::TypeError {
-                message: "reverse requires a list".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("reverse requires a list")),
         }
     }
 
+    /// Sort ascending.
+    ///
+    /// Numbers, strings, booleans and times are ordered among themselves;
+    /// any other pairing is a `RuntimeError`. Equal elements keep their order.
     pub fn sort(list: Value) -> SlvrResult<Value> {
-        match list {
-            Value::List(mut l) => {
-                l.sort_by(|a, b| match (a, b) {
-                    (Value::Integer(x), Value::Integer(y)) => x.cmp(y),
-                    (Value::Decimal(x), Value::Decimal(y)) => {
-                        x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
-                    }
-                    (Value::String(x), Value::String(y)) => x.cmp(y),
-                    _ => std::cmp::Ordering::Equal,
-                });
-                Ok(Value::List(l))
-            }
-            _ => Err(SlvrError::TypeError {
-                message: "sort requires a list".to_string(),
+        sort_list(list, "sort", false, |item| Ok(item))
+    }
+
+    /// Sort descending, with the same ordering rules as `sort`
+    pub fn sort_desc(list: Value) -> SlvrResult<Value> {
+        sort_list(list, "sort_desc", true, |item| Ok(item))
+    }
+
+    /// Sort a list of objects ascending by the field named `key`
+    pub fn sort_by_key(list: Value, key: Value) -> SlvrResult<Value> {
+        let key = field_name(key, "sort_by_key")?;
+        sort_list(list, "sort_by_key", false, |item| sort_key(item, &key))
+    }
+
+    /// Sort a list of objects descending by the field named `key`
+    pub fn sort_by_key_desc(list: Value, key: Value) -> SlvrResult<Value> {
+        let key = field_name(key, "sort_by_key_desc")?;
+        sort_list(list, "sort_by_key_desc", true, |item| sort_key(item, &key))
+    }
+
+    fn field_name(key: Value, name: &str) -> SlvrResult<String> {
+        match key {
+            Value::String(k) => Ok(k),
+            _ => Err(SlvrError::type_error(format!(
+                "{} key must be a string",
+                name
+            ))),
+        }
+    }
+
+    fn sort_key<'a>(item: &'a Value, key: &str) -> SlvrResult<&'a Value> {
+        match item {
+            Value::Object(fields) => fields.get(key).ok_or_else(|| {
+                SlvrError::runtime(format!("sort key '{}' missing from {}", key, item))
             }),
+            other => Err(SlvrError::type_error(format!(
+                "sort by key requires objects, got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn sort_list(
+        list: Value,
+        name: &str,
+        descending: bool,
+        key: impl for<'a> Fn(&'a Value) -> SlvrResult<&'a Value>,
+    ) -> SlvrResult<Value> {
+        let lst = match list {
+            Value::List(l) => l,
+            _ => return Err(SlvrError::type_error(format!("{} requires a list", name))),
+        };
+        let keys = sort_keys(&lst, key)?;
+
+        // Every key has the same variant, so this is a total order
+        let mut keyed: Vec<(SortKey, Value)> = keys.into_iter().zip(lst).collect();
+        keyed.sort_by(|(a, _), (b, _)| if descending { b.cmp(a) } else { a.cmp(b) });
+
+        Ok(Value::List(
+            keyed.into_iter().map(|(_, item)| item).collect(),
+        ))
+    }
+
+    /// A sort key extracted from a list item
+    #[derive(PartialEq, Eq, PartialOrd, Ord)]
+    enum SortKey {
+        Integer(i128),
+        Decimal(Decimal),
+        String(String),
+        Boolean(bool),
+        Time(chrono::DateTime<chrono::Utc>),
+    }
+
+    /// Extract the key of every item, checking they are all one comparable
+    /// kind. Integers sort among decimals as decimals.
+    fn sort_keys(
+        items: &[Value],
+        key: impl for<'a> Fn(&'a Value) -> SlvrResult<&'a Value>,
+    ) -> SlvrResult<Vec<SortKey>> {
+        let values = items.iter().map(key).collect::<SlvrResult<Vec<_>>>()?;
+        let as_decimal = values.iter().any(|v| matches!(v, Value::Decimal(_)));
+
+        let mut keys = Vec::with_capacity(values.len());
+        for value in &values {
+            let key = match value {
+                Value::Integer(n) if !as_decimal => SortKey::Integer(*n),
+                Value::Integer(_) | Value::Decimal(_) => SortKey::Decimal(value.to_decimal()?),
+                Value::String(s) => SortKey::String(s.clone()),
+                Value::Boolean(b) => SortKey::Boolean(*b),
+                Value::Time(t) => SortKey::Time(*t),
+                other => {
+                    return Err(SlvrError::runtime(format!(
+                        "cannot order {}",
+                        other.type_name()
+                    )))
+                }
+            };
+            if let (Some(first), Some(kind)) = (values.first(), keys.first()) {
+                if std::mem::discriminant(kind) != std::mem::discriminant(&key) {
+                    return Err(SlvrError::runtime(format!(
+                        "cannot order {} and {}",
+                        first.type_name(),
+                        value.type_name()
+                    )));
+                }
+            }
+            keys.push(key);
         }
+
+        Ok(keys)
     }
 
     pub fn append(list: Value, element: Value) -> SlvrResult<Value> {
@@ -502,9 +1271,7 @@ pub mod list {
                 l.push(element);
                 Ok(Value::List(l))
             }
-            _ => Err(SlvrError::TypeError {
-                message: "append requires a list".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("append requires a list")),
         }
     }
 
@@ -518,9 +1285,7 @@ pub mod list {
                 }
                 Ok(Value::Boolean(false))
             }
-            _ => Err(SlvrError::TypeError {
-                message: "contains requires a list".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("contains requires a list")),
         }
     }
 
@@ -533,9 +1298,7 @@ pub mod list {
                     Ok(l[0].clone())
                 }
             }
-            _ => Err(SlvrError::TypeError {
-                message: "first requires a list".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("first requires a list")),
         }
     }
 
@@ -548,38 +1311,24 @@ pub mod list {
                     Ok(l[l.len() - 1].clone())
                 }
             }
-            _ => Err(SlvrError::TypeError {
-                message: "last requires a list".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("last requires a list")),
         }
     }
 
     pub fn sublist(list: Value, start: Value, end: Value) -> SlvrResult<Value> {
         let lst = match list {
             Value::List(l) => l,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "sublist requires a list".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("sublist requires a list")),
         };
 
         let start_idx = match start {
             Value::Integer(i) => i as usize,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "sublist start must be an integer".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("sublist start must be an integer")),
         };
 
         let end_idx = match end {
             Value::Integer(i) => i as usize,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "sublist end must be an integer".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("sublist end must be an integer")),
         };
 
         if start_idx > lst.len() || end_idx > lst.len() || start_idx > end_idx {
@@ -591,6 +1340,223 @@ pub mod list {
 
         Ok(Value::List(lst[start_idx..end_idx].to_vec()))
     }
+
+    /// The first `n` elements, or the whole list if it is shorter
+    pub fn take(list: Value, n: Value) -> SlvrResult<Value> {
+        let (mut lst, n) = list_and_count(list, n, "take")?;
+        lst.truncate(n);
+        Ok(Value::List(lst))
+    }
+
+    /// Everything after the first `n` elements, or an empty list if it is shorter
+    pub fn drop(list: Value, n: Value) -> SlvrResult<Value> {
+        let (mut lst, n) = list_and_count(list, n, "drop")?;
+        lst.drain(..n.min(lst.len()));
+        Ok(Value::List(lst))
+    }
+
+    fn list_and_count(list: Value, n: Value, name: &str) -> SlvrResult<(Vec<Value>, usize)> {
+        match (list, n) {
+            (Value::List(l), Value::Integer(n)) => {
+                if n < 0 {
+                    return Err(SlvrError::runtime(format!(
+                        "{} count must be non-negative, got {}",
+                        name, n
+                    )));
+                }
+                Ok((l, usize::try_from(n).unwrap_or(usize::MAX)))
+            }
+            _ => Err(SlvrError::type_error(format!(
+                "{} requires a list and an integer",
+                name
+            ))),
+        }
+    }
+
+    /// Pair up elements as `[a, b]` lists, stopping at the shorter input
+    pub fn zip(a: Value, b: Value) -> SlvrResult<Value> {
+        match (a, b) {
+            (Value::List(a), Value::List(b)) => Ok(Value::List(
+                a.into_iter()
+                    .zip(b)
+                    .map(|(x, y)| Value::List(vec![x, y]))
+                    .collect(),
+            )),
+            _ => Err(SlvrError::type_error("zip requires two lists")),
+        }
+    }
+
+    /// Pair each element with its position as `[index, value]` lists
+    pub fn enumerate(list: Value) -> SlvrResult<Value> {
+        match list {
+            Value::List(l) => Ok(Value::List(
+                l.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| Value::List(vec![Value::Integer(i as i128), item]))
+                    .collect(),
+            )),
+            _ => Err(SlvrError::type_error("enumerate requires a list")),
+        }
+    }
+
+    /// Drop repeated elements, keeping the first occurrence of each.
+    ///
+    /// Uses `Value` equality, so `1` and `1.0` are distinct.
+    pub fn distinct(list: Value) -> SlvrResult<Value> {
+        let lst = match list {
+            Value::List(l) => l,
+            _ => return Err(SlvrError::type_error("distinct requires a list")),
+        };
+
+        let mut seen: Vec<Value> = Vec::with_capacity(lst.len());
+        for item in lst {
+            if !seen.contains(&item) {
+                seen.push(item);
+            }
+        }
+        Ok(Value::List(seen))
+    }
+
+    /// Splice nested lists one level deep; other elements pass through
+    pub fn flatten(list: Value) -> SlvrResult<Value> {
+        let lst = match list {
+            Value::List(l) => l,
+            _ => return Err(SlvrError::type_error("flatten requires a list")),
+        };
+
+        let mut flat = Vec::with_capacity(lst.len());
+        for item in lst {
+            match item {
+                Value::List(inner) => flat.extend(inner),
+                other => flat.push(other),
+            }
+        }
+        Ok(Value::List(flat))
+    }
+
+    /// Apply `f` to each element in order.
+    ///
+    /// `f` is the host's hook for invoking a contract function, so the caller
+    /// decides how each application is metered.
+    pub fn map(list: Value, mut f: impl FnMut(Value) -> SlvrResult<Value>) -> SlvrResult<Value> {
+        match list {
+            Value::List(l) => l
+                .into_iter()
+                .map(&mut f)
+                .collect::<SlvrResult<_>>()
+                .map(Value::List),
+            _ => Err(SlvrError::type_error("map requires a list")),
+        }
+    }
+
+    /// Keep the elements for which `pred` returns a truthy value
+    pub fn filter(
+        list: Value,
+        mut pred: impl FnMut(Value) -> SlvrResult<Value>,
+    ) -> SlvrResult<Value> {
+        let lst = match list {
+            Value::List(l) => l,
+            _ => return Err(SlvrError::type_error("filter requires a list")),
+        };
+
+        let mut kept = Vec::new();
+        for item in lst {
+            if pred(item.clone())?.is_truthy() {
+                kept.push(item);
+            }
+        }
+        Ok(Value::List(kept))
+    }
+
+    /// Combine the elements left to right, starting from `init`
+    pub fn fold(
+        list: Value,
+        init: Value,
+        mut f: impl FnMut(Value, Value) -> SlvrResult<Value>,
+    ) -> SlvrResult<Value> {
+        match list {
+            Value::List(l) => l.into_iter().try_fold(init, &mut f),
+            _ => Err(SlvrError::type_error("fold requires a list")),
+        }
+    }
+
+    /// Split `list` into `[matching, non_matching]` using a named predicate.
+    ///
+    /// Makes a single pass and keeps the input order within each bucket.
+    pub fn partition(list: Value, predicate_name: Value) -> SlvrResult<Value> {
+        let lst = match list {
+            Value::List(l) => l,
+            _ => return Err(SlvrError::type_error("partition requires a list")),
+        };
+        let predicate = lookup_predicate(&predicate_name, "partition")?;
+
+        let mut matching = Vec::new();
+        let mut non_matching = Vec::new();
+        for item in lst {
+            if predicate(item.clone())?.is_truthy() {
+                matching.push(item);
+            } else {
+                non_matching.push(item);
+            }
+        }
+
+        Ok(Value::List(vec![
+            Value::List(matching),
+            Value::List(non_matching),
+        ]))
+    }
+
+    /// Build an object mapping each element's `key` field to the element.
+    ///
+    /// Every element must be an object whose `key` field is a string or an
+    /// integer. Two elements with the same key are an error rather than one
+    /// silently replacing the other.
+    pub fn index_by(list: Value, key: Value) -> SlvrResult<Value> {
+        let (lst, key) = match (list, key) {
+            (Value::List(l), Value::String(k)) => (l, k),
+            _ => {
+                return Err(SlvrError::type_error(
+                    "index_by requires a list and a field name",
+                ))
+            }
+        };
+
+        let mut index = BTreeMap::new();
+        for item in lst {
+            let id = match item.get_field(&key)? {
+                Value::String(s) => s,
+                Value::Integer(i) => i.to_string(),
+                other => {
+                    return Err(SlvrError::type_error(format!(
+                        "index_by key '{}' must be a string or integer, got {}",
+                        key,
+                        other.type_name()
+                    )))
+                }
+            };
+            if index.contains_key(&id) {
+                return Err(SlvrError::runtime(format!(
+                    "index_by: duplicate key '{}'",
+                    id
+                )));
+            }
+            index.insert(id, item);
+        }
+
+        Ok(Value::Object(index))
+    }
+
+    fn lookup_predicate(name: &Value, caller: &str) -> SlvrResult<Predicate> {
+        match name {
+            Value::String(name) => type_check::predicate(name).ok_or_else(|| {
+                SlvrError::type_error(format!("{}: unknown predicate '{}'", caller, name))
+            }),
+            _ => Err(SlvrError::type_error(format!(
+                "{} predicate must be a name",
+                caller
+            ))),
+        }
+    }
 }
 
 /// Object operations
@@ -603,9 +1569,7 @@ pub mod object {
                 let keys: Vec<Value> = map.keys().map(|k| Value::String(k.clone())).collect();
                 Ok(Value::List(keys))
             }
-            _ => Err(SlvrError::TypeError {
-                message: "keys requires an object".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("keys requires an object")),
         }
     }
 
@@ -615,29 +1579,19 @@ pub mod object {
                 let vals: Vec<Value> = map.values().cloned().collect();
                 Ok(Value::List(vals))
             }
-            _ => Err(SlvrError::TypeError {
-                message: "values requires an object".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("values requires an object")),
         }
     }
 
     pub fn merge(obj1: Value, obj2: Value) -> SlvrResult<Value> {
         let mut map1 = match obj1 {
             Value::Object(m) => m,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "merge requires objects".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("merge requires objects")),
         };
 
         let map2 = match obj2 {
             Value::Object(m) => m,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "merge requires objects".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("merge requires objects")),
         };
 
         for (k, v) in map2 {
@@ -648,29 +1602,21 @@ pub mod object {
     }
 
     pub fn select(obj: Value, fields: Value) -> SlvrResult<Value> {
-        let map = match obj {
+        let mut map = match obj {
             Value::Object(m) => m,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "select requires an object".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("select requires an object")),
         };
 
         let field_list = match fields {
             Value::List(l) => l,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "select fields must be a list".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("select fields must be a list")),
         };
 
-        let mut result = HashMap::new();
+        let mut result = BTreeMap::new();
         for field in field_list {
             if let Value::String(key) = field {
-                if let Some(value) = map.get(&key) {
-                    result.insert(key, value.clone());
+                if let Some(value) = map.remove(&key) {
+                    result.insert(key, value);
                 }
             }
         }
@@ -681,24 +1627,61 @@ pub mod object {
     pub fn has_key(obj: Value, key: Value) -> SlvrResult<Value> {
         let map = match obj {
             Value::Object(m) => m,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "has-key requires an object".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("has-key requires an object")),
         };
 
         let k = match key {
             Value::String(s) => s,
-            _ => {
-                return Err(SlvrError::TypeError {
-                    message: "has-key key must be a string".to_string(),
-                })
-            }
+            _ => return Err(SlvrError::type_error("has-key key must be a string")),
         };
 
         Ok(Value::Boolean(map.contains_key(&k)))
     }
+
+    /// Follow a list of keys through nested objects, returning null as soon as
+    /// a key is missing
+    pub fn get_path(obj: Value, path: Value) -> SlvrResult<Value> {
+        if !matches!(obj, Value::Object(_)) {
+            return Err(SlvrError::type_error("get-path requires an object"));
+        }
+        let segments = match path {
+            Value::List(segments) => segments,
+            _ => return Err(SlvrError::type_error("get-path path must be a list")),
+        };
+
+        let mut current = obj;
+        let mut walked: Vec<String> = Vec::with_capacity(segments.len());
+        for (i, segment) in segments.into_iter().enumerate() {
+            let key = match segment {
+                Value::String(key) => key,
+                other => {
+                    return Err(SlvrError::type_error(format!(
+                        "get-path segment {} must be a string, got {}",
+                        i,
+                        other.type_name()
+                    )))
+                }
+            };
+            let mut map = match current {
+                Value::Object(map) => map,
+                other => {
+                    return Err(SlvrError::runtime(format!(
+                        "get-path: '{}' is a {}, not an object, so '{}' cannot be read",
+                        walked.join("."),
+                        other.type_name(),
+                        key
+                    )))
+                }
+            };
+            match map.remove(&key) {
+                Some(next) => current = next,
+                None => return Ok(Value::Null),
+            }
+            walked.push(key);
+        }
+
+        Ok(current)
+    }
 }
 
 /// Type conversion functions
@@ -708,33 +1691,103 @@ pub mod conversion {
     pub fn to_integer(val: Value) -> SlvrResult<Value> {
         match val {
             Value::Integer(i) => Ok(Value::Integer(i)),
-            Value::Decimal(d) => Ok(Value::Integer(d as i128)),
+            Value::Decimal(d) => decimal_to_integer(d).map(Value::Integer),
             Value::String(s) => match s.parse::<i128>() {
                 Ok(i) => Ok(Value::Integer(i)),
-                Err(_) => Err(SlvrError::TypeError {
-                    message: format!("cannot convert '{}' to integer", s),
-                }),
+                Err(_) => Err(SlvrError::type_error(format!(
+                    "cannot convert '{}' to integer",
+                    s
+                ))),
             },
             Value::Boolean(b) => Ok(Value::Integer(if b { 1 } else { 0 })),
-            _ => Err(SlvrError::TypeError {
-                message: "cannot convert to integer".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("cannot convert to integer")),
         }
     }
 
+    /// Parse an integer in radix 2, 8, 10 or 16. A `0b`, `0o` or `0x` prefix
+    /// matching the radix is accepted after an optional sign.
+    pub fn parse_int(s: Value, radix: Value) -> SlvrResult<Value> {
+        let (text, radix) = match (s, radix) {
+            (Value::String(s), Value::Integer(r)) => (s, r),
+            _ => {
+                return Err(SlvrError::type_error(
+                    "parse-int requires a string and an integer radix",
+                ))
+            }
+        };
+        let prefix = match radix {
+            2 => "0b",
+            8 => "0o",
+            10 => "",
+            16 => "0x",
+            _ => {
+                return Err(SlvrError::runtime(format!(
+                    "parse-int: unsupported radix {}",
+                    radix
+                )))
+            }
+        };
+
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(&text)),
+        };
+        let digits = if prefix.is_empty() {
+            unsigned
+        } else {
+            unsigned
+                .strip_prefix(prefix)
+                .or_else(|| unsigned.strip_prefix(&prefix.to_uppercase()))
+                .unwrap_or(unsigned)
+        };
+
+        let invalid = || {
+            SlvrError::runtime(format!(
+                "parse-int: invalid base-{} integer '{}'",
+                radix, text
+            ))
+        };
+        if digits.starts_with(['+', '-']) {
+            return Err(invalid());
+        }
+        let signed = if negative {
+            format!("-{}", digits)
+        } else {
+            digits.to_string()
+        };
+        i128::from_str_radix(&signed, radix as u32)
+            .map(Value::Integer)
+            .map_err(|_| invalid())
+    }
+
+    /// Exact conversion of an integral decimal. Fractional values are errors
+    /// rather than being truncated.
+    fn decimal_to_integer(d: Decimal) -> SlvrResult<i128> {
+        use rust_decimal::prelude::ToPrimitive;
+
+        if !d.fract().is_zero() {
+            return Err(SlvrError::runtime(format!(
+                "decimal {} has a fractional part",
+                d
+            )));
+        }
+        d.to_i128().ok_or_else(|| {
+            SlvrError::runtime(format!("decimal {} is outside the integer range", d))
+        })
+    }
+
     pub fn to_decimal(val: Value) -> SlvrResult<Value> {
         match val {
-            Value::Integer(i) => Ok(Value::Decimal(i as f64)),
+            Value::Integer(i) => integer_to_decimal(i).map(Value::Decimal),
             Value::Decimal(d) => Ok(Value::Decimal(d)),
-            Value::String(s) => match s.parse::<f64>() {
-                Ok(d) => Ok(Value::Decimal(d)),
-                Err(_) => Err(SlvrError::TypeError {
-                    message: format!("cannot convert '{}' to decimal", s),
-                }),
+            Value::String(s) => match parse_decimal(&s) {
+                Some(d) => Ok(Value::Decimal(d)),
+                None => Err(SlvrError::type_error(format!(
+                    "cannot convert '{}' to decimal",
+                    s
+                ))),
             },
-            _ => Err(SlvrError::TypeError {
-                message: "cannot convert to decimal".to_string(),
-            }),
+            _ => Err(SlvrError::type_error("cannot convert to decimal")),
         }
     }
 
@@ -747,10 +1800,337 @@ pub mod conversion {
     }
 }
 
+/// Time functions.
+///
+/// Times are UTC instants. Contracts only ever see the block time through
+/// `now`, never the wall clock, so every node computes the same result.
+pub mod time {
+    use super::*;
+    use chrono::{DateTime, TimeDelta, Utc};
+
+    /// Current block time, taken from the runtime's time source
+    pub fn now(source: &dyn TimeSource) -> SlvrResult<Value> {
+        let secs = source.block_time();
+        i64::try_from(secs)
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(Value::Time)
+            .ok_or_else(|| SlvrError::runtime(format!("block time {} is out of range", secs)))
+    }
+
+    /// Shift a time by a (possibly negative) number of seconds
+    pub fn add_seconds(t: Value, seconds: Value) -> SlvrResult<Value> {
+        match (t, seconds) {
+            (Value::Time(t), Value::Integer(secs)) => i64::try_from(secs)
+                .ok()
+                .and_then(TimeDelta::try_seconds)
+                .and_then(|delta| t.checked_add_signed(delta))
+                .map(Value::Time)
+                .ok_or_else(|| {
+                    SlvrError::runtime(format!(
+                        "time overflow in add_seconds: {} + {}s",
+                        format_time(&t),
+                        secs
+                    ))
+                }),
+            _ => Err(SlvrError::type_error(
+                "add_seconds requires a time and an integer",
+            )),
+        }
+    }
+
+    /// Whole seconds from `b` to `a`, truncated toward zero
+    pub fn diff_seconds(a: Value, b: Value) -> SlvrResult<Value> {
+        match (a, b) {
+            (Value::Time(a), Value::Time(b)) => Ok(Value::Integer(
+                a.signed_duration_since(b).num_seconds() as i128,
+            )),
+            _ => Err(SlvrError::type_error("diff_seconds requires two times")),
+        }
+    }
+
+    /// Parse an RFC 3339 timestamp, converting any offset to UTC
+    pub fn parse_rfc3339(s: Value) -> SlvrResult<Value> {
+        match s {
+            Value::String(s) => DateTime::parse_from_rfc3339(&s)
+                .map(|t| Value::Time(t.with_timezone(&Utc)))
+                .map_err(|e| SlvrError::runtime(format!("invalid RFC 3339 time '{}': {}", s, e))),
+            _ => Err(SlvrError::type_error("parse_rfc3339 requires a string")),
+        }
+    }
+
+    /// Format a time as RFC 3339 in UTC
+    pub fn format_rfc3339(t: Value) -> SlvrResult<Value> {
+        match t {
+            Value::Time(t) => Ok(Value::String(format_time(&t))),
+            _ => Err(SlvrError::type_error("format_rfc3339 requires a time")),
+        }
+    }
+}
+
+/// JSON encoding functions
+pub mod json {
+    use super::*;
+    use std::fmt::Write;
+
+    /// Serialize a value as canonical JSON (RFC 8785 style).
+    ///
+    /// Object keys are sorted by UTF-16 code units, no insignificant whitespace
+    /// is emitted and integers and decimals share the ECMAScript number form,
+    /// so equal values always produce byte-identical output suitable for
+    /// hashing and signing.
+    pub fn canonicalize(val: Value) -> SlvrResult<Value> {
+        let mut out = String::new();
+        write_canonical(&val, &mut out)?;
+        Ok(Value::String(out))
+    }
+
+    /// Canonical JSON of a serializable record, as hashed by SPV proofs and events
+    pub(crate) fn canonical_bytes<T: serde::Serialize>(record: &T) -> SlvrResult<Vec<u8>> {
+        let json = serde_json::to_value(record).map_err(|e| SlvrError::InternalError {
+            message: format!("Failed to encode record: {}", e),
+        })?;
+        let mut out = String::new();
+        write_canonical(&from_json(json)?, &mut out)?;
+        Ok(out.into_bytes())
+    }
+
+    /// Parse a JSON document into a value, nested at most `MAX_VALUE_DEPTH` deep
+    pub fn parse(text: Value) -> SlvrResult<Value> {
+        parse_with_max_depth(text, crate::MAX_VALUE_DEPTH)
+    }
+
+    /// Parse a JSON document, rejecting lists/objects nested deeper than `max_depth`.
+    ///
+    /// The decoder itself also stops at 128 levels regardless of `max_depth`.
+    pub fn parse_with_max_depth(text: Value, max_depth: usize) -> SlvrResult<Value> {
+        match text {
+            Value::String(s) => {
+                let json: serde_json::Value = serde_json::from_str(&s)
+                    .map_err(|e| SlvrError::runtime(format!("invalid JSON: {}", e)))?;
+                if json_depth(&json) > max_depth {
+                    return Err(SlvrError::ValueDepthExceeded { max_depth });
+                }
+                from_json(json)
+            }
+            _ => Err(SlvrError::type_error("parse requires a string")),
+        }
+    }
+
+    /// Nesting depth of lists/objects; scalars have depth 0
+    fn json_depth(json: &serde_json::Value) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(json, 0usize)];
+        while let Some((value, depth)) = stack.pop() {
+            let children: Box<dyn Iterator<Item = &serde_json::Value>> = match value {
+                serde_json::Value::Array(items) => Box::new(items.iter()),
+                serde_json::Value::Object(map) => Box::new(map.values()),
+                _ => continue,
+            };
+            max = max.max(depth + 1);
+            stack.extend(children.map(|child| (child, depth + 1)));
+        }
+        max
+    }
+
+    /// Convert a decoded JSON document into a value
+    pub(crate) fn from_json(json: serde_json::Value) -> SlvrResult<Value> {
+        Ok(match json {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Boolean(b),
+            // Numbers keep the digits they were written with, so decimals
+            // never pass through a float
+            serde_json::Value::Number(n) => {
+                let digits = n.to_string();
+                if let Ok(i) = digits.parse::<i128>() {
+                    Value::Integer(i)
+                } else {
+                    let d = parse_decimal(&digits).ok_or_else(|| {
+                        SlvrError::runtime(format!(
+                            "JSON number {} is outside the decimal range",
+                            n
+                        ))
+                    })?;
+                    Value::Decimal(d)
+                }
+            }
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(items) => Value::List(
+                items
+                    .into_iter()
+                    .map(from_json)
+                    .collect::<SlvrResult<Vec<_>>>()?,
+            ),
+            serde_json::Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| Ok((k, from_json(v)?)))
+                    .collect::<SlvrResult<BTreeMap<_, _>>>()?,
+            ),
+        })
+    }
+
+    /// Convert a value into a JSON document; unit becomes `null`
+    pub(crate) fn to_json(val: &Value) -> SlvrResult<serde_json::Value> {
+        Ok(match val {
+            Value::Integer(i) => match i64::try_from(*i) {
+                Ok(i) => serde_json::Value::from(i),
+                Err(_) => serde_json::Value::from(u64::try_from(*i).map_err(|_| {
+                    SlvrError::runtime(format!("integer {} does not fit in JSON", i))
+                })?),
+            },
+            Value::Decimal(d) => format_number(*d)
+                .parse::<serde_json::Number>()
+                .map(serde_json::Value::Number)
+                .map_err(|e| {
+                    SlvrError::runtime(format!("decimal {} cannot be encoded as JSON: {}", d, e))
+                })?,
+            Value::String(s) => serde_json::Value::String(s.clone()),
+            Value::Boolean(b) => serde_json::Value::Bool(*b),
+            Value::Time(t) => serde_json::Value::String(format_time(t)),
+            Value::List(items) => {
+                serde_json::Value::Array(items.iter().map(to_json).collect::<SlvrResult<Vec<_>>>()?)
+            }
+            Value::Object(fields) => serde_json::Value::Object(
+                fields
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), to_json(v)?)))
+                    .collect::<SlvrResult<serde_json::Map<_, _>>>()?,
+            ),
+            Value::Unit | Value::Null => serde_json::Value::Null,
+            Value::Closure(_) => return Err(SlvrError::type_error("cannot encode a function")),
+        })
+    }
+
+    fn write_canonical(val: &Value, out: &mut String) -> SlvrResult<()> {
+        match val {
+            Value::Integer(i) => out.push_str(&format_integer(*i)),
+            Value::Decimal(d) => out.push_str(&format_number(*d)),
+            Value::String(s) => write_string(s, out),
+            Value::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+            Value::Time(t) => write_string(&format_time(t), out),
+            Value::List(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_canonical(item, out)?;
+                }
+                out.push(']');
+            }
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+                out.push('{');
+                for (i, (k, v)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_string(k, out);
+                    out.push(':');
+                    write_canonical(v, out)?;
+                }
+                out.push('}');
+            }
+            Value::Unit | Value::Null => out.push_str("null"),
+            Value::Closure(_) => return Err(SlvrError::type_error("cannot encode a function")),
+        }
+        Ok(())
+    }
+
+    fn write_string(s: &str, out: &mut String) {
+        out.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\u{08}' => out.push_str("\\b"),
+                '\u{0c}' => out.push_str("\\f"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if (c as u32) < 0x20 => {
+                    let _ = write!(out, "\\u{:04x}", c as u32);
+                }
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+
+    /// Format a decimal the way ECMAScript `Number.prototype.toString` does
+    fn format_number(d: Decimal) -> String {
+        if d.is_zero() {
+            return "0".to_string();
+        }
+
+        // Normalizing strips fractional trailing zeros; integral ones are
+        // trimmed when formatting so only the significant digits remain
+        let d = d.normalize();
+        let mantissa = d.mantissa().unsigned_abs().to_string();
+        let exponent = mantissa.len() as i32 - 1 - d.scale() as i32;
+        format_scientific(d.is_sign_negative(), &mantissa, exponent)
+    }
+
+    /// Format an integer like the equal decimal, so `10^21` is `1e+21` either way
+    fn format_integer(i: i128) -> String {
+        if i == 0 {
+            return "0".to_string();
+        }
+        let mantissa = i.unsigned_abs().to_string();
+        format_scientific(i < 0, &mantissa, mantissa.len() as i32 - 1)
+    }
+
+    /// Lay out `mantissa` with its leading digit at `10^exponent` in ECMAScript notation
+    fn format_scientific(negative: bool, mantissa: &str, exponent: i32) -> String {
+        let sign = if negative { "-" } else { "" };
+        let digits = mantissa.trim_end_matches('0');
+        let k = digits.len() as i32;
+        let n = exponent + 1;
+
+        let body = if k <= n && n <= 21 {
+            format!("{}{}", digits, "0".repeat((n - k) as usize))
+        } else if 0 < n && n <= 21 {
+            format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+        } else if -6 < n && n <= 0 {
+            format!("0.{}{}", "0".repeat((-n) as usize), digits)
+        } else {
+            let exp_sign = if n - 1 < 0 { "-" } else { "+" };
+            let fraction = if k > 1 {
+                format!(".{}", &digits[1..])
+            } else {
+                String::new()
+            };
+            format!("{}{}e{}{}", &digits[..1], fraction, exp_sign, (n - 1).abs())
+        };
+
+        format!("{}{}", sign, body)
+    }
+}
+
+/// A builtin predicate applied to a single value
+pub type Predicate = fn(Value) -> SlvrResult<Value>;
+
 /// Type checking functions
 pub mod type_check {
     use super::*;
 
+    /// Look up a type predicate by its builtin name, e.g. `is_integer`
+    pub fn predicate(name: &str) -> Option<Predicate> {
+        let predicate: Predicate = match name {
+            "is_integer" => is_integer,
+            "is_decimal" => is_decimal,
+            "is_string" => is_string,
+            "is_boolean" => is_boolean,
+            "is_list" => is_list,
+            "is_object" => is_object,
+            "is_null" => is_null,
+            "is_time" => is_time,
+            _ => return None,
+        };
+        Some(predicate)
+    }
+
     pub fn is_integer(val: Value) -> SlvrResult<Value> {
         Ok(Value::Boolean(matches!(val, Value::Integer(_))))
     }
@@ -778,4 +2158,1354 @@ pub mod type_check {
     pub fn is_null(val: Value) -> SlvrResult<Value> {
         Ok(Value::Boolean(matches!(val, Value::Boolean(false))))
     }
+
+    pub fn is_time(val: Value) -> SlvrResult<Value> {
+        Ok(Value::Boolean(matches!(val, Value::Time(_))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(text: &str) -> Value {
+        Value::Decimal(parse_decimal(text).unwrap())
+    }
+
+    fn time(text: &str) -> Value {
+        time::parse_rfc3339(Value::String(text.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_time_rfc3339_round_trip() {
+        let round_trip = |text: &str| match time::format_rfc3339(time(text)).unwrap() {
+            Value::String(s) => s,
+            other => panic!("expected string, got {:?}", other),
+        };
+        assert_eq!(round_trip("2024-02-29T23:59:59Z"), "2024-02-29T23:59:59Z");
+        assert_eq!(
+            round_trip("2024-02-29T23:59:59.250Z"),
+            "2024-02-29T23:59:59.250Z"
+        );
+        // Offsets are normalized to UTC
+        assert_eq!(
+            round_trip("2024-03-01T01:00:00+02:00"),
+            "2024-02-29T23:00:00Z"
+        );
+
+        assert_eq!(
+            time("2024-01-01T00:00:00Z").to_string(),
+            "2024-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            json::to_json(&time("2024-01-01T00:00:00Z")).unwrap(),
+            serde_json::Value::String("2024-01-01T00:00:00Z".to_string())
+        );
+
+        assert!(matches!(
+            time::parse_rfc3339(Value::String("2024-02-30T00:00:00Z".to_string())),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(time::parse_rfc3339(Value::Integer(0)).is_err());
+        assert!(time::format_rfc3339(Value::String("2024-01-01T00:00:00Z".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_time_diff_across_day_boundary() {
+        let before = time("2024-12-31T23:59:30Z");
+        let after = time::add_seconds(before.clone(), Value::Integer(45)).unwrap();
+        assert_eq!(after, time("2025-01-01T00:00:15Z"));
+
+        assert_eq!(
+            time::diff_seconds(after.clone(), before.clone()).unwrap(),
+            Value::Integer(45)
+        );
+        assert_eq!(
+            time::diff_seconds(before.clone(), after).unwrap(),
+            Value::Integer(-45)
+        );
+        assert_eq!(
+            time::diff_seconds(time("2025-01-01T23:59:30Z"), before.clone()).unwrap(),
+            Value::Integer(86_400)
+        );
+        assert_eq!(
+            time::add_seconds(before.clone(), Value::Integer(-86_400)).unwrap(),
+            time("2024-12-30T23:59:30Z")
+        );
+
+        assert!(matches!(
+            time::add_seconds(before.clone(), Value::Integer(i128::MAX)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(time::add_seconds(before.clone(), dec("1.5")).is_err());
+        assert!(time::diff_seconds(before, Value::Integer(0)).is_err());
+    }
+
+    #[test]
+    fn test_time_now_uses_block_time() {
+        use crate::runtime::{BlockTime, ExecutionContext, Runtime};
+
+        assert_eq!(
+            time::now(&BlockTime(1_700_000_000)).unwrap(),
+            time("2023-11-14T22:13:20Z")
+        );
+
+        let runtime = Runtime::with_context(
+            1000,
+            ExecutionContext {
+                block_timestamp: 86_400,
+                ..ExecutionContext::default()
+            },
+        );
+        assert_eq!(
+            time::now(runtime.time_source().as_ref()).unwrap(),
+            time("1970-01-02T00:00:00Z")
+        );
+        assert!(time::now(&BlockTime(u64::MAX)).is_err());
+        assert_eq!(
+            type_check::is_time(time("1970-01-01T00:00:00Z")).unwrap(),
+            Value::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn test_list_take_and_drop_saturate() {
+        let abc = || {
+            Value::List(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+                Value::String("c".to_string()),
+            ])
+        };
+        let n = Value::Integer;
+
+        assert_eq!(list::take(abc(), n(0)).unwrap(), Value::List(vec![]));
+        assert_eq!(
+            list::take(abc(), n(2)).unwrap(),
+            Value::List(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string())
+            ])
+        );
+        assert_eq!(list::take(abc(), n(1000)).unwrap(), abc());
+        assert_eq!(list::take(abc(), n(i128::MAX)).unwrap(), abc());
+
+        assert_eq!(list::drop(abc(), n(0)).unwrap(), abc());
+        assert_eq!(
+            list::drop(abc(), n(2)).unwrap(),
+            Value::List(vec![Value::String("c".to_string())])
+        );
+        assert_eq!(list::drop(abc(), n(3)).unwrap(), Value::List(vec![]));
+        assert_eq!(list::drop(abc(), n(1000)).unwrap(), Value::List(vec![]));
+
+        assert!(matches!(
+            list::take(abc(), n(-1)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(matches!(
+            list::drop(Value::Integer(3), n(1)),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_at_negative_index() {
+        let list = || Value::List((10..13).map(Value::Integer).collect());
+        let at = |i| list::at(list(), Value::Integer(i));
+
+        assert_eq!(at(0).unwrap(), Value::Integer(10));
+        assert_eq!(at(-1).unwrap(), Value::Integer(12));
+        assert_eq!(at(-3).unwrap(), Value::Integer(10));
+        assert!(matches!(
+            at(3),
+            Err(SlvrError::IndexOutOfBounds {
+                index: 3,
+                length: 3
+            })
+        ));
+        assert!(matches!(
+            at(-4),
+            Err(SlvrError::IndexOutOfBounds {
+                index: -4,
+                length: 3
+            })
+        ));
+        assert!(matches!(
+            at(i128::MIN),
+            Err(SlvrError::IndexOutOfBounds { .. })
+        ));
+        assert!(list::at(Value::List(vec![]), Value::Integer(-1)).is_err());
+    }
+
+    #[test]
+    fn test_list_index_of_and_remove_at() {
+        let list = || {
+            Value::List(vec![
+                Value::Integer(1),
+                Value::String("x".to_string()),
+                Value::Integer(1),
+                Value::Integer(3),
+            ])
+        };
+
+        assert_eq!(
+            list::index_of(list(), Value::Integer(1)).unwrap(),
+            Value::Integer(0)
+        );
+        assert_eq!(
+            list::index_of(list(), Value::Integer(3)).unwrap(),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            list::index_of(list(), dec("1.0")).unwrap(),
+            Value::Integer(-1)
+        );
+        assert!(list::index_of(Value::Null, Value::Integer(1)).is_err());
+
+        assert_eq!(
+            list::remove_at(list(), Value::Integer(0)).unwrap(),
+            Value::List(vec![
+                Value::String("x".to_string()),
+                Value::Integer(1),
+                Value::Integer(3),
+            ])
+        );
+        let without_last = Value::List(vec![
+            Value::Integer(1),
+            Value::String("x".to_string()),
+            Value::Integer(1),
+        ]);
+        assert_eq!(
+            list::remove_at(list(), Value::Integer(3)).unwrap(),
+            without_last
+        );
+        assert_eq!(
+            list::remove_at(list(), Value::Integer(-1)).unwrap(),
+            without_last
+        );
+
+        assert!(matches!(
+            list::remove_at(list(), Value::Integer(4)),
+            Err(SlvrError::IndexOutOfBounds {
+                index: 4,
+                length: 4
+            })
+        ));
+        assert!(matches!(
+            list::remove_at(Value::List(vec![]), Value::Integer(0)),
+            Err(SlvrError::IndexOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            list::remove_at(list(), dec("1.0")),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_sort_orders_and_rejects_incomparable() {
+        let ints = |xs: &[i128]| Value::List(xs.iter().copied().map(Value::Integer).collect());
+
+        assert_eq!(list::sort(ints(&[3, 1, 2])).unwrap(), ints(&[1, 2, 3]));
+        assert_eq!(list::sort_desc(ints(&[3, 1, 2])).unwrap(), ints(&[3, 2, 1]));
+        assert_eq!(
+            list::sort(Value::List(vec![
+                dec("2.5"),
+                Value::Integer(1),
+                Value::Integer(3)
+            ]))
+            .unwrap(),
+            Value::List(vec![Value::Integer(1), dec("2.5"), Value::Integer(3)])
+        );
+
+        for bad in [
+            vec![Value::Integer(1), Value::String("1".to_string())],
+            vec![
+                Value::Object(BTreeMap::new()),
+                Value::Object(BTreeMap::new()),
+            ],
+            vec![Value::List(vec![]), Value::List(vec![])],
+        ] {
+            assert!(matches!(
+                list::sort(Value::List(bad)),
+                Err(SlvrError::RuntimeError { .. })
+            ));
+        }
+        assert!(list::sort(Value::Integer(1)).is_err());
+
+        // Long mixed lists are rejected up front instead of confusing the sort
+        let mixed = (0..200)
+            .map(|i| match i % 3 {
+                0 => Value::Integer(i),
+                1 => Value::String(format!("{}", 200 - i)),
+                _ => Value::Integer(-i),
+            })
+            .collect();
+        assert!(matches!(
+            list::sort(Value::List(mixed)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_sort_by_key_both_directions() {
+        let account = |name: &str, balance: Value| {
+            let mut fields = BTreeMap::new();
+            fields.insert("name".to_string(), Value::String(name.to_string()));
+            fields.insert("balance".to_string(), balance);
+            Value::Object(fields)
+        };
+        let accounts = || {
+            Value::List(vec![
+                account("carol", Value::Integer(30)),
+                account("alice", Value::Integer(10)),
+                account("dave", dec("20.5")),
+                account("bob", Value::Integer(10)),
+            ])
+        };
+        let names = |sorted: Value| match sorted {
+            Value::List(items) => items
+                .iter()
+                .map(|item| item.get_key("name").unwrap().clone())
+                .collect::<Vec<_>>(),
+            other => panic!("expected list, got {:?}", other),
+        };
+        let strings = |xs: &[&str]| {
+            xs.iter()
+                .map(|x| Value::String(x.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let key = || Value::String("balance".to_string());
+
+        // Ties keep their input order in both directions
+        assert_eq!(
+            names(list::sort_by_key(accounts(), key()).unwrap()),
+            strings(&["alice", "bob", "dave", "carol"])
+        );
+        assert_eq!(
+            names(list::sort_by_key_desc(accounts(), key()).unwrap()),
+            strings(&["carol", "dave", "alice", "bob"])
+        );
+
+        assert!(matches!(
+            list::sort_by_key(accounts(), Value::String("missing".to_string())),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(matches!(
+            list::sort_by_key(Value::List(vec![Value::Integer(1)]), key()),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(list::sort_by_key(accounts(), Value::Integer(0)).is_err());
+    }
+
+    #[test]
+    fn test_list_zip_truncates_to_shorter() {
+        let addresses = || {
+            Value::List(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+                Value::String("c".to_string()),
+            ])
+        };
+        let amounts = || Value::List(vec![Value::Integer(10), Value::Integer(20)]);
+        let pair =
+            |x: &str, n: i128| Value::List(vec![Value::String(x.to_string()), Value::Integer(n)]);
+
+        assert_eq!(
+            list::zip(addresses(), amounts()).unwrap(),
+            Value::List(vec![pair("a", 10), pair("b", 20)])
+        );
+        // Truncation doesn't depend on which side is shorter
+        assert_eq!(
+            list::zip(amounts(), addresses()).unwrap(),
+            Value::List(vec![
+                Value::List(vec![Value::Integer(10), Value::String("a".to_string())]),
+                Value::List(vec![Value::Integer(20), Value::String("b".to_string())]),
+            ])
+        );
+        assert_eq!(
+            list::zip(addresses(), Value::List(vec![])).unwrap(),
+            Value::List(vec![])
+        );
+        assert!(matches!(
+            list::zip(addresses(), Value::Integer(1)),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_enumerate() {
+        let list = Value::List(vec![Value::String("x".to_string()), Value::Null]);
+        assert_eq!(
+            list::enumerate(list).unwrap(),
+            Value::List(vec![
+                Value::List(vec![Value::Integer(0), Value::String("x".to_string())]),
+                Value::List(vec![Value::Integer(1), Value::Null]),
+            ])
+        );
+        assert_eq!(
+            list::enumerate(Value::List(vec![])).unwrap(),
+            Value::List(vec![])
+        );
+        assert!(matches!(
+            list::enumerate(Value::String("ab".to_string())),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_distinct_keeps_first_seen_order() {
+        let mixed = Value::List(vec![
+            Value::String("b".to_string()),
+            Value::Integer(1),
+            dec("1.0"),
+            Value::String("b".to_string()),
+            Value::Integer(1),
+            Value::Null,
+            Value::List(vec![Value::Integer(1)]),
+            Value::Null,
+            Value::List(vec![Value::Integer(1)]),
+        ]);
+        assert_eq!(
+            list::distinct(mixed).unwrap(),
+            Value::List(vec![
+                Value::String("b".to_string()),
+                Value::Integer(1),
+                dec("1.0"),
+                Value::Null,
+                Value::List(vec![Value::Integer(1)]),
+            ])
+        );
+        assert_eq!(
+            list::distinct(Value::List(vec![])).unwrap(),
+            Value::List(vec![])
+        );
+        assert!(matches!(
+            list::distinct(Value::String("aa".to_string())),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_flatten_leaves_scalars_intact() {
+        let nested = Value::List(vec![
+            Value::Integer(1),
+            Value::List(vec![Value::Integer(2), Value::Integer(3)]),
+            Value::List(vec![]),
+            Value::String("x".to_string()),
+            Value::List(vec![Value::List(vec![Value::Integer(4)])]),
+        ]);
+        assert_eq!(
+            list::flatten(nested).unwrap(),
+            Value::List(vec![
+                Value::Integer(1),
+                Value::Integer(2),
+                Value::Integer(3),
+                Value::String("x".to_string()),
+                // Only one level is removed
+                Value::List(vec![Value::Integer(4)]),
+            ])
+        );
+        assert!(matches!(
+            list::flatten(Value::Integer(1)),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_map_filter_fold() {
+        let ints = || Value::List((1..=4).map(Value::Integer).collect());
+        let square = |v: Value| match v {
+            Value::Integer(n) => Ok(Value::Integer(n * n)),
+            other => Err(SlvrError::type_mismatch("integer", other.type_name())),
+        };
+
+        assert_eq!(
+            list::map(ints(), square).unwrap(),
+            Value::List(vec![
+                Value::Integer(1),
+                Value::Integer(4),
+                Value::Integer(9),
+                Value::Integer(16)
+            ])
+        );
+        assert_eq!(
+            list::filter(ints(), |v| Ok(Value::Boolean(v.to_integer()? % 2 == 0))).unwrap(),
+            Value::List(vec![Value::Integer(2), Value::Integer(4)])
+        );
+        assert_eq!(
+            list::fold(ints(), Value::Integer(0), |acc, v| Ok(Value::Integer(
+                acc.to_integer()? + v.to_integer()?
+            )))
+            .unwrap(),
+            Value::Integer(10)
+        );
+
+        // The first failing call stops the walk
+        let mut calls = 0;
+        let result = list::map(ints(), |v| {
+            calls += 1;
+            if v == Value::Integer(2) {
+                Err(SlvrError::runtime("stop"))
+            } else {
+                Ok(v)
+            }
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+
+        assert!(matches!(
+            list::map(Value::Integer(1), square),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(list::fold(Value::Null, Value::Integer(0), |a, _| Ok(a)).is_err());
+    }
+
+    #[test]
+    fn test_compound() {
+        let compound = |p, r, n| {
+            math::compound(Value::Integer(p), Value::Integer(r), Value::Integer(n)).unwrap()
+        };
+
+        assert_eq!(compound(1000, 500, 0), Value::Integer(1000));
+        assert_eq!(compound(1000, 500, 1), Value::Integer(1050));
+        // 1000 * 1.05^2 = 1102.5, rounded down
+        assert_eq!(compound(1000, 500, 2), Value::Integer(1102));
+        // 10000 * 1.1^3 = 13310
+        assert_eq!(compound(10_000, 1000, 3), Value::Integer(13_310));
+        assert_eq!(compound(1000, 0, 50), Value::Integer(1000));
+    }
+
+    #[test]
+    fn test_decimal_formatting_in_strings() {
+        let joined = string::concat(vec![Value::String("x=".to_string()), dec("3.0")]).unwrap();
+        assert_eq!(joined, Value::String("x=3.0".to_string()));
+
+        let formatted = string::format(
+            Value::String("{0} {1}".to_string()),
+            vec![dec("1e21"), dec("1e-7")],
+        )
+        .unwrap();
+        assert_eq!(
+            formatted,
+            Value::String("1000000000000000000000.0 0.0000001".to_string())
+        );
+    }
+
+    #[test]
+    fn test_math_rejects_out_of_range_results() {
+        assert!(math::pow(dec("-8.0"), dec("0.5")).is_err());
+        assert!(math::pow(dec("10.0"), Value::Integer(400)).is_err());
+        assert!(math::sqrt(dec("-0.5")).is_err());
+        assert!(conversion::to_decimal(Value::String("NaN".to_string())).is_err());
+        assert_eq!(math::sqrt(Value::Integer(4)).unwrap(), dec("2.0"));
+    }
+
+    #[test]
+    fn test_index_by_address() {
+        let account = |address: &str, balance: i128| {
+            let mut fields = BTreeMap::new();
+            fields.insert("address".to_string(), Value::String(address.to_string()));
+            fields.insert("balance".to_string(), Value::Integer(balance));
+            Value::Object(fields)
+        };
+        let accounts = vec![account("alice", 10), account("bob", 20)];
+
+        let index = list::index_by(
+            Value::List(accounts.clone()),
+            Value::String("address".to_string()),
+        )
+        .unwrap();
+        assert_eq!(index.get_field("alice").unwrap(), accounts[0]);
+        assert_eq!(index.get_field("bob").unwrap(), accounts[1]);
+        assert_eq!(object::values(index).unwrap().len().unwrap(), 2);
+
+        let duplicated = Value::List(vec![account("alice", 10), account("alice", 30)]);
+        assert!(matches!(
+            list::index_by(duplicated, Value::String("address".to_string())),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(list::index_by(Value::List(accounts), Value::String("owner".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_modulo_and_rem_signs() {
+        let int = |a: i128, b: i128| (Value::Integer(a), Value::Integer(b));
+        let call = |f: fn(Value, Value) -> SlvrResult<Value>, (a, b): (Value, Value)| f(a, b);
+
+        assert_eq!(call(math::modulo, int(7, 3)).unwrap(), Value::Integer(1));
+        assert_eq!(call(math::modulo, int(-7, 3)).unwrap(), Value::Integer(2));
+        assert_eq!(call(math::modulo, int(7, -3)).unwrap(), Value::Integer(-2));
+        assert_eq!(call(math::modulo, int(-7, -3)).unwrap(), Value::Integer(-1));
+        assert_eq!(call(math::modulo, int(-6, 3)).unwrap(), Value::Integer(0));
+
+        assert_eq!(call(math::rem, int(7, 3)).unwrap(), Value::Integer(1));
+        assert_eq!(call(math::rem, int(-7, 3)).unwrap(), Value::Integer(-1));
+        assert_eq!(call(math::rem, int(7, -3)).unwrap(), Value::Integer(1));
+        assert_eq!(call(math::rem, int(-7, -3)).unwrap(), Value::Integer(-1));
+        assert_eq!(
+            call(math::rem, int(i128::MIN, -1)).unwrap(),
+            Value::Integer(0)
+        );
+
+        assert_eq!(
+            math::modulo(dec("-7.5"), Value::Integer(2)).unwrap(),
+            dec("0.5")
+        );
+        assert_eq!(
+            math::rem(dec("-7.5"), Value::Integer(2)).unwrap(),
+            dec("-1.5")
+        );
+
+        for f in [math::modulo, math::rem] {
+            assert!(matches!(
+                call(f, int(1, 0)),
+                Err(SlvrError::RuntimeError { .. })
+            ));
+            assert!(matches!(
+                f(dec("1.0"), dec("0.0")),
+                Err(SlvrError::RuntimeError { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_integer_pow_overflow_is_an_error() {
+        let pow = |b: i128, e: i128| math::pow(Value::Integer(b), Value::Integer(e));
+        assert_eq!(pow(2, 126).unwrap(), Value::Integer(1 << 126));
+        assert!(matches!(pow(2, 200), Err(SlvrError::RuntimeError { .. })));
+
+        let huge = u32::MAX as i128 + 2;
+        assert!(matches!(pow(2, huge), Err(SlvrError::RuntimeError { .. })));
+        assert_eq!(pow(1, huge).unwrap(), Value::Integer(1));
+        assert_eq!(pow(-1, huge).unwrap(), Value::Integer(-1));
+        assert_eq!(pow(0, huge).unwrap(), Value::Integer(0));
+    }
+
+    #[test]
+    fn test_round_to_uses_bankers_rounding() {
+        let round_to =
+            |x: &str, places: i128| math::round_to(dec(x), Value::Integer(places)).unwrap();
+
+        assert_eq!(round_to("0.5", 0), dec("0.0"));
+        assert_eq!(round_to("1.5", 0), dec("2.0"));
+        assert_eq!(round_to("2.5", 0), dec("2.0"));
+        assert_eq!(round_to("-2.5", 0), dec("-2.0"));
+        assert_eq!(round_to("2.675", 2), dec("2.68"));
+        assert_eq!(round_to("2.665", 2), dec("2.66"));
+        assert_eq!(round_to("2.6651", 2), dec("2.67"));
+        assert_eq!(round_to("9.995", 2), dec("10.0"));
+        assert_eq!(round_to("1.25", 5), dec("1.25"));
+        assert_eq!(
+            math::round_to(Value::Integer(7), Value::Integer(2)).unwrap(),
+            dec("7.0")
+        );
+
+        assert!(matches!(
+            math::round_to(dec("1.0"), Value::Integer(-1)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(math::round_to(dec("1.0"), dec("2.0")).is_err());
+    }
+
+    #[test]
+    fn test_list_aggregates_promote_to_decimal() {
+        let ints = || {
+            Value::List(vec![
+                Value::Integer(2),
+                Value::Integer(3),
+                Value::Integer(4),
+            ])
+        };
+        let mixed = || Value::List(vec![Value::Integer(2), dec("0.5"), Value::Integer(4)]);
+
+        assert_eq!(math::sum(ints()).unwrap(), Value::Integer(9));
+        assert_eq!(math::product(ints()).unwrap(), Value::Integer(24));
+        assert_eq!(math::average(ints()).unwrap(), dec("3.0"));
+
+        assert_eq!(math::sum(mixed()).unwrap(), dec("6.5"));
+        assert_eq!(math::product(mixed()).unwrap(), dec("4.0"));
+        assert_eq!(
+            math::average(mixed()).unwrap(),
+            dec("2.1666666666666666666666666667")
+        );
+
+        assert_eq!(math::sum(Value::List(vec![])).unwrap(), Value::Integer(0));
+        assert_eq!(
+            math::product(Value::List(vec![])).unwrap(),
+            Value::Integer(1)
+        );
+        assert!(matches!(
+            math::average(Value::List(vec![])),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+
+        let bad = Value::List(vec![Value::Integer(1), Value::String("2".to_string())]);
+        match math::sum(bad) {
+            Err(SlvrError::TypeError { message, .. }) => assert!(message.contains("element 1")),
+            other => panic!("expected type error, got {:?}", other),
+        }
+        assert!(math::sum(Value::List(vec![
+            Value::Integer(i128::MAX),
+            Value::Integer(1)
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_clamp_and_sign() {
+        let clamp = |x: i128| {
+            math::clamp(Value::Integer(x), Value::Integer(10), Value::Integer(20)).unwrap()
+        };
+        assert_eq!(clamp(5), Value::Integer(10));
+        assert_eq!(clamp(15), Value::Integer(15));
+        assert_eq!(clamp(25), Value::Integer(20));
+
+        let clamp = |x: &str| math::clamp(dec(x), Value::Integer(1), dec("2.5")).unwrap();
+        assert_eq!(clamp("0.5"), dec("1.0"));
+        assert_eq!(clamp("1.5"), dec("1.5"));
+        assert_eq!(clamp("3.0"), dec("2.5"));
+
+        assert!(matches!(
+            math::clamp(Value::Integer(1), Value::Integer(5), Value::Integer(0)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(math::clamp(Value::Boolean(true), Value::Integer(0), Value::Integer(1)).is_err());
+
+        assert_eq!(math::sign(Value::Integer(-7)).unwrap(), Value::Integer(-1));
+        assert_eq!(math::sign(Value::Integer(0)).unwrap(), Value::Integer(0));
+        assert_eq!(math::sign(dec("0.25")).unwrap(), Value::Integer(1));
+        assert_eq!(math::sign(dec("-0.0")).unwrap(), Value::Integer(0));
+    }
+
+    #[test]
+    fn test_gcd_and_lcm() {
+        let int = Value::Integer;
+        assert_eq!(math::gcd(int(12), int(-18)).unwrap(), int(6));
+        assert_eq!(math::gcd(int(0), int(0)).unwrap(), int(0));
+        assert_eq!(math::gcd(int(0), int(-5)).unwrap(), int(5));
+        assert_eq!(math::lcm(int(-4), int(6)).unwrap(), int(12));
+        assert_eq!(math::lcm(int(0), int(6)).unwrap(), int(0));
+
+        // Large coprime Mersenne primes
+        let p: i128 = (1 << 61) - 1;
+        let q: i128 = (1 << 31) - 1;
+        assert_eq!(math::gcd(int(p), int(q)).unwrap(), int(1));
+        assert_eq!(math::lcm(int(p), int(q)).unwrap(), int(p * q));
+        assert!(matches!(
+            math::lcm(int(p), int((1 << 89) - 1)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(math::gcd(int(i128::MIN), int(0)).is_err());
+        assert!(math::gcd(dec("2.0"), int(4)).is_err());
+    }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(math::isqrt(Value::Integer(0)).unwrap(), Value::Integer(0));
+        assert_eq!(
+            math::isqrt(Value::Integer(144)).unwrap(),
+            Value::Integer(12)
+        );
+        assert_eq!(
+            math::isqrt(Value::Integer(143)).unwrap(),
+            Value::Integer(11)
+        );
+
+        // Far beyond f64's mantissa, where the float root is off by 8
+        let root: i128 = 1_000_000_000_000_000_009;
+        let n = root * root - 1;
+        assert_ne!((n as f64).sqrt() as i128, root - 1);
+        assert_eq!(
+            math::isqrt(Value::Integer(n)).unwrap(),
+            Value::Integer(root - 1)
+        );
+        assert_eq!(
+            math::isqrt(Value::Integer(i128::MAX)).unwrap(),
+            Value::Integer(13_043_817_825_332_782_212)
+        );
+
+        assert!(math::isqrt(Value::Integer(-1)).is_err());
+        assert!(math::isqrt(dec("4.0")).is_err());
+    }
+
+    #[test]
+    fn test_length_and_substring_count_characters() {
+        let s = |text: &str| Value::String(text.to_string());
+        assert_eq!(string::length(s("héllo")).unwrap(), Value::Integer(5));
+        assert_eq!(string::byte_length(s("héllo")).unwrap(), Value::Integer(6));
+        assert_eq!(string::length(s("日本語")).unwrap(), Value::Integer(3));
+        assert_eq!(string::byte_length(s("日本語")).unwrap(), Value::Integer(9));
+
+        assert_eq!(
+            string::substring(s("héllo"), Value::Integer(1), Value::Integer(3)).unwrap(),
+            s("él")
+        );
+        assert_eq!(
+            string::substring(s("日本語"), Value::Integer(1), Value::Integer(3)).unwrap(),
+            s("本語")
+        );
+        assert_eq!(
+            string::substring(s("日本語"), Value::Integer(3), Value::Integer(3)).unwrap(),
+            s("")
+        );
+        assert!(string::substring(s("日本語"), Value::Integer(0), Value::Integer(4)).is_err());
+        assert!(string::substring(s("日本語"), Value::Integer(-1), Value::Integer(1)).is_err());
+        assert!(string::substring(s("日本語"), Value::Integer(2), Value::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn test_replace_and_replace_first() {
+        let s = |text: &str| Value::String(text.to_string());
+        assert_eq!(
+            string::replace(s("a-b-c"), s("-"), s("+")).unwrap(),
+            s("a+b+c")
+        );
+        assert_eq!(
+            string::replace(s("aaaa"), s("aa"), s("b")).unwrap(),
+            s("bb")
+        );
+        assert_eq!(
+            string::replace_first(s("a-b-c"), s("-"), s("+")).unwrap(),
+            s("a+b-c")
+        );
+        assert_eq!(string::replace(s("abc"), s(""), s("x")).unwrap(), s("abc"));
+        assert_eq!(
+            string::replace_first(s("abc"), s(""), s("x")).unwrap(),
+            s("abc")
+        );
+        assert!(matches!(
+            string::replace(s("abc"), Value::Integer(1), s("x")),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_index_of_uses_character_positions() {
+        let s = |text: &str| Value::String(text.to_string());
+        assert_eq!(
+            string::index_of(s("日本語本"), s("本")).unwrap(),
+            Value::Integer(1)
+        );
+        assert_eq!(
+            string::last_index_of(s("日本語本"), s("本")).unwrap(),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            string::index_of(s("héllo"), s("x")).unwrap(),
+            Value::Integer(-1)
+        );
+        assert_eq!(
+            string::last_index_of(s("héllo"), s("x")).unwrap(),
+            Value::Integer(-1)
+        );
+        assert_eq!(
+            string::index_of(s("héllo"), s("")).unwrap(),
+            Value::Integer(0)
+        );
+        assert_eq!(
+            string::last_index_of(s("héllo"), s("")).unwrap(),
+            Value::Integer(5)
+        );
+        assert!(matches!(
+            string::index_of(s("abc"), Value::Null),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pad_counts_characters() {
+        let s = |text: &str| Value::String(text.to_string());
+        assert_eq!(
+            string::pad_left(s("42"), Value::Integer(5), s("0")).unwrap(),
+            s("00042")
+        );
+        assert_eq!(
+            string::pad_right(s("日本"), Value::Integer(4), s("·")).unwrap(),
+            s("日本··")
+        );
+        assert_eq!(
+            string::pad_left(s("héllo"), Value::Integer(3), s(" ")).unwrap(),
+            s("héllo")
+        );
+        assert!(matches!(
+            string::pad_left(s("x"), Value::Integer(3), s("ab")),
+            Err(SlvrError::TypeError { .. })
+        ));
+        assert!(string::pad_right(s("x"), Value::Integer(3), s("")).is_err());
+        assert!(string::pad_left(s("x"), Value::Integer(i128::MAX), s(" ")).is_err());
+    }
+
+    #[test]
+    fn test_join_inverts_split() {
+        let s = |text: &str| Value::String(text.to_string());
+        for (text, delim) in [
+            ("a,b,c", ","),
+            ("a,,b,", ","),
+            ("one :: two", " :: "),
+            ("日本|語", "|"),
+            ("", ","),
+        ] {
+            let parts = string::split(s(text), s(delim)).unwrap();
+            assert_eq!(string::join(parts, s(delim)).unwrap(), s(text));
+        }
+
+        let mixed = Value::List(vec![s("a"), Value::Integer(1)]);
+        match string::join(mixed, s(",")) {
+            Err(SlvrError::TypeError { message, .. }) => assert!(message.contains("element 1")),
+            other => panic!("expected a type error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_repeat_is_bounded() {
+        let s = |text: &str| Value::String(text.to_string());
+        assert_eq!(
+            string::repeat(s("ab"), Value::Integer(3)).unwrap(),
+            s("ababab")
+        );
+        assert_eq!(string::repeat(s("ab"), Value::Integer(0)).unwrap(), s(""));
+        assert!(matches!(
+            string::repeat(s("ab"), Value::Integer(-1)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+
+        // Would be petabytes if it were allocated
+        assert!(matches!(
+            string::repeat(s("ab"), Value::Integer(i128::MAX)),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+
+        let config = crate::SlvrConfig::new().with_max_string_length(4);
+        assert!(string::repeat_with_max_length(
+            s("ab"),
+            Value::Integer(2),
+            config.max_string_length
+        )
+        .is_ok());
+        assert!(string::repeat_with_max_length(
+            s("ab"),
+            Value::Integer(3),
+            config.max_string_length
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_sha512_constant_time_comparison() {
+        let data = || Value::String("payload".to_string());
+        let digest = match crypto::sha512_hash(data()).unwrap() {
+            Value::String(h) => h,
+            other => panic!("expected hex digest, got {:?}", other),
+        };
+
+        let verify = |h: String| crypto::verify_sha512(data(), Value::String(h)).unwrap();
+        assert_eq!(verify(digest.clone()), Value::Boolean(true));
+        assert_eq!(verify(digest.to_uppercase()), Value::Boolean(true));
+
+        let mut flipped = digest.clone();
+        flipped.replace_range(..1, if digest.starts_with('0') { "1" } else { "0" });
+        assert_eq!(verify(flipped), Value::Boolean(false));
+        assert_eq!(
+            verify(digest[..digest.len() - 2].to_string()),
+            Value::Boolean(false)
+        );
+        assert_eq!(verify("not hex".to_string()), Value::Boolean(false));
+    }
+
+    #[test]
+    fn test_keccak256_and_sha3_256_empty_vectors() {
+        let empty = || Value::String(String::new());
+        assert_eq!(
+            crypto::keccak256(empty()).unwrap(),
+            Value::String(
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".to_string()
+            )
+        );
+        assert_eq!(
+            crypto::sha3_256(empty()).unwrap(),
+            Value::String(
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a".to_string()
+            )
+        );
+        assert_eq!(
+            crypto::keccak256(Value::Integer(42)).unwrap(),
+            crypto::keccak256(Value::String("42".to_string())).unwrap()
+        );
+        assert!(crypto::sha3_256(Value::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn test_ripemd160_and_hash160() {
+        assert_eq!(
+            crypto::ripemd160(Value::String(String::new())).unwrap(),
+            Value::String("9c1185a5c5e9fc54612808977ee8f548b2258d31".to_string())
+        );
+
+        // Compressed public key from the Bitcoin address derivation walkthrough
+        let public_key =
+            hex::decode("0250863ad64a87ae8a2fe83c1af1a8403cb53f53e486d8511dad8a04887e5b2352")
+                .unwrap();
+        assert_eq!(
+            hex::encode(crypto::hash160_bytes(&public_key)),
+            "f54a5851e9372b87810a8e60cdd2e7cfd80b6e31"
+        );
+
+        let data = || Value::String("slvr".to_string());
+        let hashed = crypto::hash160(data()).unwrap();
+        assert_eq!(
+            hashed,
+            Value::String(hex::encode(crypto::hash160_bytes(b"slvr")))
+        );
+        let sha_hex = hex::encode(sha2::Sha256::digest(b"slvr"));
+        assert_ne!(hashed, crypto::ripemd160(Value::String(sha_hex)).unwrap());
+    }
+
+    #[test]
+    fn test_ed25519_verify() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let pubkey = hex::encode(signing_key.verifying_key().to_bytes());
+        let signature = hex::encode(signing_key.sign(b"transfer 10").to_bytes());
+        let s = |text: &str| Value::String(text.to_string());
+        let verify = |k: &str, m: &str, sig: &str| crypto::ed25519_verify(s(k), s(m), s(sig));
+
+        assert_eq!(
+            verify(&pubkey, "transfer 10", &signature).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            verify(&pubkey, "transfer 11", &signature).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            verify(&pubkey[..62], "transfer 10", &signature).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            verify(&pubkey, "transfer 10", "zz").unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(matches!(
+            crypto::ed25519_verify(Value::Integer(1), s("m"), s(&signature)),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let s = |text: &str| Value::String(text.to_string());
+        assert_eq!(crypto::hex_encode(s("slvr")).unwrap(), s("736c7672"));
+        assert_eq!(crypto::hex_decode(s("736C7672")).unwrap(), s("slvr"));
+        assert_eq!(
+            crypto::hex_decode(crypto::hex_encode(s("日本")).unwrap()).unwrap(),
+            s("日本")
+        );
+        assert!(matches!(
+            crypto::hex_decode(s("736")),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        assert!(matches!(
+            crypto::hex_decode(s("zz")),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_base64_known_vectors() {
+        let s = |text: &str| Value::String(text.to_string());
+        // RFC 4648 section 10
+        for (plain, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(crypto::base64_encode(s(plain)).unwrap(), s(encoded));
+            assert_eq!(crypto::base64_decode(s(encoded)).unwrap(), s(plain));
+        }
+
+        assert_eq!(crypto::base64_encode(s("??>")).unwrap(), s("Pz8+"));
+        assert_eq!(crypto::base64url_encode(s("??>")).unwrap(), s("Pz8-"));
+        assert_eq!(crypto::base64url_encode(s("f")).unwrap(), s("Zg"));
+        assert_eq!(crypto::base64url_decode(s("Pz8-")).unwrap(), s("??>"));
+
+        assert!(crypto::base64_decode(s("Zg=")).is_err());
+        assert!(crypto::base64_decode(s("Zm9v!")).is_err());
+        assert!(crypto::base64_decode(s("/w==")).is_err());
+        assert!(matches!(
+            crypto::base64_encode(Value::Integer(1)),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_string_build() {
+        let parts = Value::List(vec![
+            Value::String("id-".to_string()),
+            Value::Integer(7),
+            Value::String(":".to_string()),
+            Value::Boolean(true),
+        ]);
+        let runtime = crate::runtime::Runtime::new(1_000);
+        let built = string::build_metered(parts.clone(), 64, &runtime).unwrap();
+        assert_eq!(built, Value::String("id-7:true".to_string()));
+        assert_eq!(runtime.fuel_used(), 9 * string::BUILD_FUEL_PER_BYTE);
+        assert_eq!(string::build(parts).unwrap(), built);
+    }
+
+    #[test]
+    fn test_string_build_rejects_long_output() {
+        let parts = Value::List(vec![Value::String("x".repeat(10)); 3]);
+        let runtime = crate::runtime::Runtime::new(1_000);
+        assert!(string::build_metered(parts.clone(), 29, &runtime).is_err());
+        assert_eq!(runtime.fuel_used(), 0);
+
+        let big = Value::List(vec![Value::String(
+            "x".repeat(string::MAX_BUILD_LENGTH + 1),
+        )]);
+        assert!(string::build(big).is_err());
+        assert!(string::build(Value::List(vec![Value::Null])).is_err());
+    }
+
+    #[test]
+    fn test_partition_preserves_order() {
+        let mixed = Value::List(vec![
+            Value::Integer(1),
+            Value::String("a".to_string()),
+            Value::Integer(2),
+            dec("1.5"),
+            Value::Integer(3),
+        ]);
+        let result = list::partition(mixed, Value::String("is_integer".to_string())).unwrap();
+        assert_eq!(
+            result,
+            Value::List(vec![
+                Value::List(vec![
+                    Value::Integer(1),
+                    Value::Integer(2),
+                    Value::Integer(3)
+                ]),
+                Value::List(vec![Value::String("a".to_string()), dec("1.5")]),
+            ])
+        );
+
+        let unknown = list::partition(Value::List(vec![]), Value::String("is_even".to_string()));
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn test_object_keys_ignore_insertion_order() {
+        let build = |order: &[&str]| {
+            let mut map = BTreeMap::new();
+            for (i, key) in order.iter().enumerate() {
+                map.insert(key.to_string(), Value::Integer(i as i128));
+            }
+            Value::Object(map)
+        };
+        let forward = build(&["zeta", "alpha", "mid"]);
+        let backward = build(&["mid", "alpha", "zeta"]);
+
+        let keys = object::keys(forward.clone()).unwrap();
+        assert_eq!(keys, object::keys(backward.clone()).unwrap());
+        assert_eq!(
+            keys,
+            Value::List(vec![
+                Value::String("alpha".to_string()),
+                Value::String("mid".to_string()),
+                Value::String("zeta".to_string()),
+            ])
+        );
+        assert_eq!(
+            object::values(forward).unwrap(),
+            Value::List(vec![
+                Value::Integer(1),
+                Value::Integer(2),
+                Value::Integer(0)
+            ])
+        );
+        assert_eq!(
+            object::values(backward).unwrap(),
+            Value::List(vec![
+                Value::Integer(1),
+                Value::Integer(0),
+                Value::Integer(2)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_get_path() {
+        let nested = json::from_json(serde_json::json!({
+            "a": {"b": {"c": 7}, "n": 3}
+        }))
+        .unwrap();
+        let path = |keys: &[&str]| {
+            Value::List(keys.iter().map(|k| Value::String(k.to_string())).collect())
+        };
+
+        assert_eq!(
+            object::get_path(nested.clone(), path(&["a", "b", "c"])).unwrap(),
+            Value::Integer(7)
+        );
+        assert_eq!(
+            object::get_path(nested.clone(), path(&["a", "x", "c"])).unwrap(),
+            Value::Null
+        );
+        assert_eq!(object::get_path(nested.clone(), path(&[])).unwrap(), nested);
+        match object::get_path(nested.clone(), path(&["a", "n", "c"])) {
+            Err(SlvrError::RuntimeError { message, .. }) => assert!(message.contains("'a.n'")),
+            other => panic!("expected runtime error, got {:?}", other),
+        }
+        assert!(matches!(
+            object::get_path(nested, Value::List(vec![Value::Integer(0)])),
+            Err(SlvrError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_integer_is_exact() {
+        let convert = conversion::to_integer;
+        assert_eq!(convert(dec("-42.0")).unwrap(), Value::Integer(-42));
+        assert_eq!(
+            convert(Value::Decimal(Decimal::MAX)).unwrap(),
+            Value::Integer(Decimal::MAX.mantissa())
+        );
+        assert!(matches!(
+            convert(dec("3.5")),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+        // Values beyond the decimal range can't be built in the first place
+        for bad in ["1e40", "NaN", "inf"] {
+            assert!(convert(Value::String(bad.to_string())).is_err());
+            assert!(conversion::to_decimal(Value::String(bad.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_int_radix() {
+        let parse = |s: &str, radix: i128| {
+            conversion::parse_int(Value::String(s.to_string()), Value::Integer(radix))
+        };
+        assert_eq!(parse("ff", 16).unwrap(), Value::Integer(255));
+        assert_eq!(parse("0xFF", 16).unwrap(), Value::Integer(255));
+        assert_eq!(parse("0b1010", 2).unwrap(), Value::Integer(10));
+        assert_eq!(parse("-0o17", 8).unwrap(), Value::Integer(-15));
+        assert_eq!(parse("-42", 10).unwrap(), Value::Integer(-42));
+
+        for (s, radix) in [("12", 2), ("0x", 16), ("0x1g", 16), ("-+1", 10), ("", 10)] {
+            assert!(
+                matches!(parse(s, radix), Err(SlvrError::RuntimeError { .. })),
+                "{:?} in base {} should not parse",
+                s,
+                radix
+            );
+        }
+        assert!(matches!(
+            parse("10", 3),
+            Err(SlvrError::RuntimeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_canonicalize_sorts_keys() {
+        let mut a = BTreeMap::new();
+        a.insert("b".to_string(), Value::Integer(2));
+        a.insert("a".to_string(), dec("1.5"));
+        a.insert(
+            "c".to_string(),
+            Value::List(vec![Value::Boolean(true), Value::Null]),
+        );
+        let mut b = BTreeMap::new();
+        b.insert(
+            "c".to_string(),
+            Value::List(vec![Value::Boolean(true), Value::Null]),
+        );
+        b.insert("a".to_string(), dec("1.5"));
+        b.insert("b".to_string(), Value::Integer(2));
+
+        let ca = json::canonicalize(Value::Object(a.clone())).unwrap();
+        let cb = json::canonicalize(Value::Object(b)).unwrap();
+        assert_eq!(ca, cb);
+        assert_eq!(
+            ca,
+            Value::String(r#"{"a":1.5,"b":2,"c":[true,null]}"#.to_string())
+        );
+
+        assert_eq!(json::parse(ca).unwrap(), Value::Object(a));
+
+        // Numbers are read from their digits, not rounded through a float
+        assert_eq!(
+            json::parse(Value::String("[0.1000000000000000000001]".to_string())).unwrap(),
+            Value::List(vec![dec("0.1000000000000000000001")])
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_numbers_and_strings() {
+        let canon = |v| match json::canonicalize(v).unwrap() {
+            Value::String(s) => s,
+            other => panic!("expected string, got {:?}", other),
+        };
+        assert_eq!(canon(dec("100.0")), "100");
+        assert_eq!(canon(dec("-0.000001")), "-0.000001");
+        assert_eq!(canon(dec("1e-7")), "1e-7");
+        assert_eq!(canon(dec("1e21")), "1e+21");
+        // Integers take the same form as the decimals they equal
+        assert_eq!(canon(Value::Integer(-42)), "-42");
+        assert_eq!(canon(Value::Integer(10i128.pow(21))), canon(dec("1e21")));
+        assert_eq!(canon(Value::Integer(123 * 10i128.pow(20))), "1.23e+22");
+        assert_eq!(
+            canon(Value::Integer(i128::MIN)),
+            "-1.70141183460469231731687303715884105728e+38"
+        );
+        assert_eq!(
+            canon(Value::String("a\"\n\u{1}é".to_string())),
+            r#""a\"\n\u0001é""#
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_deep_nesting() {
+        let nested =
+            |depth: usize| Value::String(format!("{}{}", "[".repeat(depth), "]".repeat(depth)));
+
+        assert!(json::parse(nested(crate::MAX_VALUE_DEPTH)).is_ok());
+        assert!(matches!(
+            json::parse(nested(crate::MAX_VALUE_DEPTH + 1)),
+            Err(SlvrError::ValueDepthExceeded {
+                max_depth: crate::MAX_VALUE_DEPTH
+            })
+        ));
+
+        let config = crate::SlvrConfig::new().with_max_value_depth(3);
+        let object = Value::String(r#"{"a": {"b": {"c": [1]}}}"#.to_string());
+        assert!(matches!(
+            json::parse_with_max_depth(object, config.max_value_depth),
+            Err(SlvrError::ValueDepthExceeded { max_depth: 3 })
+        ));
+
+        // Far beyond the limit still fails cleanly rather than overflowing
+        assert!(json::parse(nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn test_compound_rejects_overflow() {
+        let result = math::compound(
+            Value::Integer(1_000_000),
+            Value::Integer(10_000),
+            Value::Integer(200),
+        );
+        assert!(matches!(result, Err(SlvrError::RuntimeError { .. })));
+
+        let result = math::compound(
+            Value::Integer(1),
+            Value::Integer(0),
+            Value::Integer(math::MAX_COMPOUND_PERIODS + 1),
+        );
+        assert!(matches!(result, Err(SlvrError::RuntimeError { .. })));
+    }
 }
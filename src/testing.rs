@@ -112,9 +112,7 @@ impl TestRunner {
             suite.test_cases.push(test_case);
             Ok(test_id)
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("Suite not found: {}", suite_id),
-            })
+            Err(SlvrError::runtime(format!("Suite not found: {}", suite_id)))
         }
     }
 
@@ -150,9 +148,7 @@ impl TestRunner {
         let suite = self
             .suites
             .get(suite_id)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Suite not found: {}", suite_id),
-            })?
+            .ok_or_else(|| SlvrError::runtime(format!("Suite not found: {}", suite_id)))?
             .clone();
 
         let start_time = Utc::now();
@@ -235,12 +231,10 @@ impl TestRunner {
 
         // Validate contract and function names
         if test_case.contract.is_empty() || test_case.function.is_empty() {
-            return Err(SlvrError::RuntimeError {
-                message: format!(
-                    "Invalid contract or function: {}/{}",
-                    test_case.contract, test_case.function
-                ),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid contract or function: {}/{}",
+                test_case.contract, test_case.function
+            )));
         }
 
         // Base fuel cost for test execution
@@ -270,16 +264,18 @@ impl TestRunner {
             .chars()
             .all(|c| c.is_alphanumeric() || c == '_' || c == ':')
         {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid contract name: {}", contract),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid contract name: {}",
+                contract
+            )));
         }
 
         // Validate function name format
         if !function.chars().all(|c| c.is_alphanumeric() || c == '_') {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid function name: {}", function),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid function name: {}",
+                function
+            )));
         }
 
         // Execute based on contract and function combination
@@ -343,22 +339,22 @@ impl TestRunner {
 
         // Validate addresses
         if !from.starts_with("SLVR") || from.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid sender address: {}", from),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid sender address: {}",
+                from
+            )));
         }
 
         if !to.starts_with("SLVR") || to.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid recipient address: {}", to),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid recipient address: {}",
+                to
+            )));
         }
 
         // Validate amount
         if amount == 0 {
-            return Err(SlvrError::RuntimeError {
-                message: "Transfer amount must be greater than 0".to_string(),
-            });
+            return Err(SlvrError::runtime("Transfer amount must be greater than 0"));
         }
 
         Ok(Value::Boolean(true))
@@ -384,16 +380,15 @@ impl TestRunner {
 
         // Validate spender address
         if !spender.starts_with("SLVR") || spender.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid spender address: {}", spender),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid spender address: {}",
+                spender
+            )));
         }
 
         // Validate amount
         if amount == 0 {
-            return Err(SlvrError::RuntimeError {
-                message: "Approval amount must be greater than 0".to_string(),
-            });
+            return Err(SlvrError::runtime("Approval amount must be greater than 0"));
         }
 
         Ok(Value::Boolean(true))
@@ -414,9 +409,10 @@ impl TestRunner {
 
         // Validate account address
         if !account.starts_with("SLVR") || account.len() != 68 {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invalid account address: {}", account),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invalid account address: {}",
+                account
+            )));
         }
 
         Ok(Value::Integer(10000))
@@ -494,9 +490,7 @@ impl TestRunner {
 
         // Validate division by zero
         if b == 0 {
-            return Err(SlvrError::RuntimeError {
-                message: "Division by zero".to_string(),
-            });
+            return Err(SlvrError::runtime("Division by zero"));
         }
 
         Ok(Value::Integer((a / b) as i128))
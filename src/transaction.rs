@@ -107,9 +107,7 @@ impl Transaction {
     /// Begin transaction
     pub fn begin(&mut self) -> SlvrResult<()> {
         if self.status != TransactionStatus::Pending {
-            return Err(SlvrError::RuntimeError {
-                message: "transaction already started".to_string(),
-            });
+            return Err(SlvrError::runtime("transaction already started"));
         }
         self.status = TransactionStatus::Running;
         Ok(())
@@ -118,9 +116,7 @@ impl Transaction {
     /// Add operation to transaction
     pub fn add_operation(&mut self, operation: Operation) -> SlvrResult<()> {
         if self.status != TransactionStatus::Running {
-            return Err(SlvrError::RuntimeError {
-                message: "transaction not running".to_string(),
-            });
+            return Err(SlvrError::runtime("transaction not running"));
         }
         self.operations.push(operation);
         Ok(())
@@ -129,9 +125,7 @@ impl Transaction {
     /// Commit transaction
     pub fn commit(&mut self) -> SlvrResult<()> {
         if self.status != TransactionStatus::Running {
-            return Err(SlvrError::RuntimeError {
-                message: "transaction not running".to_string(),
-            });
+            return Err(SlvrError::runtime("transaction not running"));
         }
 
         for operation in &self.operations {
@@ -151,9 +145,7 @@ impl Transaction {
     /// Rollback transaction
     pub fn rollback(&mut self) -> SlvrResult<()> {
         if self.status != TransactionStatus::Running && self.status != TransactionStatus::Failed {
-            return Err(SlvrError::RuntimeError {
-                message: "cannot rollback committed transaction".to_string(),
-            });
+            return Err(SlvrError::runtime("cannot rollback committed transaction"));
         }
 
         self.operations.clear();
@@ -228,9 +220,7 @@ impl TransactionManager {
         self.transactions
             .get(tx_id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("transaction {} not found", tx_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("transaction {} not found", tx_id)))
     }
 
     /// Add operation to transaction
@@ -238,9 +228,10 @@ impl TransactionManager {
         if let Some(tx) = self.transactions.get_mut(tx_id) {
             tx.add_operation(operation)
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("transaction {} not found", tx_id),
-            })
+            Err(SlvrError::runtime(format!(
+                "transaction {} not found",
+                tx_id
+            )))
         }
     }
 
@@ -251,9 +242,10 @@ impl TransactionManager {
             self.active_transactions.retain(|id| id != tx_id);
             Ok(())
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("transaction {} not found", tx_id),
-            })
+            Err(SlvrError::runtime(format!(
+                "transaction {} not found",
+                tx_id
+            )))
         }
     }
 
@@ -264,9 +256,10 @@ impl TransactionManager {
             self.active_transactions.retain(|id| id != tx_id);
             Ok(())
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("transaction {} not found", tx_id),
-            })
+            Err(SlvrError::runtime(format!(
+                "transaction {} not found",
+                tx_id
+            )))
         }
     }
 
@@ -280,9 +273,7 @@ impl TransactionManager {
         self.transactions
             .get(tx_id)
             .map(|tx| tx.log.clone())
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("transaction {} not found", tx_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("transaction {} not found", tx_id)))
     }
 
     /// List all transactions
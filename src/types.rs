@@ -6,7 +6,7 @@
 
 use crate::error::{SlvrError, SlvrResult};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 
 /// Represents a type in the Slvr language
@@ -22,8 +22,8 @@ pub enum Type {
     Boolean,
     /// List type with element type
     List(Box<Type>),
-    /// Object/map type with field types
-    Object(HashMap<String, Type>),
+    /// Object/map type with field types; no fields means the shape is unknown
+    Object(BTreeMap<String, Type>),
     /// Function type: (arg_types) -> return_type
     Function(Vec<Type>, Box<Type>),
     /// Unit type (void)
@@ -35,7 +35,11 @@ pub enum Type {
     /// Table type for database operations
     Table(Box<Type>),
     /// Schema type for table definitions
-    Schema(HashMap<String, Type>),
+    Schema(BTreeMap<String, Type>),
+    /// Any one of the member types
+    Union(Vec<Type>),
+    /// The inner type or null, such as a row that may not exist
+    Optional(Box<Type>),
 }
 
 impl std::hash::Hash for Type {
@@ -62,6 +66,14 @@ impl std::hash::Hash for Type {
                 t.hash(state);
             }
             Type::Schema(_) => 11.hash(state),
+            Type::Union(members) => {
+                12.hash(state);
+                members.hash(state);
+            }
+            Type::Optional(inner) => {
+                13.hash(state);
+                inner.hash(state);
+            }
         }
     }
 }
@@ -74,7 +86,17 @@ impl fmt::Display for Type {
             Type::String => write!(f, "string"),
             Type::Boolean => write!(f, "boolean"),
             Type::List(inner) => write!(f, "[{}]", inner),
-            Type::Object(_) => write!(f, "object"),
+            Type::Object(fields) if fields.is_empty() => write!(f, "object"),
+            Type::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (field, ty)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", field, ty)?;
+                }
+                write!(f, "}}")
+            }
             Type::Function(args, ret) => {
                 write!(f, "(")?;
                 for (i, arg) in args.iter().enumerate() {
@@ -90,6 +112,16 @@ impl fmt::Display for Type {
             Type::Custom(name) => write!(f, "{}", name),
             Type::Table(inner) => write!(f, "table<{}>", inner),
             Type::Schema(_) => write!(f, "schema"),
+            Type::Optional(inner) => write!(f, "optional<{}>", inner),
+            Type::Union(members) => {
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " | ")?;
+                    }
+                    write!(f, "{}", member)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -99,10 +131,55 @@ impl Type {
     pub fn is_compatible_with(&self, other: &Type) -> bool {
         match (self, other) {
             (Type::Any, _) | (_, Type::Any) => true,
+            (Type::List(a), Type::List(b)) => a.is_compatible_with(b),
+            // An optional holds its inner type, but nothing else holds an optional
+            (Type::Optional(a), Type::Optional(b)) => a.is_compatible_with(b),
+            (Type::Optional(a), _) => a.is_compatible_with(other),
+            (_, Type::Optional(_)) => false,
+            (Type::Object(a), Type::Object(b)) if a.is_empty() || b.is_empty() => true,
+            (Type::Object(a), Type::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(field, ty)| b.get(field).is_some_and(|t| ty.is_compatible_with(t)))
+            }
+            // Every type `other` may hold has to be one of ours
+            (_, Type::Union(theirs)) => theirs.iter().all(|t| self.is_compatible_with(t)),
+            (Type::Union(ours), _) => ours.iter().any(|t| t.is_compatible_with(other)),
             (a, b) => a == b,
         }
     }
 
+    /// The single type covering both `self` and `other`, if there is one.
+    ///
+    /// `Any` is unknown rather than a type of its own, so it yields the other side.
+    pub fn unify(&self, other: &Type) -> Option<Type> {
+        match (self, other) {
+            (Type::Any, t) | (t, Type::Any) => Some(t.clone()),
+            (Type::List(a), Type::List(b)) => Some(Type::List(Box::new(a.unify(b)?))),
+            (Type::Optional(a), Type::Optional(b)) => Some(Type::Optional(Box::new(a.unify(b)?))),
+            (Type::Optional(a), t) | (t, Type::Optional(a)) => {
+                Some(Type::Optional(Box::new(a.unify(t)?)))
+            }
+            // Objects of different shapes are still objects, just of unknown shape
+            (Type::Object(a), Type::Object(b)) => {
+                let same_shape = a.len() == b.len() && a.keys().all(|field| b.contains_key(field));
+                let fields = if same_shape {
+                    a.iter()
+                        .map(|(field, ty)| Some((field.clone(), ty.unify(&b[field])?)))
+                        .collect::<Option<_>>()
+                        .unwrap_or_default()
+                } else {
+                    BTreeMap::new()
+                };
+                Some(Type::Object(fields))
+            }
+            (a, b) if a == b => Some(a.clone()),
+            (Type::Union(_), t) if self.is_compatible_with(t) => Some(self.clone()),
+            (t, Type::Union(_)) if other.is_compatible_with(t) => Some(other.clone()),
+            _ => None,
+        }
+    }
+
     /// Get the default value for this type
     pub fn default_value(&self) -> String {
         match self {
@@ -134,6 +211,24 @@ impl Type {
     pub fn is_collection(&self) -> bool {
         matches!(self, Type::List(_) | Type::Object(_))
     }
+
+    /// Names of the custom types this type mentions, outermost first
+    fn custom_names(&self) -> Vec<&str> {
+        match self {
+            Type::Custom(name) => vec![name.as_str()],
+            Type::List(inner) | Type::Table(inner) | Type::Optional(inner) => inner.custom_names(),
+            Type::Union(members) => members.iter().flat_map(Type::custom_names).collect(),
+            Type::Function(args, ret) => args
+                .iter()
+                .chain(std::iter::once(&**ret))
+                .flat_map(Type::custom_names)
+                .collect(),
+            Type::Object(fields) | Type::Schema(fields) => {
+                fields.values().flat_map(Type::custom_names).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
 }
 
 /// Type environment for tracking variable and function types
@@ -147,6 +242,8 @@ pub struct TypeEnv {
     custom_types: HashMap<String, Type>,
     /// Table definitions
     tables: HashMap<String, Type>,
+    /// `deftype` aliases, stored as written
+    aliases: HashMap<String, Type>,
 }
 
 impl TypeEnv {
@@ -157,6 +254,7 @@ impl TypeEnv {
             functions: HashMap::new(),
             custom_types: HashMap::new(),
             tables: HashMap::new(),
+            aliases: HashMap::new(),
         }
     }
 
@@ -217,6 +315,119 @@ impl TypeEnv {
         self.custom_types.get(name).cloned()
     }
 
+    /// Define a type alias, rejecting one that would refer back to itself
+    pub fn define_alias(&mut self, name: String, ty: Type) -> SlvrResult<()> {
+        if self.aliases.contains_key(&name) {
+            return Err(SlvrError::type_error(format!(
+                "type alias {} is already defined",
+                name
+            )));
+        }
+        let mut path = vec![name.clone()];
+        self.check_alias_cycle(&name, &ty, &mut path, &mut HashSet::new())?;
+        self.aliases.insert(name, ty);
+        Ok(())
+    }
+
+    /// Walk the aliases reachable from `ty`, failing if one of them is `name`.
+    /// `visited` holds the aliases already walked, which cannot reach `name`.
+    fn check_alias_cycle(
+        &self,
+        name: &str,
+        ty: &Type,
+        path: &mut Vec<String>,
+        visited: &mut HashSet<String>,
+    ) -> SlvrResult<()> {
+        for referenced in ty.custom_names() {
+            path.push(referenced.to_string());
+            if referenced == name {
+                return Err(SlvrError::type_error(format!(
+                    "type alias {} is cyclic: {}",
+                    name,
+                    path.join(" -> ")
+                )));
+            }
+            if let Some(target) = self.aliases.get(referenced) {
+                if visited.insert(referenced.to_string()) {
+                    self.check_alias_cycle(name, target, path, visited)?;
+                }
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+
+    /// Replace every alias in `ty` by the type it stands for, failing if the
+    /// result would have more than `MAX_TYPE_SIZE` nodes
+    pub fn resolve_type(&self, ty: &Type) -> SlvrResult<Type> {
+        let (resolved, _) = self.resolve_cached(ty, &mut HashMap::new())?;
+        Ok(resolved)
+    }
+
+    /// Resolve `ty` and count its nodes; each alias is resolved once and
+    /// then taken from `cache`
+    fn resolve_cached(
+        &self,
+        ty: &Type,
+        cache: &mut HashMap<String, (Type, usize)>,
+    ) -> SlvrResult<(Type, usize)> {
+        let mut size = 1;
+        let mut resolve = |t: &Type, cache: &mut HashMap<String, (Type, usize)>| {
+            let (resolved, inner) = self.resolve_cached(t, cache)?;
+            size += inner;
+            if size > crate::MAX_TYPE_SIZE {
+                return Err(SlvrError::type_error(format!(
+                    "type is too large once its aliases are resolved: the limit is {} nodes",
+                    crate::MAX_TYPE_SIZE
+                )));
+            }
+            Ok(resolved)
+        };
+        let resolved = match ty {
+            Type::Custom(name) => match self.aliases.get(name) {
+                Some(target) => {
+                    if let Some(hit) = cache.get(name) {
+                        return Ok(hit.clone());
+                    }
+                    let hit = self.resolve_cached(target, cache)?;
+                    cache.insert(name.clone(), hit.clone());
+                    return Ok(hit);
+                }
+                None => ty.clone(),
+            },
+            Type::List(inner) => Type::List(Box::new(resolve(inner, cache)?)),
+            Type::Table(inner) => Type::Table(Box::new(resolve(inner, cache)?)),
+            Type::Optional(inner) => Type::Optional(Box::new(resolve(inner, cache)?)),
+            Type::Union(members) => Type::Union(
+                members
+                    .iter()
+                    .map(|t| resolve(t, cache))
+                    .collect::<SlvrResult<_>>()?,
+            ),
+            Type::Function(args, ret) => {
+                let args = args
+                    .iter()
+                    .map(|t| resolve(t, cache))
+                    .collect::<SlvrResult<_>>()?;
+                Type::Function(args, Box::new(resolve(ret, cache)?))
+            }
+            Type::Object(fields) => Type::Object(
+                fields
+                    .iter()
+                    .map(|(field, t)| Ok((field.clone(), resolve(t, cache)?)))
+                    .collect::<SlvrResult<_>>()?,
+            ),
+            Type::Schema(fields) => Type::Schema(
+                fields
+                    .iter()
+                    .map(|(field, t)| Ok((field.clone(), resolve(t, cache)?)))
+                    .collect::<SlvrResult<_>>()?,
+            ),
+            _ => ty.clone(),
+        };
+        Ok((resolved, size))
+    }
+
     /// Define a table
     pub fn define_table(&mut self, name: String, schema: Type) {
         self.tables.insert(name, schema);
@@ -267,6 +478,103 @@ mod tests {
         assert!(!Type::Integer.is_compatible_with(&Type::String));
     }
 
+    #[test]
+    fn test_union_and_list_compatibility() {
+        let integers = Type::List(Box::new(Type::Integer));
+        let union = Type::Union(vec![Type::Integer, Type::String]);
+        assert!(union.is_compatible_with(&Type::String));
+        assert!(!union.is_compatible_with(&Type::Boolean));
+        assert!(!Type::Integer.is_compatible_with(&union));
+        assert!(Type::List(Box::new(union.clone())).is_compatible_with(&integers));
+        assert_eq!(union.to_string(), "integer | string");
+
+        assert_eq!(Type::Any.unify(&Type::Integer), Some(Type::Integer));
+        assert_eq!(
+            Type::List(Box::new(Type::Any)).unify(&integers),
+            Some(integers.clone())
+        );
+        assert_eq!(union.unify(&Type::Integer), Some(union.clone()));
+        assert_eq!(Type::Integer.unify(&Type::String), None);
+        assert_eq!(Type::Integer.unify(&Type::Decimal), None);
+    }
+
+    #[test]
+    fn test_alias_resolution_and_cycles() {
+        let mut env = TypeEnv::new();
+        // Forward references are fine as long as they never close a loop
+        env.define_alias(
+            "ledger".to_string(),
+            Type::List(Box::new(Type::Custom("amount".to_string()))),
+        )
+        .unwrap();
+        env.define_alias("amount".to_string(), Type::Decimal)
+            .unwrap();
+        assert_eq!(
+            env.resolve_type(&Type::Custom("ledger".to_string()))
+                .unwrap(),
+            Type::List(Box::new(Type::Decimal))
+        );
+        assert_eq!(
+            env.resolve_type(&Type::Custom("account".to_string()))
+                .unwrap(),
+            Type::Custom("account".to_string())
+        );
+
+        let self_referential = env.define_alias(
+            "tree".to_string(),
+            Type::List(Box::new(Type::Custom("tree".to_string()))),
+        );
+        assert!(matches!(self_referential, Err(SlvrError::TypeError { .. })));
+
+        env.define_alias("a".to_string(), Type::Custom("b".to_string()))
+            .unwrap();
+        match env.define_alias("b".to_string(), Type::Custom("a".to_string())) {
+            Err(SlvrError::TypeError { message, .. }) => assert!(message.contains("b -> a -> b")),
+            other => panic!("expected a cycle error, got {:?}", other),
+        }
+        assert!(env
+            .define_alias("amount".to_string(), Type::Integer)
+            .is_err());
+    }
+
+    #[test]
+    fn test_alias_diamonds_are_walked_once() {
+        // Each level names the next one twice
+        let mut env = TypeEnv::new();
+        let level = |i: usize| format!("a{}", i);
+        for i in 0..60 {
+            let next = Type::Custom(level(i + 1));
+            let fields = BTreeMap::from([("x".to_string(), next.clone()), ("y".to_string(), next)]);
+            env.define_alias(level(i), Type::Object(fields)).unwrap();
+        }
+        env.define_alias("top".to_string(), Type::Custom(level(0)))
+            .unwrap();
+        assert!(env
+            .define_alias(level(60), Type::Custom("top".to_string()))
+            .is_err());
+
+        env.define_alias(level(60), Type::Integer).unwrap();
+        assert!(matches!(
+            env.resolve_type(&Type::Custom("top".to_string())),
+            Err(SlvrError::TypeError { .. })
+        ));
+        let leaf = Type::Object(BTreeMap::from([
+            ("x".to_string(), Type::Integer),
+            ("y".to_string(), Type::Integer),
+        ]));
+        assert_eq!(env.resolve_type(&Type::Custom(level(59))).unwrap(), leaf);
+    }
+
+    #[test]
+    fn test_optional_compatibility() {
+        let optional = Type::Optional(Box::new(Type::Integer));
+        assert!(optional.is_compatible_with(&Type::Integer));
+        assert!(!Type::Integer.is_compatible_with(&optional));
+        assert!(Type::Any.is_compatible_with(&optional));
+        assert_eq!(optional.to_string(), "optional<integer>");
+        assert_eq!(Type::Integer.unify(&optional), Some(optional.clone()));
+    }
+
     #[test]
     fn test_type_properties() {
         assert!(Type::Integer.is_numeric());
@@ -278,7 +586,7 @@ mod tests {
         assert!(!Type::List(Box::new(Type::Integer)).is_comparable());
 
         assert!(Type::List(Box::new(Type::Integer)).is_collection());
-        assert!(Type::Object(HashMap::new()).is_collection());
+        assert!(Type::Object(BTreeMap::new()).is_collection());
     }
 
     #[test]
@@ -4,6 +4,7 @@
 //! mechanisms to allow contracts to evolve while maintaining state consistency.
 
 use crate::error::{SlvrError, SlvrResult};
+use crate::smartcontract_api::SchemaDefinition;
 use crate::value::Value;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -202,8 +203,8 @@ impl UpgradeManager {
         self.versions
             .get(contract_name)
             .and_then(|versions| versions.iter().find(|v| v.version == version).cloned())
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Version not found: {}/{}", contract_name, version),
+            .ok_or_else(|| {
+                SlvrError::runtime(format!("Version not found: {}/{}", contract_name, version))
             })
     }
 
@@ -217,12 +218,9 @@ impl UpgradeManager {
 
     /// Get the active version of a contract
     pub fn get_active_version(&self, contract_name: &str) -> SlvrResult<ContractVersion> {
-        let version_str =
-            self.active_versions
-                .get(contract_name)
-                .ok_or_else(|| SlvrError::RuntimeError {
-                    message: format!("No active version for contract: {}", contract_name),
-                })?;
+        let version_str = self.active_versions.get(contract_name).ok_or_else(|| {
+            SlvrError::runtime(format!("No active version for contract: {}", contract_name))
+        })?;
 
         self.get_version(contract_name, version_str)
     }
@@ -286,16 +284,12 @@ impl UpgradeManager {
     pub fn vote_on_proposal(&mut self, proposal_id: &str, vote_for: bool) -> SlvrResult<()> {
         if let Some(proposal) = self.proposals.get_mut(proposal_id) {
             if proposal.status != ProposalStatus::Voting {
-                return Err(SlvrError::RuntimeError {
-                    message: "Proposal is not open for voting".to_string(),
-                });
+                return Err(SlvrError::runtime("Proposal is not open for voting"));
             }
 
             if Utc::now() > proposal.voting_deadline {
                 proposal.status = ProposalStatus::Rejected;
-                return Err(SlvrError::RuntimeError {
-                    message: "Voting period has ended".to_string(),
-                });
+                return Err(SlvrError::runtime("Voting period has ended"));
             }
 
             if vote_for {
@@ -306,9 +300,10 @@ impl UpgradeManager {
 
             Ok(())
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("Proposal not found: {}", proposal_id),
-            })
+            Err(SlvrError::runtime(format!(
+                "Proposal not found: {}",
+                proposal_id
+            )))
         }
     }
 
@@ -316,15 +311,11 @@ impl UpgradeManager {
     pub fn finalize_proposal(&mut self, proposal_id: &str) -> SlvrResult<()> {
         if let Some(proposal) = self.proposals.get_mut(proposal_id) {
             if proposal.status != ProposalStatus::Voting {
-                return Err(SlvrError::RuntimeError {
-                    message: "Proposal is not in voting status".to_string(),
-                });
+                return Err(SlvrError::runtime("Proposal is not in voting status"));
             }
 
             if Utc::now() <= proposal.voting_deadline {
-                return Err(SlvrError::RuntimeError {
-                    message: "Voting period has not ended".to_string(),
-                });
+                return Err(SlvrError::runtime("Voting period has not ended"));
             }
 
             if proposal.votes_for > proposal.votes_against {
@@ -335,9 +326,10 @@ impl UpgradeManager {
 
             Ok(())
         } else {
-            Err(SlvrError::RuntimeError {
-                message: format!("Proposal not found: {}", proposal_id),
-            })
+            Err(SlvrError::runtime(format!(
+                "Proposal not found: {}",
+                proposal_id
+            )))
         }
     }
 
@@ -346,15 +338,11 @@ impl UpgradeManager {
         let proposal = self
             .proposals
             .get(proposal_id)
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Proposal not found: {}", proposal_id),
-            })?
+            .ok_or_else(|| SlvrError::runtime(format!("Proposal not found: {}", proposal_id)))?
             .clone();
 
         if proposal.status != ProposalStatus::Approved {
-            return Err(SlvrError::RuntimeError {
-                message: "Proposal is not approved".to_string(),
-            });
+            return Err(SlvrError::runtime("Proposal is not approved"));
         }
 
         // Create state migration record
@@ -390,9 +378,7 @@ impl UpgradeManager {
         self.proposals
             .get(proposal_id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Proposal not found: {}", proposal_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Proposal not found: {}", proposal_id)))
     }
 
     /// Get all proposals for a contract
@@ -409,9 +395,7 @@ impl UpgradeManager {
         self.migrations
             .get(migration_id)
             .cloned()
-            .ok_or_else(|| SlvrError::RuntimeError {
-                message: format!("Migration not found: {}", migration_id),
-            })
+            .ok_or_else(|| SlvrError::runtime(format!("Migration not found: {}", migration_id)))
     }
 
     /// Get upgrade statistics
@@ -441,6 +425,97 @@ impl UpgradeManager {
     }
 }
 
+/// Check that replacing `old` with `new` keeps existing table data valid.
+///
+/// Rejects removing a field that `sample_rows` still populate, adding a required
+/// field without a default while rows exist, and changing a field's type unless
+/// the change widens it (`integer` to `decimal`) or every stored value already
+/// fits the new type.
+pub fn validate_schema_change(
+    old: &SchemaDefinition,
+    new: &SchemaDefinition,
+    sample_rows: &[serde_json::Value],
+) -> SlvrResult<()> {
+    let mut old_fields: Vec<_> = old.fields.values().collect();
+    old_fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for old_field in old_fields {
+        let name = &old_field.name;
+        match new.fields.get(name) {
+            None => {
+                if let Some(index) = rows_using(sample_rows, name).next() {
+                    return Err(SlvrError::runtime(format!(
+                        "Schema {}: cannot remove field {} still used by row {}",
+                        new.name, name, index
+                    )));
+                }
+            }
+            Some(new_field) if new_field.ty != old_field.ty => {
+                if is_widening(&old_field.ty, &new_field.ty) {
+                    continue;
+                }
+                for index in rows_using(sample_rows, name) {
+                    let value = &sample_rows[index][name];
+                    if !value_has_type(value, &new_field.ty) {
+                        return Err(SlvrError::runtime(format!(
+                            "Schema {}: cannot change field {} from {} to {}: row {} holds {}",
+                            new.name, name, old_field.ty, new_field.ty, index, value
+                        )));
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut added: Vec<_> = new
+        .fields
+        .values()
+        .filter(|f| !old.fields.contains_key(&f.name))
+        .collect();
+    added.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for field in added {
+        if field.required && field.default_value.is_none() && !sample_rows.is_empty() {
+            return Err(SlvrError::runtime(format!(
+                "Schema {}: new required field {} needs a default for existing rows",
+                new.name, field.name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Indices of rows that hold a non-null value for `field`
+fn rows_using<'a>(
+    rows: &'a [serde_json::Value],
+    field: &'a str,
+) -> impl Iterator<Item = usize> + 'a {
+    rows.iter()
+        .enumerate()
+        .filter(move |(_, row)| row.get(field).is_some_and(|v| !v.is_null()))
+        .map(|(index, _)| index)
+}
+
+/// Whether every value of type `from` is also a valid `to`
+fn is_widening(from: &str, to: &str) -> bool {
+    matches!((from, to), ("integer", "decimal"))
+}
+
+fn value_has_type(value: &serde_json::Value, ty: &str) -> bool {
+    match ty {
+        "integer" => value.is_i64() || value.is_u64(),
+        "decimal" => value.is_number(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        t if t.starts_with('[') => value.is_array(),
+        // Custom types are not checked structurally
+        _ => true,
+    }
+}
+
 /// Statistics for upgrade manager
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpgradeStats {
@@ -600,4 +675,51 @@ mod tests {
         assert_eq!(stats.total_contracts, 1);
         assert_eq!(stats.total_versions, 2);
     }
+
+    fn schema(fields: &[(&str, &str, bool)]) -> SchemaDefinition {
+        let mut schema = SchemaDefinition::new("accounts".to_string(), String::new());
+        for (name, ty, required) in fields {
+            schema.add_field(crate::smartcontract_api::FieldType {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                required: *required,
+                default_value: None,
+            });
+        }
+        schema
+    }
+
+    #[test]
+    fn test_schema_change_additive_is_safe() {
+        let old = schema(&[("balance", "integer", true)]);
+        let new = schema(&[("balance", "decimal", true), ("memo", "string", false)]);
+        let rows = vec![serde_json::json!({"balance": 100})];
+        assert!(validate_schema_change(&old, &new, &rows).is_ok());
+    }
+
+    #[test]
+    fn test_schema_change_rejects_narrowing() {
+        let old = schema(&[("balance", "decimal", true)]);
+        let new = schema(&[("balance", "integer", true)]);
+        let rows = vec![
+            serde_json::json!({"balance": 100}),
+            serde_json::json!({"balance": 12.5}),
+        ];
+        let err = validate_schema_change(&old, &new, &rows).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("balance"));
+        assert!(message.contains("row 1"));
+
+        // The same narrowing is fine once the data fits
+        assert!(validate_schema_change(&old, &new, &rows[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_schema_change_rejects_removing_used_field() {
+        let old = schema(&[("balance", "integer", true), ("owner", "string", true)]);
+        let new = schema(&[("balance", "integer", true)]);
+        let rows = vec![serde_json::json!({"balance": 1, "owner": "alice"})];
+        assert!(validate_schema_change(&old, &new, &rows).is_err());
+        assert!(validate_schema_change(&old, &new, &[]).is_ok());
+    }
 }
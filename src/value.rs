@@ -2,40 +2,127 @@
 //!
 //! Represents values that can be computed and stored during execution.
 
+use crate::ast::Expr;
 use crate::error::{SlvrError, SlvrResult};
 
+use chrono::{DateTime, SecondsFormat, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt;
 
+pub use rust_decimal::Decimal;
+
 /// A runtime value in the Slvr language
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Value {
     /// Integer value (arbitrary precision)
     Integer(i128),
-    /// Decimal value (fixed-point)
-    Decimal(f64),
+    /// Decimal value (fixed-point, exact to 28 significant digits)
+    Decimal(Decimal),
     /// String value
     String(String),
     /// Boolean value
     Boolean(bool),
+    /// UTC instant
+    Time(DateTime<Utc>),
     /// List value
     List(Vec<Value>),
-    /// Object/map value
-    Object(HashMap<String, Value>),
+    /// Object/map value, iterated in key order so every node sees the same order
+    Object(BTreeMap<String, Value>),
     /// Unit value
     Unit,
     /// Null value
     Null,
+    /// Function value produced by a `lambda`
+    Closure(Box<Closure>),
+}
+
+/// A `lambda` together with the locals visible where it was created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Closure {
+    pub params: Vec<String>,
+    pub body: Expr,
+    pub env: BTreeMap<String, Value>,
+}
+
+/// Canonical text form of a decimal.
+///
+/// Always positional (never scientific), trailing zeros trimmed, and at least
+/// one fractional digit, so `1` renders as `1.0` and `1.50` as `1.5`.
+/// Negative zero renders as `0.0`.
+pub fn format_decimal(d: Decimal) -> String {
+    if d.is_zero() {
+        return "0.0".to_string();
+    }
+
+    let mut out = d.normalize().to_string();
+    if !out.contains('.') {
+        out.push_str(".0");
+    }
+    out
+}
+
+/// Canonical text form of a time: RFC 3339 in UTC with a `Z` suffix and
+/// only as many fractional-second digits as the instant needs
+pub fn format_time(t: &DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Parse decimal text, positional or scientific, exactly where it fits
+pub fn parse_decimal(text: &str) -> Option<Decimal> {
+    text.parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(text))
+        .ok()
+}
+
+/// Exact decimal form of an integer, for mixed integer/decimal arithmetic
+pub fn integer_to_decimal(i: i128) -> SlvrResult<Decimal> {
+    Decimal::try_from_i128_with_scale(i, 0)
+        .map_err(|_| SlvrError::runtime(format!("integer {} is outside the decimal range", i)))
+}
+
+/// Raise a decimal to a decimal power.
+///
+/// Integral exponents are computed exactly by repeated multiplication;
+/// fractional exponents go through `exp`/`ln` and are approximate.
+pub fn decimal_pow(base: Decimal, exponent: Decimal) -> SlvrResult<Value> {
+    use rust_decimal::MathematicalOps;
+
+    let result = if exponent.fract().is_zero() {
+        exponent.to_i64().and_then(|e| base.checked_powi(e))
+    } else if base.is_sign_negative() {
+        return Err(SlvrError::runtime(
+            "pow of a negative base needs an integer exponent",
+        ));
+    } else {
+        base.checked_powd(exponent)
+    };
+    checked_decimal(result, "pow")
+}
+
+/// Wrap the result of a checked integer operation, reporting overflow
+pub fn checked_integer(result: Option<i128>, op: &str) -> SlvrResult<Value> {
+    result
+        .map(Value::Integer)
+        .ok_or_else(|| SlvrError::runtime(format!("integer overflow in {}", op)))
+}
+
+/// Wrap the result of a checked decimal operation, reporting overflow
+pub fn checked_decimal(result: Option<Decimal>, op: &str) -> SlvrResult<Value> {
+    result
+        .map(Value::Decimal)
+        .ok_or_else(|| SlvrError::runtime(format!("decimal overflow in {}", op)))
 }
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::Integer(n) => write!(f, "{}", n),
-            Value::Decimal(d) => write!(f, "{}", d),
+            Value::Decimal(d) => write!(f, "{}", format_decimal(*d)),
             Value::String(s) => write!(f, "\"{}\"", s),
             Value::Boolean(b) => write!(f, "{}", b),
+            Value::Time(t) => write!(f, "{}", format_time(t)),
             Value::List(items) => {
                 write!(f, "[")?;
                 for (i, item) in items.iter().enumerate() {
@@ -48,9 +135,7 @@ impl fmt::Display for Value {
             }
             Value::Object(map) => {
                 write!(f, "{{")?;
-                let mut items: Vec<_> = map.iter().collect();
-                items.sort_by_key(|(k, _)| k.as_str());
-                for (i, (k, v)) in items.iter().enumerate() {
+                for (i, (k, v)) in map.iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
@@ -60,6 +145,7 @@ impl fmt::Display for Value {
             }
             Value::Unit => write!(f, "()"),
             Value::Null => write!(f, "null"),
+            Value::Closure(closure) => write!(f, "<lambda ({})>", closure.params.join(", ")),
         }
     }
 }
@@ -68,9 +154,10 @@ impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Value::Integer(a), Value::Integer(b)) => a == b,
-            (Value::Decimal(a), Value::Decimal(b)) => (a - b).abs() < f64::EPSILON,
+            (Value::Decimal(a), Value::Decimal(b)) => a == b,
             (Value::String(a), Value::String(b)) => a == b,
             (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Time(a), Value::Time(b)) => a == b,
             (Value::List(a), Value::List(b)) => a == b,
             (Value::Object(a), Value::Object(b)) => a == b,
             (Value::Unit, Value::Unit) => true,
@@ -81,6 +168,17 @@ impl PartialEq for Value {
 }
 
 impl Value {
+    /// Build a decimal from a host float via its shortest decimal form,
+    /// rejecting NaN, infinities and magnitudes beyond the decimal range
+    pub fn decimal(d: f64) -> SlvrResult<Value> {
+        if !d.is_finite() {
+            return Err(SlvrError::runtime(format!("non-finite decimal: {}", d)));
+        }
+        parse_decimal(&d.to_string())
+            .map(Value::Decimal)
+            .ok_or_else(|| SlvrError::runtime(format!("{} is outside the decimal range", d)))
+    }
+
     /// Check if this value is truthy
     pub fn is_truthy(&self) -> bool {
         match self {
@@ -88,8 +186,9 @@ impl Value {
             Value::Null => false,
             Value::Unit => false,
             Value::Integer(n) => *n != 0,
-            Value::Decimal(d) => *d != 0.0,
+            Value::Decimal(d) => !d.is_zero(),
             Value::String(s) => !s.is_empty(),
+            Value::Time(_) | Value::Closure(_) => true,
             Value::List(l) => !l.is_empty(),
             Value::Object(o) => !o.is_empty(),
         }
@@ -99,7 +198,10 @@ impl Value {
     pub fn to_integer(&self) -> SlvrResult<i128> {
         match self {
             Value::Integer(n) => Ok(*n),
-            Value::Decimal(d) => Ok(*d as i128),
+            Value::Decimal(d) => d
+                .trunc()
+                .to_i128()
+                .ok_or_else(|| SlvrError::type_mismatch("integer", "decimal")),
             Value::Boolean(b) => Ok(if *b { 1 } else { 0 }),
             Value::String(s) => s
                 .parse::<i128>()
@@ -109,14 +211,14 @@ impl Value {
     }
 
     /// Convert to decimal
-    pub fn to_decimal(&self) -> SlvrResult<f64> {
+    pub fn to_decimal(&self) -> SlvrResult<Decimal> {
         match self {
-            Value::Integer(n) => Ok(*n as f64),
+            Value::Integer(n) => integer_to_decimal(*n),
             Value::Decimal(d) => Ok(*d),
-            Value::Boolean(b) => Ok(if *b { 1.0 } else { 0.0 }),
-            Value::String(s) => s
-                .parse::<f64>()
-                .map_err(|_| SlvrError::type_mismatch("decimal", "string")),
+            Value::Boolean(b) => Ok(if *b { Decimal::ONE } else { Decimal::ZERO }),
+            Value::String(s) => {
+                parse_decimal(s).ok_or_else(|| SlvrError::type_mismatch("decimal", "string"))
+            }
             _ => Err(SlvrError::type_mismatch("decimal", self.type_name())),
         }
     }
@@ -134,7 +236,7 @@ impl Value {
         match self {
             Value::Boolean(b) => Ok(*b),
             Value::Integer(n) => Ok(*n != 0),
-            Value::Decimal(d) => Ok(*d != 0.0),
+            Value::Decimal(d) => Ok(!d.is_zero()),
             Value::String(s) => Ok(!s.is_empty()),
             Value::Null => Ok(false),
             Value::Unit => Ok(false),
@@ -149,10 +251,12 @@ impl Value {
             Value::Decimal(_) => "decimal",
             Value::String(_) => "string",
             Value::Boolean(_) => "boolean",
+            Value::Time(_) => "time",
             Value::List(_) => "list",
             Value::Object(_) => "object",
             Value::Unit => "unit",
             Value::Null => "null",
+            Value::Closure(_) => "function",
         }
     }
 
@@ -174,10 +278,36 @@ impl Value {
         Ok(self.len()? == 0)
     }
 
+    /// Borrow an element of a list, or `None` if this is not a list or is too short
+    pub fn get_index(&self, index: usize) -> Option<&Value> {
+        match self {
+            Value::List(l) => l.get(index),
+            _ => None,
+        }
+    }
+
+    /// Borrow a field of an object, or `None` if this is not an object or lacks it
+    pub fn get_key(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(o) => o.get(key),
+            _ => None,
+        }
+    }
+
     /// Get an element from a list by index
     pub fn get_list_element(&self, index: usize) -> SlvrResult<Value> {
+        self.list_element_ref(index).cloned()
+    }
+
+    /// Get a field from an object
+    pub fn get_field(&self, key: &str) -> SlvrResult<Value> {
+        self.field_ref(key).cloned()
+    }
+
+    /// Borrowing form of `get_list_element`
+    pub(crate) fn list_element_ref(&self, index: usize) -> SlvrResult<&Value> {
         match self {
-            Value::List(l) => l.get(index).cloned().ok_or(SlvrError::IndexOutOfBounds {
+            Value::List(l) => self.get_index(index).ok_or(SlvrError::IndexOutOfBounds {
                 index: index as i64,
                 length: l.len(),
             }),
@@ -188,10 +318,10 @@ impl Value {
         }
     }
 
-    /// Get a field from an object
-    pub fn get_field(&self, key: &str) -> SlvrResult<Value> {
+    /// Borrowing form of `get_field`
+    pub(crate) fn field_ref(&self, key: &str) -> SlvrResult<&Value> {
         match self {
-            Value::Object(o) => o.get(key).cloned().ok_or_else(|| SlvrError::KeyNotFound {
+            Value::Object(_) => self.get_key(key).ok_or_else(|| SlvrError::KeyNotFound {
                 key: key.to_string(),
             }),
             _ => Err(SlvrError::invalid_arg(format!(
@@ -220,11 +350,74 @@ impl Value {
 mod tests {
     use super::*;
 
+    fn dec(text: &str) -> Decimal {
+        parse_decimal(text).unwrap()
+    }
+
     #[test]
     fn test_value_display() {
         assert_eq!(Value::Integer(42).to_string(), "42");
         assert_eq!(Value::String("hello".to_string()).to_string(), "\"hello\"");
         assert_eq!(Value::Boolean(true).to_string(), "true");
+        assert_eq!(Value::Decimal(dec("2.5")).to_string(), "2.5");
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(format_decimal(dec("1")), "1.0");
+        assert_eq!(format_decimal(dec("-42.00")), "-42.0");
+        assert_eq!(format_decimal(dec("-0.0")), "0.0");
+        assert_eq!(format_decimal(dec("0.1")), "0.1");
+        assert_eq!(format_decimal(dec("1.50")), "1.5");
+        assert_eq!(format_decimal(dec("1e-7")), "0.0000001");
+        assert_eq!(format_decimal(dec("1.5e-10")), "0.00000000015");
+        assert_eq!(format_decimal(dec("1e21")), "1000000000000000000000.0");
+    }
+
+    #[test]
+    fn test_decimal_arithmetic_is_exact() {
+        let sum = dec("0.1") + dec("0.2");
+        assert_eq!(Value::Decimal(sum), Value::Decimal(dec("0.3")));
+        assert_eq!(Value::decimal(0.1).unwrap(), Value::Decimal(dec("0.1")));
+        assert_eq!(
+            Value::Integer(3).to_decimal().unwrap() / dec("4"),
+            dec("0.75")
+        );
+    }
+
+    #[test]
+    fn test_non_finite_decimals_rejected() {
+        assert!(Value::decimal(1.5).is_ok());
+        assert!(Value::decimal(f64::NAN).is_err());
+        assert!(Value::decimal(f64::INFINITY).is_err());
+        assert!(Value::decimal(1e40).is_err());
+        assert!(Value::String("NaN".to_string()).to_decimal().is_err());
+        assert!(Value::String("inf".to_string()).to_decimal().is_err());
+        assert!(integer_to_decimal(i128::MAX).is_err());
+    }
+
+    #[test]
+    fn test_borrowed_accessors_match_cloning_ones() {
+        let mut inner = BTreeMap::new();
+        inner.insert("balance".to_string(), Value::Integer(7));
+        let list = Value::List(vec![Value::Object(inner.clone()), Value::Null]);
+        let mut outer = BTreeMap::new();
+        outer.insert("rows".to_string(), list.clone());
+        let obj = Value::Object(outer);
+
+        assert_eq!(obj.get_key("rows"), Some(&obj.get_field("rows").unwrap()));
+        assert_eq!(list.get_index(0), Some(&list.get_list_element(0).unwrap()));
+        assert_eq!(
+            list.get_index(0).and_then(|row| row.get_key("balance")),
+            Some(&Value::Integer(7))
+        );
+
+        assert_eq!(list.get_index(2), None);
+        assert!(list.get_list_element(2).is_err());
+        assert_eq!(obj.get_key("missing"), None);
+        assert!(obj.get_field("missing").is_err());
+        assert_eq!(list.get_key("rows"), None);
+        assert_eq!(obj.get_index(0), None);
     }
 
     #[test]
@@ -239,7 +432,7 @@ mod tests {
     #[test]
     fn test_value_conversions() {
         assert_eq!(Value::Integer(42).to_integer().unwrap(), 42);
-        let test_decimal = 2.71_f64; // Use e instead of pi
+        let test_decimal = dec("2.71"); // Use e instead of pi
         assert_eq!(
             Value::Decimal(test_decimal).to_decimal().unwrap(),
             test_decimal
@@ -277,7 +470,7 @@ mod tests {
 
     #[test]
     fn test_object_operations() {
-        let mut obj = Value::Object(HashMap::new());
+        let mut obj = Value::Object(BTreeMap::new());
         obj.set_field("name".to_string(), Value::String("Alice".to_string()))
             .unwrap();
 
@@ -3,6 +3,7 @@
 //! This module provides formal verification capabilities for smart contracts,
 //! including constraint generation, proof checking, and invariant verification.
 
+use crate::ast::{Definition, Expr};
 use crate::error::{SlvrError, SlvrResult};
 
 use serde::{Deserialize, Serialize};
@@ -172,9 +173,10 @@ impl Verifier {
     /// Add invariant
     pub fn add_invariant(&mut self, invariant: Invariant) -> SlvrResult<()> {
         if self.invariants.iter().any(|i| i.name == invariant.name) {
-            return Err(SlvrError::RuntimeError {
-                message: format!("Invariant {} already exists", invariant.name),
-            });
+            return Err(SlvrError::runtime(format!(
+                "Invariant {} already exists",
+                invariant.name
+            )));
         }
         self.invariants.push(invariant);
         Ok(())
@@ -341,9 +343,9 @@ impl Verifier {
         match constraint {
             Constraint::Variable(name) => {
                 // Assign a default value for variables
-                if !values.contains_key(name) {
-                    values.insert(name.clone(), "0".to_string());
-                }
+                values
+                    .entry(name.clone())
+                    .or_insert_with(|| "0".to_string());
             }
             Constraint::And(a, b) => {
                 self.extract_variables_with_values(a, values);
@@ -470,6 +472,167 @@ impl Default for Verifier {
     }
 }
 
+/// Builtins that authorize the caller before state is touched
+const GUARD_FUNCTIONS: &[&str] = &[
+    "enforce",
+    "enforce-one",
+    "with-capability",
+    "require-capability",
+];
+
+/// Builtins that write a table, taking the table as their first argument.
+///
+/// `write`, `update` and `delete` are keywords with their own AST nodes.
+const WRITE_FUNCTIONS: &[&str] = &["insert"];
+
+/// A likely security problem reported by an audit pass
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Function the problem was found in
+    pub function: String,
+    /// Tables the function writes, sorted and deduplicated
+    pub tables: Vec<String>,
+    pub description: String,
+}
+
+/// Flag functions that write a table without calling any authorization guard.
+///
+/// A function counts as guarded only if its own body calls `enforce`,
+/// `enforce-one`, `with-capability` or `require-capability`; guards reached
+/// through helper functions are not followed, so such findings may need a
+/// manual look.
+pub fn audit_authorization(module: &Definition) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    audit_definition(module, &mut findings);
+    findings
+}
+
+fn audit_definition(def: &Definition, findings: &mut Vec<Finding>) {
+    match def {
+        Definition::Module { body, .. } => {
+            for inner in body {
+                audit_definition(inner, findings);
+            }
+        }
+        Definition::Function { name, body, .. } => {
+            let mut scan = AuthScan::default();
+            scan.visit(body);
+            if !scan.tables.is_empty() && !scan.guarded {
+                scan.tables.sort();
+                scan.tables.dedup();
+                findings.push(Finding {
+                    function: name.clone(),
+                    description: format!(
+                        "{} writes {} without enforce, with-capability or require-capability",
+                        name,
+                        scan.tables.join(", ")
+                    ),
+                    tables: scan.tables,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Table writes and guard calls seen in one function body
+#[derive(Default)]
+struct AuthScan {
+    tables: Vec<String>,
+    guarded: bool,
+}
+
+impl AuthScan {
+    fn visit(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) | Expr::Variable(_) => {}
+            Expr::BinOp { left, right, .. } => {
+                self.visit(left);
+                self.visit(right);
+            }
+            Expr::UnaryOp { operand, .. } => self.visit(operand),
+            Expr::Call { function, args } => {
+                if let Expr::Variable(name) = function.unspanned() {
+                    if GUARD_FUNCTIONS.contains(&name.as_str()) {
+                        self.guarded = true;
+                    } else if WRITE_FUNCTIONS.contains(&name.as_str()) {
+                        let table = match args.first().map(Expr::unspanned) {
+                            Some(Expr::Variable(table)) => table.clone(),
+                            _ => "<unknown>".to_string(),
+                        };
+                        self.tables.push(table);
+                    }
+                }
+                self.visit(function);
+                for arg in args {
+                    self.visit(arg);
+                }
+            }
+            Expr::If { .. } => {
+                // `else if` chains are walked in a loop, as the parser reads them
+                let mut next = Some(expr);
+                while let Some(Expr::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                }) = next.map(Expr::unspanned)
+                {
+                    self.visit(condition);
+                    self.visit(then_branch);
+                    next = else_branch.as_deref();
+                }
+                if let Some(else_branch) = next {
+                    self.visit(else_branch);
+                }
+            }
+            Expr::Lambda { body, .. } => self.visit(body),
+            Expr::Let { bindings, body, .. } => {
+                for (_, value) in bindings {
+                    self.visit(value);
+                }
+                self.visit(body);
+            }
+            Expr::List(items) | Expr::Block(items) => {
+                for item in items {
+                    self.visit(item);
+                }
+            }
+            Expr::Object(fields) => {
+                for (_, value) in fields {
+                    self.visit(value);
+                }
+            }
+            Expr::FieldAccess { object, .. } => self.visit(object),
+            Expr::Index { object, index } => {
+                self.visit(object);
+                self.visit(index);
+            }
+            Expr::Read { key, .. } | Expr::ReadSafe { key, .. } => self.visit(key),
+            Expr::Write { table, key, value } => {
+                self.tables.push(table.clone());
+                self.visit(key);
+                self.visit(value);
+            }
+            Expr::Update {
+                table,
+                key,
+                updates,
+            } => {
+                self.tables.push(table.clone());
+                self.visit(key);
+                for (_, value) in updates {
+                    self.visit(value);
+                }
+            }
+            Expr::Delete { table, key } => {
+                self.tables.push(table.clone());
+                self.visit(key);
+            }
+            Expr::Spanned { expr, .. } => self.visit(expr),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -531,6 +694,35 @@ mod tests {
         assert_eq!(result.invariants_passed, 1);
     }
 
+    #[test]
+    fn test_audit_authorization_flags_unguarded_writer() {
+        let source = "module bank \"Bank\" { \
+            defun credit (id: string, amount: integer) -> string \
+                insert(accounts, id, { balance: amount }) \
+            defun debit (id: string, amount: integer) -> string \
+                if enforce(amount > 0, \"positive\") insert(ledger, id, { balance: amount }) else \"\" \
+            defun balance (id: string) -> integer 0 }";
+        let program = crate::parser::Parser::new(source).unwrap().parse().unwrap();
+
+        let findings = audit_authorization(&program.definitions[0]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].function, "credit");
+        assert_eq!(findings[0].tables, vec!["accounts".to_string()]);
+
+        // Keyword writes built directly as AST nodes are audited as well
+        let delete = Definition::Function {
+            name: "close".to_string(),
+            params: vec![],
+            return_type: crate::ast::Type::Unit,
+            doc: None,
+            body: Expr::Delete {
+                table: "accounts".to_string(),
+                key: Box::new(Expr::Variable("id".to_string())),
+            },
+        };
+        assert_eq!(audit_authorization(&delete)[0].function, "close");
+    }
+
     #[test]
     fn test_smt_lib_generation() {
         let mut verifier = Verifier::new();
@@ -5,7 +5,7 @@
 use crate::bytecode::{Bytecode, Instruction};
 use crate::error::{SlvrError, SlvrResult};
 use crate::runtime::Runtime;
-use crate::value::Value;
+use crate::value::{checked_decimal, checked_integer, decimal_pow, integer_to_decimal, Value};
 
 use std::collections::HashMap;
 
@@ -42,12 +42,17 @@ impl VirtualMachine {
     }
 
     /// Execute the bytecode
+    ///
+    /// Execution is a transaction: if any instruction fails, every state
+    /// change made so far is rolled back. Either way the reads it cached are
+    /// dropped, so the next transaction pays for its own.
     pub fn execute(&mut self) -> SlvrResult<Value> {
-        while self.ip < self.bytecode.instructions.len() {
-            let instruction = self.bytecode.instructions[self.ip].clone();
-            self.execute_instruction(&instruction)?;
-            self.ip += 1;
+        let snapshot = self.runtime.snapshot();
+        if let Err(e) = self.run() {
+            self.runtime.restore(snapshot);
+            return Err(e);
         }
+        self.runtime.clear_read_cache();
 
         if self.stack.is_empty() {
             Ok(Value::Unit)
@@ -58,6 +63,17 @@ impl VirtualMachine {
         }
     }
 
+    fn run(&mut self) -> SlvrResult<()> {
+        while self.ip < self.bytecode.instructions.len() {
+            let instruction = self.bytecode.instructions[self.ip].clone();
+            let cost = self.runtime.fuel_schedule().instruction_cost(&instruction);
+            self.runtime.consume_fuel(cost)?;
+            self.execute_instruction(&instruction)?;
+            self.ip += 1;
+        }
+        Ok(())
+    }
+
     /// Get current stack
     pub fn stack(&self) -> &[Value] {
         &self.stack
@@ -95,114 +111,31 @@ impl VirtualMachine {
                 self.stack.push(val);
             }
 
-            // Arithmetic operations
-            Instruction::Add => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                let result = Self::add_values(a, b)?;
-                self.stack.push(result);
-            }
-            Instruction::Subtract => {
+            // Arithmetic, comparison, logical and string operations
+            Instruction::Add
+            | Instruction::Subtract
+            | Instruction::Multiply
+            | Instruction::Divide
+            | Instruction::Modulo
+            | Instruction::Power
+            | Instruction::Equal
+            | Instruction::NotEqual
+            | Instruction::Less
+            | Instruction::LessEqual
+            | Instruction::Greater
+            | Instruction::GreaterEqual
+            | Instruction::And
+            | Instruction::Or
+            | Instruction::Concat => {
                 let b = self.pop_stack()?;
                 let a = self.pop_stack()?;
-                let result = Self::subtract_values(a, b)?;
+                let result = Self::binary_op(instruction, a, b)?;
                 self.stack.push(result);
             }
-            Instruction::Multiply => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                let result = Self::multiply_values(a, b)?;
-                self.stack.push(result);
-            }
-            Instruction::Divide => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                let result = Self::divide_values(a, b)?;
-                self.stack.push(result);
-            }
-            Instruction::Modulo => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                let result = Self::modulo_values(a, b)?;
-                self.stack.push(result);
-            }
-            Instruction::Power => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                let result = Self::power_values(a, b)?;
-                self.stack.push(result);
-            }
-            Instruction::Negate => {
+            Instruction::Negate | Instruction::Not => {
                 let val = self.pop_stack()?;
-                match val {
-                    Value::Integer(n) => self.stack.push(Value::Integer(-n)),
-                    Value::Decimal(d) => self.stack.push(Value::Decimal(-d)),
-                    _ => return Err(SlvrError::type_mismatch("numeric", val.type_name())),
-                }
-            }
-
-            // Comparison operations
-            Instruction::Equal => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack.push(Value::Boolean(a == b));
-            }
-            Instruction::NotEqual => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack.push(Value::Boolean(a != b));
-            }
-            Instruction::Less => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack
-                    .push(Value::Boolean(Self::compare_values(&a, &b)? < 0));
-            }
-            Instruction::LessEqual => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack
-                    .push(Value::Boolean(Self::compare_values(&a, &b)? <= 0));
-            }
-            Instruction::Greater => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack
-                    .push(Value::Boolean(Self::compare_values(&a, &b)? > 0));
-            }
-            Instruction::GreaterEqual => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack
-                    .push(Value::Boolean(Self::compare_values(&a, &b)? >= 0));
-            }
-
-            // Logical operations
-            Instruction::And => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack
-                    .push(Value::Boolean(a.is_truthy() && b.is_truthy()));
-            }
-            Instruction::Or => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                self.stack
-                    .push(Value::Boolean(a.is_truthy() || b.is_truthy()));
-            }
-            Instruction::Not => {
-                let val = self.pop_stack()?;
-                self.stack.push(Value::Boolean(!val.is_truthy()));
-            }
-
-            // String operations
-            Instruction::Concat => {
-                let b = self.pop_stack()?;
-                let a = self.pop_stack()?;
-                let a_str = a.to_string_value()?;
-                let b_str = b.to_string_value()?;
-                self.stack
-                    .push(Value::String(format!("{}{}", a_str, b_str)));
+                let result = Self::unary_op(instruction, val)?;
+                self.stack.push(result);
             }
 
             // Control flow
@@ -239,13 +172,19 @@ impl VirtualMachine {
                 }
             }
             Instruction::StoreLocal(idx) => {
+                if *idx >= crate::MAX_LOCALS {
+                    return Err(SlvrError::runtime(format!(
+                        "Local slot {} exceeds the limit of {}",
+                        idx,
+                        crate::MAX_LOCALS
+                    )));
+                }
                 let val = self.pop_stack()?;
                 if let Some(locals) = self.locals.last_mut() {
-                    if *idx < locals.len() {
-                        locals[*idx] = val;
-                    } else {
-                        locals.push(val);
+                    if *idx >= locals.len() {
+                        locals.resize(*idx + 1, Value::Null);
                     }
+                    locals[*idx] = val;
                 }
             }
             Instruction::LoadGlobal(name) => {
@@ -272,7 +211,7 @@ impl VirtualMachine {
                 self.stack.push(Value::List(list));
             }
             Instruction::MakeObject(len) => {
-                let mut obj = std::collections::HashMap::new();
+                let mut obj = std::collections::BTreeMap::new();
                 for _ in 0..*len {
                     let val = self.pop_stack()?;
                     let key = self.pop_stack()?.to_string_value()?;
@@ -317,10 +256,14 @@ impl VirtualMachine {
             }
 
             // Database operations
-            Instruction::Read(table) => {
+            Instruction::Read(table) | Instruction::ReadSafe(table) => {
                 let key = self.pop_stack()?.to_string_value()?;
                 let table_key = format!("{}:{}", table, key);
-                let val = self.runtime.read(&table_key).unwrap_or(Value::Null);
+                let val = match self.runtime.read_cached(&table_key)? {
+                    Some(row) => row,
+                    None if matches!(instruction, Instruction::ReadSafe(_)) => Value::Null,
+                    None => return Err(SlvrError::KeyNotFound { key: table_key }),
+                };
                 self.stack.push(val);
             }
             Instruction::Write(table) => {
@@ -357,13 +300,30 @@ impl VirtualMachine {
             Instruction::Throw(msg) => {
                 return Err(SlvrError::runtime(msg));
             }
+            Instruction::Enforce => {
+                let message = self.pop_stack()?.to_string_value()?;
+                match self.pop_stack()? {
+                    Value::Boolean(true) => self.stack.push(Value::Unit),
+                    Value::Boolean(false) => return Err(SlvrError::EnforceFailed { message }),
+                    other => return Err(SlvrError::type_mismatch("boolean", other.type_name())),
+                }
+            }
 
             // Fuel operations
             Instruction::ConsumeFuel(amount) => {
                 self.runtime.consume_fuel(*amount)?;
             }
 
-            // Function calls
+            // Function calls; only builtins can run in the VM
+            Instruction::Call(name, 3) if name == "ed25519-verify" => {
+                let signature = self.pop_stack()?;
+                let message = self.pop_stack()?;
+                let pubkey = self.pop_stack()?;
+                self.runtime
+                    .consume_fuel(self.runtime.fuel_schedule().builtin_cost("ed25519-verify"))?;
+                let verified = crate::stdlib::crypto::ed25519_verify(pubkey, message, signature)?;
+                self.stack.push(verified);
+            }
             Instruction::Call(name, _argc) => {
                 return Err(SlvrError::undefined_func(name));
             }
@@ -377,32 +337,87 @@ impl VirtualMachine {
             .ok_or_else(|| SlvrError::runtime("Stack underflow"))
     }
 
+    /// Apply a binary instruction to the two operands it would pop
+    pub(crate) fn binary_op(instruction: &Instruction, a: Value, b: Value) -> SlvrResult<Value> {
+        match instruction {
+            Instruction::Add => Self::add_values(a, b),
+            Instruction::Subtract => Self::subtract_values(a, b),
+            Instruction::Multiply => Self::multiply_values(a, b),
+            Instruction::Divide => Self::divide_values(a, b),
+            Instruction::Modulo => Self::modulo_values(a, b),
+            Instruction::Power => Self::power_values(a, b),
+            Instruction::Equal => Ok(Value::Boolean(a == b)),
+            Instruction::NotEqual => Ok(Value::Boolean(a != b)),
+            Instruction::Less => Ok(Value::Boolean(Self::compare_values(&a, &b)? < 0)),
+            Instruction::LessEqual => Ok(Value::Boolean(Self::compare_values(&a, &b)? <= 0)),
+            Instruction::Greater => Ok(Value::Boolean(Self::compare_values(&a, &b)? > 0)),
+            Instruction::GreaterEqual => Ok(Value::Boolean(Self::compare_values(&a, &b)? >= 0)),
+            Instruction::And => Ok(Value::Boolean(a.is_truthy() && b.is_truthy())),
+            Instruction::Or => Ok(Value::Boolean(a.is_truthy() || b.is_truthy())),
+            Instruction::Concat => {
+                let a_str = a.to_string_value()?;
+                let b_str = b.to_string_value()?;
+                Ok(Value::String(format!("{}{}", a_str, b_str)))
+            }
+            _ => Err(SlvrError::internal(format!(
+                "{} is not a binary instruction",
+                instruction
+            ))),
+        }
+    }
+
+    /// Apply a unary instruction to the operand it would pop
+    pub(crate) fn unary_op(instruction: &Instruction, val: Value) -> SlvrResult<Value> {
+        match (instruction, val) {
+            (Instruction::Negate, Value::Integer(n)) => checked_integer(n.checked_neg(), "-"),
+            (Instruction::Negate, Value::Decimal(d)) => Ok(Value::Decimal(-d)),
+            (Instruction::Negate, val) => Err(SlvrError::type_mismatch("numeric", val.type_name())),
+            (Instruction::Not, val) => Ok(Value::Boolean(!val.is_truthy())),
+            (instruction, _) => Err(SlvrError::internal(format!(
+                "{} is not a unary instruction",
+                instruction
+            ))),
+        }
+    }
+
     fn add_values(a: Value, b: Value) -> SlvrResult<Value> {
         match (a, b) {
-            (Value::Integer(x), Value::Integer(y)) => Ok(Value::Integer(x + y)),
-            (Value::Decimal(x), Value::Decimal(y)) => Ok(Value::Decimal(x + y)),
-            (Value::Integer(x), Value::Decimal(y)) => Ok(Value::Decimal(x as f64 + y)),
-            (Value::Decimal(x), Value::Integer(y)) => Ok(Value::Decimal(x + y as f64)),
+            (Value::Integer(x), Value::Integer(y)) => checked_integer(x.checked_add(y), "+"),
+            (Value::Decimal(x), Value::Decimal(y)) => checked_decimal(x.checked_add(y), "+"),
+            (Value::Integer(x), Value::Decimal(y)) => {
+                checked_decimal(integer_to_decimal(x)?.checked_add(y), "+")
+            }
+            (Value::Decimal(x), Value::Integer(y)) => {
+                checked_decimal(x.checked_add(integer_to_decimal(y)?), "+")
+            }
             _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
         }
     }
 
     fn subtract_values(a: Value, b: Value) -> SlvrResult<Value> {
         match (a, b) {
-            (Value::Integer(x), Value::Integer(y)) => Ok(Value::Integer(x - y)),
-            (Value::Decimal(x), Value::Decimal(y)) => Ok(Value::Decimal(x - y)),
-            (Value::Integer(x), Value::Decimal(y)) => Ok(Value::Decimal(x as f64 - y)),
-            (Value::Decimal(x), Value::Integer(y)) => Ok(Value::Decimal(x - y as f64)),
+            (Value::Integer(x), Value::Integer(y)) => checked_integer(x.checked_sub(y), "-"),
+            (Value::Decimal(x), Value::Decimal(y)) => checked_decimal(x.checked_sub(y), "-"),
+            (Value::Integer(x), Value::Decimal(y)) => {
+                checked_decimal(integer_to_decimal(x)?.checked_sub(y), "-")
+            }
+            (Value::Decimal(x), Value::Integer(y)) => {
+                checked_decimal(x.checked_sub(integer_to_decimal(y)?), "-")
+            }
             _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
         }
     }
 
     fn multiply_values(a: Value, b: Value) -> SlvrResult<Value> {
         match (a, b) {
-            (Value::Integer(x), Value::Integer(y)) => Ok(Value::Integer(x * y)),
-            (Value::Decimal(x), Value::Decimal(y)) => Ok(Value::Decimal(x * y)),
-            (Value::Integer(x), Value::Decimal(y)) => Ok(Value::Decimal(x as f64 * y)),
-            (Value::Decimal(x), Value::Integer(y)) => Ok(Value::Decimal(x * y as f64)),
+            (Value::Integer(x), Value::Integer(y)) => checked_integer(x.checked_mul(y), "*"),
+            (Value::Decimal(x), Value::Decimal(y)) => checked_decimal(x.checked_mul(y), "*"),
+            (Value::Integer(x), Value::Decimal(y)) => {
+                checked_decimal(integer_to_decimal(x)?.checked_mul(y), "*")
+            }
+            (Value::Decimal(x), Value::Integer(y)) => {
+                checked_decimal(x.checked_mul(integer_to_decimal(y)?), "*")
+            }
             _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
         }
     }
@@ -413,28 +428,28 @@ impl VirtualMachine {
                 if y == 0 {
                     Err(SlvrError::DivisionByZero)
                 } else {
-                    Ok(Value::Integer(x / y))
+                    checked_integer(x.checked_div(y), "/")
                 }
             }
             (Value::Decimal(x), Value::Decimal(y)) => {
-                if y == 0.0 {
+                if y.is_zero() {
                     Err(SlvrError::DivisionByZero)
                 } else {
-                    Ok(Value::Decimal(x / y))
+                    checked_decimal(x.checked_div(y), "/")
                 }
             }
             (Value::Integer(x), Value::Decimal(y)) => {
-                if y == 0.0 {
+                if y.is_zero() {
                     Err(SlvrError::DivisionByZero)
                 } else {
-                    Ok(Value::Decimal(x as f64 / y))
+                    checked_decimal(integer_to_decimal(x)?.checked_div(y), "/")
                 }
             }
             (Value::Decimal(x), Value::Integer(y)) => {
                 if y == 0 {
                     Err(SlvrError::DivisionByZero)
                 } else {
-                    Ok(Value::Decimal(x / y as f64))
+                    checked_decimal(x.checked_div(integer_to_decimal(y)?), "/")
                 }
             }
             _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
@@ -447,7 +462,7 @@ impl VirtualMachine {
                 if y == 0 {
                     Err(SlvrError::DivisionByZero)
                 } else {
-                    Ok(Value::Integer(x % y))
+                    checked_integer(x.checked_rem(y), "%")
                 }
             }
             _ => Err(SlvrError::type_mismatch("integer", "non-integer")),
@@ -458,14 +473,15 @@ impl VirtualMachine {
         match (a, b) {
             (Value::Integer(x), Value::Integer(y)) => {
                 if y < 0 {
-                    Ok(Value::Decimal((x as f64).powf(y as f64)))
+                    decimal_pow(integer_to_decimal(x)?, integer_to_decimal(y)?)
                 } else {
-                    Ok(Value::Integer(x.pow(y as u32)))
+                    let power = u32::try_from(y).ok().and_then(|y| x.checked_pow(y));
+                    checked_integer(power, "^")
                 }
             }
-            (Value::Decimal(x), Value::Decimal(y)) => Ok(Value::Decimal(x.powf(y))),
-            (Value::Integer(x), Value::Decimal(y)) => Ok(Value::Decimal((x as f64).powf(y))),
-            (Value::Decimal(x), Value::Integer(y)) => Ok(Value::Decimal(x.powf(y as f64))),
+            (Value::Decimal(x), Value::Decimal(y)) => decimal_pow(x, y),
+            (Value::Integer(x), Value::Decimal(y)) => decimal_pow(integer_to_decimal(x)?, y),
+            (Value::Decimal(x), Value::Integer(y)) => decimal_pow(x, integer_to_decimal(y)?),
             _ => Err(SlvrError::type_mismatch("numeric", "non-numeric")),
         }
     }
@@ -487,20 +503,20 @@ impl VirtualMachine {
                 0
             }),
             (Value::Integer(x), Value::Decimal(y)) => {
-                let x_f = *x as f64;
-                Ok(if x_f < *y {
+                let x_d = integer_to_decimal(*x)?;
+                Ok(if x_d < *y {
                     -1
-                } else if x_f > *y {
+                } else if x_d > *y {
                     1
                 } else {
                     0
                 })
             }
             (Value::Decimal(x), Value::Integer(y)) => {
-                let y_f = *y as f64;
-                Ok(if x < &y_f {
+                let y_d = integer_to_decimal(*y)?;
+                Ok(if *x < y_d {
                     -1
-                } else if x > &y_f {
+                } else if *x > y_d {
                     1
                 } else {
                     0
@@ -521,6 +537,8 @@ impl VirtualMachine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runtime::FuelSchedule;
+    use crate::value::Decimal;
 
     #[test]
     fn test_vm_creation() {
@@ -542,4 +560,142 @@ mod tests {
         let result = vm.execute().unwrap();
         assert_eq!(result, Value::Integer(50));
     }
+
+    #[test]
+    fn test_vm_bounds_local_slots() {
+        for idx in [usize::MAX, crate::MAX_LOCALS] {
+            let mut bytecode = Bytecode::new();
+            bytecode.push(Instruction::PushInt(1));
+            bytecode.push(Instruction::StoreLocal(idx));
+            let mut vm = VirtualMachine::new(bytecode, Runtime::new(1_000_000));
+            assert!(matches!(vm.execute(), Err(SlvrError::RuntimeError { .. })));
+        }
+
+        let mut bytecode = Bytecode::new();
+        bytecode.push(Instruction::PushInt(1));
+        bytecode.push(Instruction::StoreLocal(crate::MAX_LOCALS - 1));
+        bytecode.push(Instruction::LoadLocal(crate::MAX_LOCALS - 1));
+        let mut vm = VirtualMachine::new(bytecode, Runtime::new(1_000_000));
+        assert_eq!(vm.execute().unwrap(), Value::Integer(1));
+    }
+
+    #[test]
+    fn test_vm_rejects_decimal_overflow() {
+        let mut bytecode = Bytecode::new();
+        bytecode.push(Instruction::PushDecimal(Decimal::MAX));
+        bytecode.push(Instruction::PushDecimal(Decimal::MAX));
+        bytecode.push(Instruction::Add);
+
+        let mut vm = VirtualMachine::new(bytecode, Runtime::new(1_000_000));
+        assert!(matches!(vm.execute(), Err(SlvrError::RuntimeError { .. })));
+    }
+
+    #[test]
+    fn test_vm_rejects_integer_overflow() {
+        let programs = [
+            vec![
+                Instruction::PushInt(i128::MAX),
+                Instruction::PushInt(1),
+                Instruction::Add,
+            ],
+            vec![Instruction::PushInt(i128::MIN), Instruction::Negate],
+            vec![
+                Instruction::PushInt(i128::MIN),
+                Instruction::PushInt(-1),
+                Instruction::Divide,
+            ],
+        ];
+        for instructions in programs {
+            let mut bytecode = Bytecode::new();
+            for instruction in instructions {
+                bytecode.push(instruction);
+            }
+            let mut vm = VirtualMachine::new(bytecode, Runtime::new(1_000_000));
+            assert!(matches!(vm.execute(), Err(SlvrError::RuntimeError { .. })));
+        }
+    }
+
+    #[test]
+    fn test_vm_charges_fuel_schedule() {
+        let fuel_used = |schedule: FuelSchedule| {
+            let mut bytecode = Bytecode::new();
+            bytecode.push(Instruction::PushInt(2));
+            bytecode.push(Instruction::PushInt(3));
+            bytecode.push(Instruction::Add);
+            let runtime = Runtime::new(1_000_000).with_fuel_schedule(schedule);
+            let mut vm = VirtualMachine::new(bytecode, runtime);
+            assert_eq!(vm.execute().unwrap(), Value::Integer(5));
+            vm.runtime().fuel_used()
+        };
+
+        let config = crate::SlvrConfig::new();
+        assert_eq!(
+            fuel_used(FuelSchedule::from_config(&config)),
+            3 * crate::MIN_FUEL_PER_OP
+        );
+        let config = config.with_fuel_schedule(FuelSchedule::new().with_opcode_cost("ADD", 40));
+        assert_eq!(
+            fuel_used(FuelSchedule::from_config(&config)),
+            2 * crate::MIN_FUEL_PER_OP + 40
+        );
+        let config = config.with_fuel_metering(false);
+        assert_eq!(fuel_used(FuelSchedule::from_config(&config)), 0);
+    }
+
+    #[test]
+    fn test_vm_missing_row() {
+        let read = |instruction: Instruction| {
+            let mut bytecode = Bytecode::new();
+            bytecode.push(Instruction::PushString("nobody".to_string()));
+            bytecode.push(instruction);
+            VirtualMachine::new(bytecode, Runtime::new(1_000_000)).execute()
+        };
+        assert!(matches!(
+            read(Instruction::Read("coins".to_string())),
+            Err(SlvrError::KeyNotFound { .. })
+        ));
+        assert_eq!(
+            read(Instruction::ReadSafe("coins".to_string())).unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_vm_read_cache_ends_with_the_transaction() {
+        let runtime = Runtime::new(1_000_000);
+        runtime
+            .write("coins:alice".to_string(), Value::Integer(5))
+            .unwrap();
+        let read_twice = || {
+            let mut bytecode = Bytecode::new();
+            for _ in 0..2 {
+                bytecode.push(Instruction::PushString("alice".to_string()));
+                bytecode.push(Instruction::Read("coins".to_string()));
+            }
+            bytecode
+        };
+
+        VirtualMachine::new(read_twice(), runtime.clone())
+            .execute()
+            .unwrap();
+        assert_eq!(runtime.cache_hits(), 1);
+        // A later transaction on the same runtime starts with an empty cache
+        VirtualMachine::new(read_twice(), runtime.clone())
+            .execute()
+            .unwrap();
+        assert_eq!(runtime.cache_hits(), 2);
+    }
+
+    #[test]
+    fn test_vm_decimal_addition_is_exact() {
+        let mut bytecode = Bytecode::new();
+        bytecode.push(Instruction::PushDecimal("0.1".parse().unwrap()));
+        bytecode.push(Instruction::PushDecimal("0.2".parse().unwrap()));
+        bytecode.push(Instruction::Add);
+        bytecode.push(Instruction::PushDecimal("0.3".parse().unwrap()));
+        bytecode.push(Instruction::Equal);
+
+        let mut vm = VirtualMachine::new(bytecode, Runtime::new(1_000_000));
+        assert_eq!(vm.execute().unwrap(), Value::Boolean(true));
+    }
 }